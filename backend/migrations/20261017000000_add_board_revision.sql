@@ -0,0 +1,11 @@
+-- Count the changes of a board, so that a client can tell when it missed one.
+--
+-- A change used to go out as the whole board, and a client that lost a message lost nothing:
+-- the next one carried everything. A change now goes out as a patch of the part that moved, and
+-- a patch only makes sense on top of the one before it. Every change takes the next number, and
+-- a client that sees a number skip asks for the whole board again.
+--
+-- The number lives on the row, not in memory, so that it keeps climbing across a restart and a
+-- client that reconnects never reads an old number as a new one.
+
+ALTER TABLE boards ADD COLUMN revision BIGINT NOT NULL DEFAULT 0;
@@ -0,0 +1,20 @@
+-- More than one backend can now serve the same board, so who is on a board can no longer live in
+-- the memory of one process. Each running backend registers itself as a replica and writes one
+-- row per open socket; the count of a board is the count of its rows.
+--
+-- A replica that stops without a goodbye leaves its rows behind. It also stops touching
+-- last_seen, and the others remove it once it has been quiet for a minute. The cascade takes its
+-- sockets with it.
+CREATE TABLE replicas (
+    id TEXT PRIMARY KEY,
+    last_seen TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TABLE board_presence (
+    socket_id TEXT PRIMARY KEY,
+    replica_id TEXT NOT NULL REFERENCES replicas(id) ON DELETE CASCADE,
+    board_id TEXT NOT NULL REFERENCES boards(id) ON DELETE CASCADE,
+    participant_id TEXT NOT NULL
+);
+
+CREATE INDEX idx_board_presence_board_id ON board_presence(board_id);
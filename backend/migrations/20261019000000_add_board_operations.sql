@@ -0,0 +1,22 @@
+-- The history of a board, so that a participant can take back what they did and do it again.
+-- It replaces the one merge undo that lived in memory, which a restart or a second merge lost.
+--
+-- Each row is one action of one participant. `changes` holds the cards and comments the action
+-- touched, as they stood before and after it. An undo walks back the newest row of that
+-- participant that is not undone yet; a redo walks forward the oldest that is. A new action
+-- drops the rows its author had undone, as an editor drops its redo once you type again.
+--
+-- `author_id` is the author whose card or comment the action touched, when only that author and
+-- the privileged could do it. The same rule holds when the step is walked.
+CREATE TABLE board_operations (
+    id BIGSERIAL PRIMARY KEY,
+    board_id TEXT NOT NULL REFERENCES boards(id) ON DELETE CASCADE,
+    actor_id TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    author_id TEXT,
+    changes JSONB NOT NULL,
+    undone BOOLEAN NOT NULL DEFAULT false,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_board_operations_board_id_actor_id ON board_operations(board_id, actor_id);
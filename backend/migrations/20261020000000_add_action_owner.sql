@@ -0,0 +1,11 @@
+-- An action nobody owns and nobody dates tends to come back unchanged at the next retro. An
+-- action card can now name who takes it on and the day it is due.
+--
+-- The owner is either someone on the board, by participant id, or a member of one of the teams
+-- the admins manage, by team and member id. The name is kept next to the id, since a participant
+-- has no row of their own and a team member may be renamed or removed after the retro.
+ALTER TABLE tickets
+    ADD COLUMN assignee_id TEXT,
+    ADD COLUMN assignee_team_id TEXT,
+    ADD COLUMN assignee_name TEXT,
+    ADD COLUMN due_on DATE;
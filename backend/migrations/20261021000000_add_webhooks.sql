@@ -0,0 +1,42 @@
+-- Other systems hear what happens on a board: a Slack or Teams channel learns that a retro started,
+-- that the cards were revealed, that the timer ran out, and what the team decided to do.
+--
+-- A webhook with a board belongs to that board and is set by its facilitator. One without is
+-- global and set by an admin. `events` names what it wants to hear; empty means all of it.
+CREATE TABLE webhooks (
+    id TEXT PRIMARY KEY,
+    board_id TEXT REFERENCES boards(id) ON DELETE CASCADE,
+    url TEXT NOT NULL,
+    secret TEXT NOT NULL,
+    events TEXT[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_webhooks_board_id ON webhooks(board_id);
+
+-- Each event becomes one row per webhook that wants it, and the row stays as the record of how the
+-- delivery went. A row waits while it is `pending`, and any replica sends it once its
+-- `next_attempt_at` has come, so a restart takes nothing with it.
+--
+-- The payload is text rather than JSONB: the signature covers its exact bytes, and JSONB would
+-- give back the same document in a different order.
+--
+-- The board id carries no reference. A global webhook keeps its log after the board is gone.
+CREATE TABLE webhook_deliveries (
+    id BIGSERIAL PRIMARY KEY,
+    webhook_id TEXT NOT NULL REFERENCES webhooks(id) ON DELETE CASCADE,
+    board_id TEXT NOT NULL,
+    event TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    attempts INT NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    last_status_code INT,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    delivered_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_webhook_deliveries_due ON webhook_deliveries(next_attempt_at)
+    WHERE status = 'pending';
+CREATE INDEX idx_webhook_deliveries_webhook_id ON webhook_deliveries(webhook_id, id DESC);
@@ -0,0 +1,17 @@
+-- The server now ends the timer of a board itself. `timer_action` is what it does to the board
+-- then, chosen when the timer starts; `timer_expired` says the end has been handled, so that it
+-- happens once across the replicas and a restart picks up only the timers still to run out. The
+-- timer end stays, so the clients keep showing that the time is up.
+--
+-- `cards_locked` stops new cards outside the two action columns. A timer sets it, or the
+-- facilitator does.
+ALTER TABLE boards
+    ADD COLUMN timer_action TEXT,
+    ADD COLUMN timer_expired BOOLEAN NOT NULL DEFAULT false,
+    ADD COLUMN cards_locked BOOLEAN NOT NULL DEFAULT false;
+
+-- A timer that ran out before the server watched for it has nothing left to fire.
+UPDATE boards SET timer_expired = true WHERE timer_end IS NOT NULL AND timer_end <= now();
+
+CREATE INDEX idx_boards_timer_pending ON boards (timer_end)
+    WHERE timer_end IS NOT NULL AND NOT timer_expired;
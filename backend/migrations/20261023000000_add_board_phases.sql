@@ -0,0 +1,11 @@
+-- A retro can run through phases: write, group, vote, discuss, closed. A template names the
+-- ones it uses, and a board keeps its own copy, so that a template edited later leaves the boards
+-- already made from it alone. `phase` is where the board stands now.
+--
+-- A board with no phases has no phase, and everything goes on it at any time, as it always did.
+-- The templates that exist keep none, so no board changes until someone asks for phases.
+ALTER TABLE templates ADD COLUMN phases TEXT[] NOT NULL DEFAULT '{}';
+
+ALTER TABLE boards
+    ADD COLUMN phases TEXT[] NOT NULL DEFAULT '{}',
+    ADD COLUMN phase TEXT;
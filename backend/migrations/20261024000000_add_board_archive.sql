@@ -0,0 +1,4 @@
+-- A facilitator can close a board once the retro is over. A closed board keeps everything it
+-- holds and can be read as before, but it takes no change until it is reopened. Boards that are
+-- already here stay open.
+ALTER TABLE boards ADD COLUMN archived_at TIMESTAMPTZ;
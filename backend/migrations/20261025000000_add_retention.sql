@@ -0,0 +1,45 @@
+-- Retro content does not live forever. A retention policy deletes a board, or strips the names off
+-- it, once nothing has happened on it for a set number of days. Boards that carry one of the
+-- exempt labels are left alone.
+--
+-- `last_activity_at` moves with every change to the board; people coming and going do not count.
+-- Boards already here take the newest of their own creation, their cards, their comments and
+-- their history, so the first run does not see every old board as untouched since today.
+ALTER TABLE boards ADD COLUMN last_activity_at TIMESTAMPTZ NOT NULL DEFAULT now();
+ALTER TABLE boards ADD COLUMN anonymized_at TIMESTAMPTZ;
+
+UPDATE boards b SET last_activity_at = GREATEST(
+    b.created_at,
+    (SELECT max(t.created_at) FROM tickets t JOIN columns c ON t.column_id = c.id
+      WHERE c.board_id = b.id),
+    (SELECT max(tc.created_at) FROM ticket_comments tc JOIN tickets t ON tc.ticket_id = t.id
+      JOIN columns c ON t.column_id = c.id WHERE c.board_id = b.id),
+    (SELECT max(o.created_at) FROM board_operations o WHERE o.board_id = b.id)
+);
+
+CREATE INDEX idx_boards_last_activity_at ON boards(last_activity_at);
+
+-- The policy an admin set. With no row the environment decides; `days` NULL keeps boards forever
+-- even when the environment says otherwise.
+CREATE TABLE retention_policy (
+    id BOOLEAN PRIMARY KEY DEFAULT true CHECK (id),
+    days INT,
+    action TEXT NOT NULL,
+    exempt_labels TEXT[] NOT NULL DEFAULT '{}',
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- What the purge removed, one row for each board. The board id carries no reference: the board
+-- is gone, and the row is the record that it was.
+CREATE TABLE retention_log (
+    id BIGSERIAL PRIMARY KEY,
+    board_id TEXT NOT NULL,
+    title TEXT NOT NULL,
+    action TEXT NOT NULL,
+    labels TEXT[] NOT NULL DEFAULT '{}',
+    card_count BIGINT NOT NULL,
+    last_activity_at TIMESTAMPTZ NOT NULL,
+    purged_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_retention_log_purged_at ON retention_log(purged_at DESC);
@@ -0,0 +1,8 @@
+-- On a deployment that signs people in, a board remembers the account that made it. The
+-- facilitator cookie stays with one browser; the account follows the person to another browser
+-- or another laptop, so clearing cookies no longer loses them their boards.
+--
+-- `owner_sub` is the `sub` claim of the id_token: the account, not its name or address, which
+-- can change. Boards made before, or on a deployment that asks nobody to sign in, have none.
+ALTER TABLE boards ADD COLUMN owner_sub TEXT;
+CREATE INDEX idx_boards_owner_sub ON boards(owner_sub);
@@ -0,0 +1,18 @@
+-- Personal API tokens, for the scripts that call the REST API. Each one acts for the person who
+-- made it — their account, or on a deployment with no sign-in the facilitator cookie of their
+-- browser — and only within its scopes. The secret is kept as an Argon2 hash, as the admin token
+-- and the board passwords are; the id written in front of it is what a request is looked up by.
+CREATE TABLE api_tokens (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    secret_hash TEXT NOT NULL,
+    scopes TEXT[] NOT NULL,
+    owner_sub TEXT,
+    facilitator_id TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    last_used_at TIMESTAMPTZ,
+    CHECK (owner_sub IS NOT NULL OR facilitator_id IS NOT NULL)
+);
+
+CREATE INDEX api_tokens_owner_sub_idx ON api_tokens (owner_sub);
+CREATE INDEX api_tokens_facilitator_id_idx ON api_tokens (facilitator_id);
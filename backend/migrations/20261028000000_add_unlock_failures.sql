@@ -0,0 +1,15 @@
+-- The wrong passwords tried on a board, counted by the address they came from and, in the row whose
+-- address is '*', for the board as a whole. Past a few tries an address waits before its next one
+-- counts, and past many the board stops taking tries from anyone for a while; the wait doubles with
+-- each try past the limit. A tally is forgotten a day after its last wrong try, and the rows of a
+-- board go with it.
+--
+-- Kept in the database rather than in the process, so that every replica counts the same tries.
+CREATE TABLE unlock_failures (
+    board_id TEXT NOT NULL REFERENCES boards(id) ON DELETE CASCADE,
+    address TEXT NOT NULL,
+    failures INTEGER NOT NULL DEFAULT 0,
+    locked_until TIMESTAMPTZ,
+    last_failed_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (board_id, address)
+);
@@ -0,0 +1,39 @@
+-- Who did what to a board, and what the admins did. A row is written by the privileged changes of
+-- the socket, by the board routes that set the password, the title and the labels or bring
+-- something in, and by the admin routes that change anything.
+--
+-- The board id carries no reference, so that the record of a board outlives it. The actor reads
+-- `participant:<id>`, `account:<sub>`, `token:<id>`, `admin:<account>`, `admin token` or
+-- `facilitator`; its name is the one shown at the time, when there was one.
+CREATE TABLE audit_log (
+    id BIGSERIAL PRIMARY KEY,
+    at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    board_id TEXT,
+    actor TEXT NOT NULL,
+    actor_name TEXT,
+    action TEXT NOT NULL,
+    target TEXT,
+    detail TEXT
+);
+
+CREATE INDEX idx_audit_log_board_id_at ON audit_log(board_id, at DESC);
+CREATE INDEX idx_audit_log_at ON audit_log(at DESC);
+
+-- The log is appended to and nothing else. The one change it takes is the name of an actor let go,
+-- which the retention purge does to a board it deletes or anonymizes: the entries stay, and no
+-- longer say who the person was.
+CREATE FUNCTION audit_log_append_only() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP = 'UPDATE' AND NEW.actor_name IS NULL
+       AND (NEW.id, NEW.at, NEW.board_id, NEW.actor, NEW.action, NEW.target, NEW.detail)
+           IS NOT DISTINCT FROM
+           (OLD.id, OLD.at, OLD.board_id, OLD.actor, OLD.action, OLD.target, OLD.detail) THEN
+        RETURN NEW;
+    END IF;
+    RAISE EXCEPTION 'The audit log is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER audit_log_append_only
+    BEFORE UPDATE OR DELETE ON audit_log
+    FOR EACH ROW EXECUTE FUNCTION audit_log_append_only();
@@ -0,0 +1,9 @@
+-- The secrets the server signs with, by what they sign. The first replica to start makes each one
+-- and the rest read it, so every replica checks what any of them signed without a value to set.
+-- `participant` signs the participant ids the socket gives out, so that an id is resumed only by
+-- the browser it was given to.
+CREATE TABLE server_keys (
+    name TEXT PRIMARY KEY,
+    secret TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
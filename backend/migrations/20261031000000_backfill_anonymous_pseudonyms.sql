@@ -0,0 +1,146 @@
+-- The anonymous boards made before their people went by pseudonyms still hold the ids the browsers
+-- were given, and an id ties one person to every board they were on. This rewrites each of them the
+-- way `credentials::author_id` makes a pseudonym: the HMAC-SHA256 of 'pseudonym', the board and the
+-- id, each behind a zero byte, under the `participant` key, its first 12 bytes in hex.
+--
+-- It gives nobody their cards back. A browser from before the credentials holds none, so it joins
+-- under a fresh id and owns nothing it wrote before. What the rewrite keeps is the board agreeing
+-- with itself: a card, its comments, its votes, the steps of the history that made it and the
+-- entries of the audit log still name one and the same person, only no longer one anyone can find
+-- on another board.
+--
+-- The key is made here when no replica has made it yet, and the server reads this one when it
+-- starts. A value that already reads as a pseudonym — 24 hex digits, which no id the socket gives
+-- out is — was written since, and stays. Where a row for the id and one for its pseudonym are both
+-- there, the pseudonym's is the newer and is kept.
+CREATE EXTENSION IF NOT EXISTS pgcrypto;
+
+INSERT INTO server_keys (name, secret)
+VALUES ('participant', encode(gen_random_bytes(32), 'hex'))
+ON CONFLICT (name) DO NOTHING;
+
+CREATE FUNCTION pg_temp.pseudonym(board_id TEXT, participant_id TEXT) RETURNS TEXT AS $$
+    SELECT CASE
+        WHEN participant_id ~ '^[0-9a-f]{24}$' THEN participant_id
+        ELSE encode(substring(hmac(
+            convert_to('pseudonym', 'UTF8') || '\x00'::bytea
+                || convert_to(board_id, 'UTF8') || '\x00'::bytea
+                || convert_to(participant_id, 'UTF8'),
+            convert_to((SELECT secret FROM server_keys WHERE name = 'participant'), 'UTF8'),
+            'sha256'
+        ) FROM 1 FOR 12), 'hex')
+    END
+$$ LANGUAGE sql STABLE;
+
+-- A card or a comment as the history keeps it (see `CardRecord` and `CommentRecord`): its author,
+-- and the voters of a card.
+CREATE FUNCTION pg_temp.pseudonym_record(board_id TEXT, record JSONB) RETURNS JSONB AS $$
+    SELECT CASE
+        WHEN record IS NULL OR jsonb_typeof(record) <> 'object' THEN record
+        WHEN record ? 'votes' THEN jsonb_set(
+            jsonb_set(record, '{author_id}', to_jsonb(pg_temp.pseudonym(board_id, record->>'author_id'))),
+            '{votes}',
+            COALESCE(
+                (SELECT jsonb_agg(to_jsonb(pg_temp.pseudonym(board_id, v.id)) ORDER BY v.n)
+                 FROM jsonb_array_elements_text(record->'votes') WITH ORDINALITY AS v(id, n)),
+                '[]'::jsonb
+            )
+        )
+        ELSE jsonb_set(record, '{author_id}', to_jsonb(pg_temp.pseudonym(board_id, record->>'author_id')))
+    END
+$$ LANGUAGE sql STABLE;
+
+-- The before and after of each card and comment of one step of the history (see `Changes`).
+CREATE FUNCTION pg_temp.pseudonym_changes(board_id TEXT, changes JSONB) RETURNS JSONB AS $$
+    SELECT jsonb_build_object(
+        'cards', COALESCE(
+            (SELECT jsonb_agg(jsonb_build_object(
+                 'before', pg_temp.pseudonym_record(board_id, c.change->'before'),
+                 'after', pg_temp.pseudonym_record(board_id, c.change->'after')) ORDER BY c.n)
+             FROM jsonb_array_elements(changes->'cards') WITH ORDINALITY AS c(change, n)),
+            '[]'::jsonb
+        ),
+        'comments', COALESCE(
+            (SELECT jsonb_agg(jsonb_build_object(
+                 'before', pg_temp.pseudonym_record(board_id, c.change->'before'),
+                 'after', pg_temp.pseudonym_record(board_id, c.change->'after')) ORDER BY c.n)
+             FROM jsonb_array_elements(changes->'comments') WITH ORDINALITY AS c(change, n)),
+            '[]'::jsonb
+        )
+    )
+$$ LANGUAGE sql STABLE;
+
+UPDATE tickets t SET author_id = pg_temp.pseudonym(b.id, t.author_id)
+FROM columns c JOIN boards b ON b.id = c.board_id
+WHERE t.column_id = c.id AND b.is_anonymous AND t.author_id !~ '^[0-9a-f]{24}$';
+
+UPDATE ticket_comments tc SET author_id = pg_temp.pseudonym(b.id, tc.author_id)
+FROM tickets t JOIN columns c ON c.id = t.column_id JOIN boards b ON b.id = c.board_id
+WHERE tc.ticket_id = t.id AND b.is_anonymous AND tc.author_id !~ '^[0-9a-f]{24}$';
+
+DELETE FROM votes v USING tickets t, columns c, boards b
+WHERE v.ticket_id = t.id AND t.column_id = c.id AND c.board_id = b.id AND b.is_anonymous
+  AND v.participant_id !~ '^[0-9a-f]{24}$'
+  AND EXISTS (SELECT 1 FROM votes o WHERE o.ticket_id = v.ticket_id
+              AND o.participant_id = pg_temp.pseudonym(b.id, v.participant_id));
+UPDATE votes v SET participant_id = pg_temp.pseudonym(b.id, v.participant_id)
+FROM tickets t JOIN columns c ON c.id = t.column_id JOIN boards b ON b.id = c.board_id
+WHERE v.ticket_id = t.id AND b.is_anonymous AND v.participant_id !~ '^[0-9a-f]{24}$';
+
+DELETE FROM meeting_ratings r USING boards b
+WHERE r.board_id = b.id AND b.is_anonymous AND r.participant_id !~ '^[0-9a-f]{24}$'
+  AND EXISTS (SELECT 1 FROM meeting_ratings o WHERE o.board_id = r.board_id
+              AND o.participant_id = pg_temp.pseudonym(b.id, r.participant_id));
+UPDATE meeting_ratings r SET participant_id = pg_temp.pseudonym(b.id, r.participant_id)
+FROM boards b
+WHERE r.board_id = b.id AND b.is_anonymous AND r.participant_id !~ '^[0-9a-f]{24}$';
+
+DELETE FROM board_editors e USING boards b
+WHERE e.board_id = b.id AND b.is_anonymous AND e.participant_id !~ '^[0-9a-f]{24}$'
+  AND EXISTS (SELECT 1 FROM board_editors o WHERE o.board_id = e.board_id
+              AND o.participant_id = pg_temp.pseudonym(b.id, e.participant_id));
+UPDATE board_editors e SET participant_id = pg_temp.pseudonym(b.id, e.participant_id)
+FROM boards b
+WHERE e.board_id = b.id AND b.is_anonymous AND e.participant_id !~ '^[0-9a-f]{24}$';
+
+DELETE FROM editor_requests e USING boards b
+WHERE e.board_id = b.id AND b.is_anonymous AND e.participant_id !~ '^[0-9a-f]{24}$'
+  AND EXISTS (SELECT 1 FROM editor_requests o WHERE o.board_id = e.board_id
+              AND o.participant_id = pg_temp.pseudonym(b.id, e.participant_id));
+UPDATE editor_requests e SET participant_id = pg_temp.pseudonym(b.id, e.participant_id)
+FROM boards b
+WHERE e.board_id = b.id AND b.is_anonymous AND e.participant_id !~ '^[0-9a-f]{24}$';
+
+UPDATE board_presence p SET participant_id = pg_temp.pseudonym(b.id, p.participant_id)
+FROM boards b
+WHERE p.board_id = b.id AND b.is_anonymous AND p.participant_id !~ '^[0-9a-f]{24}$';
+
+-- The history: who took each step, whose card it was, and the cards and comments as they stood,
+-- so that an undo puts back the pseudonym and not the id.
+UPDATE board_operations o SET
+    actor_id = pg_temp.pseudonym(b.id, o.actor_id),
+    author_id = CASE WHEN o.author_id IS NULL THEN NULL ELSE pg_temp.pseudonym(b.id, o.author_id) END,
+    changes = pg_temp.pseudonym_changes(b.id, o.changes)
+FROM boards b
+WHERE o.board_id = b.id AND b.is_anonymous;
+
+-- The audit log takes no change but a name let go (see `audit_log_append_only`). This is the one
+-- other it is made to take, once and here: the participant an entry names, and the participant it
+-- names as its target, go by their pseudonym, as every entry written since does. What was done,
+-- and when, stays as it was.
+ALTER TABLE audit_log DISABLE TRIGGER audit_log_append_only;
+UPDATE audit_log a SET
+    actor = CASE WHEN a.actor LIKE 'participant:%'
+                 THEN 'participant:' || pg_temp.pseudonym(b.id, substring(a.actor FROM 13))
+                 ELSE a.actor END,
+    target = CASE WHEN a.target LIKE 'participant:%'
+                  THEN 'participant:' || pg_temp.pseudonym(b.id, substring(a.target FROM 13))
+                  ELSE a.target END
+FROM boards b
+WHERE a.board_id = b.id AND b.is_anonymous
+  AND (a.actor LIKE 'participant:%' OR a.target LIKE 'participant:%');
+ALTER TABLE audit_log ENABLE TRIGGER audit_log_append_only;
+
+DROP FUNCTION pg_temp.pseudonym_changes(TEXT, JSONB);
+DROP FUNCTION pg_temp.pseudonym_record(TEXT, JSONB);
+DROP FUNCTION pg_temp.pseudonym(TEXT, TEXT);
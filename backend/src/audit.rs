@@ -0,0 +1,163 @@
+//! The audit log: who removed a card, revealed a board, changed its password, or changed anything
+//! as an admin.
+//!
+//! An entry is written after the change it records has gone through. A log that cannot be written
+//! to says so in the server log and stops nothing: the change is already made, and a meeting does
+//! not wait on the audit table.
+
+use tracing::warn;
+
+use crate::auth::Owner;
+use crate::db;
+use crate::models::{ApiCaller, AuditAction};
+use crate::state::AppState;
+
+/// Who made a change, as the audit log names them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Actor {
+    pub id: String,
+    pub name: Option<String>,
+}
+
+impl Actor {
+    /// Someone on a board, by the id their browser keeps, and the name they go by there. An
+    /// anonymous board has no name to give.
+    pub fn participant(participant_id: &str, name: &str) -> Self {
+        Actor {
+            id: format!("participant:{participant_id}"),
+            name: (!name.is_empty()).then(|| name.to_string()),
+        }
+    }
+
+    /// The caller of a board route. A token is named before the account it acts for, so that the
+    /// entry tells a script from its owner; an editor is named by the participant id they sent.
+    /// The facilitator token and the facilitator cookie name nobody, and the entry says only that
+    /// it was the facilitator.
+    pub fn owner(owner: &Owner, participant_id: Option<&str>) -> Self {
+        let id = if let Some(token_id) = &owner.token_id {
+            format!("token:{token_id}")
+        } else if let Some(sub) = &owner.sub {
+            format!("account:{sub}")
+        } else if let Some(participant_id) = participant_id.filter(|p| !p.is_empty()) {
+            format!("participant:{participant_id}")
+        } else {
+            "facilitator".to_string()
+        };
+        Actor { id, name: None }
+    }
+
+    /// A script with a personal API token.
+    pub fn token(caller: &ApiCaller) -> Self {
+        Actor {
+            id: format!("token:{}", caller.token_id),
+            name: Some(caller.name.clone()),
+        }
+    }
+
+    /// An admin on their own account.
+    pub fn admin(account: &str) -> Self {
+        Actor {
+            id: format!("admin:{account}"),
+            name: None,
+        }
+    }
+
+    /// The shared admin token. It names nobody; the account signed in beside it, if any, is kept
+    /// as its name.
+    pub fn admin_token(signed_in_as: Option<&str>) -> Self {
+        Actor {
+            id: "admin token".to_string(),
+            name: signed_in_as.map(str::to_string),
+        }
+    }
+}
+
+/// What a change does to a card or a comment, as `is_audited` weighs it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Touch {
+    /// Takes it off the board.
+    Remove,
+    /// Changes what it says or where it stands, or puts it back.
+    Edit,
+}
+
+/// The one rule for the cards and comments of a board, whether a message or an undo or redo made
+/// the change. A removal is always kept: nothing is left on the board to show it happened. An edit
+/// is kept only when someone other than the author makes it, since an author editing their own
+/// words is writing. `author_id` is None for work open to anyone on the board, such as a move,
+/// and then an edit is not kept.
+pub fn is_audited(touch: Touch, author_id: Option<&str>, participant_id: &str) -> bool {
+    match touch {
+        Touch::Remove => true,
+        Touch::Edit => author_id.is_some_and(|author_id| author_id != participant_id),
+    }
+}
+
+/// Appends an entry to the log. `board_id` is None for an admin change that is to no one board.
+pub async fn record(
+    state: &AppState,
+    board_id: Option<&str>,
+    actor: &Actor,
+    action: AuditAction,
+    target: Option<&str>,
+    detail: Option<&str>,
+) {
+    if let Err(e) = db::insert_audit_entry(
+        &state.db,
+        board_id,
+        &actor.id,
+        actor.name.as_deref(),
+        action,
+        target,
+        detail,
+    )
+    .await
+    {
+        warn!(
+            action = action.as_str(),
+            actor = actor.id,
+            board_id,
+            "Failed to write the audit log: {e}"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_removal_is_always_kept_and_an_edit_only_of_the_work_of_another() {
+        assert!(is_audited(Touch::Remove, Some("p1"), "p1"));
+        assert!(is_audited(Touch::Remove, None, "p1"));
+        assert!(is_audited(Touch::Edit, Some("p2"), "p1"));
+        assert!(!is_audited(Touch::Edit, Some("p1"), "p1"));
+        assert!(!is_audited(Touch::Edit, None, "p1"));
+    }
+
+    #[test]
+    fn a_token_is_named_before_the_account_it_acts_for() {
+        let owner = Owner {
+            sub: Some("alice".to_string()),
+            token_id: Some("t1".to_string()),
+            ..Owner::default()
+        };
+        assert_eq!(Actor::owner(&owner, Some("p1")).id, "token:t1");
+
+        let owner = Owner {
+            sub: Some("alice".to_string()),
+            ..Owner::default()
+        };
+        assert_eq!(Actor::owner(&owner, Some("p1")).id, "account:alice");
+    }
+
+    #[test]
+    fn the_facilitator_cookie_is_not_written_down() {
+        let owner = Owner {
+            facilitator_id: Some("the-cookie".to_string()),
+            ..Owner::default()
+        };
+        assert_eq!(Actor::owner(&owner, Some("p1")).id, "participant:p1");
+        assert_eq!(Actor::owner(&owner, None).id, "facilitator");
+    }
+}
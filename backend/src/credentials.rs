@@ -0,0 +1,120 @@
+//! The credential that goes with a participant id.
+//!
+//! A participant id is no secret: the editors of a board are listed by it, and so are the votes.
+//! What makes someone that participant — the author of their cards, an editor if they were made
+//! one — is the credential the socket gave them with it, the first time they joined. It is the
+//! HMAC of the board and the id under a key only the server holds, so it cannot be made up from
+//! the id, and the one for a board opens no other board.
+//!
+//! The key lives in the database (see `db::server_key`), so that every replica checks what any of
+//! them gave out, and a credential outlives a restart.
+//!
+//! The same key names the people of an anonymous board. Such a board never holds the id a browser
+//! was given: it holds a hash of it, keyed and of that board alone, on the cards, the comments, the
+//! votes and everywhere else. The author of a card is still known by it, so they keep the card,
+//! and nobody who reads two boards, or the database under them, can tell the same person on both.
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::state::AppState;
+
+/// The name the key is kept under.
+pub const KEY_NAME: &str = "participant";
+
+/// What a hash is for. A pseudonym goes out to everyone on the board, so it must never be the
+/// credential of the same id.
+const CREDENTIAL: &[u8] = b"credential";
+const PSEUDONYM: &[u8] = b"pseudonym";
+
+/// How much of the hash makes a pseudonym, in bytes. Plenty to keep the people of one board apart.
+const PSEUDONYM_BYTES: usize = 12;
+
+/// The credential of `participant_id` on `board_id`, in hex.
+pub fn issue(key: &str, board_id: &str, participant_id: &str) -> String {
+    hex::encode(mac(key, CREDENTIAL, board_id, participant_id).finalize().into_bytes())
+}
+
+/// The id `participant_id` goes by on `board_id`: a pseudonym of it on an anonymous board, and the
+/// id itself on any other.
+pub fn author_id(key: &str, board_id: &str, participant_id: &str, anonymous: bool) -> String {
+    if !anonymous {
+        return participant_id.to_string();
+    }
+    let hash = mac(key, PSEUDONYM, board_id, participant_id).finalize().into_bytes();
+    hex::encode(&hash[..PSEUDONYM_BYTES])
+}
+
+/// Whether `credential` is the one given out for `participant_id` on `board_id`. The comparison
+/// takes the same time however much of it was right.
+pub fn verify(key: &str, board_id: &str, participant_id: &str, credential: &str) -> bool {
+    let Ok(given) = hex::decode(credential) else {
+        return false;
+    };
+    mac(key, CREDENTIAL, board_id, participant_id)
+        .verify_slice(&given)
+        .is_ok()
+}
+
+/// The participant id a caller claims, if the credential they sent with it is its own. A caller who
+/// sends an id without one, or with another's, is nobody in particular.
+pub fn verified<'a>(
+    state: &AppState,
+    board_id: &str,
+    participant_id: Option<&'a str>,
+    credential: Option<&str>,
+) -> Option<&'a str> {
+    let participant_id = participant_id.filter(|p| !p.is_empty())?;
+    verify(&state.participant_key, board_id, participant_id, credential?).then_some(participant_id)
+}
+
+fn mac(key: &str, purpose: &[u8], board_id: &str, participant_id: &str) -> Hmac<Sha256> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(key.as_bytes()).expect("HMAC takes a key of any length");
+    // A byte that no id holds, so that no other board and id run together into the same text.
+    mac.update(purpose);
+    mac.update(b"\0");
+    mac.update(board_id.as_bytes());
+    mac.update(b"\0");
+    mac.update(participant_id.as_bytes());
+    mac
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_credential_resumes_its_own_id_on_its_own_board() {
+        let credential = issue("k", "board1", "p1");
+        assert!(verify("k", "board1", "p1", &credential));
+        assert!(!verify("k", "board1", "p2", &credential));
+        assert!(!verify("k", "board2", "p1", &credential));
+        assert!(!verify("another key", "board1", "p1", &credential));
+    }
+
+    #[test]
+    fn a_credential_that_was_not_given_out_is_refused() {
+        let credential = issue("k", "board1", "p1");
+        assert!(!verify("k", "board1", "p1", ""));
+        assert!(!verify("k", "board1", "p1", "not hex"));
+        assert!(!verify("k", "board1", "p1", &credential[..62]));
+
+        let mut tampered = credential.clone();
+        let last = if tampered.pop() == Some('0') { '1' } else { '0' };
+        tampered.push(last);
+        assert!(!verify("k", "board1", "p1", &tampered));
+    }
+
+    #[test]
+    fn an_anonymous_board_knows_a_participant_by_a_pseudonym_of_its_own() {
+        let here = author_id("k", "board1", "p1", true);
+        assert_eq!(here, author_id("k", "board1", "p1", true));
+        assert_ne!(here, "p1");
+        assert_ne!(here, author_id("k", "board2", "p1", true));
+        assert_ne!(here, author_id("k", "board1", "p2", true));
+        // What goes out to the board is not what lets someone in as its owner.
+        assert!(!issue("k", "board1", "p1").starts_with(&here));
+        assert_eq!(author_id("k", "board1", "p1", false), "p1");
+    }
+}
@@ -1,21 +1,27 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use nanoid::nanoid;
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgPool};
 use std::collections::HashSet;
 
 use crate::models::{
-    is_previous_actions_name, ActionSourceBoard, ApplyTemplateResult, Board, Column, Comment,
-    EditorRequestView, EditorView, Gif, ImportResult, LabelCount, MeetingRatingView,
-    ScorecardMetric, Ticket, DONE_COLUMN_ROLES, ROLE_ACTIONS, ROLE_PREVIOUS_ACTIONS, ROLE_ROCKS,
-    TEMPLATE_LEVEL10,
+    is_previous_actions_name, parse_phases, phase_names, ActionCard, ActionSourceBoard, ApiCaller,
+    ApiTokenView, AuditAction, AuditEntry,
+    ApplyTemplateResult, Assignee, Board, BoardSettings, CardRecord, Changes, Column, Comment,
+    CommentRecord, EditorRequestView, EditorView, Gif, ImportResult, LabelCount,
+    MeetingRatingView, Operation, OperationKind, PasswordLockout, Phase, Record, RecordChange,
+    RetentionAction, RetentionCandidate, RetentionPolicy, RetentionRecord, ScorecardMetric,
+    Snapshot, Ticket, TimerAction, TokenScope, UnlockAttempt, UnlockTally, WebhookDelivery, WebhookView,
+    DONE_COLUMN_ROLES, ROLE_ACTIONS, ROLE_PREVIOUS_ACTIONS, ROLE_ROCKS, TEMPLATE_LEVEL10,
+    UNLOCK_FORGET_AFTER_HOURS, UNLOCK_FREE_TRIES_PER_ADDRESS, UNLOCK_FREE_TRIES_PER_BOARD,
+    UNLOCK_LONGEST_BOARD_WAIT_SECONDS, UNLOCK_LONGEST_WAIT_SECONDS,
 };
-use crate::state::MergeSnapshot;
 
 /// The columns that every read of a card asks for, in one place so that a new column
 /// reaches every query at once.
 const TICKET_COLUMNS: &str = "id, column_id, content, author_id, author_name, created_at, \
      carried_from_board_id, carried_from_board_title, \
-     gif_id, gif_url, gif_still_url, gif_width, gif_height, gif_title, rock_status, done_at";
+     gif_id, gif_url, gif_still_url, gif_width, gif_height, gif_title, rock_status, done_at, \
+     assignee_id, assignee_team_id, assignee_name, due_on";
 
 /// The same list for a comment.
 const COMMENT_COLUMNS: &str = "id, ticket_id, content, author_id, author_name, created_at, \
@@ -29,6 +35,8 @@ pub async fn create_board(
     title: &str,
     facilitator_token: &str,
     facilitator_id: &str,
+    // The account of the person who made it, on a deployment that signs people in.
+    owner_sub: Option<&str>,
     columns: &[(String, String, Option<&str>)], // (id, name, role)
     created_at: DateTime<Utc>,
     is_anonymous: bool,
@@ -38,11 +46,13 @@ pub async fn create_board(
     access_token: &str,
     // How the board starts, which the template of the board decides.
     is_blurred: bool,
+    // The phases it runs through. The board starts at the first.
+    phases: &[Phase],
 ) -> Result<Board, sqlx::Error> {
     let mut tx = pool.begin().await?;
 
     sqlx::query(
-        "INSERT INTO boards (id, title, facilitator_token, facilitator_id, is_blurred, is_anonymous, created_at, template_id, password_hash, access_token) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+        "INSERT INTO boards (id, title, facilitator_token, facilitator_id, is_blurred, is_anonymous, created_at, template_id, password_hash, access_token, phases, phase, owner_sub) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)",
     )
     .bind(id)
     .bind(title)
@@ -54,6 +64,9 @@ pub async fn create_board(
     .bind(template_id)
     .bind(password_hash)
     .bind(access_token)
+    .bind(phase_names(phases))
+    .bind(phases.first().map(|p| p.as_str()))
+    .bind(owner_sub)
     .execute(&mut *tx)
     .await?;
 
@@ -101,17 +114,177 @@ pub async fn create_board(
         participants: Vec::new(),
         vote_limit_per_column: None,
         timer_end: None,
+        timer_action: None,
+        cards_locked: false,
+        phase: phases.first().copied(),
+        phases: phases.to_vec(),
+        archived_at: None,
         labels: labels.to_vec(),
         template_id: template_id.map(|t| t.to_string()),
         scorecard: Vec::new(),
         meeting_ratings: Vec::new(),
         has_password: password_hash.is_some(),
+        password_lockout: None,
+        revision: 0,
     })
 }
 
+/// Writes a whole board at once: the board, its columns and labels, every card with its votes
+/// and comments, the scorecard and the ratings. The import builds the board with fresh ids, and
+/// this lays it down in one transaction, so that a failure leaves no half of it behind.
+pub async fn import_board(
+    pool: &PgPool,
+    board: &Board,
+    access_token: &str,
+    owner_sub: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        "INSERT INTO boards (id, title, facilitator_token, facilitator_id, is_blurred, is_anonymous, \
+         hide_votes, vote_limit_per_column, created_at, template_id, access_token, phases, phase, \
+         owner_sub) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)",
+    )
+    .bind(&board.id)
+    .bind(&board.title)
+    .bind(&board.facilitator_token)
+    .bind(&board.facilitator_id)
+    .bind(board.is_blurred)
+    .bind(board.is_anonymous)
+    .bind(board.hide_votes)
+    .bind(board.vote_limit_per_column)
+    .bind(board.created_at)
+    .bind(&board.template_id)
+    .bind(access_token)
+    .bind(phase_names(&board.phases))
+    .bind(board.phase.map(Phase::as_str))
+    .bind(owner_sub)
+    .execute(&mut *tx)
+    .await?;
+
+    for (pos, column) in board.columns.iter().enumerate() {
+        sqlx::query(
+            "INSERT INTO columns (id, board_id, name, position, role) VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(&column.id)
+        .bind(&board.id)
+        .bind(&column.name)
+        .bind(pos as i32)
+        .bind(&column.role)
+        .execute(&mut *tx)
+        .await?;
+
+        for ticket in &column.tickets {
+            let (gid, gurl, gstill, gw, gh, gtitle) = gif_binds(ticket.gif.as_ref());
+            let (aid, ateam, aname) = assignee_binds(ticket.assignee.as_ref());
+            sqlx::query(
+                "INSERT INTO tickets (id, column_id, content, author_id, author_name, created_at, \
+                 carried_from_board_title, gif_id, gif_url, gif_still_url, gif_width, gif_height, \
+                 gif_title, rock_status, done_at, assignee_id, assignee_team_id, assignee_name, \
+                 due_on) \
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, \
+                 $17, $18, $19)",
+            )
+            .bind(&ticket.id)
+            .bind(&column.id)
+            .bind(&ticket.content)
+            .bind(&ticket.author_id)
+            .bind(&ticket.author_name)
+            .bind(ticket.created_at)
+            .bind(&ticket.carried_from_board_title)
+            .bind(gid)
+            .bind(gurl)
+            .bind(gstill)
+            .bind(gw)
+            .bind(gh)
+            .bind(gtitle)
+            .bind(&ticket.rock_status)
+            .bind(ticket.done_at)
+            .bind(aid)
+            .bind(ateam)
+            .bind(aname)
+            .bind(ticket.due_on)
+            .execute(&mut *tx)
+            .await?;
+
+            for voter_id in &ticket.votes {
+                sqlx::query("INSERT INTO votes (ticket_id, participant_id) VALUES ($1, $2)")
+                    .bind(&ticket.id)
+                    .bind(voter_id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+
+            for comment in &ticket.comments {
+                let (gid, gurl, gstill, gw, gh, gtitle) = gif_binds(comment.gif.as_ref());
+                sqlx::query(
+                    "INSERT INTO ticket_comments (id, ticket_id, content, author_id, author_name, \
+                     created_at, gif_id, gif_url, gif_still_url, gif_width, gif_height, gif_title) \
+                     VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)",
+                )
+                .bind(&comment.id)
+                .bind(&ticket.id)
+                .bind(&comment.content)
+                .bind(&comment.author_id)
+                .bind(&comment.author_name)
+                .bind(comment.created_at)
+                .bind(gid)
+                .bind(gurl)
+                .bind(gstill)
+                .bind(gw)
+                .bind(gh)
+                .bind(gtitle)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+    }
+
+    for label in &board.labels {
+        sqlx::query("INSERT INTO board_labels (board_id, label) VALUES ($1, $2) ON CONFLICT DO NOTHING")
+            .bind(&board.id)
+            .bind(label)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    for (pos, metric) in board.scorecard.iter().enumerate() {
+        sqlx::query(
+            "INSERT INTO scorecard_metrics (id, board_id, name, goal, actual, on_track, position) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        )
+        .bind(&metric.id)
+        .bind(&board.id)
+        .bind(&metric.name)
+        .bind(&metric.goal)
+        .bind(&metric.actual)
+        .bind(metric.on_track)
+        .bind(pos as i32)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    for rating in &board.meeting_ratings {
+        sqlx::query(
+            "INSERT INTO meeting_ratings (board_id, participant_id, rating) VALUES ($1, $2, $3) \
+             ON CONFLICT (board_id, participant_id) DO UPDATE SET rating = EXCLUDED.rating",
+        )
+        .bind(&board.id)
+        .bind(&rating.participant_id)
+        .bind(rating.rating)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
 pub async fn get_board(pool: &PgPool, board_id: &str) -> Result<Option<Board>, sqlx::Error> {
+    let _timer = crate::metrics::METRICS.get_board_seconds.start_timer();
     let row = sqlx::query_as::<_, BoardRow>(
-        "SELECT id, title, is_blurred, is_anonymous, hide_votes, facilitator_token, facilitator_id, created_at, vote_limit_per_column, timer_end, template_id, password_hash IS NOT NULL AS has_password FROM boards WHERE id = $1",
+        "SELECT id, title, is_blurred, is_anonymous, hide_votes, facilitator_token, facilitator_id, created_at, vote_limit_per_column, timer_end, timer_action, cards_locked, phase, phases, archived_at, template_id, password_hash IS NOT NULL AS has_password, revision FROM boards WHERE id = $1",
     )
     .bind(board_id)
     .fetch_optional(pool)
@@ -180,39 +353,19 @@ pub async fn get_board(pool: &PgPool, board_id: &str) -> Result<Option<Board>, s
     // Group comments by ticket_id. They keep the order of the query: oldest first.
     let mut comments_map: std::collections::HashMap<String, Vec<Comment>> =
         std::collections::HashMap::new();
-    for mut c in comment_rows {
-        let gif = c.take_gif();
-        comments_map.entry(c.ticket_id).or_default().push(Comment {
-            id: c.id,
-            content: c.content,
-            author_id: c.author_id,
-            author_name: c.author_name,
-            created_at: c.created_at,
-            gif,
-        });
+    for c in comment_rows {
+        let (ticket_id, comment) = comment_from_row(c);
+        comments_map.entry(ticket_id).or_default().push(comment);
     }
 
     // Group tickets by column_id
     let mut tickets_map: std::collections::HashMap<String, Vec<Ticket>> =
         std::collections::HashMap::new();
-    for mut t in ticket_rows {
+    for t in ticket_rows {
         let votes = votes_map.remove(&t.id).unwrap_or_default();
         let comments = comments_map.remove(&t.id).unwrap_or_default();
-        let gif = t.take_gif();
-        tickets_map.entry(t.column_id.clone()).or_default().push(Ticket {
-            id: t.id,
-            content: t.content,
-            author_id: t.author_id,
-            author_name: t.author_name,
-            votes,
-            created_at: t.created_at,
-            carried_from_board_id: t.carried_from_board_id,
-            carried_from_board_title: t.carried_from_board_title,
-            comments,
-            gif,
-            rock_status: t.rock_status,
-            done_at: t.done_at,
-        });
+        let (column_id, ticket) = ticket_from_row(t, votes, comments);
+        tickets_map.entry(column_id).or_default().push(ticket);
     }
 
     let columns = col_rows
@@ -229,6 +382,11 @@ pub async fn get_board(pool: &PgPool, board_id: &str) -> Result<Option<Board>, s
         .collect();
 
     let labels = get_board_labels(pool, board_id).await?;
+    let password_lockout = if board_row.has_password {
+        get_password_lockout(pool, board_id).await?
+    } else {
+        None
+    };
 
     // The scorecard and the ratings belong to a Level 10 board alone. Every other board would
     // pay for two more queries on each broadcast and read two empty lists.
@@ -255,14 +413,127 @@ pub async fn get_board(pool: &PgPool, board_id: &str) -> Result<Option<Board>, s
         participants: Vec::new(),
         vote_limit_per_column: board_row.vote_limit_per_column,
         timer_end: board_row.timer_end,
+        timer_action: board_row.timer_action.as_deref().and_then(TimerAction::parse),
+        cards_locked: board_row.cards_locked,
+        phase: board_row.phase.as_deref().and_then(Phase::parse),
+        phases: parse_phases(&board_row.phases),
+        archived_at: board_row.archived_at,
         labels,
         template_id: board_row.template_id,
         scorecard,
         meeting_ratings,
         has_password: board_row.has_password,
+        password_lockout,
+        revision: board_row.revision,
+    }))
+}
+
+/// Reads one card as a board would hold it, with its votes and its comments, and the column it
+/// sits in. This is what a patch sends in place of the whole board.
+pub async fn get_ticket(
+    pool: &PgPool,
+    ticket_id: &str,
+) -> Result<Option<(String, Ticket)>, sqlx::Error> {
+    let row = sqlx::query_as::<_, TicketRow>(&format!(
+        "SELECT {TICKET_COLUMNS} FROM tickets WHERE id = $1"
+    ))
+    .bind(ticket_id)
+    .fetch_optional(pool)
+    .await?;
+
+    let Some(row) = row else {
+        return Ok(None);
+    };
+
+    let votes = get_ticket_votes(pool, ticket_id).await?;
+
+    let comments = sqlx::query_as::<_, CommentRow>(&format!(
+        "SELECT {COMMENT_COLUMNS} FROM ticket_comments WHERE ticket_id = $1 ORDER BY created_at"
+    ))
+    .bind(ticket_id)
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|c| comment_from_row(c).1)
+    .collect();
+
+    Ok(Some(ticket_from_row(row, votes, comments)))
+}
+
+/// Everyone who votes for one card.
+pub async fn get_ticket_votes(
+    pool: &PgPool,
+    ticket_id: &str,
+) -> Result<HashSet<String>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, VoteRow>(
+        "SELECT ticket_id, participant_id FROM votes WHERE ticket_id = $1",
+    )
+    .bind(ticket_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(|v| v.participant_id).collect())
+}
+
+/// Reads the settings of a board, for a patch that changed one of them.
+pub async fn get_board_settings(
+    pool: &PgPool,
+    board_id: &str,
+) -> Result<Option<BoardSettings>, sqlx::Error> {
+    let row = sqlx::query_as::<_, BoardSettingsRow>(
+        "SELECT title, hide_votes, vote_limit_per_column, timer_end, timer_action, cards_locked, \
+         phase, phases, archived_at, password_hash IS NOT NULL AS has_password FROM boards \
+         WHERE id = $1",
+    )
+    .bind(board_id)
+    .fetch_optional(pool)
+    .await?;
+
+    let Some(row) = row else {
+        return Ok(None);
+    };
+
+    Ok(Some(BoardSettings {
+        title: row.title,
+        hide_votes: row.hide_votes,
+        vote_limit_per_column: row.vote_limit_per_column,
+        timer_end: row.timer_end,
+        timer_action: row.timer_action.as_deref().and_then(TimerAction::parse),
+        cards_locked: row.cards_locked,
+        phase: row.phase.as_deref().and_then(Phase::parse),
+        phases: parse_phases(&row.phases),
+        archived_at: row.archived_at,
+        labels: get_board_labels(pool, board_id).await?,
+        has_password: row.has_password,
+        password_lockout: if row.has_password {
+            get_password_lockout(pool, board_id).await?
+        } else {
+            None
+        },
     }))
 }
 
+/// Takes the next revision of a board and gives it back. None when there is no such board.
+///
+/// The count goes up in the row itself, so two changes that land at once still take two numbers.
+/// A change that `touches` the board also moves its last activity, which the retention policy
+/// reads; people coming and going do not.
+pub async fn next_revision(
+    pool: &PgPool,
+    board_id: &str,
+    touches: bool,
+) -> Result<Option<i64>, sqlx::Error> {
+    let row = sqlx::query_as::<_, RevisionRow>(
+        "UPDATE boards SET revision = revision + 1, \
+         last_activity_at = CASE WHEN $2 THEN now() ELSE last_activity_at END \
+         WHERE id = $1 RETURNING revision",
+    )
+    .bind(board_id)
+    .bind(touches)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.map(|r| r.revision))
+}
+
 pub async fn get_board_facilitator_token(
     pool: &PgPool,
     board_id: &str,
@@ -323,6 +594,12 @@ pub async fn set_board_password(
     .execute(pool)
     .await?;
 
+    // The wrong tries were wrong about the old password. The new one starts with a clean tally.
+    sqlx::query("DELETE FROM unlock_failures WHERE board_id = $1")
+        .bind(board_id)
+        .execute(pool)
+        .await?;
+
     Ok(result.rows_affected() > 0)
 }
 
@@ -354,9 +631,38 @@ pub async fn get_board_facilitator_id(
     Ok(row.and_then(|r| r.facilitator_id))
 }
 
-pub async fn get_boards_by_facilitator_id(
+/// Whether the board was made by this account.
+pub async fn is_board_owner(pool: &PgPool, board_id: &str, sub: &str) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query_as::<_, OwnerSubRow>("SELECT owner_sub FROM boards WHERE id = $1")
+        .bind(board_id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(row.and_then(|r| r.owner_sub).as_deref() == Some(sub))
+}
+
+/// Ties the boards this browser made, and no account holds yet, to the account that just signed
+/// in on it. The boards made before sign-in was turned on follow the person from then on.
+pub async fn claim_boards(
     pool: &PgPool,
     facilitator_id: &str,
+    sub: &str,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE boards SET owner_sub = $2 WHERE facilitator_id = $1 AND owner_sub IS NULL",
+    )
+    .bind(facilitator_id)
+    .bind(sub)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// The boards of a person: those of their account when they signed in, or else those of the
+/// facilitator cookie of their browser.
+pub async fn get_boards_by_owner(
+    pool: &PgPool,
+    facilitator_id: Option<&str>,
+    owner_sub: Option<&str>,
 ) -> Result<Vec<crate::models::MyBoardSummary>, sqlx::Error> {
     let rows = sqlx::query_as::<_, MyBoardRow>(
         r#"
@@ -365,15 +671,17 @@ pub async fn get_boards_by_facilitator_id(
             b.title,
             b.created_at,
             b.is_anonymous,
+            b.archived_at,
             (SELECT COUNT(*) FROM columns c WHERE c.board_id = b.id) AS column_count,
             (SELECT COUNT(*) FROM tickets t JOIN columns c ON t.column_id = c.id WHERE c.board_id = b.id) AS ticket_count,
             COALESCE((SELECT array_agg(l.label ORDER BY l.label) FROM board_labels l WHERE l.board_id = b.id), '{}'::text[]) AS labels
         FROM boards b
-        WHERE b.facilitator_id = $1
+        WHERE CASE WHEN $2::text IS NOT NULL THEN b.owner_sub = $2 ELSE b.facilitator_id = $1 END
         ORDER BY b.created_at DESC
         "#,
     )
     .bind(facilitator_id)
+    .bind(owner_sub)
     .fetch_all(pool)
     .await?;
 
@@ -387,6 +695,7 @@ pub async fn get_boards_by_facilitator_id(
             ticket_count: r.ticket_count,
             is_anonymous: r.is_anonymous,
             labels: r.labels,
+            archived_at: r.archived_at,
         })
         .collect())
 }
@@ -395,7 +704,7 @@ pub async fn get_boards_by_facilitator_id(
 
 pub async fn list_templates(pool: &PgPool) -> Result<Vec<crate::models::Template>, sqlx::Error> {
     let rows = sqlx::query_as::<_, TemplateRow>(
-        "SELECT id, name, description, columns, default_blurred, position FROM templates ORDER BY position",
+        "SELECT id, name, description, columns, default_blurred, position, phases FROM templates ORDER BY position",
     )
     .fetch_all(pool)
     .await?;
@@ -410,7 +719,7 @@ pub async fn get_template(
     template_id: &str,
 ) -> Result<Option<crate::models::Template>, sqlx::Error> {
     let row = sqlx::query_as::<_, TemplateRow>(
-        "SELECT id, name, description, columns, default_blurred, position FROM templates WHERE id = $1",
+        "SELECT id, name, description, columns, default_blurred, position, phases FROM templates WHERE id = $1",
     )
     .bind(template_id)
     .fetch_optional(pool)
@@ -427,27 +736,24 @@ fn template_from_row(r: TemplateRow) -> crate::models::Template {
         columns: r.columns,
         default_blurred: r.default_blurred,
         position: r.position,
+        phases: parse_phases(&r.phases),
     }
 }
 
 pub async fn create_template(
     pool: &PgPool,
-    id: &str,
-    name: &str,
-    description: &str,
-    columns: &[String],
-    position: i32,
-    default_blurred: bool,
+    template: &crate::models::Template,
 ) -> Result<(), sqlx::Error> {
     sqlx::query(
-        "INSERT INTO templates (id, name, description, columns, position, default_blurred) VALUES ($1, $2, $3, $4, $5, $6)",
+        "INSERT INTO templates (id, name, description, columns, position, default_blurred, phases) VALUES ($1, $2, $3, $4, $5, $6, $7)",
     )
-    .bind(id)
-    .bind(name)
-    .bind(description)
-    .bind(columns)
-    .bind(position)
-    .bind(default_blurred)
+    .bind(&template.id)
+    .bind(&template.name)
+    .bind(&template.description)
+    .bind(&template.columns)
+    .bind(template.position)
+    .bind(template.default_blurred)
+    .bind(phase_names(&template.phases))
     .execute(pool)
     .await?;
     Ok(())
@@ -455,22 +761,18 @@ pub async fn create_template(
 
 pub async fn update_template(
     pool: &PgPool,
-    id: &str,
-    name: &str,
-    description: &str,
-    columns: &[String],
-    position: i32,
-    default_blurred: bool,
+    template: &crate::models::Template,
 ) -> Result<bool, sqlx::Error> {
     let result = sqlx::query(
-        "UPDATE templates SET name = $1, description = $2, columns = $3, position = $4, default_blurred = $5 WHERE id = $6",
+        "UPDATE templates SET name = $1, description = $2, columns = $3, position = $4, default_blurred = $5, phases = $6 WHERE id = $7",
     )
-    .bind(name)
-    .bind(description)
-    .bind(columns)
-    .bind(position)
-    .bind(default_blurred)
-    .bind(id)
+    .bind(&template.name)
+    .bind(&template.description)
+    .bind(&template.columns)
+    .bind(template.position)
+    .bind(template.default_blurred)
+    .bind(phase_names(&template.phases))
+    .bind(&template.id)
     .execute(pool)
     .await?;
     Ok(result.rows_affected() > 0)
@@ -834,9 +1136,10 @@ pub async fn edit_ticket(
 /// Puts a card into a different column of the same board. The votes stay with the card.
 /// Moves a card to another column of the same board.
 ///
-/// A mark belongs to the column the card sits in: a done mark to an action column, a rock status
-/// to the Rocks column. The server refuses to set either one anywhere else, so a card that leaves
-/// such a column leaves the mark behind rather than carrying a mark nothing can clear.
+/// A mark belongs to the column the card sits in: a done mark, an owner and a due day to an
+/// action column, a rock status to the Rocks column. The server refuses to set any of them
+/// anywhere else, so a card that leaves such a column leaves the mark behind rather than carrying
+/// a mark nothing can clear.
 pub async fn move_ticket(
     pool: &PgPool,
     ticket_id: &str,
@@ -856,6 +1159,10 @@ pub async fn move_ticket(
     sqlx::query(
         "UPDATE tickets SET column_id = $1, \
          done_at = CASE WHEN $3 THEN done_at ELSE NULL END, \
+         assignee_id = CASE WHEN $3 THEN assignee_id ELSE NULL END, \
+         assignee_team_id = CASE WHEN $3 THEN assignee_team_id ELSE NULL END, \
+         assignee_name = CASE WHEN $3 THEN assignee_name ELSE NULL END, \
+         due_on = CASE WHEN $3 THEN due_on ELSE NULL END, \
          rock_status = CASE WHEN $4 THEN rock_status ELSE NULL END \
          WHERE id = $2",
     )
@@ -984,6 +1291,20 @@ pub async fn get_comment_author_on_board(
     Ok(row.map(|r| r.author_id))
 }
 
+/// Gives the card a comment hangs on.
+pub async fn get_comment_ticket_id(
+    pool: &PgPool,
+    comment_id: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    let row = sqlx::query_as::<_, CommentTicketRow>(
+        "SELECT ticket_id FROM ticket_comments WHERE id = $1",
+    )
+    .bind(comment_id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.map(|r| r.ticket_id))
+}
+
 // --- Votes ---
 
 pub async fn toggle_vote(
@@ -1015,7 +1336,7 @@ pub async fn merge_tickets(
     pool: &PgPool,
     source_id: &str,
     target_id: &str,
-) -> Result<Option<MergeSnapshot>, sqlx::Error> {
+) -> Result<bool, sqlx::Error> {
     let mut tx = pool.begin().await?;
 
     // Fetch both tickets
@@ -1035,10 +1356,13 @@ pub async fn merge_tickets(
 
     let (mut source, mut target) = match (source, target) {
         (Some(s), Some(t)) => (s, t),
-        _ => return Ok(None),
+        _ => return Ok(false),
     };
     let source_gif = source.take_gif();
     let target_gif = target.take_gif();
+    let source_assignee = source.take_assignee();
+    let target_assignee = target.take_assignee();
+    let target_holds_actions = column_holds_actions(&mut tx, &target.column_id).await?;
 
     // Fetch source votes
     let source_votes: Vec<VoteRow> =
@@ -1064,11 +1388,24 @@ pub async fn merge_tickets(
     // source, so that the picture is not lost with the card it came on.
     let merged_gif = target_gif.clone().or_else(|| source_gif.clone());
 
+    // The owner and the due day go the same way, as long as the target is an action that can
+    // hold them.
+    let (merged_assignee, merged_due_on) = if target_holds_actions {
+        (
+            target_assignee.or(source_assignee),
+            target.due_on.or(source.due_on),
+        )
+    } else {
+        (None, None)
+    };
+
     // Update target content
     let (gid, gurl, gstill, gw, gh, gtitle) = gif_binds(merged_gif.as_ref());
+    let (aid, ateam, aname) = assignee_binds(merged_assignee.as_ref());
     sqlx::query(
         "UPDATE tickets SET content = $1, gif_id = $2, gif_url = $3, gif_still_url = $4, \
-         gif_width = $5, gif_height = $6, gif_title = $7 WHERE id = $8",
+         gif_width = $5, gif_height = $6, gif_title = $7, assignee_id = $9, \
+         assignee_team_id = $10, assignee_name = $11, due_on = $12 WHERE id = $8",
     )
     .bind(&combined)
     .bind(gid)
@@ -1078,6 +1415,10 @@ pub async fn merge_tickets(
     .bind(gh)
     .bind(gtitle)
     .bind(target_id)
+    .bind(aid)
+    .bind(ateam)
+    .bind(aname)
+    .bind(merged_due_on)
     .execute(&mut *tx)
     .await?;
 
@@ -1106,95 +1447,7 @@ pub async fn merge_tickets(
         .await?;
 
     tx.commit().await?;
-
-    Ok(Some(MergeSnapshot {
-        source_id: source.id,
-        source_column_id: source.column_id,
-        source_content: source.content,
-        source_author_id: source.author_id,
-        source_author_name: source.author_name,
-        source_created_at: source.created_at,
-        source_votes: source_vote_ids,
-        source_carried_from_board_id: source.carried_from_board_id,
-        source_carried_from_board_title: source.carried_from_board_title,
-        source_comment_ids,
-        source_gif,
-        // The target keeps its own mark. The one from the source card waits here for the undo.
-        source_rock_status: source.rock_status,
-        source_done_at: source.done_at,
-        target_id: target.id,
-        target_original_content: target.content,
-        target_original_gif: target_gif,
-    }))
-}
-
-pub async fn undo_merge(pool: &PgPool, snapshot: &MergeSnapshot) -> Result<(), sqlx::Error> {
-    let mut tx = pool.begin().await?;
-
-    // Restore target's original content, and with it the GIF the target had before the merge
-    let (gid, gurl, gstill, gw, gh, gtitle) = gif_binds(snapshot.target_original_gif.as_ref());
-    sqlx::query(
-        "UPDATE tickets SET content = $1, gif_id = $2, gif_url = $3, gif_still_url = $4, \
-         gif_width = $5, gif_height = $6, gif_title = $7 WHERE id = $8",
-    )
-    .bind(&snapshot.target_original_content)
-    .bind(gid)
-    .bind(gurl)
-    .bind(gstill)
-    .bind(gw)
-    .bind(gh)
-    .bind(gtitle)
-    .bind(&snapshot.target_id)
-    .execute(&mut *tx)
-    .await?;
-
-    // Re-create source ticket, GIF, rock status, done mark and all
-    let (gid, gurl, gstill, gw, gh, gtitle) = gif_binds(snapshot.source_gif.as_ref());
-    sqlx::query(
-        "INSERT INTO tickets (id, column_id, content, author_id, author_name, created_at, \
-         carried_from_board_id, carried_from_board_title, \
-         gif_id, gif_url, gif_still_url, gif_width, gif_height, gif_title, rock_status, done_at) \
-         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)",
-    )
-    .bind(&snapshot.source_id)
-    .bind(&snapshot.source_column_id)
-    .bind(&snapshot.source_content)
-    .bind(&snapshot.source_author_id)
-    .bind(&snapshot.source_author_name)
-    .bind(snapshot.source_created_at)
-    .bind(&snapshot.source_carried_from_board_id)
-    .bind(&snapshot.source_carried_from_board_title)
-    .bind(gid)
-    .bind(gurl)
-    .bind(gstill)
-    .bind(gw)
-    .bind(gh)
-    .bind(gtitle)
-    .bind(&snapshot.source_rock_status)
-    .bind(snapshot.source_done_at)
-    .execute(&mut *tx)
-    .await?;
-
-    // Re-create source votes
-    for voter_id in &snapshot.source_votes {
-        sqlx::query("INSERT INTO votes (ticket_id, participant_id) VALUES ($1, $2) ON CONFLICT DO NOTHING")
-            .bind(&snapshot.source_id)
-            .bind(voter_id)
-            .execute(&mut *tx)
-            .await?;
-    }
-
-    // Send the comments of the source card back to it. The card exists again by now.
-    if !snapshot.source_comment_ids.is_empty() {
-        sqlx::query("UPDATE ticket_comments SET ticket_id = $1 WHERE id = ANY($2)")
-            .bind(&snapshot.source_id)
-            .bind(&snapshot.source_comment_ids)
-            .execute(&mut *tx)
-            .await?;
-    }
-
-    tx.commit().await?;
-    Ok(())
+    Ok(true)
 }
 
 // --- Split ---
@@ -1216,9 +1469,10 @@ pub async fn split_ticket(
     .fetch_optional(&mut *tx)
     .await?;
 
-    let Some(ticket) = ticket else {
+    let Some(mut ticket) = ticket else {
         return Ok(false);
     };
+    let assignee = ticket.take_assignee();
 
     let segments: Vec<&str> = ticket.content.split("\n---\n").collect();
     if segments.len() < 2 || segment_index >= segments.len() {
@@ -1241,9 +1495,13 @@ pub async fn split_ticket(
         .execute(&mut *tx)
         .await?;
 
-    // Insert new ticket with the extracted segment. It keeps the source board of the original.
+    // Insert new ticket with the extracted segment. It keeps the source board of the original,
+    // and the owner and due day of an action, since the part was taken on along with the whole.
+    let (aid, ateam, aname) = assignee_binds(assignee.as_ref());
     sqlx::query(
-        "INSERT INTO tickets (id, column_id, content, author_id, author_name, created_at, carried_from_board_id, carried_from_board_title) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+        "INSERT INTO tickets (id, column_id, content, author_id, author_name, created_at, \
+         carried_from_board_id, carried_from_board_title, assignee_id, assignee_team_id, \
+         assignee_name, due_on) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)",
     )
     .bind(new_ticket_id)
     .bind(&ticket.column_id)
@@ -1253,6 +1511,10 @@ pub async fn split_ticket(
     .bind(Utc::now())
     .bind(&ticket.carried_from_board_id)
     .bind(&ticket.carried_from_board_title)
+    .bind(aid)
+    .bind(ateam)
+    .bind(aname)
+    .bind(ticket.due_on)
     .execute(&mut *tx)
     .await?;
 
@@ -1329,39 +1591,212 @@ pub async fn column_belongs_to_board(
     Ok(row.count > 0)
 }
 
-// --- Admin ---
+// --- Columns ---
 
-pub async fn admin_global_stats(pool: &PgPool) -> Result<AdminStatsRow, sqlx::Error> {
-    sqlx::query_as::<_, AdminStatsRow>(
-        r#"
-        SELECT
-            (SELECT COUNT(*) FROM boards) AS board_count,
-            (SELECT COUNT(*) FROM tickets) AS ticket_count,
-            (SELECT COUNT(*) FROM votes) AS vote_count
-        "#,
-    )
-    .fetch_one(pool)
-    .await
-}
+/// Adds a column to a live board, just before Actions, which stays last. On a Level 10 board
+/// that has lost its Rocks column, a new column named "rocks" takes the role back, as it would
+/// at creation; a second one is a column like any other.
+pub async fn add_column(
+    pool: &PgPool,
+    board_id: &str,
+    column_id: &str,
+    name: &str,
+    is_level10: bool,
+) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
 
-pub async fn admin_list_boards(pool: &PgPool) -> Result<Vec<AdminBoardRow>, sqlx::Error> {
-    sqlx::query_as::<_, AdminBoardRow>(
-        r#"
-        SELECT
-            b.id,
-            b.title,
-            b.is_blurred,
-            b.created_at,
-            (SELECT COUNT(*) FROM columns c WHERE c.board_id = b.id) AS column_count,
-            (SELECT COUNT(*) FROM tickets t JOIN columns c ON t.column_id = c.id WHERE c.board_id = b.id) AS ticket_count,
-            (SELECT COUNT(*) FROM votes v JOIN tickets t ON v.ticket_id = t.id JOIN columns c ON t.column_id = c.id WHERE c.board_id = b.id) AS vote_count
-        FROM boards b
-        ORDER BY b.created_at DESC
-        "#,
+    let columns = sqlx::query_as::<_, ColumnRow>(
+        "SELECT id, name, position, role FROM columns WHERE board_id = $1 ORDER BY position \
+         FOR UPDATE",
     )
-    .fetch_all(pool)
-    .await
-}
+    .bind(board_id)
+    .fetch_all(&mut *tx)
+    .await?;
+    let Some(actions) = columns
+        .iter()
+        .find(|c| c.role.as_deref() == Some(ROLE_ACTIONS))
+    else {
+        return Ok(false);
+    };
+    let position = actions.position;
+
+    let takes_rocks = is_level10
+        && name.trim().eq_ignore_ascii_case("rocks")
+        && !columns.iter().any(|c| c.role.as_deref() == Some(ROLE_ROCKS));
+
+    sqlx::query("UPDATE columns SET position = position + 1 WHERE board_id = $1 AND position >= $2")
+        .bind(board_id)
+        .bind(position)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        "INSERT INTO columns (id, board_id, name, position, role) VALUES ($1, $2, $3, $4, $5)",
+    )
+    .bind(column_id)
+    .bind(board_id)
+    .bind(name)
+    .bind(position)
+    .bind(takes_rocks.then_some(ROLE_ROCKS))
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// Renames a column of the board. A column with a role keeps the name that goes with it, so the
+/// WHERE leaves those out.
+pub async fn rename_column(
+    pool: &PgPool,
+    board_id: &str,
+    column_id: &str,
+    name: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE columns SET name = $1 WHERE id = $2 AND board_id = $3 AND role IS NULL",
+    )
+    .bind(name)
+    .bind(column_id)
+    .bind(board_id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Puts the columns of a board in the order given. The order has to name every column of the
+/// board once and keep Actions last; anything else changes nothing.
+pub async fn reorder_columns(
+    pool: &PgPool,
+    board_id: &str,
+    column_ids: &[String],
+) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let columns = sqlx::query_as::<_, ColumnRow>(
+        "SELECT id, name, position, role FROM columns WHERE board_id = $1 ORDER BY position \
+         FOR UPDATE",
+    )
+    .bind(board_id)
+    .fetch_all(&mut *tx)
+    .await?;
+    if !is_valid_column_order(&columns, column_ids) {
+        return Ok(false);
+    }
+
+    for (position, column_id) in column_ids.iter().enumerate() {
+        sqlx::query("UPDATE columns SET position = $1 WHERE id = $2")
+            .bind(position as i32)
+            .bind(column_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// Whether `order` names each column of the board exactly once, with Actions at the end.
+fn is_valid_column_order(columns: &[ColumnRow], order: &[String]) -> bool {
+    if order.len() != columns.len() {
+        return false;
+    }
+    let named: HashSet<&str> = order.iter().map(String::as_str).collect();
+    if named.len() != order.len() || !columns.iter().all(|c| named.contains(c.id.as_str())) {
+        return false;
+    }
+    let last = order.last().and_then(|id| columns.iter().find(|c| &c.id == id));
+    last.is_some_and(|c| c.role.as_deref() == Some(ROLE_ACTIONS))
+}
+
+/// Removes a column with no role. The cards in it go to `target_column_id` first, another
+/// column of the same board; a column that still holds cards is not removed without one, so
+/// that no card is lost by a slip of the hand. Gives false, and changes nothing, when any of
+/// that does not hold.
+pub async fn remove_column(
+    pool: &PgPool,
+    board_id: &str,
+    column_id: &str,
+    target_column_id: Option<&str>,
+) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let columns = sqlx::query_as::<_, ColumnIdRoleRow>(
+        "SELECT id, role FROM columns WHERE board_id = $1 FOR UPDATE",
+    )
+    .bind(board_id)
+    .fetch_all(&mut *tx)
+    .await?;
+    match columns.iter().find(|c| c.id == column_id) {
+        Some(column) if column.role.is_none() => {}
+        _ => return Ok(false),
+    }
+
+    let cards = sqlx::query_as::<_, CountRow>(
+        "SELECT COUNT(*) AS count FROM tickets WHERE column_id = $1",
+    )
+    .bind(column_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    if cards.count > 0 {
+        let Some(target) = target_column_id
+            .filter(|t| *t != column_id)
+            .and_then(|t| columns.iter().find(|c| c.id == t))
+        else {
+            return Ok(false);
+        };
+        // The removed column has no role, so its cards carry no done mark or rock status to
+        // lose on the way.
+        sqlx::query("UPDATE tickets SET column_id = $1 WHERE column_id = $2")
+            .bind(&target.id)
+            .bind(column_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    sqlx::query("DELETE FROM columns WHERE id = $1")
+        .bind(column_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(true)
+}
+
+// --- Admin ---
+
+pub async fn admin_global_stats(pool: &PgPool) -> Result<AdminStatsRow, sqlx::Error> {
+    sqlx::query_as::<_, AdminStatsRow>(
+        r#"
+        SELECT
+            (SELECT COUNT(*) FROM boards) AS board_count,
+            (SELECT COUNT(*) FROM tickets) AS ticket_count,
+            (SELECT COUNT(*) FROM votes) AS vote_count
+        "#,
+    )
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn admin_list_boards(pool: &PgPool) -> Result<Vec<AdminBoardRow>, sqlx::Error> {
+    sqlx::query_as::<_, AdminBoardRow>(
+        r#"
+        SELECT
+            b.id,
+            b.title,
+            b.is_blurred,
+            b.created_at,
+            b.archived_at,
+            (SELECT COUNT(*) FROM columns c WHERE c.board_id = b.id) AS column_count,
+            (SELECT COUNT(*) FROM tickets t JOIN columns c ON t.column_id = c.id WHERE c.board_id = b.id) AS ticket_count,
+            (SELECT COUNT(*) FROM votes v JOIN tickets t ON v.ticket_id = t.id JOIN columns c ON t.column_id = c.id WHERE c.board_id = b.id) AS vote_count
+        FROM boards b
+        ORDER BY b.created_at DESC
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+}
 
 pub async fn admin_delete_board(pool: &PgPool, board_id: &str) -> Result<bool, sqlx::Error> {
     let result = sqlx::query("DELETE FROM boards WHERE id = $1")
@@ -1444,19 +1879,167 @@ pub async fn count_votes_in_column(
 
 // --- Timer ---
 
+/// Starts the timer of a board, or stops it with None. Either way the end still has to be
+/// handled, and what happens then is `action`.
 pub async fn set_timer_end(
     pool: &PgPool,
     board_id: &str,
     timer_end: Option<DateTime<Utc>>,
+    action: Option<TimerAction>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE boards SET timer_end = $1, timer_action = $2, timer_expired = false WHERE id = $3",
+    )
+    .bind(timer_end)
+    .bind(action.map(TimerAction::as_str))
+    .bind(board_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// --- Phases ---
+
+pub async fn get_phase(pool: &PgPool, board_id: &str) -> Result<Option<Phase>, sqlx::Error> {
+    let row = sqlx::query_as::<_, PhaseRow>("SELECT phase FROM boards WHERE id = $1")
+        .bind(board_id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(row.and_then(|r| r.phase).as_deref().and_then(Phase::parse))
+}
+
+/// Moves a board to one of its phases. A phase the board does not run through is refused.
+///
+/// Closing the board by its phase stops a running timer, as `close_board` does: what the timer
+/// would do when it runs out belongs to a phase the board has left.
+pub async fn set_phase(pool: &PgPool, board_id: &str, phase: Phase) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE boards SET phase = $1, \
+         timer_end = CASE WHEN $1 = 'closed' THEN NULL ELSE timer_end END, \
+         timer_action = CASE WHEN $1 = 'closed' THEN NULL ELSE timer_action END \
+         WHERE id = $2 AND $1 = ANY(phases)",
+    )
+    .bind(phase.as_str())
+    .bind(board_id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Moves the board on to `phase`, but only forward: from a phase that comes before it. False when
+/// the board has no such phase or got that far already — a discussion, or a closed board, is not
+/// sent back to the vote by a timer that was started before it.
+pub async fn advance_phase(
+    pool: &PgPool,
+    board_id: &str,
+    phase: Phase,
+) -> Result<bool, sqlx::Error> {
+    let earlier: Vec<&str> = Phase::ALL
+        .into_iter()
+        .filter(|p| *p < phase)
+        .map(Phase::as_str)
+        .collect();
+    let result = sqlx::query(
+        "UPDATE boards SET phase = $1 WHERE id = $2 AND $1 = ANY(phases) AND phase = ANY($3)",
+    )
+    .bind(phase.as_str())
+    .bind(board_id)
+    .bind(&earlier)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+// --- Archive ---
+
+/// Whether the board has been closed. A board that is not there reads as open; the caller finds
+/// out it is missing soon enough.
+pub async fn is_archived(pool: &PgPool, board_id: &str) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query_as::<_, ArchivedRow>("SELECT archived_at FROM boards WHERE id = $1")
+        .bind(board_id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(row.is_some_and(|r| r.archived_at.is_some()))
+}
+
+/// Closes a board. A running timer stops with it, since nothing may change once it runs out.
+/// False when the board was closed already, or is not there.
+pub async fn close_board(pool: &PgPool, board_id: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE boards SET archived_at = now(), timer_end = NULL, timer_action = NULL \
+         WHERE id = $1 AND archived_at IS NULL",
+    )
+    .bind(board_id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Opens a closed board to changes again. False when it was open already.
+pub async fn reopen_board(pool: &PgPool, board_id: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE boards SET archived_at = NULL WHERE id = $1 AND archived_at IS NOT NULL",
+    )
+    .bind(board_id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// A timer that ran out, and what it asked for.
+pub struct ExpiredTimer {
+    pub board_id: String,
+    pub action: Option<TimerAction>,
+}
+
+/// Takes the timers that have run out and not been handled, and marks them handled. Each one
+/// comes back to one caller only, whichever replica asks first.
+pub async fn take_expired_timers(pool: &PgPool) -> Result<Vec<ExpiredTimer>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, ExpiredTimerRow>(
+        "WITH due AS ( \
+             SELECT id, timer_action FROM boards \
+             WHERE timer_end IS NOT NULL AND timer_end <= now() AND NOT timer_expired \
+             FOR UPDATE SKIP LOCKED \
+         ) \
+         UPDATE boards b SET timer_expired = true, timer_action = NULL FROM due \
+         WHERE b.id = due.id \
+         RETURNING due.id, due.timer_action",
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|row| ExpiredTimer {
+            board_id: row.id,
+            action: row.timer_action.as_deref().and_then(TimerAction::parse),
+        })
+        .collect())
+}
+
+pub async fn set_cards_locked(
+    pool: &PgPool,
+    board_id: &str,
+    cards_locked: bool,
 ) -> Result<(), sqlx::Error> {
-    sqlx::query("UPDATE boards SET timer_end = $1 WHERE id = $2")
-        .bind(timer_end)
+    sqlx::query("UPDATE boards SET cards_locked = $1 WHERE id = $2")
+        .bind(cards_locked)
         .bind(board_id)
         .execute(pool)
         .await?;
     Ok(())
 }
 
+pub async fn get_cards_locked(
+    pool: &PgPool,
+    board_id: &str,
+) -> Result<Option<bool>, sqlx::Error> {
+    let row = sqlx::query_as::<_, CardsLockedRow>("SELECT cards_locked FROM boards WHERE id = $1")
+        .bind(board_id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(row.map(|r| r.cards_locked))
+}
+
 // --- Editor Requests & Board Editors ---
 
 pub async fn get_board_editors(
@@ -1634,6 +2217,25 @@ pub async fn list_teams(pool: &PgPool) -> Result<Vec<crate::models::Team>, sqlx:
     Ok(teams)
 }
 
+/// Reads one member of one team, or None when either is gone.
+pub async fn get_team_member(
+    pool: &PgPool,
+    team_id: &str,
+    member_id: &str,
+) -> Result<Option<crate::models::TeamMember>, sqlx::Error> {
+    let row = sqlx::query_as::<_, TeamMemberRow>(
+        "SELECT id, team_id, name FROM team_members WHERE team_id = $1 AND id = $2",
+    )
+    .bind(team_id)
+    .bind(member_id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.map(|m| crate::models::TeamMember {
+        id: m.id,
+        name: m.name,
+    }))
+}
+
 pub async fn create_team(
     pool: &PgPool,
     id: &str,
@@ -1734,7 +2336,8 @@ pub async fn list_action_sources(
               WHERE c.board_id = b.id) AS card_count,
             COALESCE((SELECT array_agg(l.label ORDER BY l.label) FROM board_labels l WHERE l.board_id = b.id), '{}'::text[]) AS labels,
             -- A locked board is named here but not opened here. The copy asks for its password.
-            b.password_hash IS NOT NULL AS is_locked
+            b.password_hash IS NOT NULL AS is_locked,
+            b.archived_at
         FROM boards b
         WHERE b.id <> $1
           AND ($2 = '' OR b.title ILIKE '%' || $2 || '%')
@@ -1765,10 +2368,111 @@ pub async fn list_action_sources(
             card_count: r.card_count,
             labels: r.labels,
             is_locked: r.is_locked,
+            archived_at: r.archived_at,
         })
         .collect())
 }
 
+/// Reads the cards of action columns that a reader without a password could read: nothing from a
+/// locked board, and from a blurred board only what it shows before the reveal — the actions
+/// carried in from the last retro.
+///
+/// Only the lines of the newest `limit` cards that pass the filters come back: those cards, and
+/// every card they were carried from or carried into, so that `models::trace_actions` still finds
+/// where each line starts and where it stands. A card passes when its board carries any of
+/// `labels`, when it is `done` or not, and when a member of `team` owns it; an empty or absent
+/// filter passes everything. The line is judged again by its newest card once it is traced.
+///
+/// Several of those cards can fall on one line, and a line can fail once it is judged, so the
+/// cards make fewer actions than `limit`. The flag says whether more cards pass than were taken,
+/// for a caller that needs more actions to ask again with a higher `limit`.
+pub async fn list_action_cards(
+    pool: &PgPool,
+    labels: &[String],
+    done: Option<bool>,
+    team: Option<&str>,
+    limit: i64,
+) -> Result<(Vec<ActionCard>, bool), sqlx::Error> {
+    let rows = sqlx::query_as::<_, ActionCardRow>(
+        r#"
+        WITH RECURSIVE cards AS (
+            SELECT
+                t.id, t.content, btrim(t.content) AS words, t.created_at, t.done_at,
+                t.carried_from_board_id, t.assignee_id, t.assignee_team_id, t.assignee_name,
+                t.due_on, b.id AS board_id, b.title AS board_title,
+                b.created_at AS board_created_at
+            FROM tickets t
+            JOIN columns c ON c.id = t.column_id
+            JOIN boards b ON b.id = c.board_id
+            WHERE c.role = ANY($1)
+              AND b.password_hash IS NULL
+              AND (NOT b.is_blurred OR c.role = $2 OR t.carried_from_board_id IS NOT NULL)
+        ),
+        passing AS (
+            SELECT cards.id, cards.created_at FROM cards
+            WHERE (cardinality($3::text[]) = 0
+                   OR EXISTS (SELECT 1 FROM board_labels l
+                               WHERE l.board_id = cards.board_id AND l.label = ANY($3)))
+              AND ($4::boolean IS NULL OR (cards.done_at IS NOT NULL) = $4)
+              AND ($5::text IS NULL OR cards.assignee_team_id = $5)
+        ),
+        picked AS (
+            SELECT passing.id FROM passing
+            ORDER BY passing.created_at DESC
+            LIMIT $6
+        ),
+        -- A line runs through the same words on the board a card came from, on the boards it
+        -- went to, and on its own board. UNION stops at a card already reached.
+        lines AS (
+            SELECT cards.* FROM cards JOIN picked ON picked.id = cards.id
+            UNION
+            SELECT a.* FROM cards a
+            JOIN lines p ON a.words = p.words
+             AND (a.board_id = p.board_id
+                  OR a.board_id = p.carried_from_board_id
+                  OR a.carried_from_board_id = p.board_id)
+        )
+        SELECT
+            l.id, l.content, l.created_at, l.done_at, l.carried_from_board_id,
+            l.assignee_id, l.assignee_team_id, l.assignee_name, l.due_on,
+            l.board_id, l.board_title, l.board_created_at,
+            COALESCE((SELECT array_agg(bl.label ORDER BY bl.label) FROM board_labels bl WHERE bl.board_id = l.board_id), '{}'::text[]) AS board_labels,
+            (SELECT count(*) FROM passing) > $6 AS more
+        FROM lines l
+        "#,
+    )
+    .bind(&DONE_COLUMN_ROLES[..])
+    .bind(ROLE_PREVIOUS_ACTIONS)
+    .bind(labels)
+    .bind(done)
+    .bind(team)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    let more = rows.first().is_some_and(|r| r.more);
+    let cards = rows
+        .into_iter()
+        .map(|r| {
+            let assignee = row_assignee(r.assignee_id, r.assignee_team_id, r.assignee_name);
+            ActionCard {
+                ticket_id: r.id,
+                content: r.content,
+                created_at: r.created_at,
+                done_at: r.done_at,
+                assignee,
+                due_on: r.due_on,
+                carried_from_board_id: r.carried_from_board_id,
+                board_id: r.board_id,
+                board_title: r.board_title,
+                board_created_at: r.board_created_at,
+                board_labels: r.board_labels,
+            }
+        })
+        .collect();
+    Ok((cards, more))
+}
+
 /// What a copy could not find. The caller turns each one into its own message, because "that
 /// column is gone" and "that board is gone" send the reader to different places.
 pub enum CopyOutcome {
@@ -1887,26 +2591,34 @@ pub async fn copy_cards(
 
         // The GIF comes across with the card, so the record of the last meeting reads the same way.
         let gif = ticket.take_gif();
-        let author_name = if target_anonymous {
-            String::new()
+        let assignee = ticket.take_assignee().filter(|_| target_keeps_done);
+        // An anonymous board knows its people by ids of its own (see `credentials::author_id`).
+        // The id from another board would tie the card to someone there, so it gets one that
+        // points at nobody.
+        let (author_id, author_name) = if target_anonymous {
+            (nanoid!(8), String::new())
         } else {
-            ticket.author_name
+            (ticket.author_id, ticket.author_name)
         };
 
         // A done action comes across done, because Previous Actions is the record of the last
-        // retro and the record has to say which of the actions the team closed. A card that
-        // lands anywhere else arrives open: no column but the two action ones holds the mark.
+        // retro and the record has to say which of the actions the team closed. Its owner and
+        // its due day come with it, so the team can ask how it went and see what ran late. A card
+        // that lands anywhere else arrives open: no column but the two action ones holds a mark.
         let (gid, gurl, gstill, gw, gh, gtitle) = gif_binds(gif.as_ref());
+        let (aid, ateam, aname) = assignee_binds(assignee.as_ref());
         sqlx::query(
             "INSERT INTO tickets (id, column_id, content, author_id, author_name, created_at, \
              carried_from_board_id, carried_from_board_title, \
-             gif_id, gif_url, gif_still_url, gif_width, gif_height, gif_title, done_at) \
-             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)",
+             gif_id, gif_url, gif_still_url, gif_width, gif_height, gif_title, done_at, \
+             assignee_id, assignee_team_id, assignee_name, due_on) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, \
+             $16, $17, $18, $19)",
         )
         .bind(nanoid!(8))
         .bind(&target_column.id)
         .bind(&ticket.content)
-        .bind(&ticket.author_id)
+        .bind(&author_id)
         .bind(&author_name)
         .bind(Utc::now())
         .bind(source_board_id)
@@ -1918,6 +2630,10 @@ pub async fn copy_cards(
         .bind(gh)
         .bind(gtitle)
         .bind(if target_keeps_done { ticket.done_at } else { None })
+        .bind(aid)
+        .bind(ateam)
+        .bind(aname)
+        .bind(if target_keeps_done { ticket.due_on } else { None })
         .execute(&mut *tx)
         .await?;
 
@@ -2046,22 +2762,77 @@ pub async fn set_ticket_done(
     Ok(())
 }
 
-/// Marks a rock on track or off track. None takes the mark off again.
-pub async fn set_rock_status(
+/// Gives an action its owner, or takes the owner off again with None.
+pub async fn set_ticket_assignee(
     pool: &PgPool,
     ticket_id: &str,
-    status: Option<&str>,
+    assignee: Option<&Assignee>,
 ) -> Result<(), sqlx::Error> {
-    sqlx::query("UPDATE tickets SET rock_status = $1 WHERE id = $2")
-        .bind(status)
+    let (aid, ateam, aname) = assignee_binds(assignee);
+    sqlx::query(
+        "UPDATE tickets SET assignee_id = $1, assignee_team_id = $2, assignee_name = $3 \
+         WHERE id = $4",
+    )
+    .bind(aid)
+    .bind(ateam)
+    .bind(aname)
+    .bind(ticket_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Gives an action the day it is due, or takes the day off again with None.
+pub async fn set_ticket_due_on(
+    pool: &PgPool,
+    ticket_id: &str,
+    due_on: Option<NaiveDate>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE tickets SET due_on = $1 WHERE id = $2")
+        .bind(due_on)
         .bind(ticket_id)
         .execute(pool)
         .await?;
     Ok(())
 }
 
-/// Records what one participant thinks of the meeting. A second mark replaces the first.
-pub async fn upsert_meeting_rating(
+/// Tells whether a column is one of the two action columns.
+/// Whether a column is one of the two action columns of its board.
+pub async fn is_action_column(pool: &PgPool, column_id: &str) -> Result<bool, sqlx::Error> {
+    let mut conn = pool.acquire().await?;
+    column_holds_actions(&mut conn, column_id).await
+}
+
+async fn column_holds_actions(
+    conn: &mut PgConnection,
+    column_id: &str,
+) -> Result<bool, sqlx::Error> {
+    let role = sqlx::query_as::<_, ColumnIdRoleRow>("SELECT id, role FROM columns WHERE id = $1")
+        .bind(column_id)
+        .fetch_optional(conn)
+        .await?
+        .and_then(|c| c.role);
+    Ok(role
+        .as_deref()
+        .is_some_and(|role| DONE_COLUMN_ROLES.contains(&role)))
+}
+
+/// Marks a rock on track or off track. None takes the mark off again.
+pub async fn set_rock_status(
+    pool: &PgPool,
+    ticket_id: &str,
+    status: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE tickets SET rock_status = $1 WHERE id = $2")
+        .bind(status)
+        .bind(ticket_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Records what one participant thinks of the meeting. A second mark replaces the first.
+pub async fn upsert_meeting_rating(
     pool: &PgPool,
     board_id: &str,
     participant_id: &str,
@@ -2166,31 +2937,1194 @@ pub async fn update_scorecard_metric(
     .bind(board_id)
     .execute(pool)
     .await?;
-    Ok(result.rows_affected() > 0)
-}
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn remove_scorecard_metric(
+    pool: &PgPool,
+    metric_id: &str,
+    board_id: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM scorecard_metrics WHERE id = $1 AND board_id = $2")
+        .bind(metric_id)
+        .bind(board_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+// --- History ---
+
+fn card_record(mut t: TicketRow, votes: &[VoteRow]) -> CardRecord {
+    let gif = t.take_gif();
+    let assignee = t.take_assignee();
+    let votes = votes
+        .iter()
+        .filter(|v| v.ticket_id == t.id)
+        .map(|v| v.participant_id.clone())
+        .collect();
+    CardRecord {
+        id: t.id,
+        column_id: t.column_id,
+        content: t.content,
+        author_id: t.author_id,
+        author_name: t.author_name,
+        created_at: t.created_at,
+        carried_from_board_id: t.carried_from_board_id,
+        carried_from_board_title: t.carried_from_board_title,
+        gif,
+        rock_status: t.rock_status,
+        done_at: t.done_at,
+        assignee,
+        due_on: t.due_on,
+        votes,
+    }
+}
+
+fn comment_record(mut c: CommentRow) -> CommentRecord {
+    let gif = c.take_gif();
+    CommentRecord {
+        id: c.id,
+        ticket_id: c.ticket_id,
+        content: c.content,
+        author_id: c.author_id,
+        author_name: c.author_name,
+        created_at: c.created_at,
+        gif,
+    }
+}
+
+async fn read_cards(
+    conn: &mut PgConnection,
+    ticket_ids: &[String],
+) -> Result<Vec<CardRecord>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, TicketRow>(&format!(
+        "SELECT {TICKET_COLUMNS} FROM tickets WHERE id = ANY($1)"
+    ))
+    .bind(ticket_ids)
+    .fetch_all(&mut *conn)
+    .await?;
+    let votes = sqlx::query_as::<_, VoteRow>(
+        "SELECT ticket_id, participant_id FROM votes WHERE ticket_id = ANY($1) \
+         ORDER BY participant_id",
+    )
+    .bind(ticket_ids)
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(rows.into_iter().map(|t| card_record(t, &votes)).collect())
+}
+
+/// Reads the comments whose `key` — `id` or `ticket_id` — is one of `ids`.
+async fn read_comments(
+    conn: &mut PgConnection,
+    key: &str,
+    ids: &[String],
+) -> Result<Vec<CommentRecord>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, CommentRow>(&format!(
+        "SELECT {COMMENT_COLUMNS} FROM ticket_comments WHERE {key} = ANY($1) ORDER BY created_at"
+    ))
+    .bind(ids)
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(rows.into_iter().map(comment_record).collect())
+}
+
+/// Reads the cards an action is about to touch, or has touched, with every comment under them.
+pub async fn snapshot(pool: &PgPool, ticket_ids: &[String]) -> Result<Snapshot, sqlx::Error> {
+    let mut conn = pool.acquire().await?;
+    Ok(Snapshot {
+        cards: read_cards(&mut conn, ticket_ids).await?,
+        comments: read_comments(&mut conn, "ticket_id", ticket_ids).await?,
+    })
+}
+
+/// Writes one step into the history of a participant, and drops the steps they had undone: a
+/// new action starts a new line, and the old redo no longer follows from it.
+pub async fn record_operation(
+    pool: &PgPool,
+    board_id: &str,
+    actor_id: &str,
+    kind: OperationKind,
+    author_id: Option<&str>,
+    changes: &Changes,
+) -> Result<(), sqlx::Error> {
+    let changes = serde_json::to_string(changes).map_err(|e| sqlx::Error::Encode(e.into()))?;
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM board_operations WHERE board_id = $1 AND actor_id = $2 AND undone")
+        .bind(board_id)
+        .bind(actor_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        "INSERT INTO board_operations (board_id, actor_id, kind, author_id, changes) \
+         VALUES ($1, $2, $3, $4, $5::jsonb)",
+    )
+    .bind(board_id)
+    .bind(actor_id)
+    .bind(kind.as_str())
+    .bind(author_id)
+    .bind(changes)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// The newest step of this participant on this board that is not undone yet.
+pub async fn operation_to_undo(
+    pool: &PgPool,
+    board_id: &str,
+    actor_id: &str,
+) -> Result<Option<Operation>, sqlx::Error> {
+    read_operation(pool, board_id, actor_id, "NOT undone ORDER BY id DESC").await
+}
+
+/// The step of this participant on this board that was undone last, which is the oldest of the
+/// undone ones: every undo walks one further back.
+pub async fn operation_to_redo(
+    pool: &PgPool,
+    board_id: &str,
+    actor_id: &str,
+) -> Result<Option<Operation>, sqlx::Error> {
+    read_operation(pool, board_id, actor_id, "undone ORDER BY id ASC").await
+}
+
+async fn read_operation(
+    pool: &PgPool,
+    board_id: &str,
+    actor_id: &str,
+    which: &str,
+) -> Result<Option<Operation>, sqlx::Error> {
+    let row = sqlx::query_as::<_, OperationRow>(&format!(
+        "SELECT id, kind, author_id, changes::text AS changes FROM board_operations \
+         WHERE board_id = $1 AND actor_id = $2 AND {which} LIMIT 1"
+    ))
+    .bind(board_id)
+    .bind(actor_id)
+    .fetch_optional(pool)
+    .await?;
+
+    let Some(row) = row else {
+        return Ok(None);
+    };
+    let kind = OperationKind::parse(&row.kind)
+        .ok_or_else(|| sqlx::Error::Decode(format!("unknown operation {}", row.kind).into()))?;
+    let changes =
+        serde_json::from_str(&row.changes).map_err(|e| sqlx::Error::Decode(e.into()))?;
+    Ok(Some(Operation {
+        id: row.id,
+        kind,
+        author_id: row.author_id,
+        changes,
+    }))
+}
+
+pub async fn set_operation_undone(
+    pool: &PgPool,
+    operation_id: i64,
+    undone: bool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE board_operations SET undone = $1 WHERE id = $2")
+        .bind(undone)
+        .bind(operation_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn drop_operation(pool: &PgPool, operation_id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM board_operations WHERE id = $1")
+        .bind(operation_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Makes the changes of one step, all of them or none. Gives false, and changes nothing, when a
+/// card or a comment has moved on from where the step starts: making it then would throw away
+/// what someone did since.
+///
+/// The order keeps the foreign keys whole. The cards that stay or come back are written first,
+/// so a comment has a card to hang on; the comments follow; the cards that go are removed last,
+/// so their cascade takes no comment the step moved elsewhere.
+pub async fn apply_changes(pool: &PgPool, changes: &Changes) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let card_ids: Vec<String> = changes.cards.iter().filter_map(change_id).collect();
+    let comment_ids: Vec<String> = changes.comments.iter().filter_map(change_id).collect();
+    let current_cards = read_cards(&mut tx, &card_ids).await?;
+    let current_comments = read_comments(&mut tx, "id", &comment_ids).await?;
+
+    let mut cards = Vec::new();
+    for change in &changes.cards {
+        let current = find_record(&current_cards, change);
+        if !change.starts_from(current) {
+            return Ok(false);
+        }
+        cards.push((change, change.applied_to(current)));
+    }
+    let mut comments = Vec::new();
+    for change in &changes.comments {
+        let current = find_record(&current_comments, change);
+        if !change.starts_from(current) {
+            return Ok(false);
+        }
+        comments.push((change, change.applied_to(current)));
+    }
+
+    for (change, card) in &cards {
+        let Some(card) = card else { continue };
+        let (gid, gurl, gstill, gw, gh, gtitle) = gif_binds(card.gif.as_ref());
+        let (aid, ateam, aname) = assignee_binds(card.assignee.as_ref());
+        sqlx::query(
+            "INSERT INTO tickets (id, column_id, content, author_id, author_name, created_at, \
+             carried_from_board_id, carried_from_board_title, \
+             gif_id, gif_url, gif_still_url, gif_width, gif_height, gif_title, rock_status, done_at, \
+             assignee_id, assignee_team_id, assignee_name, due_on) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, \
+             $17, $18, $19, $20) \
+             ON CONFLICT (id) DO UPDATE SET column_id = EXCLUDED.column_id, \
+             content = EXCLUDED.content, gif_id = EXCLUDED.gif_id, gif_url = EXCLUDED.gif_url, \
+             gif_still_url = EXCLUDED.gif_still_url, gif_width = EXCLUDED.gif_width, \
+             gif_height = EXCLUDED.gif_height, gif_title = EXCLUDED.gif_title, \
+             rock_status = EXCLUDED.rock_status, done_at = EXCLUDED.done_at, \
+             assignee_id = EXCLUDED.assignee_id, assignee_team_id = EXCLUDED.assignee_team_id, \
+             assignee_name = EXCLUDED.assignee_name, due_on = EXCLUDED.due_on",
+        )
+        .bind(&card.id)
+        .bind(&card.column_id)
+        .bind(&card.content)
+        .bind(&card.author_id)
+        .bind(&card.author_name)
+        .bind(card.created_at)
+        .bind(&card.carried_from_board_id)
+        .bind(&card.carried_from_board_title)
+        .bind(gid)
+        .bind(gurl)
+        .bind(gstill)
+        .bind(gw)
+        .bind(gh)
+        .bind(gtitle)
+        .bind(&card.rock_status)
+        .bind(card.done_at)
+        .bind(aid)
+        .bind(ateam)
+        .bind(aname)
+        .bind(card.due_on)
+        .execute(&mut *tx)
+        .await?;
+
+        // A card that comes back brings its votes. One that only changed keeps the votes it has.
+        if change.before.is_none() {
+            for voter_id in &card.votes {
+                sqlx::query(
+                    "INSERT INTO votes (ticket_id, participant_id) VALUES ($1, $2) \
+                     ON CONFLICT DO NOTHING",
+                )
+                .bind(&card.id)
+                .bind(voter_id)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+    }
+
+    for (change, comment) in &comments {
+        match comment {
+            Some(comment) => {
+                let (gid, gurl, gstill, gw, gh, gtitle) = gif_binds(comment.gif.as_ref());
+                sqlx::query(
+                    "INSERT INTO ticket_comments (id, ticket_id, content, author_id, author_name, \
+                     created_at, gif_id, gif_url, gif_still_url, gif_width, gif_height, gif_title) \
+                     VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12) \
+                     ON CONFLICT (id) DO UPDATE SET ticket_id = EXCLUDED.ticket_id, \
+                     content = EXCLUDED.content, gif_id = EXCLUDED.gif_id, \
+                     gif_url = EXCLUDED.gif_url, gif_still_url = EXCLUDED.gif_still_url, \
+                     gif_width = EXCLUDED.gif_width, gif_height = EXCLUDED.gif_height, \
+                     gif_title = EXCLUDED.gif_title",
+                )
+                .bind(&comment.id)
+                .bind(&comment.ticket_id)
+                .bind(&comment.content)
+                .bind(&comment.author_id)
+                .bind(&comment.author_name)
+                .bind(comment.created_at)
+                .bind(gid)
+                .bind(gurl)
+                .bind(gstill)
+                .bind(gw)
+                .bind(gh)
+                .bind(gtitle)
+                .execute(&mut *tx)
+                .await?;
+            }
+            None => {
+                if let Some(id) = change_id(change) {
+                    sqlx::query("DELETE FROM ticket_comments WHERE id = $1")
+                        .bind(id)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+            }
+        }
+    }
+
+    for (change, card) in &cards {
+        if card.is_none() {
+            if let Some(id) = change_id(change) {
+                sqlx::query("DELETE FROM tickets WHERE id = $1")
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+    }
+
+    tx.commit().await?;
+    Ok(true)
+}
+
+fn change_id<T: Record>(change: &RecordChange<T>) -> Option<String> {
+    change
+        .before
+        .as_ref()
+        .or(change.after.as_ref())
+        .map(|r| r.id().to_string())
+}
+
+fn find_record<'a, T: Record>(records: &'a [T], change: &RecordChange<T>) -> Option<&'a T> {
+    let id = change_id(change)?;
+    records.iter().find(|r| r.id() == id)
+}
+
+// --- Presence ---
+
+/// Records that this replica is alive. The first call registers it; every later one keeps it from
+/// being swept.
+pub async fn touch_replica(pool: &PgPool, replica_id: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO replicas (id, last_seen) VALUES ($1, now()) \
+         ON CONFLICT (id) DO UPDATE SET last_seen = now()",
+    )
+    .bind(replica_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Removes the replicas that have not been heard from in `quiet_secs` seconds, and with them the
+/// sockets they held. Gives how many went.
+pub async fn sweep_replicas(pool: &PgPool, quiet_secs: i64) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "DELETE FROM replicas WHERE last_seen < now() - make_interval(secs => $1)",
+    )
+    .bind(quiet_secs as f64)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+pub async fn add_presence(
+    pool: &PgPool,
+    socket_id: &str,
+    replica_id: &str,
+    board_id: &str,
+    participant_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO board_presence (socket_id, replica_id, board_id, participant_id) \
+         VALUES ($1, $2, $3, $4)",
+    )
+    .bind(socket_id)
+    .bind(replica_id)
+    .bind(board_id)
+    .bind(participant_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn remove_presence(pool: &PgPool, socket_id: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM board_presence WHERE socket_id = $1")
+        .bind(socket_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// How many sockets are open on a board, across every replica.
+pub async fn count_presence(pool: &PgPool, board_id: &str) -> Result<i64, sqlx::Error> {
+    let row = sqlx::query_as::<_, CountRow>(
+        "SELECT COUNT(*) as count FROM board_presence WHERE board_id = $1",
+    )
+    .bind(board_id)
+    .fetch_one(pool)
+    .await?;
+    Ok(row.count)
+}
+
+/// The open sockets of every board that has any, across every replica.
+pub async fn presence_counts(
+    pool: &PgPool,
+) -> Result<std::collections::HashMap<String, i64>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, PresenceCountRow>(
+        "SELECT board_id, COUNT(*) as count FROM board_presence GROUP BY board_id",
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(|r| (r.board_id, r.count)).collect())
+}
+
+// --- Webhooks ---
+
+pub async fn create_webhook(
+    pool: &PgPool,
+    id: &str,
+    board_id: Option<&str>,
+    url: &str,
+    secret: &str,
+    events: &[String],
+) -> Result<WebhookView, sqlx::Error> {
+    let row = sqlx::query_as::<_, WebhookRow>(
+        "INSERT INTO webhooks (id, board_id, url, secret, events) VALUES ($1, $2, $3, $4, $5) \
+         RETURNING id, board_id, url, events, created_at",
+    )
+    .bind(id)
+    .bind(board_id)
+    .bind(url)
+    .bind(secret)
+    .bind(events)
+    .fetch_one(pool)
+    .await?;
+    Ok(row.into())
+}
+
+/// The webhooks of one board, or the global ones with None.
+pub async fn list_webhooks(
+    pool: &PgPool,
+    board_id: Option<&str>,
+) -> Result<Vec<WebhookView>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, WebhookRow>(
+        "SELECT id, board_id, url, events, created_at FROM webhooks \
+         WHERE board_id IS NOT DISTINCT FROM $1 ORDER BY created_at",
+    )
+    .bind(board_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(Into::into).collect())
+}
+
+/// Removes a webhook, and its log with it. The board scopes the id as it does in `list_webhooks`,
+/// so a facilitator reaches the webhooks of their own board and nothing else.
+pub async fn delete_webhook(
+    pool: &PgPool,
+    id: &str,
+    board_id: Option<&str>,
+) -> Result<bool, sqlx::Error> {
+    let result =
+        sqlx::query("DELETE FROM webhooks WHERE id = $1 AND board_id IS NOT DISTINCT FROM $2")
+            .bind(id)
+            .bind(board_id)
+            .execute(pool)
+            .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Queues one event of a board for every webhook that wants it: those of the board and the
+/// global ones.
+pub async fn enqueue_webhook_deliveries(
+    pool: &PgPool,
+    board_id: &str,
+    event: &str,
+    payload: &str,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT INTO webhook_deliveries (webhook_id, board_id, event, payload) \
+         SELECT id, $1, $2, $3 FROM webhooks \
+         WHERE (board_id = $1 OR board_id IS NULL) \
+           AND (cardinality(events) = 0 OR $2 = ANY(events))",
+    )
+    .bind(board_id)
+    .bind(event)
+    .bind(payload)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Takes the deliveries whose time has come. Each one is pushed `lease_secs` into the future as
+/// it is taken, so that another replica leaves it alone while this one sends it, and a replica
+/// that dies mid-send hands it back when the lease runs out.
+pub async fn claim_webhook_deliveries(
+    pool: &PgPool,
+    limit: i64,
+    lease_secs: i64,
+) -> Result<Vec<DueDelivery>, sqlx::Error> {
+    sqlx::query_as::<_, DueDelivery>(
+        r#"
+        WITH due AS (
+            SELECT id FROM webhook_deliveries
+            WHERE status = 'pending' AND next_attempt_at <= now()
+            ORDER BY next_attempt_at
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+        ),
+        claimed AS (
+            UPDATE webhook_deliveries d
+            SET attempts = d.attempts + 1,
+                next_attempt_at = now() + make_interval(secs => $2)
+            FROM due WHERE d.id = due.id
+            RETURNING d.id, d.webhook_id, d.event, d.payload, d.attempts
+        )
+        SELECT c.id, c.event, c.payload, c.attempts, w.url, w.secret,
+               w.board_id IS NOT NULL AS is_board_webhook
+        FROM claimed c JOIN webhooks w ON w.id = c.webhook_id
+        "#,
+    )
+    .bind(limit)
+    .bind(lease_secs as f64)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn mark_webhook_delivered(
+    pool: &PgPool,
+    id: i64,
+    status_code: i32,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE webhook_deliveries SET status = 'delivered', last_status_code = $2, \
+         last_error = NULL, delivered_at = now() WHERE id = $1",
+    )
+    .bind(id)
+    .bind(status_code)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Records a try that did not land. `retry_at` is when the next one goes; None gives up.
+pub async fn mark_webhook_failed(
+    pool: &PgPool,
+    id: i64,
+    status_code: Option<i32>,
+    error: &str,
+    retry_at: Option<DateTime<Utc>>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE webhook_deliveries SET last_status_code = $2, last_error = $3, \
+         status = CASE WHEN $4::timestamptz IS NULL THEN 'failed' ELSE 'pending' END, \
+         next_attempt_at = COALESCE($4, next_attempt_at) WHERE id = $1",
+    )
+    .bind(id)
+    .bind(status_code)
+    .bind(error)
+    .bind(retry_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// The log of deliveries, newest first. Each filter that is given narrows it.
+pub async fn list_webhook_deliveries(
+    pool: &PgPool,
+    webhook_id: Option<&str>,
+    board_id: Option<&str>,
+    status: Option<&str>,
+    limit: i64,
+) -> Result<Vec<WebhookDelivery>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, WebhookDeliveryRow>(
+        "SELECT d.id, d.webhook_id, w.url, d.board_id, d.event, d.status, d.attempts, \
+         d.next_attempt_at, d.last_status_code, d.last_error, d.created_at, d.delivered_at \
+         FROM webhook_deliveries d JOIN webhooks w ON w.id = d.webhook_id \
+         WHERE ($1::text IS NULL OR d.webhook_id = $1) \
+           AND ($2::text IS NULL OR d.board_id = $2) \
+           AND ($3::text IS NULL OR d.status = $3) \
+         ORDER BY d.id DESC LIMIT $4",
+    )
+    .bind(webhook_id)
+    .bind(board_id)
+    .bind(status)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|r| WebhookDelivery {
+            id: r.id,
+            webhook_id: r.webhook_id,
+            url: r.url,
+            board_id: r.board_id,
+            event: r.event,
+            status: r.status,
+            attempts: r.attempts,
+            next_attempt_at: r.next_attempt_at,
+            last_status_code: r.last_status_code,
+            last_error: r.last_error,
+            created_at: r.created_at,
+            delivered_at: r.delivered_at,
+        })
+        .collect())
+}
+
+// --- Retention ---
+
+/// The policy an admin set. None when no admin has, and the environment decides.
+pub async fn get_retention_policy(pool: &PgPool) -> Result<Option<RetentionPolicy>, sqlx::Error> {
+    let row = sqlx::query_as::<_, RetentionPolicyRow>(
+        "SELECT days, action, exempt_labels FROM retention_policy",
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.map(|r| RetentionPolicy {
+        days: r.days,
+        action: RetentionAction::parse(&r.action).unwrap_or_default(),
+        exempt_labels: r.exempt_labels,
+    }))
+}
+
+pub async fn set_retention_policy(
+    pool: &PgPool,
+    policy: &RetentionPolicy,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO retention_policy (id, days, action, exempt_labels) VALUES (true, $1, $2, $3) \
+         ON CONFLICT (id) DO UPDATE SET days = $1, action = $2, exempt_labels = $3, \
+         updated_at = now()",
+    )
+    .bind(policy.days)
+    .bind(policy.action.as_str())
+    .bind(&policy.exempt_labels)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Drops the policy an admin set, which hands the decision back to the environment.
+pub async fn clear_retention_policy(pool: &PgPool) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM retention_policy").execute(pool).await?;
+    Ok(())
+}
+
+/// Lists the boards whose time is up under the policy, the longest untouched first. A board the
+/// policy anonymized already is not listed again unless the policy now deletes.
+pub async fn list_retention_candidates(
+    pool: &PgPool,
+    days: i32,
+    policy: &RetentionPolicy,
+) -> Result<Vec<RetentionCandidate>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, RetentionCandidateRow>(
+        r#"
+        SELECT
+            b.id,
+            b.title,
+            COALESCE((SELECT array_agg(l.label ORDER BY l.label) FROM board_labels l WHERE l.board_id = b.id), '{}'::text[]) AS labels,
+            (SELECT COUNT(*) FROM tickets t JOIN columns c ON t.column_id = c.id WHERE c.board_id = b.id) AS card_count,
+            b.last_activity_at
+        FROM boards b
+        WHERE b.last_activity_at < now() - make_interval(days => $1)
+          AND ($2 = 'delete' OR b.anonymized_at IS NULL)
+          AND NOT EXISTS (SELECT 1 FROM board_labels l WHERE l.board_id = b.id AND l.label = ANY($3))
+        ORDER BY b.last_activity_at
+        "#,
+    )
+    .bind(days)
+    .bind(policy.action.as_str())
+    .bind(&policy.exempt_labels)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|r| RetentionCandidate {
+            board_id: r.id,
+            title: r.title,
+            labels: r.labels,
+            card_count: r.card_count,
+            last_activity_at: r.last_activity_at,
+            action: policy.action,
+        })
+        .collect())
+}
+
+/// Deletes or anonymizes one board the purge listed, and records it. None when the board moved
+/// on in the meantime: it changed, it took an exempt label, it is gone, or another replica has
+/// it in hand.
+///
+/// The deliveries of the board go either way. Their payloads carry the words and names of its
+/// cards, and the log of a global webhook would otherwise keep them after the board is gone.
+pub async fn purge_board(
+    pool: &PgPool,
+    candidate: &RetentionCandidate,
+    days: i32,
+    exempt_labels: &[String],
+) -> Result<Option<RetentionRecord>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let still_due = sqlx::query_as::<_, IdRow>(
+        "SELECT id FROM boards WHERE id = $1 AND last_activity_at < now() - make_interval(days => $2) \
+         AND ($3 = 'delete' OR anonymized_at IS NULL) \
+         AND NOT EXISTS (SELECT 1 FROM board_labels l WHERE l.board_id = boards.id AND l.label = ANY($4)) \
+         FOR UPDATE SKIP LOCKED",
+    )
+    .bind(&candidate.board_id)
+    .bind(days)
+    .bind(candidate.action.as_str())
+    .bind(exempt_labels)
+    .fetch_optional(&mut *tx)
+    .await?;
+    if still_due.is_none() {
+        return Ok(None);
+    }
+
+    match candidate.action {
+        RetentionAction::Delete => {
+            sqlx::query("DELETE FROM boards WHERE id = $1")
+                .bind(&candidate.board_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        RetentionAction::Anonymize => anonymize_board(&mut tx, &candidate.board_id).await?,
+    }
+    // The audit log keeps what was done to the board, and lets go of the names of who did it.
+    sqlx::query(
+        "UPDATE audit_log SET actor_name = NULL WHERE board_id = $1 AND actor_name IS NOT NULL",
+    )
+    .bind(&candidate.board_id)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query("DELETE FROM webhook_deliveries WHERE board_id = $1")
+        .bind(&candidate.board_id)
+        .execute(&mut *tx)
+        .await?;
+
+    let row = sqlx::query_as::<_, RetentionLogRow>(
+        "INSERT INTO retention_log (board_id, title, action, labels, card_count, last_activity_at) \
+         VALUES ($1, $2, $3, $4, $5, $6) \
+         RETURNING id, board_id, title, action, labels, card_count, last_activity_at, purged_at",
+    )
+    .bind(&candidate.board_id)
+    .bind(&candidate.title)
+    .bind(candidate.action.as_str())
+    .bind(&candidate.labels)
+    .bind(candidate.card_count)
+    .bind(candidate.last_activity_at)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(Some(row.into()))
+}
+
+/// Takes every name and participant id off a board. The words stay, and so do the counts: a vote
+/// or a mark is kept under an id of its own that leads nowhere.
+async fn anonymize_board(conn: &mut PgConnection, board_id: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE tickets SET author_id = md5(random()::text || id), author_name = '', \
+         assignee_id = NULL, assignee_team_id = NULL, assignee_name = NULL \
+         WHERE column_id IN (SELECT id FROM columns WHERE board_id = $1)",
+    )
+    .bind(board_id)
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query(
+        "UPDATE ticket_comments SET author_id = md5(random()::text || id), author_name = '' \
+         WHERE ticket_id IN (SELECT t.id FROM tickets t JOIN columns c ON t.column_id = c.id \
+                             WHERE c.board_id = $1)",
+    )
+    .bind(board_id)
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query(
+        "UPDATE votes SET participant_id = md5(random()::text || ticket_id || participant_id) \
+         WHERE ticket_id IN (SELECT t.id FROM tickets t JOIN columns c ON t.column_id = c.id \
+                             WHERE c.board_id = $1)",
+    )
+    .bind(board_id)
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query(
+        "UPDATE meeting_ratings SET participant_id = md5(random()::text || participant_id) \
+         WHERE board_id = $1",
+    )
+    .bind(board_id)
+    .execute(&mut *conn)
+    .await?;
+    // The history holds the cards as they stood, names and all. It goes rather than be rewritten.
+    sqlx::query("DELETE FROM board_operations WHERE board_id = $1")
+        .bind(board_id)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query("DELETE FROM board_editors WHERE board_id = $1")
+        .bind(board_id)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query("DELETE FROM editor_requests WHERE board_id = $1")
+        .bind(board_id)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query(
+        "UPDATE boards SET facilitator_id = NULL, owner_sub = NULL, anonymized_at = now() \
+         WHERE id = $1",
+    )
+        .bind(board_id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// The record of the purge, the newest first.
+pub async fn list_retention_log(
+    pool: &PgPool,
+    limit: i64,
+) -> Result<Vec<RetentionRecord>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, RetentionLogRow>(
+        "SELECT id, board_id, title, action, labels, card_count, last_activity_at, purged_at \
+         FROM retention_log ORDER BY id DESC LIMIT $1",
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(Into::into).collect())
+}
+
+// --- API tokens ---
+
+/// Keeps a new token. Its owner is the account that made it, or, with no sign-in, the facilitator
+/// cookie of the browser that did.
+pub async fn create_api_token(
+    pool: &PgPool,
+    id: &str,
+    name: &str,
+    secret_hash: &str,
+    scopes: &[TokenScope],
+    owner_sub: Option<&str>,
+    facilitator_id: Option<&str>,
+) -> Result<ApiTokenView, sqlx::Error> {
+    let scopes: Vec<&str> = scopes.iter().map(|s| s.as_str()).collect();
+    let row = sqlx::query_as::<_, ApiTokenRow>(
+        "INSERT INTO api_tokens (id, name, secret_hash, scopes, owner_sub, facilitator_id) \
+         VALUES ($1, $2, $3, $4, $5, $6) \
+         RETURNING id, name, scopes, created_at, last_used_at",
+    )
+    .bind(id)
+    .bind(name)
+    .bind(secret_hash)
+    .bind(&scopes)
+    .bind(owner_sub)
+    .bind(facilitator_id)
+    .fetch_one(pool)
+    .await?;
+    Ok(row.into())
+}
+
+/// The tokens of one person, the newest first. A signed-in person's are those of the account, as
+/// with their boards.
+pub async fn list_api_tokens(
+    pool: &PgPool,
+    facilitator_id: Option<&str>,
+    owner_sub: Option<&str>,
+) -> Result<Vec<ApiTokenView>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, ApiTokenRow>(
+        "SELECT id, name, scopes, created_at, last_used_at FROM api_tokens \
+         WHERE CASE WHEN $2::text IS NOT NULL THEN owner_sub = $2 ELSE facilitator_id = $1 END \
+         ORDER BY created_at DESC",
+    )
+    .bind(facilitator_id)
+    .bind(owner_sub)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(Into::into).collect())
+}
+
+/// Revokes a token of one person. Someone else's id matches nothing.
+pub async fn delete_api_token(
+    pool: &PgPool,
+    id: &str,
+    facilitator_id: Option<&str>,
+    owner_sub: Option<&str>,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "DELETE FROM api_tokens WHERE id = $1 \
+         AND CASE WHEN $3::text IS NOT NULL THEN owner_sub = $3 ELSE facilitator_id = $2 END",
+    )
+    .bind(id)
+    .bind(facilitator_id)
+    .bind(owner_sub)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// A token by its id: the hash its secret has to match, and who it acts for.
+pub async fn get_api_token(
+    pool: &PgPool,
+    id: &str,
+) -> Result<Option<(String, ApiCaller)>, sqlx::Error> {
+    let row = sqlx::query_as::<_, ApiCallerRow>(
+        "SELECT id, name, secret_hash, scopes, owner_sub, facilitator_id FROM api_tokens \
+         WHERE id = $1",
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.map(|r| {
+        (
+            r.secret_hash,
+            ApiCaller {
+                token_id: r.id,
+                name: r.name,
+                scopes: read_scopes(&r.scopes),
+                owner_sub: r.owner_sub,
+                facilitator_id: r.facilitator_id,
+            },
+        )
+    }))
+}
+
+/// Notes that a token was used. Once a minute is close enough, and spares a script that makes a
+/// hundred calls a hundred writes.
+pub async fn touch_api_token(pool: &PgPool, id: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE api_tokens SET last_used_at = now() WHERE id = $1 \
+         AND (last_used_at IS NULL OR last_used_at < now() - interval '1 minute')",
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// The scopes of a stored token. One this build does not know, from a newer one, grants nothing.
+fn read_scopes(raw: &[String]) -> Vec<TokenScope> {
+    raw.iter().filter_map(|s| TokenScope::parse(s)).collect()
+}
+
+// --- Password lockout ---
+
+/// The address of the row that tallies the tries of every address on a board together. No client
+/// address reads like it.
+const WHOLE_BOARD: &str = "*";
+
+/// Counts a try at the password of a board against the address it came from and against the
+/// board, before the password is checked. When either tally makes the caller wait, the try is
+/// refused and counts on neither.
+///
+/// Both rows are locked for the length of the count, the address first, so that two tries at once
+/// take two places on each tally.
+pub async fn begin_unlock_attempt(
+    pool: &PgPool,
+    board_id: &str,
+    address: &str,
+) -> Result<UnlockAttempt, sqlx::Error> {
+    let now = Utc::now();
+    let mut wait_until = None;
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        "DELETE FROM unlock_failures \
+         WHERE board_id = $1 AND last_failed_at < now() - $2 * interval '1 hour'",
+    )
+    .bind(board_id)
+    .bind(UNLOCK_FORGET_AFTER_HOURS as f64)
+    .execute(&mut *tx)
+    .await?;
+
+    let tallies = [
+        (address, UNLOCK_FREE_TRIES_PER_ADDRESS, UNLOCK_LONGEST_WAIT_SECONDS),
+        (WHOLE_BOARD, UNLOCK_FREE_TRIES_PER_BOARD, UNLOCK_LONGEST_BOARD_WAIT_SECONDS),
+    ];
+    for (address, free_tries, longest_wait) in tallies {
+        sqlx::query(
+            "INSERT INTO unlock_failures (board_id, address) VALUES ($1, $2) \
+             ON CONFLICT (board_id, address) DO NOTHING",
+        )
+        .bind(board_id)
+        .bind(address)
+        .execute(&mut *tx)
+        .await?;
+
+        let row = sqlx::query_as::<_, UnlockTallyRow>(
+            "SELECT failures, locked_until, last_failed_at FROM unlock_failures \
+             WHERE board_id = $1 AND address = $2 FOR UPDATE",
+        )
+        .bind(board_id)
+        .bind(address)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let tally = UnlockTally {
+            failures: row.failures,
+            locked_until: row.locked_until,
+            last_failed_at: Some(row.last_failed_at),
+        };
+        let tally = match tally.attempt(free_tries, longest_wait, now) {
+            Ok(tally) => tally,
+            // The transaction goes back on drop, and takes the count of the other tally with it.
+            Err(until) => return Ok(UnlockAttempt::Refused { until }),
+        };
+        wait_until = wait_until.max(tally.locked_until);
+
+        sqlx::query(
+            "UPDATE unlock_failures SET failures = $3, locked_until = $4, last_failed_at = $5 \
+             WHERE board_id = $1 AND address = $2",
+        )
+        .bind(board_id)
+        .bind(address)
+        .bind(tally.failures)
+        .bind(tally.locked_until)
+        .bind(tally.last_failed_at)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(UnlockAttempt::Counted { wait_until })
+}
+
+/// Takes a try that turned out right back off the tallies. The address starts afresh; the board
+/// keeps the wrong tries of everyone else.
+pub async fn take_back_unlock_attempt(
+    pool: &PgPool,
+    board_id: &str,
+    address: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM unlock_failures WHERE board_id = $1 AND address = $2")
+        .bind(board_id)
+        .bind(address)
+        .execute(pool)
+        .await?;
+
+    sqlx::query(
+        "UPDATE unlock_failures SET failures = failures - 1, \
+         locked_until = CASE WHEN failures - 1 >= $3 THEN locked_until END \
+         WHERE board_id = $1 AND address = $2 AND failures > 0",
+    )
+    .bind(board_id)
+    .bind(WHOLE_BOARD)
+    .bind(UNLOCK_FREE_TRIES_PER_BOARD)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// The wrong passwords tried on a board since its tallies were last forgotten. None when there
+/// were none.
+pub async fn get_password_lockout(
+    pool: &PgPool,
+    board_id: &str,
+) -> Result<Option<PasswordLockout>, sqlx::Error> {
+    let row = sqlx::query_as::<_, PasswordLockoutRow>(
+        "SELECT COALESCE(MAX(failures) FILTER (WHERE address = $2), 0) AS failures, \
+         COUNT(*) FILTER (WHERE address <> $2 AND locked_until > now()) AS locked_addresses, \
+         MAX(locked_until) FILTER (WHERE address = $2 AND locked_until > now()) AS locked_until \
+         FROM unlock_failures \
+         WHERE board_id = $1 AND last_failed_at > now() - $3 * interval '1 hour'",
+    )
+    .bind(board_id)
+    .bind(WHOLE_BOARD)
+    .bind(UNLOCK_FORGET_AFTER_HOURS as f64)
+    .fetch_one(pool)
+    .await?;
+
+    Ok((row.failures > 0).then_some(PasswordLockout {
+        failures: row.failures,
+        locked_addresses: row.locked_addresses,
+        locked_until: row.locked_until,
+    }))
+}
+
+// --- Audit log ---
+
+/// Appends an entry to the audit log.
+pub async fn insert_audit_entry(
+    pool: &PgPool,
+    board_id: Option<&str>,
+    actor: &str,
+    actor_name: Option<&str>,
+    action: AuditAction,
+    target: Option<&str>,
+    detail: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO audit_log (board_id, actor, actor_name, action, target, detail) \
+         VALUES ($1, $2, $3, $4, $5, $6)",
+    )
+    .bind(board_id)
+    .bind(actor)
+    .bind(actor_name)
+    .bind(action.as_str())
+    .bind(target)
+    .bind(detail)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// The audit log, newest first. Each filter that is given narrows it; an action that ends in a dot
+/// takes every action under it.
+pub async fn list_audit_entries(
+    pool: &PgPool,
+    board_id: Option<&str>,
+    action: Option<&str>,
+    actor: Option<&str>,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    limit: i64,
+) -> Result<Vec<AuditEntry>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, AuditEntryRow>(
+        "SELECT id, at, board_id, actor, actor_name, action, target, detail FROM audit_log \
+         WHERE ($1::text IS NULL OR board_id = $1) \
+           AND ($2::text IS NULL OR action = $2 \
+                OR (right($2, 1) = '.' AND left(action, length($2)) = $2)) \
+           AND ($3::text IS NULL OR actor = $3) \
+           AND ($4::timestamptz IS NULL OR at >= $4) \
+           AND ($5::timestamptz IS NULL OR at < $5) \
+         ORDER BY id DESC LIMIT $6",
+    )
+    .bind(board_id)
+    .bind(action)
+    .bind(actor)
+    .bind(since)
+    .bind(until)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(Into::into).collect())
+}
+
+// --- Server keys ---
+
+/// The secret named `name`, made from `fresh` if no replica has made it yet. Two replicas that start
+/// at once both offer one, and both get back whichever went in first.
+pub async fn server_key(pool: &PgPool, name: &str, fresh: &str) -> Result<String, sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO server_keys (name, secret) VALUES ($1, $2) ON CONFLICT (name) DO NOTHING",
+    )
+    .bind(name)
+    .bind(fresh)
+    .execute(pool)
+    .await?;
 
-pub async fn remove_scorecard_metric(
-    pool: &PgPool,
-    metric_id: &str,
-    board_id: &str,
-) -> Result<bool, sqlx::Error> {
-    let result = sqlx::query("DELETE FROM scorecard_metrics WHERE id = $1 AND board_id = $2")
-        .bind(metric_id)
-        .bind(board_id)
-        .execute(pool)
+    let row = sqlx::query_as::<_, ServerKeyRow>("SELECT secret FROM server_keys WHERE name = $1")
+        .bind(name)
+        .fetch_one(pool)
         .await?;
-    Ok(result.rows_affected() > 0)
+
+    Ok(row.secret)
 }
 
 // --- Authorization ---
 
-/// Tells if the caller can change this board: the facilitator token, the facilitator cookie, or a
-/// place in the editor list. This is the rule that the WebSocket handler also applies.
+/// Tells if the caller can change this board: the facilitator token, the facilitator cookie, the
+/// account that made the board, or a place in the editor list. This is the rule that the WebSocket
+/// handler also applies.
 pub async fn is_board_privileged(
     pool: &PgPool,
     board_id: &str,
     facilitator_token: Option<&str>,
     facilitator_id_cookie: Option<&str>,
+    owner_sub: Option<&str>,
     participant_id: Option<&str>,
 ) -> Result<bool, sqlx::Error> {
     if let Some(token) = facilitator_token {
@@ -2209,6 +4143,12 @@ pub async fn is_board_privileged(
         }
     }
 
+    if let Some(sub) = owner_sub {
+        if is_board_owner(pool, board_id, sub).await? {
+            return Ok(true);
+        }
+    }
+
     if let Some(participant_id) = participant_id {
         if is_editor(pool, board_id, participant_id).await? {
             return Ok(true);
@@ -2232,8 +4172,47 @@ struct BoardRow {
     created_at: DateTime<Utc>,
     vote_limit_per_column: Option<i32>,
     timer_end: Option<DateTime<Utc>>,
+    timer_action: Option<String>,
+    cards_locked: bool,
+    phase: Option<String>,
+    phases: Vec<String>,
+    archived_at: Option<DateTime<Utc>>,
     template_id: Option<String>,
     has_password: bool,
+    revision: i64,
+}
+
+#[derive(sqlx::FromRow)]
+struct BoardSettingsRow {
+    title: String,
+    hide_votes: bool,
+    vote_limit_per_column: Option<i32>,
+    timer_end: Option<DateTime<Utc>>,
+    timer_action: Option<String>,
+    cards_locked: bool,
+    phase: Option<String>,
+    phases: Vec<String>,
+    archived_at: Option<DateTime<Utc>>,
+    has_password: bool,
+}
+
+#[derive(sqlx::FromRow)]
+struct UnlockTallyRow {
+    failures: i32,
+    locked_until: Option<DateTime<Utc>>,
+    last_failed_at: DateTime<Utc>,
+}
+
+#[derive(sqlx::FromRow)]
+struct PasswordLockoutRow {
+    failures: i32,
+    locked_addresses: i64,
+    locked_until: Option<DateTime<Utc>>,
+}
+
+#[derive(sqlx::FromRow)]
+struct RevisionRow {
+    revision: i64,
 }
 
 /// What the gate of a board reads. The hash stays inside this layer and the route above it;
@@ -2255,7 +4234,6 @@ struct HideVotesRow {
 struct ColumnRow {
     id: String,
     name: String,
-    #[allow(dead_code)]
     position: i32,
     role: Option<String>,
 }
@@ -2291,6 +4269,10 @@ struct TicketRow {
     gif_title: Option<String>,
     rock_status: Option<String>,
     done_at: Option<DateTime<Utc>>,
+    assignee_id: Option<String>,
+    assignee_team_id: Option<String>,
+    assignee_name: Option<String>,
+    due_on: Option<NaiveDate>,
 }
 
 impl TicketRow {
@@ -2304,6 +4286,14 @@ impl TicketRow {
             self.gif_title.take(),
         )
     }
+
+    fn take_assignee(&mut self) -> Option<Assignee> {
+        row_assignee(
+            self.assignee_id.take(),
+            self.assignee_team_id.take(),
+            self.assignee_name.take(),
+        )
+    }
 }
 
 impl CommentRow {
@@ -2319,6 +4309,55 @@ impl CommentRow {
     }
 }
 
+/// Puts a card together from its row, and says which column it sits in.
+fn ticket_from_row(
+    mut t: TicketRow,
+    votes: HashSet<String>,
+    comments: Vec<Comment>,
+) -> (String, Ticket) {
+    let gif = t.take_gif();
+    let assignee = t.take_assignee();
+    let is_overdue = crate::models::is_overdue(t.due_on, t.done_at, Utc::now().date_naive());
+    (
+        t.column_id,
+        Ticket {
+            id: t.id,
+            content: t.content,
+            author_id: t.author_id,
+            author_name: t.author_name,
+            votes,
+            vote_count: None,
+            voted: false,
+            created_at: t.created_at,
+            carried_from_board_id: t.carried_from_board_id,
+            carried_from_board_title: t.carried_from_board_title,
+            comments,
+            gif,
+            rock_status: t.rock_status,
+            done_at: t.done_at,
+            assignee,
+            due_on: t.due_on,
+            is_overdue,
+        },
+    )
+}
+
+/// Puts a comment together from its row, and says which card it sits on.
+fn comment_from_row(mut c: CommentRow) -> (String, Comment) {
+    let gif = c.take_gif();
+    (
+        c.ticket_id,
+        Comment {
+            id: c.id,
+            content: c.content,
+            author_id: c.author_id,
+            author_name: c.author_name,
+            created_at: c.created_at,
+            gif,
+        },
+    )
+}
+
 /// Puts the six GIF columns of a row back together. A row that holds only part of a GIF
 /// gives None, so a half-written picture never reaches the board.
 fn row_gif(
@@ -2364,6 +4403,46 @@ fn gif_binds(
     }
 }
 
+fn row_assignee(
+    id: Option<String>,
+    team_id: Option<String>,
+    name: Option<String>,
+) -> Option<Assignee> {
+    let (id, name) = (id?, name?);
+    Some(match team_id {
+        Some(team_id) => Assignee::TeamMember {
+            team_id,
+            member_id: id,
+            name,
+        },
+        None => Assignee::Participant {
+            participant_id: id,
+            name,
+        },
+    })
+}
+
+/// Spreads an owner into the id, team and name that the statements bind. A participant has no
+/// team, and that is how a read tells the two kinds apart again.
+fn assignee_binds(assignee: Option<&Assignee>) -> (Option<&str>, Option<&str>, Option<&str>) {
+    match assignee {
+        Some(Assignee::Participant {
+            participant_id,
+            name,
+        }) => (Some(participant_id.as_str()), None, Some(name.as_str())),
+        Some(Assignee::TeamMember {
+            team_id,
+            member_id,
+            name,
+        }) => (
+            Some(member_id.as_str()),
+            Some(team_id.as_str()),
+            Some(name.as_str()),
+        ),
+        None => (None, None, None),
+    }
+}
+
 #[derive(sqlx::FromRow)]
 struct VoteRow {
     ticket_id: String,
@@ -2396,6 +4475,11 @@ struct FacilitatorTokenRow {
     facilitator_token: String,
 }
 
+#[derive(sqlx::FromRow)]
+struct ServerKeyRow {
+    secret: String,
+}
+
 #[derive(sqlx::FromRow)]
 struct AuthorRow {
     author_id: String,
@@ -2406,6 +4490,32 @@ struct BlurRow {
     is_blurred: bool,
 }
 
+#[derive(sqlx::FromRow)]
+struct PhaseRow {
+    phase: Option<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct OwnerSubRow {
+    owner_sub: Option<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct ArchivedRow {
+    archived_at: Option<DateTime<Utc>>,
+}
+
+#[derive(sqlx::FromRow)]
+struct CardsLockedRow {
+    cards_locked: bool,
+}
+
+#[derive(sqlx::FromRow)]
+struct ExpiredTimerRow {
+    id: String,
+    timer_action: Option<String>,
+}
+
 #[derive(sqlx::FromRow)]
 struct AnonymousRow {
     is_anonymous: bool,
@@ -2422,11 +4532,30 @@ struct MyBoardRow {
     title: String,
     created_at: DateTime<Utc>,
     is_anonymous: bool,
+    archived_at: Option<DateTime<Utc>>,
     column_count: i64,
     ticket_count: i64,
     labels: Vec<String>,
 }
 
+#[derive(sqlx::FromRow)]
+struct ActionCardRow {
+    id: String,
+    content: String,
+    created_at: DateTime<Utc>,
+    done_at: Option<DateTime<Utc>>,
+    carried_from_board_id: Option<String>,
+    assignee_id: Option<String>,
+    assignee_team_id: Option<String>,
+    assignee_name: Option<String>,
+    due_on: Option<NaiveDate>,
+    board_id: String,
+    board_title: String,
+    board_created_at: DateTime<Utc>,
+    board_labels: Vec<String>,
+    more: bool,
+}
+
 #[derive(sqlx::FromRow)]
 struct ActionSourceRow {
     id: String,
@@ -2436,6 +4565,7 @@ struct ActionSourceRow {
     card_count: i64,
     labels: Vec<String>,
     is_locked: bool,
+    archived_at: Option<DateTime<Utc>>,
 }
 
 #[derive(sqlx::FromRow)]
@@ -2467,6 +4597,7 @@ struct TemplateRow {
     columns: Vec<String>,
     default_blurred: bool,
     position: i32,
+    phases: Vec<String>,
 }
 
 #[derive(sqlx::FromRow)]
@@ -2474,6 +4605,20 @@ struct CountRow {
     count: i64,
 }
 
+#[derive(sqlx::FromRow)]
+struct OperationRow {
+    id: i64,
+    kind: String,
+    author_id: Option<String>,
+    changes: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct PresenceCountRow {
+    board_id: String,
+    count: i64,
+}
+
 #[derive(sqlx::FromRow)]
 struct TemplateIdRow {
     template_id: Option<String>,
@@ -2506,6 +4651,169 @@ struct TicketColumnRow {
     column_id: String,
 }
 
+#[derive(sqlx::FromRow)]
+struct CommentTicketRow {
+    ticket_id: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct WebhookRow {
+    id: String,
+    board_id: Option<String>,
+    url: String,
+    events: Vec<String>,
+    created_at: DateTime<Utc>,
+}
+
+impl From<WebhookRow> for WebhookView {
+    fn from(r: WebhookRow) -> Self {
+        WebhookView {
+            id: r.id,
+            board_id: r.board_id,
+            url: r.url,
+            events: r.events,
+            created_at: r.created_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct ApiTokenRow {
+    id: String,
+    name: String,
+    scopes: Vec<String>,
+    created_at: DateTime<Utc>,
+    last_used_at: Option<DateTime<Utc>>,
+}
+
+impl From<ApiTokenRow> for ApiTokenView {
+    fn from(r: ApiTokenRow) -> Self {
+        ApiTokenView {
+            id: r.id,
+            name: r.name,
+            scopes: read_scopes(&r.scopes),
+            created_at: r.created_at,
+            last_used_at: r.last_used_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct ApiCallerRow {
+    id: String,
+    name: String,
+    secret_hash: String,
+    scopes: Vec<String>,
+    owner_sub: Option<String>,
+    facilitator_id: Option<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct IdRow {
+    #[allow(dead_code)]
+    id: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct RetentionPolicyRow {
+    days: Option<i32>,
+    action: String,
+    exempt_labels: Vec<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct RetentionCandidateRow {
+    id: String,
+    title: String,
+    labels: Vec<String>,
+    card_count: i64,
+    last_activity_at: DateTime<Utc>,
+}
+
+#[derive(sqlx::FromRow)]
+struct RetentionLogRow {
+    id: i64,
+    board_id: String,
+    title: String,
+    action: String,
+    labels: Vec<String>,
+    card_count: i64,
+    last_activity_at: DateTime<Utc>,
+    purged_at: DateTime<Utc>,
+}
+
+impl From<RetentionLogRow> for RetentionRecord {
+    fn from(r: RetentionLogRow) -> Self {
+        RetentionRecord {
+            id: r.id,
+            board_id: r.board_id,
+            title: r.title,
+            action: r.action,
+            labels: r.labels,
+            card_count: r.card_count,
+            last_activity_at: r.last_activity_at,
+            purged_at: r.purged_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct AuditEntryRow {
+    id: i64,
+    at: DateTime<Utc>,
+    board_id: Option<String>,
+    actor: String,
+    actor_name: Option<String>,
+    action: String,
+    target: Option<String>,
+    detail: Option<String>,
+}
+
+impl From<AuditEntryRow> for AuditEntry {
+    fn from(r: AuditEntryRow) -> Self {
+        AuditEntry {
+            id: r.id,
+            at: r.at,
+            board_id: r.board_id,
+            actor: r.actor,
+            actor_name: r.actor_name,
+            action: r.action,
+            target: r.target,
+            detail: r.detail,
+        }
+    }
+}
+
+/// A delivery that is due, with where it goes and the key it is signed with.
+#[derive(sqlx::FromRow)]
+pub struct DueDelivery {
+    pub id: i64,
+    pub event: String,
+    pub payload: String,
+    /// The tries so far, this one included.
+    pub attempts: i32,
+    pub url: String,
+    pub secret: String,
+    /// True for a webhook a facilitator set, false for a global one an admin set.
+    pub is_board_webhook: bool,
+}
+
+#[derive(sqlx::FromRow)]
+struct WebhookDeliveryRow {
+    id: i64,
+    webhook_id: String,
+    url: String,
+    board_id: String,
+    event: String,
+    status: String,
+    attempts: i32,
+    next_attempt_at: DateTime<Utc>,
+    last_status_code: Option<i32>,
+    last_error: Option<String>,
+    created_at: DateTime<Utc>,
+    delivered_at: Option<DateTime<Utc>>,
+}
+
 #[derive(sqlx::FromRow, Debug)]
 pub struct AdminStatsRow {
     pub board_count: i64,
@@ -2519,6 +4827,7 @@ pub struct AdminBoardRow {
     pub title: String,
     pub is_blurred: bool,
     pub created_at: DateTime<Utc>,
+    pub archived_at: Option<DateTime<Utc>>,
     pub column_count: i64,
     pub ticket_count: i64,
     pub vote_count: i64,
@@ -2550,9 +4859,314 @@ struct TeamMemberRow {
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
 
+    /// A pool on the database `TEST_DATABASE_URL` names, with the migrations run. None when it is
+    /// not set, and the tests that read and write Postgres pass without doing anything: they need
+    /// a database of their own, which not every machine that runs the tests has. Each test makes
+    /// its own boards under fresh ids, so they run side by side and against a database in use.
+    pub(crate) async fn test_pool() -> Option<PgPool> {
+        let url = std::env::var("TEST_DATABASE_URL").ok()?;
+        let pool = PgPool::connect(&url)
+            .await
+            .expect("could not connect to TEST_DATABASE_URL");
+        sqlx::migrate!()
+            .run(&pool)
+            .await
+            .expect("could not migrate the test database");
+        Some(pool)
+    }
+
+    /// A new board with a column, made by `facilitator_id` and held by the account `owner_sub`.
+    pub(crate) async fn test_board(
+        pool: &PgPool,
+        facilitator_id: &str,
+        owner_sub: Option<&str>,
+    ) -> String {
+        let id = nanoid::nanoid!(10);
+        let columns = [(nanoid::nanoid!(8), "Went well".to_string(), None)];
+        create_board(
+            pool,
+            &id,
+            "Test",
+            &nanoid::nanoid!(32),
+            facilitator_id,
+            owner_sub,
+            &columns,
+            Utc::now(),
+            false,
+            &[],
+            None,
+            None,
+            &nanoid::nanoid!(32),
+            false,
+            &[],
+        )
+        .await
+        .expect("could not make a test board");
+        id
+    }
+
+    fn ids(boards: Vec<crate::models::MyBoardSummary>) -> Vec<String> {
+        let mut ids: Vec<String> = boards.into_iter().map(|b| b.id).collect();
+        ids.sort();
+        ids
+    }
+
+    fn sorted(mut ids: Vec<String>) -> Vec<String> {
+        ids.sort();
+        ids
+    }
+
+    #[tokio::test]
+    async fn an_account_holds_its_own_boards_and_a_cookie_only_those_it_made() {
+        let Some(pool) = test_pool().await else {
+            return;
+        };
+        let (cookie, other_cookie) = (nanoid::nanoid!(), nanoid::nanoid!());
+        let (sub, other_sub) = (nanoid::nanoid!(), nanoid::nanoid!());
+        let held = test_board(&pool, &cookie, Some(&sub)).await;
+        let unclaimed = test_board(&pool, &cookie, None).await;
+        let elsewhere = test_board(&pool, &other_cookie, Some(&sub)).await;
+        let taken = test_board(&pool, &cookie, Some(&other_sub)).await;
+
+        // Signed in, the account decides, from whatever browser.
+        let boards = get_boards_by_owner(&pool, Some(&cookie), Some(&sub)).await.unwrap();
+        assert_eq!(ids(boards), sorted(vec![held.clone(), elsewhere.clone()]));
+        // Signed out, the cookie does, whoever holds the boards since.
+        let boards = get_boards_by_owner(&pool, Some(&cookie), None).await.unwrap();
+        assert_eq!(ids(boards), sorted(vec![held, unclaimed.clone(), taken.clone()]));
+
+        let privileged = |board: String, cookie: Option<String>, sub: Option<String>| {
+            let pool = pool.clone();
+            async move {
+                is_board_privileged(&pool, &board, None, cookie.as_deref(), sub.as_deref(), None)
+                    .await
+                    .unwrap()
+            }
+        };
+        assert!(privileged(elsewhere.clone(), None, Some(sub.clone())).await);
+        assert!(!privileged(elsewhere, Some(cookie.clone()), None).await);
+        assert!(!privileged(taken.clone(), None, Some(sub.clone())).await);
+        assert!(!privileged(unclaimed, None, Some(sub)).await);
+    }
+
+    #[tokio::test]
+    async fn signing_in_claims_only_the_boards_no_account_holds() {
+        let Some(pool) = test_pool().await else {
+            return;
+        };
+        let cookie = nanoid::nanoid!();
+        let (sub, other_sub) = (nanoid::nanoid!(), nanoid::nanoid!());
+        let unclaimed = test_board(&pool, &cookie, None).await;
+        let taken = test_board(&pool, &cookie, Some(&other_sub)).await;
+
+        assert_eq!(claim_boards(&pool, &cookie, &sub).await.unwrap(), 1);
+        assert!(is_board_owner(&pool, &unclaimed, &sub).await.unwrap());
+        assert!(is_board_owner(&pool, &taken, &other_sub).await.unwrap());
+        assert!(!is_board_owner(&pool, &taken, &sub).await.unwrap());
+        // A second sign-in finds nothing left to claim.
+        assert_eq!(claim_boards(&pool, &cookie, &sub).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn the_purge_spares_a_board_that_took_an_exempt_label_after_it_was_listed() {
+        let Some(pool) = test_pool().await else {
+            return;
+        };
+        let board = test_board(&pool, &nanoid::nanoid!(), None).await;
+        sqlx::query("UPDATE boards SET last_activity_at = now() - interval '400 days' WHERE id = $1")
+            .bind(&board)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let candidate = RetentionCandidate {
+            board_id: board.clone(),
+            title: "Test".into(),
+            labels: Vec::new(),
+            card_count: 0,
+            last_activity_at: Utc::now(),
+            action: RetentionAction::Delete,
+        };
+        let exempt = vec!["legal".to_string()];
+        set_board_labels(&pool, &board, &exempt).await.unwrap();
+
+        let purged = purge_board(&pool, &candidate, 365, &exempt).await.unwrap();
+        assert!(purged.is_none());
+        assert!(get_board(&pool, &board).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn an_anonymized_board_still_hides_its_cards_while_it_is_blurred() {
+        let Some(pool) = test_pool().await else {
+            return;
+        };
+        let board = test_board(&pool, &nanoid::nanoid!(), None).await;
+        sqlx::query(
+            "UPDATE boards SET is_blurred = true, last_activity_at = now() - interval '400 days' \
+             WHERE id = $1",
+        )
+        .bind(&board)
+        .execute(&pool)
+        .await
+        .unwrap();
+        let column_id = get_board(&pool, &board).await.unwrap().unwrap().columns[0].id.clone();
+        add_ticket(&pool, &nanoid::nanoid!(), &column_id, "Ann wrote this", "ann", "Ann", Utc::now(), None)
+            .await
+            .unwrap();
+        let candidate = RetentionCandidate {
+            board_id: board.clone(),
+            title: "Test".into(),
+            labels: Vec::new(),
+            card_count: 1,
+            last_activity_at: Utc::now(),
+            action: RetentionAction::Anonymize,
+        };
+        assert!(purge_board(&pool, &candidate, 365, &[]).await.unwrap().is_some());
+
+        // As the REST route reads it, for a reader who is not the facilitator.
+        let mut view = get_board(&pool, &board)
+            .await
+            .unwrap()
+            .unwrap()
+            .to_view_with_participants(0, Vec::new(), Vec::new());
+        view.redact_hidden_for("", false);
+        let ticket = &view.columns[0].tickets[0];
+        assert_ne!(ticket.content, "Ann wrote this");
+        assert!(!ticket.author_id.is_empty());
+    }
+
+    /// A board of Previous Actions, a column of no role that holds a card, and Actions, in that
+    /// order, by their ids.
+    async fn board_with_roles(pool: &PgPool) -> (String, [String; 3]) {
+        let board = test_board(pool, &nanoid::nanoid!(), None).await;
+        let plain = get_board(pool, &board).await.unwrap().unwrap().columns[0].id.clone();
+        let (prev, act) = (nanoid::nanoid!(8), nanoid::nanoid!(8));
+        sqlx::query("UPDATE columns SET position = 1 WHERE id = $1")
+            .bind(&plain)
+            .execute(pool)
+            .await
+            .unwrap();
+        for (id, name, position, role) in [
+            (&prev, "Previous Actions", 0, ROLE_PREVIOUS_ACTIONS),
+            (&act, "Actions", 2, ROLE_ACTIONS),
+        ] {
+            sqlx::query(
+                "INSERT INTO columns (id, board_id, name, position, role) \
+                 VALUES ($1, $2, $3, $4, $5)",
+            )
+            .bind(id)
+            .bind(&board)
+            .bind(name)
+            .bind(position)
+            .bind(role)
+            .execute(pool)
+            .await
+            .unwrap();
+        }
+        add_ticket(pool, &nanoid::nanoid!(), &plain, "A card", "p1", "P", Utc::now(), None)
+            .await
+            .unwrap();
+        (board, [prev, plain, act])
+    }
+
+    async fn column_names(pool: &PgPool, board_id: &str) -> Vec<(String, usize)> {
+        let board = get_board(pool, board_id).await.unwrap().unwrap();
+        board.columns.into_iter().map(|c| (c.name, c.tickets.len())).collect()
+    }
+
+    fn named(columns: &[(&str, usize)]) -> Vec<(String, usize)> {
+        columns.iter().map(|(n, c)| (n.to_string(), *c)).collect()
+    }
+
+    #[tokio::test]
+    async fn a_column_is_added_before_actions_and_only_where_there_is_one() {
+        let Some(pool) = test_pool().await else {
+            return;
+        };
+        let (board, _) = board_with_roles(&pool).await;
+        assert!(add_column(&pool, &board, &nanoid::nanoid!(8), "Kudos", false).await.unwrap());
+        assert_eq!(
+            column_names(&pool, &board).await,
+            named(&[("Previous Actions", 0), ("Went well", 1), ("Kudos", 0), ("Actions", 0)])
+        );
+
+        // A board with no Actions has no place to put it.
+        let bare = test_board(&pool, &nanoid::nanoid!(), None).await;
+        assert!(!add_column(&pool, &bare, &nanoid::nanoid!(8), "Kudos", false).await.unwrap());
+        assert_eq!(column_names(&pool, &bare).await, named(&[("Went well", 0)]));
+    }
+
+    #[tokio::test]
+    async fn a_column_with_a_role_or_of_another_board_keeps_its_name() {
+        let Some(pool) = test_pool().await else {
+            return;
+        };
+        let (board, [prev, plain, act]) = board_with_roles(&pool).await;
+        let (other, _) = board_with_roles(&pool).await;
+        assert!(!rename_column(&pool, &board, &prev, "Kudos").await.unwrap());
+        assert!(!rename_column(&pool, &board, &act, "Kudos").await.unwrap());
+        assert!(!rename_column(&pool, &other, &plain, "Kudos").await.unwrap());
+        assert_eq!(
+            column_names(&pool, &board).await,
+            named(&[("Previous Actions", 0), ("Went well", 1), ("Actions", 0)])
+        );
+
+        assert!(rename_column(&pool, &board, &plain, "Kudos").await.unwrap());
+        assert_eq!(column_names(&pool, &board).await[1].0, "Kudos");
+    }
+
+    #[tokio::test]
+    async fn a_new_order_that_moves_actions_or_misses_a_column_changes_nothing() {
+        let Some(pool) = test_pool().await else {
+            return;
+        };
+        let (board, [prev, plain, act]) = board_with_roles(&pool).await;
+        let unchanged = named(&[("Previous Actions", 0), ("Went well", 1), ("Actions", 0)]);
+        for order in [
+            vec![act.clone(), prev.clone(), plain.clone()],
+            vec![prev.clone(), act.clone()],
+            vec![prev.clone(), prev.clone(), act.clone()],
+        ] {
+            assert!(!reorder_columns(&pool, &board, &order).await.unwrap());
+            assert_eq!(column_names(&pool, &board).await, unchanged);
+        }
+
+        assert!(reorder_columns(&pool, &board, &[plain, prev, act]).await.unwrap());
+        assert_eq!(
+            column_names(&pool, &board).await,
+            named(&[("Went well", 1), ("Previous Actions", 0), ("Actions", 0)])
+        );
+    }
+
+    #[tokio::test]
+    async fn a_column_goes_only_without_a_role_and_with_somewhere_for_its_cards() {
+        let Some(pool) = test_pool().await else {
+            return;
+        };
+        let (board, [prev, plain, act]) = board_with_roles(&pool).await;
+        let (other, [_, elsewhere, _]) = board_with_roles(&pool).await;
+        let unchanged = named(&[("Previous Actions", 0), ("Went well", 1), ("Actions", 0)]);
+
+        // The action columns stay, empty or not.
+        assert!(!remove_column(&pool, &board, &prev, Some(&plain)).await.unwrap());
+        assert!(!remove_column(&pool, &board, &act, Some(&plain)).await.unwrap());
+        // A column with a card needs a column of its own board to send it to, and not itself.
+        assert!(!remove_column(&pool, &board, &plain, None).await.unwrap());
+        assert!(!remove_column(&pool, &board, &plain, Some(&plain)).await.unwrap());
+        assert!(!remove_column(&pool, &board, &plain, Some(&elsewhere)).await.unwrap());
+        // Nor does a board remove the column of another.
+        assert!(!remove_column(&pool, &other, &plain, Some(&act)).await.unwrap());
+        assert_eq!(column_names(&pool, &board).await, unchanged);
+
+        assert!(remove_column(&pool, &board, &plain, Some(&act)).await.unwrap());
+        assert_eq!(
+            column_names(&pool, &board).await,
+            named(&[("Previous Actions", 0), ("Actions", 1)])
+        );
+    }
+
     fn col(id: &str, name: &str, position: i32, role: Option<&str>) -> ColumnRow {
         ColumnRow {
             id: id.into(),
@@ -2589,6 +5203,19 @@ mod tests {
             .collect()
     }
 
+    #[test]
+    fn a_new_order_names_every_column_once_and_keeps_actions_last() {
+        let board = level10_board();
+        let order = names(&["solved", "prev", "ids", "rocks", "head", "act"]);
+        assert!(is_valid_column_order(&board, &order));
+
+        // Actions somewhere else, a column left out, a column twice, a column of another board.
+        assert!(!is_valid_column_order(&board, &names(&["act", "prev", "solved", "head", "rocks", "ids"])));
+        assert!(!is_valid_column_order(&board, &names(&["prev", "solved", "head", "rocks", "act"])));
+        assert!(!is_valid_column_order(&board, &names(&["prev", "prev", "head", "rocks", "ids", "act"])));
+        assert!(!is_valid_column_order(&board, &names(&["prev", "other", "head", "rocks", "ids", "act"])));
+    }
+
     #[test]
     fn a_rename_goes_by_position_and_keeps_the_column() {
         let plan = plan_template_apply(
@@ -0,0 +1,645 @@
+//! Renders a board as a file to keep: Markdown to paste into a wiki page, CSV for a spreadsheet,
+//! JSON for anything that reads it, and the archive that another instance imports.
+//!
+//! The renderers take a `BoardView` as it stands, with nothing redacted, and write every word on
+//! it. What keeps the words from the wrong reader is the route, which refuses the file instead of
+//! cutting the board down: a blurred board, or one whose votes are hidden, goes to the facilitator
+//! and the editors alone, and so does the archive. A new caller must refuse the same way, or
+//! redact the view first.
+//!
+//! The first three leave out what a reader of the file has no use for: participant ids, who voted
+//! for what, and who gave the meeting which mark. A card carries its vote count; the meeting
+//! carries the average of its marks. The archive keeps every vote and mark, since the board it
+//! becomes has to count them again, but under names that hold only inside the file.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::models::{
+    ArchivedCard, ArchivedColumn, ArchivedComment, ArchivedMetric, ArchivedRating, Assignee,
+    BoardArchive, BoardView, Column, Ticket, BOARD_ARCHIVE_VERSION,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Markdown,
+    Csv,
+    Json,
+    /// The whole board, for `POST /api/boards/import`.
+    Archive,
+}
+
+impl ExportFormat {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "markdown" | "md" => Some(ExportFormat::Markdown),
+            "csv" => Some(ExportFormat::Csv),
+            "json" => Some(ExportFormat::Json),
+            "archive" => Some(ExportFormat::Archive),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "text/markdown; charset=utf-8",
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Json | ExportFormat::Archive => "application/json",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+            ExportFormat::Archive => "rewind.json",
+        }
+    }
+}
+
+pub fn render(board: &BoardView, format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Markdown => markdown(board),
+        ExportFormat::Csv => csv(board),
+        ExportFormat::Json => serde_json::to_string_pretty(&ExportedBoard::from(board))
+            .unwrap_or_default(),
+        ExportFormat::Archive => serde_json::to_string_pretty(&archive(board)).unwrap_or_default(),
+    }
+}
+
+/// The name the download gets: the title of the board, cut down to what every file system takes.
+pub fn file_name(board: &BoardView, format: ExportFormat) -> String {
+    let mut stem: String = board
+        .title
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect();
+    while stem.contains("--") {
+        stem = stem.replace("--", "-");
+    }
+    let stem = stem.trim_matches('-');
+    let stem = if stem.is_empty() { board.id.as_str() } else { stem };
+    format!("{stem}.{}", format.extension())
+}
+
+/// The average mark of the meeting, or None before anyone gave one.
+fn average_rating(board: &BoardView) -> Option<f64> {
+    if board.meeting_ratings.is_empty() {
+        return None;
+    }
+    let sum: i32 = board.meeting_ratings.iter().map(|r| r.rating).sum();
+    Some(sum as f64 / board.meeting_ratings.len() as f64)
+}
+
+fn rock_label(status: &str) -> &str {
+    match status {
+        "on_track" => "on track",
+        "off_track" => "off track",
+        other => other,
+    }
+}
+
+// --- Archive ---
+
+/// Gives each participant a name that holds only inside one file, in the order they first
+/// appear.
+#[derive(Default)]
+struct Pseudonyms(HashMap<String, String>);
+
+impl Pseudonyms {
+    fn of(&mut self, participant_id: &str) -> String {
+        let next = self.0.len() + 1;
+        self.0
+            .entry(participant_id.to_string())
+            .or_insert_with(|| format!("participant-{next}"))
+            .clone()
+    }
+}
+
+pub fn archive(board: &BoardView) -> BoardArchive {
+    let mut names = Pseudonyms::default();
+    let columns = board
+        .columns
+        .iter()
+        .map(|column| ArchivedColumn {
+            name: column.name.clone(),
+            role: column.role.clone(),
+            cards: column
+                .tickets
+                .iter()
+                .map(|ticket| {
+                    // Sorted, so that one board gives the same file twice.
+                    let mut votes: Vec<&String> = ticket.votes.iter().collect();
+                    votes.sort();
+                    ArchivedCard {
+                        content: ticket.content.clone(),
+                        author: names.of(&ticket.author_id),
+                        author_name: ticket.author_name.clone(),
+                        created_at: ticket.created_at,
+                        votes: votes.into_iter().map(|v| names.of(v)).collect(),
+                        gif: ticket.gif.clone(),
+                        carried_from_board_title: ticket.carried_from_board_title.clone(),
+                        rock_status: ticket.rock_status.clone(),
+                        done_at: ticket.done_at,
+                        assignee: ticket.assignee.as_ref().map(|a| match a {
+                            Assignee::Participant { participant_id, .. } => names.of(participant_id),
+                            Assignee::TeamMember {
+                                team_id, member_id, ..
+                            } => names.of(&format!("team:{team_id}:{member_id}")),
+                        }),
+                        assignee_name: ticket.assignee.as_ref().map(|a| a.name().to_string()),
+                        due_on: ticket.due_on,
+                        comments: ticket
+                            .comments
+                            .iter()
+                            .map(|c| ArchivedComment {
+                                content: c.content.clone(),
+                                author: names.of(&c.author_id),
+                                author_name: c.author_name.clone(),
+                                created_at: c.created_at,
+                                gif: c.gif.clone(),
+                            })
+                            .collect(),
+                    }
+                })
+                .collect(),
+        })
+        .collect();
+
+    BoardArchive {
+        version: BOARD_ARCHIVE_VERSION,
+        title: board.title.clone(),
+        created_at: board.created_at,
+        is_blurred: board.is_blurred,
+        is_anonymous: board.is_anonymous,
+        hide_votes: board.hide_votes,
+        vote_limit_per_column: board.vote_limit_per_column,
+        labels: board.labels.clone(),
+        template_id: board.template_id.clone(),
+        columns,
+        scorecard: board
+            .scorecard
+            .iter()
+            .map(|m| ArchivedMetric {
+                name: m.name.clone(),
+                goal: m.goal.clone(),
+                actual: m.actual.clone(),
+                on_track: m.on_track,
+            })
+            .collect(),
+        meeting_ratings: board
+            .meeting_ratings
+            .iter()
+            .map(|r| ArchivedRating {
+                participant: names.of(&r.participant_id),
+                rating: r.rating,
+            })
+            .collect(),
+    }
+}
+
+// --- JSON ---
+
+#[derive(Debug, Serialize)]
+struct ExportedBoard<'a> {
+    id: &'a str,
+    title: &'a str,
+    created_at: DateTime<Utc>,
+    labels: &'a [String],
+    columns: Vec<ExportedColumn<'a>>,
+    scorecard: Vec<ExportedMetric<'a>>,
+    meeting_rating: Option<ExportedRating>,
+}
+
+#[derive(Debug, Serialize)]
+struct ExportedColumn<'a> {
+    name: &'a str,
+    role: Option<&'a str>,
+    cards: Vec<ExportedCard<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct ExportedCard<'a> {
+    content: &'a str,
+    author_name: &'a str,
+    created_at: DateTime<Utc>,
+    votes: usize,
+    gif_url: Option<&'a str>,
+    carried_from_board_title: Option<&'a str>,
+    done_at: Option<DateTime<Utc>>,
+    assignee_name: Option<&'a str>,
+    due_on: Option<NaiveDate>,
+    rock_status: Option<&'a str>,
+    comments: Vec<ExportedComment<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct ExportedComment<'a> {
+    content: &'a str,
+    author_name: &'a str,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+struct ExportedMetric<'a> {
+    name: &'a str,
+    goal: &'a str,
+    actual: &'a str,
+    on_track: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+struct ExportedRating {
+    average: f64,
+    count: usize,
+}
+
+impl<'a> From<&'a BoardView> for ExportedBoard<'a> {
+    fn from(board: &'a BoardView) -> Self {
+        ExportedBoard {
+            id: &board.id,
+            title: &board.title,
+            created_at: board.created_at,
+            labels: &board.labels,
+            columns: board.columns.iter().map(ExportedColumn::from).collect(),
+            scorecard: board
+                .scorecard
+                .iter()
+                .map(|m| ExportedMetric {
+                    name: &m.name,
+                    goal: &m.goal,
+                    actual: &m.actual,
+                    on_track: m.on_track,
+                })
+                .collect(),
+            meeting_rating: average_rating(board).map(|average| ExportedRating {
+                average,
+                count: board.meeting_ratings.len(),
+            }),
+        }
+    }
+}
+
+impl<'a> From<&'a Column> for ExportedColumn<'a> {
+    fn from(column: &'a Column) -> Self {
+        ExportedColumn {
+            name: &column.name,
+            role: column.role.as_deref(),
+            cards: column.tickets.iter().map(ExportedCard::from).collect(),
+        }
+    }
+}
+
+impl<'a> From<&'a Ticket> for ExportedCard<'a> {
+    fn from(ticket: &'a Ticket) -> Self {
+        ExportedCard {
+            content: &ticket.content,
+            author_name: &ticket.author_name,
+            created_at: ticket.created_at,
+            votes: ticket.votes.len(),
+            gif_url: ticket.gif.as_ref().map(|g| g.url.as_str()),
+            carried_from_board_title: ticket.carried_from_board_title.as_deref(),
+            done_at: ticket.done_at,
+            assignee_name: ticket.assignee.as_ref().map(|a| a.name()),
+            due_on: ticket.due_on,
+            rock_status: ticket.rock_status.as_deref(),
+            comments: ticket
+                .comments
+                .iter()
+                .map(|c| ExportedComment {
+                    content: &c.content,
+                    author_name: &c.author_name,
+                    created_at: c.created_at,
+                })
+                .collect(),
+        }
+    }
+}
+
+// --- Markdown ---
+
+/// Puts a line of user text on one Markdown line. A card may hold line breaks; in a list item
+/// they would end the item, so they become `<br>`, which Confluence and GitHub both read.
+fn md_inline(text: &str) -> String {
+    text.trim().replace("\r\n", "\n").replace('\n', "<br>")
+}
+
+/// A cell of a Markdown table, where a pipe would end the cell.
+fn md_cell(text: &str) -> String {
+    md_inline(text).replace('|', "\\|")
+}
+
+fn markdown(board: &BoardView) -> String {
+    let mut out = format!("# {}\n\n", md_inline(&board.title));
+    out.push_str(&format!("_{}_\n", board.created_at.format("%Y-%m-%d")));
+    if !board.labels.is_empty() {
+        out.push_str(&format!("\nLabels: {}\n", board.labels.join(", ")));
+    }
+
+    for column in &board.columns {
+        out.push_str(&format!("\n## {}\n\n", md_inline(&column.name)));
+        if column.tickets.is_empty() {
+            out.push_str("_No cards._\n");
+            continue;
+        }
+        for ticket in &column.tickets {
+            out.push_str(&format!("- {}", md_inline(&ticket.content)));
+            if let Some(gif) = &ticket.gif {
+                out.push_str(&format!(" ![{}]({})", md_inline(&gif.title), gif.url));
+            }
+            let mut marks = Vec::new();
+            if !ticket.votes.is_empty() {
+                let n = ticket.votes.len();
+                marks.push(format!("{n} vote{}", if n == 1 { "" } else { "s" }));
+            }
+            if let Some(assignee) = &ticket.assignee {
+                marks.push(format!("owner {}", md_inline(assignee.name())));
+            }
+            if let Some(due_on) = ticket.due_on {
+                marks.push(format!("due {}", due_on.format("%Y-%m-%d")));
+            }
+            if let Some(done_at) = ticket.done_at {
+                marks.push(format!("done {}", done_at.format("%Y-%m-%d")));
+            }
+            if let Some(status) = &ticket.rock_status {
+                marks.push(rock_label(status).to_string());
+            }
+            if let Some(title) = &ticket.carried_from_board_title {
+                marks.push(format!("from {}", md_inline(title)));
+            }
+            if !ticket.author_name.is_empty() {
+                marks.push(md_inline(&ticket.author_name));
+            }
+            if !marks.is_empty() {
+                out.push_str(&format!(" _({})_", marks.join(", ")));
+            }
+            out.push('\n');
+            for comment in &ticket.comments {
+                if comment.author_name.is_empty() {
+                    out.push_str(&format!("  - {}\n", md_inline(&comment.content)));
+                } else {
+                    out.push_str(&format!(
+                        "  - **{}:** {}\n",
+                        md_inline(&comment.author_name),
+                        md_inline(&comment.content)
+                    ));
+                }
+            }
+        }
+    }
+
+    if !board.scorecard.is_empty() {
+        out.push_str("\n## Scorecard\n\n| Metric | Goal | This week | On track |\n|---|---|---|---|\n");
+        for metric in &board.scorecard {
+            let on_track = match metric.on_track {
+                Some(true) => "yes",
+                Some(false) => "no",
+                None => "",
+            };
+            out.push_str(&format!(
+                "| {} | {} | {} | {} |\n",
+                md_cell(&metric.name),
+                md_cell(&metric.goal),
+                md_cell(&metric.actual),
+                on_track
+            ));
+        }
+    }
+
+    if let Some(average) = average_rating(board) {
+        out.push_str(&format!(
+            "\n## Meeting rating\n\n{average:.1} out of 10, from {} ratings\n",
+            board.meeting_ratings.len()
+        ));
+    }
+
+    out
+}
+
+// --- CSV ---
+
+/// One field of a CSV row. A field with a separator, a quote or a line break goes in quotes.
+/// A field that starts like a formula gets a leading quote mark, so that a spreadsheet shows the
+/// words of a card rather than run them.
+fn csv_field(text: &str) -> String {
+    let text = if text.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{text}")
+    } else {
+        text.to_string()
+    };
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
+
+fn csv_row(fields: &[String]) -> String {
+    let mut row = fields
+        .iter()
+        .map(|f| csv_field(f))
+        .collect::<Vec<_>>()
+        .join(",");
+    row.push_str("\r\n");
+    row
+}
+
+/// One row for each card, with its comments joined in one field. A board with a scorecard or
+/// with ratings adds them below, each under its own header after an empty line.
+fn csv(board: &BoardView) -> String {
+    let mut out = csv_row(
+        &[
+            "column", "content", "author", "votes", "comments", "owner", "due_on", "done_at",
+            "rock_status", "carried_from",
+        ]
+        .map(String::from),
+    );
+    for column in &board.columns {
+        for ticket in &column.tickets {
+            let comments = ticket
+                .comments
+                .iter()
+                .map(|c| {
+                    if c.author_name.is_empty() {
+                        c.content.clone()
+                    } else {
+                        format!("{}: {}", c.author_name, c.content)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            out.push_str(&csv_row(&[
+                column.name.clone(),
+                ticket.content.clone(),
+                ticket.author_name.clone(),
+                ticket.votes.len().to_string(),
+                comments,
+                ticket.assignee.as_ref().map(|a| a.name().to_string()).unwrap_or_default(),
+                ticket.due_on.map(|d| d.to_string()).unwrap_or_default(),
+                ticket.done_at.map(|d| d.to_rfc3339()).unwrap_or_default(),
+                ticket.rock_status.as_deref().map(rock_label).unwrap_or_default().to_string(),
+                ticket.carried_from_board_title.clone().unwrap_or_default(),
+            ]));
+        }
+    }
+
+    if !board.scorecard.is_empty() {
+        out.push_str("\r\n");
+        out.push_str(&csv_row(&["metric", "goal", "actual", "on_track"].map(String::from)));
+        for metric in &board.scorecard {
+            out.push_str(&csv_row(&[
+                metric.name.clone(),
+                metric.goal.clone(),
+                metric.actual.clone(),
+                metric.on_track.map(|t| t.to_string()).unwrap_or_default(),
+            ]));
+        }
+    }
+
+    if let Some(average) = average_rating(board) {
+        out.push_str("\r\n");
+        out.push_str(&csv_row(&["meeting_rating", "ratings"].map(String::from)));
+        out.push_str(&csv_row(&[
+            format!("{average:.1}"),
+            board.meeting_ratings.len().to_string(),
+        ]));
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Comment, MeetingRatingView, ScorecardMetric};
+    use std::collections::HashSet;
+
+    fn board() -> BoardView {
+        let created_at = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        BoardView {
+            id: "b1".into(),
+            title: "Sprint 12 / Retro".into(),
+            columns: vec![Column {
+                id: "c1".into(),
+                name: "Went well".into(),
+                role: None,
+                tickets: vec![Ticket {
+                    id: "t1".into(),
+                    content: "=SUM(A1), and \"quotes\"\nsecond line".into(),
+                    author_id: "p1".into(),
+                    author_name: "Rita".into(),
+                    votes: HashSet::from(["p1".to_string(), "p2".to_string()]),
+                    vote_count: None,
+                    voted: false,
+                    created_at,
+                    carried_from_board_id: None,
+                    carried_from_board_title: None,
+                    comments: vec![Comment {
+                        id: "m1".into(),
+                        content: "agreed".into(),
+                        author_id: "p2".into(),
+                        author_name: "Sam".into(),
+                        created_at,
+                        gif: None,
+                    }],
+                    gif: None,
+                    rock_status: None,
+                    done_at: None,
+                    assignee: None,
+                    due_on: None,
+                    is_overdue: false,
+                }],
+            }],
+            is_blurred: false,
+            is_anonymous: false,
+            hide_votes: false,
+            created_at,
+            participant_count: 0,
+            vote_limit_per_column: None,
+            timer_end: None,
+            timer_action: None,
+            cards_locked: false,
+            phase: None,
+            phases: Vec::new(),
+            archived_at: None,
+            editors: vec![],
+            editor_requests: vec![],
+            labels: vec![],
+            template_id: None,
+            scorecard: vec![ScorecardMetric {
+                id: "s1".into(),
+                name: "Uptime | prod".into(),
+                goal: ">= 99%".into(),
+                actual: "99.5%".into(),
+                on_track: Some(true),
+            }],
+            meeting_ratings: vec![
+                MeetingRatingView { participant_id: "p1".into(), rating: 8 },
+                MeetingRatingView { participant_id: "p2".into(), rating: 9 },
+            ],
+            has_password: false,
+            password_lockout: None,
+            revision: 0,
+        }
+    }
+
+    #[test]
+    fn formats_parse_by_name() {
+        assert_eq!(ExportFormat::parse("Markdown"), Some(ExportFormat::Markdown));
+        assert_eq!(ExportFormat::parse("md"), Some(ExportFormat::Markdown));
+        assert_eq!(ExportFormat::parse("csv"), Some(ExportFormat::Csv));
+        assert_eq!(ExportFormat::parse("json"), Some(ExportFormat::Json));
+        assert_eq!(ExportFormat::parse("archive"), Some(ExportFormat::Archive));
+        assert_eq!(ExportFormat::parse("pdf"), None);
+    }
+
+    #[test]
+    fn markdown_keeps_each_card_on_one_line() {
+        let out = render(&board(), ExportFormat::Markdown);
+        assert!(out.starts_with("# Sprint 12 / Retro\n"));
+        assert!(out.contains(
+            "- =SUM(A1), and \"quotes\"<br>second line _(2 votes, Rita)_\n  - **Sam:** agreed\n"
+        ));
+        assert!(out.contains("| Uptime \\| prod | >= 99% | 99.5% | yes |"));
+        assert!(out.contains("8.5 out of 10, from 2 ratings"));
+    }
+
+    #[test]
+    fn csv_quotes_fields_and_defuses_formulas() {
+        let out = render(&board(), ExportFormat::Csv);
+        let card = out.lines().nth(1).unwrap();
+        assert!(card.starts_with("Went well,\"'=SUM(A1), and \"\"quotes\"\""));
+        assert!(out.contains("Rita,2,Sam: agreed,,,,,\r\n"));
+    }
+
+    #[test]
+    fn json_leaves_out_who_voted_and_who_rated() {
+        let out = render(&board(), ExportFormat::Json);
+        assert!(out.contains("\"votes\": 2"));
+        assert!(out.contains("\"average\": 8.5"));
+        assert!(!out.contains("\"p1\""));
+        assert!(!out.contains("\"p2\""));
+    }
+
+    #[test]
+    fn the_archive_names_each_participant_the_same_way_everywhere() {
+        let archive = archive(&board());
+        let card = &archive.columns[0].cards[0];
+        assert_eq!(card.author, "participant-1");
+        assert_eq!(card.votes, vec!["participant-1", "participant-2"]);
+        assert_eq!(card.comments[0].author, "participant-2");
+        assert_eq!(archive.meeting_ratings[1].participant, "participant-2");
+        let out = render(&board(), ExportFormat::Archive);
+        assert!(!out.contains("\"p1\""));
+    }
+
+    #[test]
+    fn the_file_is_named_after_the_title() {
+        assert_eq!(file_name(&board(), ExportFormat::Csv), "sprint-12-retro.csv");
+        let mut untitled = board();
+        untitled.title = "???".into();
+        assert_eq!(file_name(&untitled, ExportFormat::Json), "b1.json");
+    }
+}
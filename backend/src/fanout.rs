@@ -0,0 +1,279 @@
+//! Carries board changes between the replicas of the backend.
+//!
+//! Each replica holds the sockets of its own clients, and a change made on one has to reach the
+//! clients of all. The replica that made the change sends it to its own sockets at once and then
+//! publishes it with `NOTIFY` on a channel of the board. Every replica listens on the channels of
+//! the boards it has sockets for, and hands what it hears to those sockets.
+//!
+//! A full board is too large for a notification, and so is a card with a long discussion under
+//! it. What goes over Postgres is therefore either a patch that fits, or word that the board
+//! changed, on which each replica reads the board itself.
+
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::{PgListener, PgNotification};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use crate::db;
+use crate::models::{BoardPatch, TimerAction};
+use crate::protocol::ServerMessage;
+use crate::state::{send_local, AppState};
+
+/// Postgres refuses a payload of 8000 bytes or more. The margin leaves room for the envelope.
+const MAX_NOTIFY_PAYLOAD: usize = 7_500;
+
+/// How often a replica tells the others it is alive.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+
+/// How long a replica may stay quiet before the others take its sockets off the boards.
+const REPLICA_TIMEOUT_SECS: i64 = 60;
+
+/// What one replica tells the others about a board.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum Update {
+    /// The board changed in a way only the whole of it carries. Each replica reads it itself.
+    State,
+    Patch { revision: i64, patch: Box<BoardPatch> },
+    /// The timer of the board ran out. It changes nothing by itself, so it takes no revision.
+    TimerExpired { action: Option<TimerAction> },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Envelope {
+    /// The replica that sent it, which has already told its own sockets.
+    origin: String,
+    board_id: String,
+    update: Update,
+}
+
+enum Command {
+    Listen(String),
+    Unlisten(String),
+}
+
+/// The handle of one replica on the fan-out: who it is, and the listener it asks to follow a
+/// board.
+pub struct Fanout {
+    pub replica_id: String,
+    commands: mpsc::UnboundedSender<Command>,
+}
+
+/// The listener half of a new `Fanout`. `listen` runs it.
+pub struct Listener(mpsc::UnboundedReceiver<Command>);
+
+impl Fanout {
+    pub fn new() -> (Self, Listener) {
+        let (commands, rx) = mpsc::unbounded_channel();
+        let fanout = Self {
+            replica_id: nanoid::nanoid!(12),
+            commands,
+        };
+        (fanout, Listener(rx))
+    }
+
+    /// Starts hearing the changes other replicas make to this board. The listener takes it up a
+    /// moment later; see `listen` for what it does about the changes made in between.
+    pub fn follow(&self, board_id: &str) {
+        let _ = self.commands.send(Command::Listen(board_id.to_string()));
+    }
+
+    /// Stops hearing a board, once the last socket of this replica left it.
+    pub fn unfollow(&self, board_id: &str) {
+        let _ = self.commands.send(Command::Unlisten(board_id.to_string()));
+    }
+
+    /// Tells the other replicas about a change this one has already sent to its own sockets.
+    pub async fn publish(&self, pool: &sqlx::PgPool, board_id: &str, update: Update) {
+        let payload = encode(Envelope {
+            origin: self.replica_id.clone(),
+            board_id: board_id.to_string(),
+            update,
+        });
+
+        if let Err(e) = sqlx::query("SELECT pg_notify($1, $2)")
+            .bind(channel_name(board_id))
+            .bind(payload)
+            .execute(pool)
+            .await
+        {
+            warn!("Failed to publish a board change: {e}");
+        }
+    }
+}
+
+/// The payload of a notification. An update too large for one goes as word that the board changed.
+fn encode(mut envelope: Envelope) -> String {
+    let payload = serde_json::to_string(&envelope).unwrap();
+    if payload.len() <= MAX_NOTIFY_PAYLOAD {
+        return payload;
+    }
+    envelope.update = Update::State;
+    serde_json::to_string(&envelope).unwrap()
+}
+
+/// The Postgres channel of one board. A board id is at most a few characters of nanoid, so the
+/// name stays far below the 63 bytes Postgres allows.
+fn channel_name(board_id: &str) -> String {
+    format!("rewind_board_{board_id}")
+}
+
+/// Follows the boards this replica has sockets for, and hands what the other replicas publish to
+/// those sockets. Runs for the life of the process.
+///
+/// A connection that drops loses the notifications sent while it was down, so after each
+/// reconnect every followed board gets its full state again. A board followed for the first time
+/// gets its full state as soon as the `LISTEN` is in place, for the same reason: its first socket
+/// read the board when it joined, and a change another replica published between that and the
+/// `LISTEN` reached nobody here.
+pub async fn listen(state: AppState, Listener(mut commands): Listener) {
+    loop {
+        let mut listener = match PgListener::connect_with(&state.db).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                warn!("Failed to open the fan-out listener: {e}");
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                continue;
+            }
+        };
+
+        let followed: Vec<String> = state.channels.read().await.keys().cloned().collect();
+        for board_id in &followed {
+            if let Err(e) = listener.listen(&channel_name(board_id)).await {
+                warn!("Failed to follow board {board_id}: {e}");
+            }
+        }
+        for board_id in &followed {
+            crate::routes::ws::send_board_state_here(&state, board_id).await;
+        }
+
+        loop {
+            tokio::select! {
+                command = commands.recv() => {
+                    let result = match command {
+                        Some(Command::Listen(board_id)) => {
+                            let result = listener.listen(&channel_name(&board_id)).await;
+                            if result.is_ok() {
+                                crate::routes::ws::send_board_state_here(&state, &board_id).await;
+                            }
+                            result
+                        }
+                        Some(Command::Unlisten(board_id)) => {
+                            listener.unlisten(&channel_name(&board_id)).await
+                        }
+                        None => return,
+                    };
+                    if let Err(e) = result {
+                        warn!("Failed to change the followed boards: {e}");
+                    }
+                }
+                notification = listener.try_recv() => match notification {
+                    Ok(Some(notification)) => deliver(&state, notification).await,
+                    Ok(None) => {
+                        warn!("The fan-out listener lost its connection");
+                        break;
+                    }
+                    Err(e) => {
+                        warn!("The fan-out listener failed: {e}");
+                        break;
+                    }
+                },
+            }
+        }
+    }
+}
+
+async fn deliver(state: &AppState, notification: PgNotification) {
+    let envelope: Envelope = match serde_json::from_str(notification.payload()) {
+        Ok(envelope) => envelope,
+        Err(e) => {
+            warn!("Ignoring an unreadable board change: {e}");
+            return;
+        }
+    };
+    if envelope.origin == state.fanout.replica_id {
+        return;
+    }
+
+    match envelope.update {
+        Update::State => {
+            crate::routes::ws::send_board_state_here(state, &envelope.board_id).await;
+        }
+        Update::Patch { revision, patch } => {
+            if let Some(tx) = state.local_channel(&envelope.board_id).await {
+                send_local(&tx, ServerMessage::BoardPatch { revision, patch });
+            }
+        }
+        Update::TimerExpired { action } => {
+            if let Some(tx) = state.local_channel(&envelope.board_id).await {
+                send_local(&tx, ServerMessage::TimerExpired { action });
+            }
+        }
+    }
+}
+
+/// Keeps this replica registered, and takes the sockets of a replica that stopped without a
+/// goodbye off the boards. Runs for the life of the process.
+pub async fn heartbeat(state: AppState) {
+    let mut interval = tokio::time::interval(HEARTBEAT_INTERVAL);
+    loop {
+        interval.tick().await;
+        if let Err(e) = db::touch_replica(&state.db, &state.fanout.replica_id).await {
+            warn!("Failed to touch the replica: {e}");
+        }
+        match db::sweep_replicas(&state.db, REPLICA_TIMEOUT_SECS).await {
+            Ok(0) => {}
+            Ok(n) => info!("Removed {n} replicas that stopped answering"),
+            Err(e) => warn!("Failed to sweep the replicas: {e}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn envelope(update: Update) -> Envelope {
+        Envelope {
+            origin: "replica".into(),
+            board_id: "b1".into(),
+            update,
+        }
+    }
+
+    fn decoded(payload: &str) -> Update {
+        serde_json::from_str::<Envelope>(payload).unwrap().update
+    }
+
+    #[test]
+    fn a_patch_that_fits_goes_as_it_is() {
+        let patch = BoardPatch::TicketRemoved {
+            ticket_id: "t1".into(),
+        };
+        let payload = encode(envelope(Update::Patch {
+            revision: 3,
+            patch: Box::new(patch),
+        }));
+        assert!(matches!(decoded(&payload), Update::Patch { revision: 3, .. }));
+    }
+
+    #[test]
+    fn a_patch_too_large_for_a_notification_goes_as_word_of_a_change() {
+        let patch = BoardPatch::TicketRemoved {
+            ticket_id: "t".repeat(MAX_NOTIFY_PAYLOAD),
+        };
+        let payload = encode(envelope(Update::Patch {
+            revision: 3,
+            patch: Box::new(patch),
+        }));
+        assert!(payload.len() <= MAX_NOTIFY_PAYLOAD);
+        assert!(matches!(decoded(&payload), Update::State));
+    }
+
+    #[test]
+    fn a_board_channel_is_named_after_the_board_within_the_limit_of_postgres() {
+        assert_eq!(channel_name("abc"), "rewind_board_abc");
+        assert!(channel_name(&nanoid::nanoid!(10)).len() <= 63);
+    }
+}
@@ -1,16 +1,27 @@
+mod audit;
 mod auth;
+mod credentials;
 mod db;
 mod error;
+mod export;
+mod fanout;
+mod metrics;
 mod models;
 mod password;
 mod protocol;
+mod retention;
 mod routes;
 mod state;
+mod timers;
+mod throttle;
+mod tokens;
+mod webhooks;
 
-use axum::routing::{get, post, put};
+use axum::routing::{delete, get, post, put};
 use axum::Router;
 use sqlx::postgres::PgPoolOptions;
 use state::AppState;
+use std::net::SocketAddr;
 use std::path::PathBuf;
 use tower_http::cors::{AllowOrigin, CorsLayer};
 use tower_http::services::{ServeDir, ServeFile};
@@ -58,18 +69,100 @@ async fn main() {
         tracing::info!("no GIPHY key set — GIF controls are off");
     }
 
-    // The three values of the Entra app registration, or nothing at all. Nothing leaves the server
-    // open to whoever holds a board link, as it has always been.
-    let entra = auth::EntraAuth::from_env();
-    if entra.is_none() {
-        tracing::info!("no Entra app registration set — the server asks nobody to sign in");
+    // An identity provider, or nothing at all. Nothing leaves the server open to whoever holds a
+    // board link, as it has always been.
+    let oidc = auth::OidcAuth::from_env();
+    if oidc.is_none() {
+        tracing::info!("no identity provider set — the server asks nobody to sign in");
     }
 
-    let state = AppState::new(db, admin_token_hash, giphy_api_key, entra);
+    // This process is one replica of maybe several. It registers before it takes a socket, because
+    // each socket it records on a board points at it.
+    let (fanout, fanout_listener) = fanout::Fanout::new();
+    db::touch_replica(&db, &fanout.replica_id)
+        .await
+        .expect("Failed to register the replica");
+    tracing::info!(replica_id = fanout.replica_id, "replica registered");
+
+    let webhooks = webhooks::Webhooks::from_env();
+
+    // A policy that cannot be read stops the server rather than keep, or drop, the wrong boards.
+    let retention = retention::from_env().unwrap_or_else(|e| panic!("{e}"));
+    match retention.days {
+        Some(days) => tracing::info!(
+            days,
+            action = retention.action.as_str(),
+            "boards are purged after {days} days without a change"
+        ),
+        None => tracing::info!("no retention set in the environment — boards are kept"),
+    }
+
+    // The proxies in front, whose X-Forwarded-For says who the client is. None leaves the peer of
+    // the socket as the client, which is right only when nothing stands in front.
+    let trusted_proxy_hops = std::env::var("TRUSTED_PROXY_HOPS")
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .map(|v| {
+            v.parse::<usize>()
+                .unwrap_or_else(|_| panic!("TRUSTED_PROXY_HOPS must be a number, not {v:?}"))
+        })
+        .unwrap_or(0);
+
+    // The bearer token Prometheus sends. Empty, as the chart writes it when none is set, leaves
+    // `/metrics` open, which is right when only the cluster can reach the pods.
+    let metrics_token = std::env::var("METRICS_TOKEN")
+        .ok()
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty());
+    if metrics_token.is_none() {
+        tracing::info!("no METRICS_TOKEN set — /metrics answers anyone who asks");
+    }
+
+    // How far behind a socket may fall before it is sent the whole board instead. A board where
+    // many vote at once fills the channel fastest.
+    let channel_capacity = std::env::var("BOARD_CHANNEL_CAPACITY")
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .map(|v| match v.parse::<usize>() {
+            Ok(capacity) if capacity > 0 => capacity,
+            _ => panic!("BOARD_CHANNEL_CAPACITY must be a number above 0, not {v:?}"),
+        })
+        .unwrap_or(state::DEFAULT_CHANNEL_CAPACITY);
+
+    // The key the credentials of the participants are signed with, made by the first replica to
+    // start. A server that cannot read it would let nobody back in as themselves.
+    let participant_key =
+        db::server_key(&db, credentials::KEY_NAME, &nanoid::nanoid!(43))
+            .await
+            .expect("Failed to read the participant key");
+
+    let state = AppState {
+        trusted_proxy_hops,
+        participant_key,
+        metrics_token,
+        channel_capacity,
+        ..AppState::new(
+            db,
+            admin_token_hash,
+            giphy_api_key,
+            oidc,
+            fanout,
+            webhooks,
+            retention,
+        )
+    };
+    tokio::spawn(fanout::listen(state.clone(), fanout_listener));
+    tokio::spawn(fanout::heartbeat(state.clone()));
+    tokio::spawn(timers::run(state.clone()));
+    tokio::spawn(webhooks::run(state.clone()));
+    tokio::spawn(retention::run(state.clone()));
     let static_dir = std::env::var("STATIC_DIR").unwrap_or_default();
 
     let mut app = Router::new()
         .route("/api/health", get(routes::config::health))
+        .route("/metrics", get(metrics::handler))
         .route("/api/auth/login", get(auth::login))
         .route("/api/auth/callback", get(auth::callback))
         .route("/api/auth/logout", get(auth::logout))
@@ -77,7 +170,9 @@ async fn main() {
         .route("/api/config", get(routes::config::get_config))
         .route("/api/templates", get(routes::boards::list_templates))
         .route("/api/boards", post(routes::boards::create_board))
+        .route("/api/boards/import", post(routes::boards::import_board))
         .route("/api/boards/{id}", get(routes::boards::get_board))
+        .route("/api/boards/{id}/export", get(routes::boards::export_board))
         .route(
             "/api/boards/{id}/access",
             get(routes::boards::board_access),
@@ -97,10 +192,24 @@ async fn main() {
         )
         .route("/api/boards/{id}/title", put(routes::boards::set_title))
         .route("/api/boards/{id}/labels", put(routes::boards::set_labels))
+        .route(
+            "/api/boards/{id}/webhooks",
+            get(routes::boards::list_webhooks).post(routes::boards::create_webhook),
+        )
+        .route(
+            "/api/boards/{id}/webhooks/{webhook_id}",
+            delete(routes::boards::delete_webhook),
+        )
+        .route("/api/actions", get(routes::boards::list_actions))
         .route("/api/labels", get(routes::boards::list_labels))
         .route("/api/my-boards", get(routes::boards::my_boards))
         .route("/api/teams", get(routes::boards::list_teams))
         .route("/ws/boards/{id}", get(routes::ws::ws_handler))
+        .route(
+            "/api/tokens",
+            get(routes::tokens::list_tokens).post(routes::tokens::create_token),
+        )
+        .route("/api/tokens/{id}", delete(routes::tokens::delete_token))
         .route("/api/admin/verify", post(routes::admin::verify_token))
         .route("/api/admin/stats", get(routes::admin::global_stats))
         .route("/api/admin/restart", post(routes::admin::restart_service))
@@ -109,6 +218,11 @@ async fn main() {
             "/api/admin/boards/{id}",
             get(routes::admin::get_board_detail).delete(routes::admin::delete_board),
         )
+        .route(
+            "/api/admin/boards/{id}/audit",
+            get(routes::admin::list_board_audit_log),
+        )
+        .route("/api/admin/audit", get(routes::admin::list_audit_log))
         .route(
             "/api/admin/templates",
             get(routes::admin::list_templates).post(routes::admin::create_template),
@@ -129,6 +243,32 @@ async fn main() {
         .route(
             "/api/admin/teams/{id}",
             put(routes::admin::update_team).delete(routes::admin::delete_team),
+        )
+        .route(
+            "/api/admin/webhooks",
+            get(routes::admin::list_webhooks).post(routes::admin::create_webhook),
+        )
+        .route(
+            "/api/admin/webhooks/deliveries",
+            get(routes::admin::list_webhook_deliveries),
+        )
+        .route(
+            "/api/admin/webhooks/{id}",
+            delete(routes::admin::delete_webhook),
+        )
+        .route(
+            "/api/admin/retention",
+            get(routes::admin::get_retention)
+                .put(routes::admin::set_retention)
+                .delete(routes::admin::clear_retention),
+        )
+        .route(
+            "/api/admin/retention/preview",
+            get(routes::admin::preview_retention),
+        )
+        .route(
+            "/api/admin/retention/log",
+            get(routes::admin::list_retention_log),
         );
 
     // Serve frontend static files if STATIC_DIR is set (production)
@@ -141,7 +281,7 @@ async fn main() {
         // The gate goes on last of the three, so that it sits inside the CORS layer: a preflight is
         // answered before anyone is asked to sign in, and a 401 still carries the CORS headers that
         // let the desktop app read the reason. It wraps the static files as well, so a browser that
-        // asks for a page while signed out is sent to Entra before it is sent the bundle.
+        // asks for a page while signed out is sent to the provider before it is sent the bundle.
         .layer(axum::middleware::from_fn_with_state(
             state.clone(),
             auth::gate,
@@ -160,11 +300,18 @@ async fn main() {
                 .allow_credentials(true),
         )
         .layer(TraceLayer::new_for_http())
+        .layer(axum::middleware::from_fn(metrics::track_http))
         .with_state(state);
 
     let port = std::env::var("PORT").unwrap_or_else(|_| "3001".to_string());
     let addr = format!("0.0.0.0:{port}");
     tracing::info!("listening on {addr}");
     let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    // The peer address goes along with each request, for the ones that count tries by address.
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .unwrap();
 }
@@ -0,0 +1,229 @@
+//! What this replica is doing, in the Prometheus text format at `/metrics`.
+//!
+//! Every figure is of this replica alone: the sockets it holds, the messages they sent it, the
+//! time it spent on them. Prometheus scrapes each pod and adds them up. The collectors live in one
+//! registry for the process, so that the code that times something — a password check deep in
+//! `password.rs`, a read of a board in `db.rs` — needs no state handed down to it.
+
+use std::sync::LazyLock;
+use std::time::Instant;
+
+use axum::extract::{MatchedPath, Request, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, Opts,
+    Registry, TextEncoder,
+};
+use sha2::{Digest, Sha256};
+
+use crate::error::AppError;
+use crate::state::AppState;
+
+/// A board and a socket take milliseconds. The buckets start well under one and end at a few
+/// seconds, where something is wrong.
+const FAST_BUCKETS: &[f64] = &[
+    0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5,
+];
+
+/// Argon2 takes tens of milliseconds on purpose, and more on a busy node.
+const PASSWORD_BUCKETS: &[f64] = &[0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0];
+
+pub struct Metrics {
+    registry: Registry,
+    pub sockets: IntGauge,
+    pub boards: IntGauge,
+    /// The messages of the sockets that got past the throttle, by the type of each.
+    pub messages: IntCounterVec,
+    /// The messages the throttle dropped, by their class.
+    pub throttled: IntCounterVec,
+    /// From a change to the board going out on this replica's channel and the fan-out, by whether
+    /// it went as the whole board or as a patch.
+    pub broadcast_seconds: HistogramVec,
+    pub get_board_seconds: Histogram,
+    /// The messages a socket fell behind on and skipped.
+    pub broadcast_lagged: IntCounter,
+    /// The messages sent on the channel of a board that no socket was left to hear.
+    pub broadcast_dropped: IntCounter,
+    pub http_seconds: HistogramVec,
+    pub password_verify_seconds: Histogram,
+}
+
+pub static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new();
+        let metrics = Metrics {
+            sockets: IntGauge::new(
+                "rewind_active_sockets",
+                "The sockets this replica holds open",
+            )
+            .unwrap(),
+            boards: IntGauge::new(
+                "rewind_active_boards",
+                "The boards this replica holds a socket for",
+            )
+            .unwrap(),
+            messages: IntCounterVec::new(
+                Opts::new(
+                    "rewind_ws_messages_total",
+                    "The socket messages handled, by type",
+                ),
+                &["type"],
+            )
+            .unwrap(),
+            throttled: IntCounterVec::new(
+                Opts::new(
+                    "rewind_ws_messages_throttled_total",
+                    "The socket messages the rate limit dropped, by class",
+                ),
+                &["class"],
+            )
+            .unwrap(),
+            broadcast_seconds: HistogramVec::new(
+                HistogramOpts::new(
+                    "rewind_broadcast_duration_seconds",
+                    "The time to send a change of a board to its sockets and the other replicas",
+                )
+                .buckets(FAST_BUCKETS.to_vec()),
+                &["kind"],
+            )
+            .unwrap(),
+            get_board_seconds: Histogram::with_opts(
+                HistogramOpts::new(
+                    "rewind_db_get_board_duration_seconds",
+                    "The time to read a whole board from the database",
+                )
+                .buckets(FAST_BUCKETS.to_vec()),
+            )
+            .unwrap(),
+            broadcast_lagged: IntCounter::new(
+                "rewind_broadcast_lagged_messages_total",
+                "The board messages a slow socket fell behind on and skipped",
+            )
+            .unwrap(),
+            broadcast_dropped: IntCounter::new(
+                "rewind_broadcast_dropped_messages_total",
+                "The board messages sent when no socket on this replica was left to hear them",
+            )
+            .unwrap(),
+            http_seconds: HistogramVec::new(
+                HistogramOpts::new(
+                    "rewind_http_request_duration_seconds",
+                    "The time to answer an HTTP request, by route",
+                )
+                .buckets(FAST_BUCKETS.to_vec()),
+                &["method", "route", "status"],
+            )
+            .unwrap(),
+            password_verify_seconds: Histogram::with_opts(
+                HistogramOpts::new(
+                    "rewind_password_verify_duration_seconds",
+                    "The time to check an Argon2 hash: a board password, the admin token or an API token",
+                )
+                .buckets(PASSWORD_BUCKETS.to_vec()),
+            )
+            .unwrap(),
+            registry,
+        };
+
+        let r = &metrics.registry;
+        r.register(Box::new(metrics.sockets.clone())).unwrap();
+        r.register(Box::new(metrics.boards.clone())).unwrap();
+        r.register(Box::new(metrics.messages.clone())).unwrap();
+        r.register(Box::new(metrics.throttled.clone())).unwrap();
+        r.register(Box::new(metrics.broadcast_seconds.clone())).unwrap();
+        r.register(Box::new(metrics.get_board_seconds.clone())).unwrap();
+        r.register(Box::new(metrics.broadcast_lagged.clone())).unwrap();
+        r.register(Box::new(metrics.broadcast_dropped.clone())).unwrap();
+        r.register(Box::new(metrics.http_seconds.clone())).unwrap();
+        r.register(Box::new(metrics.password_verify_seconds.clone()))
+            .unwrap();
+        metrics
+    }
+
+    fn render(&self) -> String {
+        let mut out = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut out)
+            .expect("the text format takes every metric");
+        String::from_utf8(out).expect("the text format is UTF-8")
+    }
+}
+
+/// Times every request, under the route it matched rather than its path, so that each board id
+/// does not become a series of its own.
+pub async fn track_http(req: Request, next: Next) -> Response {
+    let method = req.method().to_string();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| "static".to_string());
+    let started = Instant::now();
+    let response = next.run(req).await;
+    METRICS
+        .http_seconds
+        .with_label_values(&[&method, &route, response.status().as_str()])
+        .observe(started.elapsed().as_secs_f64());
+    response
+}
+
+/// The metrics of this replica. With `METRICS_TOKEN` set, only a scrape that sends it as a bearer
+/// token is answered.
+pub async fn handler(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if let Some(expected) = &state.metrics_token {
+        let given = crate::tokens::bearer(&headers).unwrap_or_default();
+        if !same_token(given, expected) {
+            return AppError::Unauthorized("A metrics token is required".to_string())
+                .into_response();
+        }
+    }
+
+    // The two gauges are read off the state at the scrape, so they cannot drift from it.
+    let boards = state.channels.read().await.len();
+    let sockets: usize = state.participants.read().await.values().map(Vec::len).sum();
+    METRICS.boards.set(boards as i64);
+    METRICS.sockets.set(sockets as i64);
+
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, prometheus::TEXT_FORMAT)],
+        METRICS.render(),
+    )
+        .into_response()
+}
+
+/// Compares two tokens by their digests, which are always the same length, so the time the
+/// comparison takes says nothing about how much of the token was right.
+fn same_token(given: &str, expected: &str) -> bool {
+    let given = Sha256::digest(given.as_bytes());
+    let expected = Sha256::digest(expected.as_bytes());
+    given
+        .iter()
+        .zip(expected.iter())
+        .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+        == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_token_must_match_exactly() {
+        assert!(same_token("scrape-me", "scrape-me"));
+        assert!(!same_token("scrape-m", "scrape-me"));
+        assert!(!same_token("", "scrape-me"));
+    }
+
+    #[test]
+    fn the_registry_renders_in_the_text_format() {
+        METRICS.messages.with_label_values(&["AddTicket"]).inc();
+        let text = METRICS.render();
+        assert!(text.contains("# TYPE rewind_ws_messages_total counter"));
+        assert!(text.contains("rewind_ws_messages_total{type=\"AddTicket\"}"));
+    }
+}
@@ -1,6 +1,6 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 /// Column roles. Every board has one column of each role.
 pub const ROLE_PREVIOUS_ACTIONS: &str = "previous_actions";
@@ -36,6 +36,29 @@ pub fn is_previous_actions_name(name: &str) -> bool {
     name.trim().eq_ignore_ascii_case("previous actions")
 }
 
+/// The most characters that the name of a column can hold.
+pub const MAX_COLUMN_NAME_LENGTH: usize = 60;
+
+/// Reads the name of a column that is added to a live board, or renamed on one, or says what is
+/// wrong with it. The names of Actions and Previous Actions stay theirs: every board already has
+/// one column of each, and a second by name would only mislead.
+pub fn read_column_name(raw: &str) -> Result<String, String> {
+    let name = raw.trim();
+    if name.is_empty() {
+        return Err("A column needs a name".to_string());
+    }
+    if name.chars().count() > MAX_COLUMN_NAME_LENGTH {
+        return Err(format!(
+            "A column name is at most {MAX_COLUMN_NAME_LENGTH} characters"
+        ));
+    }
+    if is_previous_actions_name(name) || RESERVED_COLUMN_NAMES.contains(&name.to_lowercase().as_str())
+    {
+        return Err("That name belongs to the action columns".to_string());
+    }
+    Ok(name.to_string())
+}
+
 /// Plans the columns of a new board from the names the caller asked for.
 ///
 /// Every board gets Previous Actions and Actions. The caller places Previous Actions by naming
@@ -97,6 +120,17 @@ pub struct Board {
     pub participants: Vec<Participant>,
     pub vote_limit_per_column: Option<i32>,
     pub timer_end: Option<DateTime<Utc>>,
+    /// What the server does to the board when the timer runs out.
+    pub timer_action: Option<TimerAction>,
+    /// Whether new cards are stopped outside the two action columns.
+    pub cards_locked: bool,
+    /// Where the board stands among its phases. None on a board with no phases.
+    pub phase: Option<Phase>,
+    /// The phases the board runs through, in order. Empty lets everything through at any time.
+    pub phases: Vec<Phase>,
+    /// When the facilitator closed the board. A closed board reads as it stood and takes no
+    /// change until it is reopened.
+    pub archived_at: Option<DateTime<Utc>>,
     pub labels: Vec<String>,
     /// The template the board started from, kept as a format tag. None for a custom board.
     pub template_id: Option<String>,
@@ -105,6 +139,10 @@ pub struct Board {
     pub meeting_ratings: Vec<MeetingRatingView>,
     /// Whether the board asks for a password. The hash itself never leaves the database layer.
     pub has_password: bool,
+    /// The wrong passwords tried on the board lately. None when there were none.
+    pub password_lockout: Option<PasswordLockout>,
+    /// How many changes the board has been through. Each patch carries the next one.
+    pub revision: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -121,7 +159,16 @@ pub struct Ticket {
     pub content: String,
     pub author_id: String,
     pub author_name: String,
+    /// Everyone who votes for the card. It reaches a reader only where they may see who voted;
+    /// see `Redaction`.
     pub votes: HashSet<String>,
+    /// How many vote for the card, as the reader is told. None while the votes are hidden. Worked
+    /// out for each reader when the card goes out, never stored.
+    #[serde(default)]
+    pub vote_count: Option<usize>,
+    /// Whether the reader votes for the card. Worked out the same way.
+    #[serde(default)]
+    pub voted: bool,
     pub created_at: DateTime<Utc>,
     pub carried_from_board_id: Option<String>,
     pub carried_from_board_title: Option<String>,
@@ -132,6 +179,65 @@ pub struct Ticket {
     /// When the action was marked done. None on an open action and on every card outside the
     /// two action columns.
     pub done_at: Option<DateTime<Utc>>,
+    /// Who took the action on. Like the done mark, only a card in an action column has one.
+    pub assignee: Option<Assignee>,
+    /// The day the action is due.
+    pub due_on: Option<NaiveDate>,
+    /// An open action whose day has passed. Worked out when the card is read, never stored.
+    #[serde(default)]
+    pub is_overdue: bool,
+}
+
+/// The owner of an action: someone on the board, or a member of one of the teams the admins
+/// manage. The name is kept with the id, so the card still reads after the person has gone.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Assignee {
+    Participant {
+        participant_id: String,
+        name: String,
+    },
+    TeamMember {
+        team_id: String,
+        member_id: String,
+        name: String,
+    },
+}
+
+impl Assignee {
+    pub fn name(&self) -> &str {
+        match self {
+            Assignee::Participant { name, .. } | Assignee::TeamMember { name, .. } => name,
+        }
+    }
+
+    fn name_mut(&mut self) -> &mut String {
+        match self {
+            Assignee::Participant { name, .. } | Assignee::TeamMember { name, .. } => name,
+        }
+    }
+}
+
+/// The most characters that the name of an owner can hold.
+pub const MAX_ASSIGNEE_NAME_LENGTH: usize = 60;
+
+/// Reads the name a client gives the owner of an action, or says what is wrong with it.
+pub fn read_assignee_name(raw: &str) -> Result<String, String> {
+    let name = raw.trim();
+    if name.is_empty() {
+        return Err("An owner needs a name".to_string());
+    }
+    if name.chars().count() > MAX_ASSIGNEE_NAME_LENGTH {
+        return Err(format!(
+            "An owner's name is at most {MAX_ASSIGNEE_NAME_LENGTH} characters"
+        ));
+    }
+    Ok(name.to_string())
+}
+
+/// An action is overdue from the day after its due day, for as long as it stays open.
+pub fn is_overdue(due_on: Option<NaiveDate>, done_at: Option<DateTime<Utc>>, today: NaiveDate) -> bool {
+    done_at.is_none() && due_on.is_some_and(|due| due < today)
 }
 
 /// One line of the scorecard: a number the team reads each week, and how it stands.
@@ -165,6 +271,10 @@ pub struct Comment {
     pub gif: Option<Gif>,
 }
 
+/// The most characters that one card can hold. A card is a line or a short paragraph; a wall of
+/// text pasted into one is sent to every reader at each change of the board.
+pub const MAX_TICKET_LENGTH: usize = 2000;
+
 /// The most characters that one comment can hold.
 pub const MAX_COMMENT_LENGTH: usize = 500;
 
@@ -175,7 +285,7 @@ pub const MAX_SCORECARD_FIELD_LENGTH: usize = 200;
 ///
 /// The board keeps enough of the picture to draw it on its own: the two URLs, the natural size,
 /// and the title. It keeps the id as well, because the attribution link points at the GIPHY page.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Gif {
     pub id: String,
     pub url: String,
@@ -273,6 +383,11 @@ pub struct BoardView {
     pub participant_count: usize,
     pub vote_limit_per_column: Option<i32>,
     pub timer_end: Option<DateTime<Utc>>,
+    pub timer_action: Option<TimerAction>,
+    pub cards_locked: bool,
+    pub phase: Option<Phase>,
+    pub phases: Vec<Phase>,
+    pub archived_at: Option<DateTime<Utc>>,
     pub editors: Vec<EditorView>,
     pub editor_requests: Vec<EditorRequestView>,
     pub labels: Vec<String>,
@@ -282,6 +397,11 @@ pub struct BoardView {
     /// Whether the board asks a newcomer for a password. Says that a lock is there, and nothing
     /// about the word itself.
     pub has_password: bool,
+    /// Someone trying passwords at the gate, for the facilitator to see.
+    pub password_lockout: Option<PasswordLockout>,
+    /// The change this state stands at. The next patch a client can lay on top carries this plus
+    /// one.
+    pub revision: i64,
 }
 
 /// The letters that stand in for the words of a card that the reader may not read yet.
@@ -337,6 +457,11 @@ impl Board {
             participant_count: count,
             vote_limit_per_column: self.vote_limit_per_column,
             timer_end: self.timer_end,
+            timer_action: self.timer_action,
+            cards_locked: self.cards_locked,
+            phase: self.phase,
+            phases: self.phases.clone(),
+            archived_at: self.archived_at,
             editors,
             editor_requests,
             labels: self.labels.clone(),
@@ -344,11 +469,34 @@ impl Board {
             scorecard: self.scorecard.clone(),
             meeting_ratings: self.meeting_ratings.clone(),
             has_password: self.has_password,
+            password_lockout: self.password_lockout.clone(),
+            revision: self.revision,
         }
     }
 }
 
 impl BoardView {
+    /// Works out what this reader may not read on the board as it stands. See `Redaction`.
+    pub fn redaction_for(&self, participant_id: &str, is_facilitator: bool) -> Redaction {
+        let is_privileged = is_facilitator
+            || self
+                .editors
+                .iter()
+                .any(|e| e.participant_id == participant_id);
+
+        Redaction {
+            hides: self.is_blurred && !is_privileged,
+            hides_votes: self.hide_votes,
+            shows_voters: is_privileged && !self.is_anonymous && !self.hide_votes,
+            carried_columns: self
+                .columns
+                .iter()
+                .filter(|c| c.role.as_deref() == Some(ROLE_PREVIOUS_ACTIONS))
+                .map(|c| c.id.clone())
+                .collect(),
+        }
+    }
+
     /// Replaces the words of every card this reader may not read yet with filler of the same
     /// shape, and does the same to the comments under it.
     ///
@@ -359,40 +507,554 @@ impl BoardView {
     ///
     /// GIFs stay as they are. The picture is hidden in the browser, and a card that lost its
     /// picture here would change shape when the board opens.
+    ///
+    /// The votes go the same way: who voted for what leaves the server only where the reader may
+    /// see it, and the counts not at all while the votes are hidden.
     pub fn redact_hidden_for(&mut self, participant_id: &str, is_facilitator: bool) {
-        if !self.is_blurred {
+        let redaction = self.redaction_for(participant_id, is_facilitator);
+        for column in &mut self.columns {
+            for ticket in &mut column.tickets {
+                redaction.redact_ticket(&column.id, ticket, participant_id);
+            }
+        }
+    }
+}
+
+/// What one reader may not read, kept by their socket between two full states.
+///
+/// A patch carries a card, not the board, so the socket cannot ask the board whether it is
+/// blurred or who the editors are. It keeps the answer from the last full state instead. Every
+/// change that moves the line — a reveal, an editor let in or sent away, a column added — goes out
+/// as a full state, so the answer is never older than the patches it masks.
+#[derive(Debug, Clone, Default)]
+pub struct Redaction {
+    /// False when the reader sees every word: an open board, or a facilitator or an editor.
+    hides: bool,
+    /// The votes are hidden, and nobody gets a count until they are shown.
+    hides_votes: bool,
+    /// The reader gets the voters of a card, not just how many there are: a facilitator or an
+    /// editor, on a board that is not anonymous, while the votes are shown. On an anonymous board
+    /// the ids of the voters are the ids of the authors, and would put names back on the cards.
+    shows_voters: bool,
+    /// The Previous Actions column. A carried action is a record of the last retro, not fresh
+    /// input, so it never blurs.
+    carried_columns: HashSet<String>,
+}
+
+impl Redaction {
+    /// Masks one card that sits in `column_id`, unless this reader may read it, and leaves them
+    /// of its votes what they may see. A reader with no id, as on the REST route, owns no card.
+    pub fn redact_ticket(&self, column_id: &str, ticket: &mut Ticket, participant_id: &str) {
+        (ticket.vote_count, ticket.voted) = self.redact_votes(&mut ticket.votes, participant_id);
+        if !self.hides {
             return;
         }
-        let is_privileged = is_facilitator
-            || self
-                .editors
-                .iter()
-                .any(|e| e.participant_id == participant_id);
-        if is_privileged {
+        let readable = self.carried_columns.contains(column_id)
+            || ticket.carried_from_board_title.is_some()
+            || (!participant_id.is_empty() && ticket.author_id == participant_id);
+        if readable {
             return;
         }
+        ticket.content = mask_text(&ticket.content);
+        ticket.author_name = mask_text(&ticket.author_name);
+        if let Some(assignee) = &mut ticket.assignee {
+            *assignee.name_mut() = mask_text(assignee.name());
+        }
+        // A card you cannot read yet carries no discussion either, so the remarks under it go
+        // the same way. The count stays, because the card keeps its comment mark.
+        for comment in &mut ticket.comments {
+            comment.content = mask_text(&comment.content);
+            comment.author_name = mask_text(&comment.author_name);
+        }
+    }
 
-        for column in &mut self.columns {
-            // A carried action is a record of the last retro, not fresh input. It never blurs.
-            let carried_column = column.role.as_deref() == Some(ROLE_PREVIOUS_ACTIONS);
-            for ticket in &mut column.tickets {
-                let readable = carried_column
-                    || ticket.carried_from_board_title.is_some()
-                    || ticket.author_id == participant_id;
-                if readable {
-                    continue;
-                }
-                ticket.content = mask_text(&ticket.content);
-                ticket.author_name = mask_text(&ticket.author_name);
-                // A card you cannot read yet carries no discussion either, so the remarks under
-                // it go the same way. The count stays, because the card keeps its comment mark.
-                for comment in &mut ticket.comments {
-                    comment.content = mask_text(&comment.content);
-                    comment.author_name = mask_text(&comment.author_name);
-                }
+    /// Takes the voters of a card out unless this reader may see them, and gives the count they
+    /// are told and whether they vote for it themselves. A reader always knows their own vote,
+    /// or they could not take it back.
+    fn redact_votes(
+        &self,
+        votes: &mut HashSet<String>,
+        participant_id: &str,
+    ) -> (Option<usize>, bool) {
+        let voted = votes.contains(participant_id);
+        let count = (!self.hides_votes).then_some(votes.len());
+        if !self.shows_voters {
+            votes.clear();
+        }
+        (count, voted)
+    }
+}
+
+/// The settings of a board that change without changing what anyone may read. The blur is not
+/// here: a reveal changes the words every reader gets, so it goes out as a full state. Hiding and
+/// showing the votes goes out as a full state for the same reason, though the flag is here too.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardSettings {
+    pub title: String,
+    pub hide_votes: bool,
+    pub vote_limit_per_column: Option<i32>,
+    pub timer_end: Option<DateTime<Utc>>,
+    pub timer_action: Option<TimerAction>,
+    pub cards_locked: bool,
+    pub phase: Option<Phase>,
+    pub phases: Vec<Phase>,
+    pub archived_at: Option<DateTime<Utc>>,
+    pub labels: Vec<String>,
+    pub has_password: bool,
+    pub password_lockout: Option<PasswordLockout>,
+}
+
+/// The stage a retro is at. A board runs through the phases its template declares, in this order,
+/// though the facilitator can step back to an earlier one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    Write,
+    Group,
+    Vote,
+    Discuss,
+    /// Nothing changes on the board any more, until the facilitator opens another phase.
+    Closed,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 5] = [
+        Phase::Write,
+        Phase::Group,
+        Phase::Vote,
+        Phase::Discuss,
+        Phase::Closed,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Phase::Write => "write",
+            Phase::Group => "group",
+            Phase::Vote => "vote",
+            Phase::Discuss => "discuss",
+            Phase::Closed => "closed",
+        }
+    }
+
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "write" => Some(Phase::Write),
+            "group" => Some(Phase::Group),
+            "vote" => Some(Phase::Vote),
+            "discuss" => Some(Phase::Discuss),
+            "closed" => Some(Phase::Closed),
+            _ => None,
+        }
+    }
+
+    /// Whether new cards go up outside the two action columns. Actions are not held to it: they
+    /// come out of the discussion, and the discussion comes after the writing.
+    pub fn takes_cards(self) -> bool {
+        matches!(self, Phase::Write | Phase::Group)
+    }
+
+    pub fn takes_votes(self) -> bool {
+        self == Phase::Vote
+    }
+}
+
+/// Puts phases in the order they run, each once.
+pub fn normalize_phases(phases: &[Phase]) -> Vec<Phase> {
+    let mut phases = phases.to_vec();
+    phases.sort();
+    phases.dedup();
+    phases
+}
+
+/// Reads the phases a row keeps, leaving out a name this build does not know.
+pub fn parse_phases(raw: &[String]) -> Vec<Phase> {
+    raw.iter().filter_map(|p| Phase::parse(p)).collect()
+}
+
+/// The names of phases, the way a row keeps them.
+pub fn phase_names(phases: &[Phase]) -> Vec<&'static str> {
+    phases.iter().map(|p| p.as_str()).collect()
+}
+
+/// What the server does to a board when its timer runs out. The facilitator picks one, or none,
+/// when the timer starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimerAction {
+    /// Takes the blur off, as `ToggleBlur` does.
+    Reveal,
+    /// Stops new cards outside the two action columns. Actions still come out of the discussion.
+    LockCards,
+    /// Ends the writing: the blur comes off and new cards stop, so the votes go on what is there.
+    StartVoting,
+}
+
+impl TimerAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TimerAction::Reveal => "reveal",
+            TimerAction::LockCards => "lock_cards",
+            TimerAction::StartVoting => "start_voting",
+        }
+    }
+
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "reveal" => Some(TimerAction::Reveal),
+            "lock_cards" => Some(TimerAction::LockCards),
+            "start_voting" => Some(TimerAction::StartVoting),
+            _ => None,
+        }
+    }
+
+    /// Whether the action takes the blur off the board.
+    pub fn reveals(self) -> bool {
+        matches!(self, TimerAction::Reveal | TimerAction::StartVoting)
+    }
+
+    /// Whether the action stops new cards.
+    pub fn locks_cards(self) -> bool {
+        matches!(self, TimerAction::LockCards | TimerAction::StartVoting)
+    }
+}
+
+/// One change to a board, small enough to send in place of the whole of it.
+///
+/// A card that changed goes out whole, with its votes and its comments, so a client puts it in
+/// place of the one it holds and needs no rules of its own about what an edit or a move touches.
+/// Anything bigger than one card — a merge, a split, a reveal, a change of editors — goes out as
+/// a full state instead.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum BoardPatch {
+    TicketAdded { column_id: String, ticket: Ticket },
+    /// New words, a new GIF, a done mark or a rock status.
+    TicketEdited { column_id: String, ticket: Ticket },
+    /// The card now sits in `column_id`. A mark that belongs to the old column is gone from it.
+    TicketMoved { column_id: String, ticket: Ticket },
+    TicketRemoved { ticket_id: String },
+    /// Everyone who now votes for the card, and the count and the vote of the reader, redacted
+    /// as on a card.
+    VoteToggled {
+        ticket_id: String,
+        votes: HashSet<String>,
+        #[serde(default)]
+        vote_count: Option<usize>,
+        #[serde(default)]
+        voted: bool,
+    },
+    /// A comment was added, edited or removed. The card carries the whole thread as it is now.
+    CommentChanged { column_id: String, ticket: Ticket },
+    SettingsChanged { settings: BoardSettings },
+    /// Someone joined or left.
+    ParticipantCount { participant_count: usize },
+}
+
+impl BoardPatch {
+    /// Takes out of the patch what this reader may not read, by the same rule as a full state.
+    pub fn redact_for(&mut self, redaction: &Redaction, participant_id: &str) {
+        match self {
+            BoardPatch::TicketAdded { column_id, ticket }
+            | BoardPatch::TicketEdited { column_id, ticket }
+            | BoardPatch::TicketMoved { column_id, ticket }
+            | BoardPatch::CommentChanged { column_id, ticket } => {
+                redaction.redact_ticket(column_id, ticket, participant_id);
+            }
+            BoardPatch::VoteToggled {
+                votes,
+                vote_count,
+                voted,
+                ..
+            } => {
+                (*vote_count, *voted) = redaction.redact_votes(votes, participant_id);
+            }
+            BoardPatch::TicketRemoved { .. }
+            | BoardPatch::SettingsChanged { .. }
+            | BoardPatch::ParticipantCount { .. } => {}
+        }
+    }
+}
+
+// --- History ---
+
+/// The actions the history of a board can walk back. Each one is named as the message that asked
+/// for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperationKind {
+    RemoveTicket,
+    EditTicket,
+    MoveTicket,
+    MergeTickets,
+    SplitTicket,
+    AddComment,
+    EditComment,
+    RemoveComment,
+    SetTicketDone,
+    SetTicketAssignee,
+    SetTicketDueDate,
+    SetRockStatus,
+}
+
+impl OperationKind {
+    const ALL: [OperationKind; 12] = [
+        OperationKind::RemoveTicket,
+        OperationKind::EditTicket,
+        OperationKind::MoveTicket,
+        OperationKind::MergeTickets,
+        OperationKind::SplitTicket,
+        OperationKind::AddComment,
+        OperationKind::EditComment,
+        OperationKind::RemoveComment,
+        OperationKind::SetTicketDone,
+        OperationKind::SetTicketAssignee,
+        OperationKind::SetTicketDueDate,
+        OperationKind::SetRockStatus,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OperationKind::RemoveTicket => "RemoveTicket",
+            OperationKind::EditTicket => "EditTicket",
+            OperationKind::MoveTicket => "MoveTicket",
+            OperationKind::MergeTickets => "MergeTickets",
+            OperationKind::SplitTicket => "SplitTicket",
+            OperationKind::AddComment => "AddComment",
+            OperationKind::EditComment => "EditComment",
+            OperationKind::RemoveComment => "RemoveComment",
+            OperationKind::SetTicketDone => "SetTicketDone",
+            OperationKind::SetTicketAssignee => "SetTicketAssignee",
+            OperationKind::SetTicketDueDate => "SetTicketDueDate",
+            OperationKind::SetRockStatus => "SetRockStatus",
+        }
+    }
+
+    pub fn parse(raw: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.as_str() == raw)
+    }
+}
+
+/// A card as the history keeps it: every field it takes to put the card back as it was.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CardRecord {
+    pub id: String,
+    pub column_id: String,
+    pub content: String,
+    pub author_id: String,
+    pub author_name: String,
+    pub created_at: DateTime<Utc>,
+    pub carried_from_board_id: Option<String>,
+    pub carried_from_board_title: Option<String>,
+    pub gif: Option<Gif>,
+    pub rock_status: Option<String>,
+    pub done_at: Option<DateTime<Utc>>,
+    pub assignee: Option<Assignee>,
+    pub due_on: Option<NaiveDate>,
+    /// Written back only with a card that comes back whole. A vote cast since an action is no
+    /// reason to refuse its undo, so the votes never take part in the comparison either.
+    pub votes: Vec<String>,
+}
+
+/// A comment as the history keeps it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommentRecord {
+    pub id: String,
+    pub ticket_id: String,
+    pub content: String,
+    pub author_id: String,
+    pub author_name: String,
+    pub created_at: DateTime<Utc>,
+    pub gif: Option<Gif>,
+}
+
+/// The cards an action touched and every comment under them, read at one moment.
+#[derive(Debug, Clone, Default)]
+pub struct Snapshot {
+    pub cards: Vec<CardRecord>,
+    pub comments: Vec<CommentRecord>,
+}
+
+/// Something the history keeps, known by its id.
+pub trait Record: Clone + PartialEq + Serialize + serde::de::DeserializeOwned {
+    fn id(&self) -> &str;
+}
+
+impl Record for CardRecord {
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl Record for CommentRecord {
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+/// One card or comment before and after an action. None on a side is a record that did not exist
+/// there: a card that was removed has no after, a comment that was added has no before.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordChange<T> {
+    pub before: Option<T>,
+    pub after: Option<T>,
+}
+
+/// The fields of a record as the history compares them, which is every field but the votes.
+fn compared_fields<T: Serialize>(record: &T) -> serde_json::Map<String, serde_json::Value> {
+    let mut fields = match serde_json::to_value(record) {
+        Ok(serde_json::Value::Object(fields)) => fields,
+        _ => serde_json::Map::new(),
+    };
+    fields.remove("votes");
+    fields
+}
+
+impl<T: Record> RecordChange<T> {
+    pub fn reversed(&self) -> Self {
+        Self {
+            before: self.after.clone(),
+            after: self.before.clone(),
+        }
+    }
+
+    /// Tells whether `current` is still where this change starts, so that making it loses nothing
+    /// that happened since.
+    ///
+    /// A record that is to go must be exactly as it was left: removing it would take any later
+    /// edit with it. A record that changes must still hold the old value in each field the change
+    /// touches; a field the change leaves alone may have moved on. A record that is to appear
+    /// must not exist yet.
+    pub fn starts_from(&self, current: Option<&T>) -> bool {
+        match (&self.before, &self.after, current) {
+            (None, _, current) => current.is_none(),
+            (Some(_), _, None) => false,
+            (Some(before), None, Some(current)) => {
+                compared_fields(before) == compared_fields(current)
+            }
+            (Some(before), Some(after), Some(current)) => {
+                let before = compared_fields(before);
+                let after = compared_fields(after);
+                let current = compared_fields(current);
+                before
+                    .iter()
+                    .filter(|(field, value)| after.get(*field) != Some(*value))
+                    .all(|(field, value)| current.get(field) == Some(value))
             }
         }
     }
+
+    /// What the record becomes when this change is made on `current`. Only the fields the change
+    /// touches are taken from its after side; the rest stay as `current` has them. None is a
+    /// record that goes.
+    pub fn applied_to(&self, current: Option<&T>) -> Option<T> {
+        let after = self.after.as_ref()?;
+        let (Some(before), Some(current)) = (&self.before, current) else {
+            return Some(after.clone());
+        };
+        let before = compared_fields(before);
+        let mut fields = match serde_json::to_value(current) {
+            Ok(serde_json::Value::Object(fields)) => fields,
+            _ => return Some(after.clone()),
+        };
+        for (field, value) in compared_fields(after) {
+            if before.get(&field) != Some(&value) {
+                fields.insert(field, value);
+            }
+        }
+        serde_json::from_value(serde_json::Value::Object(fields)).ok()
+    }
+}
+
+/// Pairs the records of two snapshots by id, and keeps the pairs that differ.
+fn changed_records<T: Record>(before: Vec<T>, after: Vec<T>) -> Vec<RecordChange<T>> {
+    let mut after: Vec<Option<T>> = after.into_iter().map(Some).collect();
+    let mut changes = Vec::new();
+    for old in before {
+        let new = after
+            .iter_mut()
+            .find(|new| new.as_ref().is_some_and(|new| new.id() == old.id()))
+            .and_then(Option::take);
+        let unchanged = new
+            .as_ref()
+            .is_some_and(|new| compared_fields(new) == compared_fields(&old));
+        if !unchanged {
+            changes.push(RecordChange {
+                before: Some(old),
+                after: new,
+            });
+        }
+    }
+    changes.extend(after.into_iter().flatten().map(|new| RecordChange {
+        before: None,
+        after: Some(new),
+    }));
+    changes
+}
+
+/// What one action did to the cards and the comments of a board, as the history keeps it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Changes {
+    pub cards: Vec<RecordChange<CardRecord>>,
+    pub comments: Vec<RecordChange<CommentRecord>>,
+}
+
+impl Changes {
+    pub fn between(before: Snapshot, after: Snapshot) -> Self {
+        Self {
+            cards: changed_records(before.cards, after.cards),
+            comments: changed_records(before.comments, after.comments),
+        }
+    }
+
+    /// The same changes the other way round: what an undo makes.
+    pub fn reversed(&self) -> Self {
+        Self {
+            cards: self.cards.iter().map(RecordChange::reversed).collect(),
+            comments: self.comments.iter().map(RecordChange::reversed).collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty() && self.comments.is_empty()
+    }
+
+    /// The cards these changes put up that were not on the board: a card an undo brings back, or
+    /// the second half of a split a redo makes again.
+    pub fn added_cards(&self) -> impl Iterator<Item = &CardRecord> {
+        self.cards
+            .iter()
+            .filter(|c| c.before.is_none())
+            .filter_map(|c| c.after.as_ref())
+    }
+
+    /// Whether these changes take a card or a comment off the board.
+    pub fn removes_any(&self) -> bool {
+        self.cards.iter().any(|c| c.before.is_some() && c.after.is_none())
+            || self.comments.iter().any(|c| c.before.is_some() && c.after.is_none())
+    }
+
+    /// The cards these changes touch, each once, with the card of each comment among them.
+    pub fn ticket_ids(&self) -> Vec<&str> {
+        let cards = self.cards.iter().filter_map(|c| c.before.as_ref().or(c.after.as_ref()));
+        let comments = self.comments.iter().filter_map(|c| c.before.as_ref().or(c.after.as_ref()));
+        let mut ids: Vec<&str> = Vec::new();
+        for id in cards.map(|c| c.id.as_str()).chain(comments.map(|c| c.ticket_id.as_str())) {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        ids
+    }
+}
+
+/// One step in the history of a board.
+#[derive(Debug, Clone)]
+pub struct Operation {
+    pub id: i64,
+    pub kind: OperationKind,
+    /// The author whose card or comment the action touched, when the action was open to its
+    /// author and to the privileged only. None when anyone on the board could do it.
+    pub author_id: Option<String>,
+    pub changes: Changes,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -404,6 +1066,8 @@ pub struct MyBoardSummary {
     pub ticket_count: i64,
     pub is_anonymous: bool,
     pub labels: Vec<String>,
+    /// When the board was closed. None while it is open.
+    pub archived_at: Option<DateTime<Utc>>,
 }
 
 /// A board that can supply cards to another board.
@@ -420,6 +1084,266 @@ pub struct ActionSourceBoard {
     /// True when the board asks for a password. The copy then asks for it as well, unless the
     /// caller can open that board already.
     pub is_locked: bool,
+    /// When the board was closed. Its cards can still be copied out; nothing goes in.
+    pub archived_at: Option<DateTime<Utc>>,
+}
+
+// --- Action tracker ---
+
+/// One card of an action column, with the board it sits on, as the tracker reads it.
+#[derive(Debug, Clone)]
+pub struct ActionCard {
+    pub ticket_id: String,
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+    pub done_at: Option<DateTime<Utc>>,
+    pub assignee: Option<Assignee>,
+    pub due_on: Option<NaiveDate>,
+    pub carried_from_board_id: Option<String>,
+    pub board_id: String,
+    pub board_title: String,
+    pub board_created_at: DateTime<Utc>,
+    pub board_labels: Vec<String>,
+}
+
+/// One action across the retros it went through. The card it reads from is the one on the newest
+/// board: that is where the team last looked at it.
+#[derive(Debug, Clone, Serialize)]
+pub struct TrackedAction {
+    pub ticket_id: String,
+    pub content: String,
+    pub board_id: String,
+    pub board_title: String,
+    pub labels: Vec<String>,
+    pub assignee: Option<Assignee>,
+    pub due_on: Option<NaiveDate>,
+    pub is_overdue: bool,
+    pub done_at: Option<DateTime<Utc>>,
+    /// The board the action was first written on, and when.
+    pub first_board_id: String,
+    pub first_board_title: String,
+    pub first_raised_at: DateTime<Utc>,
+    /// How many times the action was carried into the next retro.
+    pub carry_count: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActionStatus {
+    Open,
+    Done,
+}
+
+impl ActionStatus {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "open" => Some(ActionStatus::Open),
+            "done" => Some(ActionStatus::Done),
+            _ => None,
+        }
+    }
+
+    pub fn matches(self, action: &TrackedAction) -> bool {
+        match self {
+            ActionStatus::Open => action.done_at.is_none(),
+            ActionStatus::Done => action.done_at.is_some(),
+        }
+    }
+}
+
+/// Folds the copies of each action into one.
+///
+/// A carried card names the board it came from, not the card, so the card it came from is the
+/// one with the same words in an action column of that board. The words are the link because a
+/// copy keeps them as they were; an action reworded after the copy starts a line of its own. The
+/// same words twice on one board are one action, which is what a team does when it writes a
+/// carried action into its Actions again to keep it going.
+pub fn trace_actions(cards: Vec<ActionCard>, today: NaiveDate) -> Vec<TrackedAction> {
+    let mut nodes: Vec<Vec<ActionCard>> = Vec::new();
+    let mut index: HashMap<(String, String), usize> = HashMap::new();
+    for card in cards {
+        let key = (card.board_id.clone(), card.content.trim().to_string());
+        let node = *index.entry(key).or_insert_with(|| {
+            nodes.push(Vec::new());
+            nodes.len() - 1
+        });
+        nodes[node].push(card);
+    }
+
+    let parents: Vec<Option<usize>> = nodes
+        .iter()
+        .enumerate()
+        .map(|(node, cards)| {
+            cards.iter().find_map(|card| {
+                let from = card.carried_from_board_id.clone()?;
+                let parent = *index.get(&(from, card.content.trim().to_string()))?;
+                (parent != node).then_some(parent)
+            })
+        })
+        .collect();
+
+    // Each node walks up to the first board of its line. The walk stops at a node it has seen, so
+    // a loop in bad data ends rather than spins.
+    let mut lines: HashMap<usize, Vec<(usize, usize)>> = HashMap::new();
+    for node in 0..nodes.len() {
+        let (mut at, mut depth) = (node, 0);
+        let mut seen = HashSet::from([node]);
+        while let Some(parent) = parents[at] {
+            if !seen.insert(parent) {
+                break;
+            }
+            at = parent;
+            depth += 1;
+        }
+        lines.entry(at).or_default().push((node, depth));
+    }
+
+    let mut actions: Vec<TrackedAction> = lines
+        .into_iter()
+        .filter_map(|(root, members)| {
+            let first = nodes[root].iter().min_by_key(|c| c.created_at)?;
+            let &(head, carry_count) = members
+                .iter()
+                .max_by_key(|(node, depth)| (nodes[*node][0].board_created_at, *depth))?;
+            let card = nodes[head].iter().max_by_key(|c| c.created_at)?;
+            Some(TrackedAction {
+                ticket_id: card.ticket_id.clone(),
+                content: card.content.clone(),
+                board_id: card.board_id.clone(),
+                board_title: card.board_title.clone(),
+                labels: card.board_labels.clone(),
+                assignee: card.assignee.clone(),
+                due_on: card.due_on,
+                is_overdue: is_overdue(card.due_on, card.done_at, today),
+                done_at: card.done_at,
+                first_board_id: first.board_id.clone(),
+                first_board_title: first.board_title.clone(),
+                first_raised_at: first.created_at,
+                carry_count,
+            })
+        })
+        .collect();
+    actions.sort_by(|a, b| {
+        (a.first_raised_at, &a.ticket_id).cmp(&(b.first_raised_at, &b.ticket_id))
+    });
+    actions
+}
+
+// --- Webhooks ---
+
+/// A webhook as its owner reads it. The secret is shown once, when the webhook is made.
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookView {
+    pub id: String,
+    /// None on a global webhook, which hears every board.
+    pub board_id: Option<String>,
+    pub url: String,
+    /// The events it hears. Empty means all of them.
+    pub events: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CreatedWebhook {
+    pub webhook: WebhookView,
+    /// The key the receiver checks the signature with.
+    pub secret: String,
+}
+
+/// One event on its way to one webhook, and how far it got.
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub webhook_id: String,
+    pub url: String,
+    pub board_id: String,
+    pub event: String,
+    /// `pending` while it waits for its next try, then `delivered` or `failed`.
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_status_code: Option<i32>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+// --- Retention ---
+
+/// What the purge does to a board whose time is up.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionAction {
+    /// Removes the board and everything on it.
+    #[default]
+    Delete,
+    /// Keeps the board and its words, and takes every name and participant id off it.
+    Anonymize,
+}
+
+impl RetentionAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RetentionAction::Delete => "delete",
+            RetentionAction::Anonymize => "anonymize",
+        }
+    }
+
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "delete" => Some(RetentionAction::Delete),
+            "anonymize" => Some(RetentionAction::Anonymize),
+            _ => None,
+        }
+    }
+}
+
+/// How long boards are kept. A board goes once it has seen no change for `days`, unless it
+/// carries one of the exempt labels.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    /// None keeps every board forever.
+    pub days: Option<i32>,
+    pub action: RetentionAction,
+    pub exempt_labels: Vec<String>,
+}
+
+/// Where the policy in force came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionSource {
+    Environment,
+    Admin,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RetentionSettings {
+    pub policy: RetentionPolicy,
+    pub source: RetentionSource,
+}
+
+/// A board the next purge would take, and what it would do to it.
+#[derive(Debug, Clone, Serialize)]
+pub struct RetentionCandidate {
+    pub board_id: String,
+    pub title: String,
+    pub labels: Vec<String>,
+    pub card_count: i64,
+    pub last_activity_at: DateTime<Utc>,
+    pub action: RetentionAction,
+}
+
+/// One board the purge removed or anonymized.
+#[derive(Debug, Clone, Serialize)]
+pub struct RetentionRecord {
+    pub id: i64,
+    pub board_id: String,
+    pub title: String,
+    /// `delete` or `anonymize`.
+    pub action: String,
+    pub labels: Vec<String>,
+    pub card_count: i64,
+    pub last_activity_at: DateTime<Utc>,
+    pub purged_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -462,11 +1386,300 @@ pub struct Template {
     /// where the team writes before it reads. False for a meeting that works a list together.
     /// It sets the first state of the board and nothing after it: the facilitator still decides.
     pub default_blurred: bool,
+    /// The phases a board from this template runs through. Empty gives a board with none.
+    pub phases: Vec<Phase>,
     /// Where the template stands in the list on the home page. It goes out with the row so that
     /// the admin form can put it back: a form that did not know it wrote 0 over it at each edit.
     pub position: i32,
 }
 
+// --- API tokens ---
+
+/// What a personal API token may do. A token acts for the person who made it, and only here.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TokenScope {
+    /// Reads a board, its actions and its labels, past the password of a board of theirs.
+    #[serde(rename = "board:read")]
+    BoardRead,
+    /// Changes a board of theirs: its title, its labels, its password, its webhooks.
+    #[serde(rename = "board:write")]
+    BoardWrite,
+    /// Carries the actions of an earlier board onto a board of theirs, and nothing else.
+    #[serde(rename = "actions:import")]
+    ActionImport,
+    /// Reads the admin routes. It can only be given by an admin, and it changes nothing.
+    #[serde(rename = "admin:read")]
+    AdminRead,
+}
+
+impl TokenScope {
+    pub const ALL: [TokenScope; 4] = [
+        TokenScope::BoardRead,
+        TokenScope::BoardWrite,
+        TokenScope::ActionImport,
+        TokenScope::AdminRead,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TokenScope::BoardRead => "board:read",
+            TokenScope::BoardWrite => "board:write",
+            TokenScope::ActionImport => "actions:import",
+            TokenScope::AdminRead => "admin:read",
+        }
+    }
+
+    pub fn parse(raw: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|scope| scope.as_str() == raw)
+    }
+}
+
+/// The longest name a token can have.
+pub const MAX_TOKEN_NAME_LENGTH: usize = 100;
+
+/// A token as its owner reads it. The secret is shown once, when the token is made.
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiTokenView {
+    pub id: String,
+    pub name: String,
+    pub scopes: Vec<TokenScope>,
+    pub created_at: DateTime<Utc>,
+    /// None until a script first uses it.
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// Who a request made with a token comes from: the token, and the person it acts for.
+#[derive(Debug, Clone)]
+pub struct ApiCaller {
+    pub token_id: String,
+    pub name: String,
+    pub scopes: Vec<TokenScope>,
+    pub owner_sub: Option<String>,
+    pub facilitator_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CreatedApiToken {
+    pub token: ApiTokenView,
+    /// What a script sends as `Authorization: Bearer <secret>`.
+    pub secret: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateApiTokenRequest {
+    pub name: String,
+    pub scopes: Vec<TokenScope>,
+}
+
+/// Reads the name and the scopes of a new token, or says what is wrong with them. The scopes come
+/// back in one order and once each.
+pub fn read_token_request(
+    name: &str,
+    scopes: &[TokenScope],
+) -> Result<(String, Vec<TokenScope>), String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("A token needs a name".to_string());
+    }
+    if name.chars().count() > MAX_TOKEN_NAME_LENGTH {
+        return Err(format!(
+            "A token name is at most {MAX_TOKEN_NAME_LENGTH} characters"
+        ));
+    }
+    let scopes: Vec<TokenScope> = TokenScope::ALL
+        .into_iter()
+        .filter(|scope| scopes.contains(scope))
+        .collect();
+    if scopes.is_empty() {
+        return Err("A token needs at least one scope".to_string());
+    }
+    Ok((name.to_string(), scopes))
+}
+
+// --- Audit log ---
+
+/// A change the audit log keeps: one a participant makes to the cards of someone else or to the
+/// board itself, one made through the board routes, and one an admin makes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    TicketRemove,
+    TicketEdit,
+    TicketMerge,
+    TicketUnmerge,
+    HistoryUndo,
+    HistoryRedo,
+    CommentRemove,
+    CommentEdit,
+    BoardBlur,
+    BoardReveal,
+    VotesHide,
+    VotesShow,
+    CardsLock,
+    CardsUnlock,
+    BoardPhase,
+    BoardClose,
+    BoardReopen,
+    BoardVoteLimit,
+    ColumnAdd,
+    ColumnRename,
+    ColumnReorder,
+    ColumnRemove,
+    EditorApprove,
+    EditorDecline,
+    EditorRemove,
+    BoardPassword,
+    BoardTitle,
+    BoardLabels,
+    BoardImport,
+    ActionsImport,
+    AdminBoardDelete,
+    AdminTemplateCreate,
+    AdminTemplateUpdate,
+    AdminTemplateApply,
+    AdminTemplateDelete,
+    AdminTeamCreate,
+    AdminTeamUpdate,
+    AdminTeamDelete,
+    AdminWebhookCreate,
+    AdminWebhookDelete,
+    AdminRetentionSet,
+    AdminRetentionClear,
+    AdminRestart,
+}
+
+impl AuditAction {
+    pub const ALL: [AuditAction; 43] = [
+        AuditAction::TicketRemove,
+        AuditAction::TicketEdit,
+        AuditAction::TicketMerge,
+        AuditAction::TicketUnmerge,
+        AuditAction::HistoryUndo,
+        AuditAction::HistoryRedo,
+        AuditAction::CommentRemove,
+        AuditAction::CommentEdit,
+        AuditAction::BoardBlur,
+        AuditAction::BoardReveal,
+        AuditAction::VotesHide,
+        AuditAction::VotesShow,
+        AuditAction::CardsLock,
+        AuditAction::CardsUnlock,
+        AuditAction::BoardPhase,
+        AuditAction::BoardClose,
+        AuditAction::BoardReopen,
+        AuditAction::BoardVoteLimit,
+        AuditAction::ColumnAdd,
+        AuditAction::ColumnRename,
+        AuditAction::ColumnReorder,
+        AuditAction::ColumnRemove,
+        AuditAction::EditorApprove,
+        AuditAction::EditorDecline,
+        AuditAction::EditorRemove,
+        AuditAction::BoardPassword,
+        AuditAction::BoardTitle,
+        AuditAction::BoardLabels,
+        AuditAction::BoardImport,
+        AuditAction::ActionsImport,
+        AuditAction::AdminBoardDelete,
+        AuditAction::AdminTemplateCreate,
+        AuditAction::AdminTemplateUpdate,
+        AuditAction::AdminTemplateApply,
+        AuditAction::AdminTemplateDelete,
+        AuditAction::AdminTeamCreate,
+        AuditAction::AdminTeamUpdate,
+        AuditAction::AdminTeamDelete,
+        AuditAction::AdminWebhookCreate,
+        AuditAction::AdminWebhookDelete,
+        AuditAction::AdminRetentionSet,
+        AuditAction::AdminRetentionClear,
+        AuditAction::AdminRestart,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AuditAction::TicketRemove => "ticket.remove",
+            AuditAction::TicketEdit => "ticket.edit",
+            AuditAction::TicketMerge => "ticket.merge",
+            AuditAction::TicketUnmerge => "ticket.unmerge",
+            AuditAction::HistoryUndo => "history.undo",
+            AuditAction::HistoryRedo => "history.redo",
+            AuditAction::CommentRemove => "comment.remove",
+            AuditAction::CommentEdit => "comment.edit",
+            AuditAction::BoardBlur => "board.blur",
+            AuditAction::BoardReveal => "board.reveal",
+            AuditAction::VotesHide => "votes.hide",
+            AuditAction::VotesShow => "votes.show",
+            AuditAction::CardsLock => "cards.lock",
+            AuditAction::CardsUnlock => "cards.unlock",
+            AuditAction::BoardPhase => "board.phase",
+            AuditAction::BoardClose => "board.close",
+            AuditAction::BoardReopen => "board.reopen",
+            AuditAction::BoardVoteLimit => "board.vote_limit",
+            AuditAction::ColumnAdd => "column.add",
+            AuditAction::ColumnRename => "column.rename",
+            AuditAction::ColumnReorder => "column.reorder",
+            AuditAction::ColumnRemove => "column.remove",
+            AuditAction::EditorApprove => "editor.approve",
+            AuditAction::EditorDecline => "editor.decline",
+            AuditAction::EditorRemove => "editor.remove",
+            AuditAction::BoardPassword => "board.password",
+            AuditAction::BoardTitle => "board.title",
+            AuditAction::BoardLabels => "board.labels",
+            AuditAction::BoardImport => "board.import",
+            AuditAction::ActionsImport => "actions.import",
+            AuditAction::AdminBoardDelete => "admin.board.delete",
+            AuditAction::AdminTemplateCreate => "admin.template.create",
+            AuditAction::AdminTemplateUpdate => "admin.template.update",
+            AuditAction::AdminTemplateApply => "admin.template.apply",
+            AuditAction::AdminTemplateDelete => "admin.template.delete",
+            AuditAction::AdminTeamCreate => "admin.team.create",
+            AuditAction::AdminTeamUpdate => "admin.team.update",
+            AuditAction::AdminTeamDelete => "admin.team.delete",
+            AuditAction::AdminWebhookCreate => "admin.webhook.create",
+            AuditAction::AdminWebhookDelete => "admin.webhook.delete",
+            AuditAction::AdminRetentionSet => "admin.retention.set",
+            AuditAction::AdminRetentionClear => "admin.retention.clear",
+            AuditAction::AdminRestart => "admin.restart",
+        }
+    }
+}
+
+/// Reads the action filter of an audit query: one action by its name, or every action under a
+/// prefix that ends in a dot, such as `admin.` or `ticket.`.
+pub fn read_audit_action_filter(raw: &str) -> Result<String, String> {
+    let raw = raw.trim();
+    let known = AuditAction::ALL.into_iter().any(|action| {
+        let name = action.as_str();
+        if raw.ends_with('.') {
+            name.starts_with(raw)
+        } else {
+            name == raw
+        }
+    });
+    if known {
+        Ok(raw.to_string())
+    } else {
+        Err(format!("No audited action is called {raw}"))
+    }
+}
+
+/// One entry of the audit log.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub at: DateTime<Utc>,
+    /// None for an admin change that is not to one board, such as a template.
+    pub board_id: Option<String>,
+    /// `participant:<id>`, `account:<sub>`, `token:<id>`, `admin:<account>`, `admin token`, or
+    /// `facilitator` for the facilitator token or cookie, which name nobody.
+    pub actor: String,
+    /// The name the actor went by at the time, when there was one.
+    pub actor_name: Option<String>,
+    pub action: String,
+    /// What was changed: a card, a comment, a column, a participant, a template, a team.
+    pub target: Option<String>,
+    pub detail: Option<String>,
+}
+
 // --- Teams ---
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -497,6 +1710,9 @@ pub struct CreateBoardRequest {
     /// holds the link.
     #[serde(default)]
     pub password: Option<String>,
+    /// The phases the board runs through. Absent takes those of the template.
+    #[serde(default)]
+    pub phases: Option<Vec<Phase>>,
 }
 
 /// The shortest and the longest password a board takes. The floor keeps out a password of one
@@ -525,7 +1741,94 @@ pub fn read_password(raw: Option<&str>) -> Result<Option<String>, String> {
         ));
     }
 
-    Ok(Some(password.to_string()))
+    Ok(Some(password.to_string()))
+}
+
+/// The wrong passwords a board takes from one address before that address waits between tries,
+/// and from every address together before the board stops taking tries at all. A four-letter
+/// password falls to an online guess in days without the first; the second holds when the guesses
+/// come from many addresses, at the price of making newcomers wait a little.
+pub const UNLOCK_FREE_TRIES_PER_ADDRESS: i32 = 5;
+pub const UNLOCK_FREE_TRIES_PER_BOARD: i32 = 50;
+
+/// The first wait past the free tries. Each try past them doubles it, up to the longest.
+const UNLOCK_FIRST_WAIT_SECONDS: i64 = 30;
+pub const UNLOCK_LONGEST_WAIT_SECONDS: i64 = 15 * 60;
+
+/// The longest the whole board waits, well under the longest wait of one address. Anyone can send
+/// wrong passwords, so the wait of the board is one they can put on every reader: it slows the
+/// guesses from many addresses down, and keeps nobody out for more than a minute at a time.
+pub const UNLOCK_LONGEST_BOARD_WAIT_SECONDS: i64 = 60;
+
+/// How long a tally outlives its last wrong try.
+pub const UNLOCK_FORGET_AFTER_HOURS: i64 = 24;
+
+/// The wrong passwords tried on a board, from one address or from all of them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UnlockTally {
+    pub failures: i32,
+    pub locked_until: Option<DateTime<Utc>>,
+    pub last_failed_at: Option<DateTime<Utc>>,
+}
+
+impl UnlockTally {
+    /// Counts one more try, or gives the time it may be made at while a wait holds. The wait
+    /// doubles with each try past `free_tries`, up to `longest_wait` seconds.
+    ///
+    /// The try counts as wrong before the password is checked, and the wait it earns starts at
+    /// once. Tries sent all at once then cannot all slip in under the limit before the first of
+    /// them is known to be wrong. A right one is taken back off the tally afterwards.
+    pub fn attempt(
+        self,
+        free_tries: i32,
+        longest_wait: i64,
+        now: DateTime<Utc>,
+    ) -> Result<Self, DateTime<Utc>> {
+        if let Some(until) = self.locked_until.filter(|until| *until > now) {
+            return Err(until);
+        }
+
+        let forget_after = chrono::Duration::hours(UNLOCK_FORGET_AFTER_HOURS);
+        let failures = match self.last_failed_at {
+            Some(at) if now - at < forget_after => self.failures + 1,
+            _ => 1,
+        };
+        let locked_until = (failures >= free_tries)
+            .then(|| now + chrono::Duration::seconds(unlock_wait(failures - free_tries, longest_wait)));
+
+        Ok(Self {
+            failures,
+            locked_until,
+            last_failed_at: Some(now),
+        })
+    }
+}
+
+/// What the tallies of a board say about one try at its password.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnlockAttempt {
+    /// The try is counted and the password may be checked. If it is wrong, the caller waits until
+    /// `wait_until` before the next one.
+    Counted { wait_until: Option<DateTime<Utc>> },
+    /// A wait holds. The try was not counted, and the password is not checked.
+    Refused { until: DateTime<Utc> },
+}
+
+/// The seconds of the wait after `over` tries past the free ones.
+fn unlock_wait(over: i32, longest_wait: i64) -> i64 {
+    let doubled = UNLOCK_FIRST_WAIT_SECONDS.saturating_mul(1 << over.clamp(0, 20));
+    doubled.min(longest_wait)
+}
+
+/// The wrong passwords tried on a board lately, for its facilitator to see.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PasswordLockout {
+    /// Wrong tries since the tally was last forgotten, from every address together.
+    pub failures: i32,
+    /// The addresses that wait before their next try counts.
+    pub locked_addresses: i64,
+    /// Until when the board takes no try from anyone. None while the tries are under the limit.
+    pub locked_until: Option<DateTime<Utc>>,
 }
 
 /// Reads a board title the way the board will keep it, or says what is wrong with it.
@@ -594,6 +1897,186 @@ pub fn normalize_labels(raw: &[String]) -> Vec<String> {
     out
 }
 
+// --- Board archive ---
+
+/// The version of the archive this server writes, and the only one it reads.
+pub const BOARD_ARCHIVE_VERSION: u32 = 1;
+
+/// A whole board as a file that another instance can take in: the round-trip export, and the
+/// body of the import.
+///
+/// Nothing in it names the instance it came from. A participant goes by a name that holds only
+/// inside the file — the same person has the same name on every card, vote and rating — and the
+/// ids of cards, columns and other boards are left out. The import makes fresh ones.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardArchive {
+    pub version: u32,
+    pub title: String,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub is_blurred: bool,
+    #[serde(default)]
+    pub is_anonymous: bool,
+    #[serde(default)]
+    pub hide_votes: bool,
+    #[serde(default)]
+    pub vote_limit_per_column: Option<i32>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    #[serde(default)]
+    pub template_id: Option<String>,
+    pub columns: Vec<ArchivedColumn>,
+    #[serde(default)]
+    pub scorecard: Vec<ArchivedMetric>,
+    #[serde(default)]
+    pub meeting_ratings: Vec<ArchivedRating>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedColumn {
+    pub name: String,
+    #[serde(default)]
+    pub role: Option<String>,
+    #[serde(default)]
+    pub cards: Vec<ArchivedCard>,
+}
+
+impl ArchivedColumn {
+    /// Whether this is the Actions column, by its role or by one of the names kept for it.
+    pub fn is_actions(&self) -> bool {
+        self.role.as_deref() == Some(ROLE_ACTIONS)
+            || RESERVED_COLUMN_NAMES.contains(&self.name.trim().to_lowercase().as_str())
+    }
+
+    /// Whether this is the Previous Actions column, by its role or by its name.
+    pub fn is_previous_actions(&self) -> bool {
+        self.role.as_deref() == Some(ROLE_PREVIOUS_ACTIONS) || is_previous_actions_name(&self.name)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedCard {
+    pub content: String,
+    /// The participant who wrote it, by their name in the file.
+    pub author: String,
+    pub author_name: String,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub votes: Vec<String>,
+    #[serde(default)]
+    pub gif: Option<Gif>,
+    /// The board a carried action came from. Only its title travels, since the id would point
+    /// at a board on the other instance.
+    #[serde(default)]
+    pub carried_from_board_title: Option<String>,
+    #[serde(default)]
+    pub rock_status: Option<String>,
+    #[serde(default)]
+    pub done_at: Option<DateTime<Utc>>,
+    /// The owner of an action, by their name in the file. A team member gets a name in the file
+    /// as well, since the team lives on the other instance.
+    #[serde(default)]
+    pub assignee: Option<String>,
+    #[serde(default)]
+    pub assignee_name: Option<String>,
+    #[serde(default)]
+    pub due_on: Option<NaiveDate>,
+    #[serde(default)]
+    pub comments: Vec<ArchivedComment>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedComment {
+    pub content: String,
+    pub author: String,
+    pub author_name: String,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub gif: Option<Gif>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedMetric {
+    pub name: String,
+    #[serde(default)]
+    pub goal: String,
+    #[serde(default)]
+    pub actual: String,
+    #[serde(default)]
+    pub on_track: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedRating {
+    pub participant: String,
+    pub rating: i32,
+}
+
+/// Plans the columns of an imported board, and says for each column of the archive which of the
+/// planned columns its cards go to.
+///
+/// The plan is the one `plan_new_board_columns` makes, so an imported board keeps the same rules
+/// as a new one: one Previous Actions, one Actions at the end, and the reserved names on Actions
+/// alone. The archive names its columns by role as well as by name. A Previous Actions column is
+/// found by either, and so is Actions, whose cards go to the Actions column the plan puts last. A
+/// second column of either role folds into the first. On a Level 10 board the column the archive
+/// marks as the Rocks keeps the role under any name.
+pub fn plan_imported_columns(
+    columns: &[ArchivedColumn],
+    is_level10: bool,
+) -> (Vec<(String, Option<&'static str>)>, Vec<usize>) {
+    let requested: Vec<String> = columns
+        .iter()
+        .filter(|c| !c.is_actions())
+        .map(|c| {
+            if c.is_previous_actions() {
+                PREVIOUS_ACTIONS_NAME.to_string()
+            } else {
+                c.name.clone()
+            }
+        })
+        .collect();
+    let mut plan = plan_new_board_columns(&requested, is_level10);
+
+    // The plan always holds both. The rest of it is the other columns, in the order asked for.
+    let previous = plan
+        .iter()
+        .position(|(_, role)| *role == Some(ROLE_PREVIOUS_ACTIONS))
+        .unwrap_or(0);
+    let actions = plan.len() - 1;
+    let mut others = (0..plan.len()).filter(|&i| i != previous && i != actions);
+    let targets: Vec<usize> = columns
+        .iter()
+        .map(|c| {
+            if c.is_actions() {
+                actions
+            } else if c.is_previous_actions() {
+                previous
+            } else {
+                others.next().unwrap_or(actions)
+            }
+        })
+        .collect();
+
+    let rocks = columns
+        .iter()
+        .zip(&targets)
+        .find(|(c, &target)| {
+            c.role.as_deref() == Some(ROLE_ROCKS) && target != previous && target != actions
+        })
+        .map(|(_, &target)| target);
+    if let (true, Some(rocks)) = (is_level10, rocks) {
+        for (i, (_, role)) in plan.iter_mut().enumerate() {
+            if *role == Some(ROLE_ROCKS) && i != rocks {
+                *role = None;
+            }
+        }
+        plan[rocks].1 = Some(ROLE_ROCKS);
+    }
+
+    (plan, targets)
+}
+
 #[derive(Debug, Serialize)]
 pub struct CreateBoardResponse {
     pub board: BoardView,
@@ -677,6 +2160,8 @@ mod tests {
             author_id: author_id.into(),
             author_name: "Rita".into(),
             votes: HashSet::new(),
+            vote_count: None,
+            voted: false,
             created_at: Utc::now(),
             carried_from_board_id: None,
             carried_from_board_title: None,
@@ -691,6 +2176,9 @@ mod tests {
             gif: None,
             rock_status: None,
             done_at: None,
+            assignee: None,
+            due_on: None,
+            is_overdue: false,
         }
     }
 
@@ -722,6 +2210,11 @@ mod tests {
             participant_count: 2,
             vote_limit_per_column: None,
             timer_end: None,
+            timer_action: None,
+            cards_locked: false,
+            phase: None,
+            phases: Vec::new(),
+            archived_at: None,
             editors: Vec::new(),
             editor_requests: Vec::new(),
             labels: Vec::new(),
@@ -729,6 +2222,8 @@ mod tests {
             scorecard: Vec::new(),
             meeting_ratings: Vec::new(),
             has_password: false,
+            password_lockout: None,
+            revision: 0,
         }
     }
 
@@ -761,6 +2256,65 @@ mod tests {
         assert!(read_password(Some(&"x".repeat(MAX_BOARD_PASSWORD_LENGTH + 1))).is_err());
     }
 
+    #[test]
+    fn the_free_tries_go_by_without_a_wait() {
+        let now = Utc::now();
+        let mut tally = UnlockTally::default();
+        for _ in 1..3 {
+            tally = tally.attempt(3, UNLOCK_LONGEST_WAIT_SECONDS, now).unwrap();
+            assert_eq!(tally.locked_until, None);
+        }
+        // The last free try starts the wait at once, in case it turns out wrong.
+        tally = tally.attempt(3, UNLOCK_LONGEST_WAIT_SECONDS, now).unwrap();
+        assert_eq!(tally.failures, 3);
+        assert_eq!(tally.locked_until, Some(now + chrono::Duration::seconds(30)));
+        assert_eq!(tally.attempt(3, UNLOCK_LONGEST_WAIT_SECONDS, now), Err(now + chrono::Duration::seconds(30)));
+    }
+
+    #[test]
+    fn the_wait_doubles_up_to_the_longest() {
+        let start = Utc::now();
+        let mut tally = UnlockTally {
+            failures: 3,
+            locked_until: Some(start),
+            last_failed_at: Some(start),
+        };
+        let mut waits = Vec::new();
+        for _ in 0..7 {
+            let now = tally.locked_until.unwrap();
+            tally = tally.attempt(3, UNLOCK_LONGEST_WAIT_SECONDS, now).unwrap();
+            waits.push((tally.locked_until.unwrap() - now).num_seconds());
+        }
+        assert_eq!(waits, vec![60, 120, 240, 480, 900, 900, 900]);
+    }
+
+    #[test]
+    fn the_board_never_waits_longer_than_a_minute() {
+        let now = Utc::now();
+        let tally = UnlockTally {
+            failures: UNLOCK_FREE_TRIES_PER_BOARD + 20,
+            locked_until: None,
+            last_failed_at: Some(now),
+        };
+        let tally = tally
+            .attempt(UNLOCK_FREE_TRIES_PER_BOARD, UNLOCK_LONGEST_BOARD_WAIT_SECONDS, now)
+            .unwrap();
+        assert_eq!(tally.locked_until, Some(now + chrono::Duration::seconds(60)));
+    }
+
+    #[test]
+    fn a_tally_is_forgotten_a_day_after_its_last_wrong_try() {
+        let now = Utc::now();
+        let tally = UnlockTally {
+            failures: 40,
+            locked_until: Some(now - chrono::Duration::hours(25)),
+            last_failed_at: Some(now - chrono::Duration::hours(25)),
+        };
+        let tally = tally.attempt(5, UNLOCK_LONGEST_WAIT_SECONDS, now).unwrap();
+        assert_eq!(tally.failures, 1);
+        assert_eq!(tally.locked_until, None);
+    }
+
     #[test]
     fn a_title_loses_the_space_at_its_ends_and_keeps_the_rest() {
         assert_eq!(read_title("  Sprint 12  "), Ok("Sprint 12".to_string()));
@@ -803,6 +2357,24 @@ mod tests {
         assert_eq!(find(&view, "t-carried").content, "Book the room");
     }
 
+    #[test]
+    fn an_action_is_overdue_only_once_its_day_has_passed_and_while_it_is_open() {
+        let due = NaiveDate::from_ymd_opt(2026, 3, 10);
+        let day = |d| NaiveDate::from_ymd_opt(2026, 3, d).unwrap();
+
+        assert!(!is_overdue(due, None, day(10)));
+        assert!(is_overdue(due, None, day(11)));
+        assert!(!is_overdue(due, Some(Utc::now()), day(11)));
+        assert!(!is_overdue(None, None, day(11)));
+    }
+
+    #[test]
+    fn an_owner_name_is_trimmed_and_bounded() {
+        assert_eq!(read_assignee_name("  Sam ").unwrap(), "Sam");
+        assert!(read_assignee_name("   ").is_err());
+        assert!(read_assignee_name(&"x".repeat(MAX_ASSIGNEE_NAME_LENGTH + 1)).is_err());
+    }
+
     #[test]
     fn an_open_board_keeps_every_word() {
         let mut view = board_view(false);
@@ -825,6 +2397,134 @@ mod tests {
         assert_eq!(find(&view, "t-theirs").content, "The deploy broke");
     }
 
+    #[test]
+    fn a_patch_masks_a_blurred_card_as_the_full_state_does() {
+        let view = board_view(true);
+        let redaction = view.redaction_for("me", false);
+
+        let mut patch = BoardPatch::TicketAdded {
+            column_id: "col1".into(),
+            ticket: ticket("t-theirs", "The deploy broke", "someone"),
+        };
+        patch.redact_for(&redaction, "me");
+        let BoardPatch::TicketAdded { ticket: sent, .. } = &patch else {
+            unreachable!()
+        };
+        assert_ne!(sent.content, "The deploy broke");
+        assert_ne!(sent.comments[0].content, "I agree");
+
+        // The reader's own card and a card in Previous Actions go through as they are.
+        let mut patch = BoardPatch::TicketEdited {
+            column_id: "col1".into(),
+            ticket: ticket("t-mine", "My own card", "me"),
+        };
+        patch.redact_for(&redaction, "me");
+        let BoardPatch::TicketEdited { ticket: sent, .. } = &patch else {
+            unreachable!()
+        };
+        assert_eq!(sent.content, "My own card");
+
+        let mut patch = BoardPatch::TicketMoved {
+            column_id: "col-prev".into(),
+            ticket: ticket("t-theirs", "The deploy broke", "someone"),
+        };
+        patch.redact_for(&redaction, "me");
+        let BoardPatch::TicketMoved { ticket: sent, .. } = &patch else {
+            unreachable!()
+        };
+        assert_eq!(sent.content, "The deploy broke");
+    }
+
+    #[test]
+    fn a_patch_reaches_an_editor_whole() {
+        let mut view = board_view(true);
+        view.editors.push(EditorView {
+            participant_id: "me".into(),
+            participant_name: "Me".into(),
+        });
+        let redaction = view.redaction_for("me", false);
+
+        let mut patch = BoardPatch::CommentChanged {
+            column_id: "col1".into(),
+            ticket: ticket("t-theirs", "The deploy broke", "someone"),
+        };
+        patch.redact_for(&redaction, "me");
+        let BoardPatch::CommentChanged { ticket, .. } = &patch else {
+            unreachable!()
+        };
+        assert_eq!(ticket.content, "The deploy broke");
+        assert_eq!(ticket.comments[0].content, "I agree");
+    }
+
+    fn voted_board(hide_votes: bool, is_anonymous: bool) -> BoardView {
+        let mut view = board_view(false);
+        view.hide_votes = hide_votes;
+        view.is_anonymous = is_anonymous;
+        for column in &mut view.columns {
+            for ticket in &mut column.tickets {
+                if ticket.id == "t-theirs" {
+                    ticket.votes = HashSet::from(["me".to_string(), "someone".to_string()]);
+                }
+            }
+        }
+        view
+    }
+
+    #[test]
+    fn a_reader_gets_the_count_and_their_own_vote_and_not_the_voters() {
+        let mut view = voted_board(false, false);
+        view.redact_hidden_for("me", false);
+        let theirs = find(&view, "t-theirs");
+        assert_eq!(theirs.vote_count, Some(2));
+        assert!(theirs.voted);
+        assert!(theirs.votes.is_empty());
+        assert!(!find(&view, "t-mine").voted);
+
+        let mut view = voted_board(false, false);
+        view.redact_hidden_for("me", true);
+        assert_eq!(find(&view, "t-theirs").votes.len(), 2);
+    }
+
+    #[test]
+    fn hidden_votes_and_an_anonymous_board_keep_the_voters_from_the_facilitator() {
+        let mut view = voted_board(true, false);
+        view.redact_hidden_for("me", true);
+        let theirs = find(&view, "t-theirs");
+        assert_eq!(theirs.vote_count, None);
+        assert!(theirs.voted);
+        assert!(theirs.votes.is_empty());
+
+        let mut view = voted_board(false, true);
+        view.redact_hidden_for("me", true);
+        let theirs = find(&view, "t-theirs");
+        assert_eq!(theirs.vote_count, Some(2));
+        assert!(theirs.votes.is_empty());
+    }
+
+    #[test]
+    fn a_vote_goes_out_as_a_count_by_the_same_rule() {
+        let redaction = voted_board(false, false).redaction_for("me", false);
+        let mut patch = BoardPatch::VoteToggled {
+            ticket_id: "t-theirs".into(),
+            votes: HashSet::from(["someone".to_string()]),
+            vote_count: None,
+            voted: false,
+        };
+        patch.redact_for(&redaction, "me");
+        let BoardPatch::VoteToggled {
+            votes,
+            vote_count,
+            voted,
+            ..
+        } = &patch
+        else {
+            unreachable!()
+        };
+        assert!(votes.is_empty());
+        assert_eq!(*vote_count, Some(1));
+        assert!(!voted);
+    }
+
     fn names(list: &[&str]) -> Vec<String> {
         list.iter().map(|n| n.to_string()).collect()
     }
@@ -898,4 +2598,267 @@ mod tests {
         let plan = plan_new_board_columns(&requested, false);
         assert_eq!(plan[0], ("Rocks".to_string(), None));
     }
+
+    fn card(content: &str, column_id: &str) -> CardRecord {
+        CardRecord {
+            id: "t1".into(),
+            column_id: column_id.into(),
+            content: content.into(),
+            author_id: "author".into(),
+            author_name: "Rita".into(),
+            created_at: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            carried_from_board_id: None,
+            carried_from_board_title: None,
+            gif: None,
+            rock_status: None,
+            done_at: None,
+            assignee: None,
+            due_on: None,
+            votes: vec![],
+        }
+    }
+
+    #[test]
+    fn an_undo_leaves_alone_what_happened_since_on_other_fields() {
+        let before = card("old", "col1");
+        let edited = card("new", "col1");
+        let changes = Changes::between(
+            Snapshot { cards: vec![before], comments: vec![] },
+            Snapshot { cards: vec![edited], comments: vec![] },
+        );
+        let undo = changes.reversed();
+
+        // Someone moved the card and voted on it after the edit. The undo still fits, and it
+        // takes back the words without taking back the move or the votes.
+        let mut now = card("new", "col2");
+        now.votes = vec!["voter".into()];
+        let change = &undo.cards[0];
+        assert!(change.starts_from(Some(&now)));
+        let undone = change.applied_to(Some(&now)).unwrap();
+        assert_eq!(undone.content, "old");
+        assert_eq!(undone.column_id, "col2");
+        assert_eq!(undone.votes, vec!["voter".to_string()]);
+
+        // Someone edited the words again. The undo would throw that away, so it does not fit.
+        assert!(!change.starts_from(Some(&card("newer", "col1"))));
+    }
+
+    #[test]
+    fn a_removed_card_comes_back_only_while_it_is_still_gone() {
+        let changes = Changes::between(
+            Snapshot { cards: vec![card("text", "col1")], comments: vec![] },
+            Snapshot::default(),
+        );
+        let undo = changes.reversed();
+        let change = &undo.cards[0];
+        assert!(change.starts_from(None));
+        assert!(!change.starts_from(Some(&card("text", "col1"))));
+        assert_eq!(change.applied_to(None), Some(card("text", "col1")));
+        assert!(Changes::between(Snapshot::default(), Snapshot::default()).is_empty());
+
+        // Bringing it back puts up a card; removing it did not.
+        assert_eq!(undo.added_cards().count(), 1);
+        assert_eq!(changes.added_cards().count(), 0);
+        assert!(changes.removes_any());
+        assert!(!undo.removes_any());
+        assert_eq!(undo.ticket_ids(), changes.ticket_ids());
+    }
+
+    #[test]
+    fn a_live_column_cannot_take_the_name_of_an_action_column() {
+        assert_eq!(read_column_name("  Kudos "), Ok("Kudos".to_string()));
+        assert!(read_column_name("   ").is_err());
+        assert!(read_column_name("previous ACTIONS").is_err());
+        assert!(read_column_name("Action Items").is_err());
+        assert!(read_column_name(&"x".repeat(MAX_COLUMN_NAME_LENGTH + 1)).is_err());
+        // The rocks are not reserved by name: the role goes by the column, not by what it reads.
+        assert!(read_column_name("Rocks").is_ok());
+    }
+
+    fn archived(name: &str, role: Option<&str>) -> ArchivedColumn {
+        ArchivedColumn {
+            name: name.into(),
+            role: role.map(String::from),
+            cards: vec![],
+        }
+    }
+
+    #[test]
+    fn an_imported_board_keeps_one_column_of_each_action_role() {
+        let columns = [
+            archived("Good", None),
+            archived("Previous Actions", Some(ROLE_PREVIOUS_ACTIONS)),
+            archived("Big rocks", Some(ROLE_ROCKS)),
+            archived("Actions", Some(ROLE_ACTIONS)),
+            archived("previous actions", None),
+            archived("Action items", None),
+        ];
+        let (plan, targets) = plan_imported_columns(&columns, true);
+        assert_eq!(
+            plan,
+            vec![
+                ("Good".to_string(), None),
+                ("Previous Actions".to_string(), Some(ROLE_PREVIOUS_ACTIONS)),
+                ("Big rocks".to_string(), Some(ROLE_ROCKS)),
+                ("Actions".to_string(), Some(ROLE_ACTIONS)),
+            ]
+        );
+        assert_eq!(targets, vec![0, 1, 2, 3, 1, 3]);
+
+        // Off a Level 10 board the rocks are a column like any other.
+        let (plan, _) = plan_imported_columns(&columns, false);
+        assert_eq!(plan[2], ("Big rocks".to_string(), None));
+
+        // An archive that names neither still gets both.
+        let (plan, targets) = plan_imported_columns(&[archived("Only", None)], false);
+        assert_eq!(plan.len(), 3);
+        assert_eq!(targets, vec![1]);
+    }
+
+    fn action(id: &str, board: &str, day: i64, content: &str, from: Option<&str>) -> ActionCard {
+        let at = DateTime::from_timestamp(1_700_000_000 + day * 86_400, 0).unwrap();
+        ActionCard {
+            ticket_id: id.into(),
+            content: content.into(),
+            created_at: at,
+            done_at: None,
+            assignee: None,
+            due_on: None,
+            carried_from_board_id: from.map(Into::into),
+            board_id: board.into(),
+            board_title: format!("Retro {board}"),
+            board_created_at: at,
+            board_labels: vec![],
+        }
+    }
+
+    #[test]
+    fn an_action_carried_through_three_retros_is_listed_once_where_it_stands_now() {
+        let today = NaiveDate::from_ymd_opt(2030, 1, 1).unwrap();
+        let mut last = action("c1", "c", 28, "Fix CI", Some("b"));
+        last.done_at = Some(DateTime::from_timestamp(1_700_000_000 + 30 * 86_400, 0).unwrap());
+        let actions = trace_actions(
+            vec![
+                action("a1", "a", 0, "Fix CI", None),
+                action("b1", "b", 14, "Fix CI ", Some("a")),
+                // Written into the Actions of the same board again, to keep it going.
+                action("b2", "b", 14, "Fix CI", None),
+                last,
+                action("a2", "a", 0, "Book the room", None),
+            ],
+            today,
+        );
+
+        assert_eq!(actions.len(), 2);
+        let fix = actions.iter().find(|a| a.content.trim() == "Fix CI").unwrap();
+        assert_eq!(fix.ticket_id, "c1");
+        assert_eq!(fix.carry_count, 2);
+        assert_eq!(fix.first_board_id, "a");
+        assert!(fix.done_at.is_some());
+        let book = actions.iter().find(|a| a.content == "Book the room").unwrap();
+        assert_eq!(book.carry_count, 0);
+    }
+
+    #[test]
+    fn an_action_reworded_after_the_copy_starts_a_line_of_its_own() {
+        let today = NaiveDate::from_ymd_opt(2030, 1, 1).unwrap();
+        let actions = trace_actions(
+            vec![
+                action("a1", "a", 0, "Fix CI", None),
+                action("b1", "b", 14, "Fix CI for good", Some("a")),
+            ],
+            today,
+        );
+        assert_eq!(actions.len(), 2);
+        assert!(actions.iter().all(|a| a.carry_count == 0));
+    }
+
+    #[test]
+    fn a_timer_action_reads_back_what_it_stores() {
+        for action in [
+            TimerAction::Reveal,
+            TimerAction::LockCards,
+            TimerAction::StartVoting,
+        ] {
+            assert_eq!(TimerAction::parse(action.as_str()), Some(action));
+        }
+        assert_eq!(TimerAction::parse("close"), None);
+    }
+
+    #[test]
+    fn starting_the_vote_both_reveals_and_locks() {
+        assert!(TimerAction::StartVoting.reveals() && TimerAction::StartVoting.locks_cards());
+        assert!(TimerAction::Reveal.reveals() && !TimerAction::Reveal.locks_cards());
+        assert!(!TimerAction::LockCards.reveals() && TimerAction::LockCards.locks_cards());
+    }
+
+    #[test]
+    fn phases_run_in_order_each_once() {
+        let phases = normalize_phases(&[Phase::Discuss, Phase::Write, Phase::Vote, Phase::Write]);
+        assert_eq!(phases, vec![Phase::Write, Phase::Vote, Phase::Discuss]);
+        assert_eq!(
+            parse_phases(&["write".into(), "nap".into(), "closed".into()]),
+            vec![Phase::Write, Phase::Closed]
+        );
+    }
+
+    #[test]
+    fn cards_go_up_before_the_vote_and_votes_only_during_it() {
+        assert!(Phase::Write.takes_cards() && Phase::Group.takes_cards());
+        assert!(!Phase::Vote.takes_cards() && !Phase::Discuss.takes_cards());
+        assert!(Phase::Vote.takes_votes());
+        assert!(!Phase::Write.takes_votes() && !Phase::Discuss.takes_votes());
+    }
+
+    #[test]
+    fn a_token_request_keeps_each_scope_once_in_one_order() {
+        let (name, scopes) = read_token_request(
+            "  nightly carry-over ",
+            &[
+                TokenScope::ActionImport,
+                TokenScope::BoardRead,
+                TokenScope::ActionImport,
+            ],
+        )
+        .unwrap();
+        assert_eq!(name, "nightly carry-over");
+        assert_eq!(scopes, vec![TokenScope::BoardRead, TokenScope::ActionImport]);
+    }
+
+    #[test]
+    fn a_token_needs_a_name_and_a_scope() {
+        assert!(read_token_request("  ", &[TokenScope::BoardRead]).is_err());
+        let long = "x".repeat(MAX_TOKEN_NAME_LENGTH + 1);
+        assert!(read_token_request(&long, &[TokenScope::BoardRead]).is_err());
+        assert!(read_token_request("ci", &[]).is_err());
+    }
+
+    #[test]
+    fn token_scopes_round_trip() {
+        for scope in TokenScope::ALL {
+            assert_eq!(TokenScope::parse(scope.as_str()), Some(scope));
+            assert_eq!(
+                serde_json::to_value(scope).unwrap(),
+                serde_json::json!(scope.as_str())
+            );
+        }
+        assert_eq!(TokenScope::parse("board:delete"), None);
+    }
+
+    #[test]
+    fn an_audit_filter_names_an_action_or_a_prefix_of_some() {
+        assert_eq!(read_audit_action_filter("ticket.remove").unwrap(), "ticket.remove");
+        assert_eq!(read_audit_action_filter(" admin. ").unwrap(), "admin.");
+        assert!(read_audit_action_filter("ticket.delete").is_err());
+        assert!(read_audit_action_filter("nothing.").is_err());
+        // Without the dot it is the name of one action, and there is none by that name.
+        assert!(read_audit_action_filter("board.vote").is_err());
+    }
+
+    #[test]
+    fn every_audit_action_has_a_name_of_its_own() {
+        let names: std::collections::HashSet<_> =
+            AuditAction::ALL.iter().map(|a| a.as_str()).collect();
+        assert_eq!(names.len(), AuditAction::ALL.len());
+    }
 }
@@ -10,6 +10,7 @@ use argon2::password_hash::{PasswordHash, SaltString};
 use argon2::{Argon2, PasswordHasher, PasswordVerifier};
 
 use crate::error::AppError;
+use crate::metrics::METRICS;
 
 fn internal() -> AppError {
     AppError::Internal("Internal server error".to_string())
@@ -38,6 +39,7 @@ pub async fn hash(secret: String) -> Result<String, AppError> {
 /// Tells whether a secret matches a stored hash. A wrong secret gives `false`; only a hash that
 /// will not parse is an error, because that one is our fault and not the caller's.
 pub async fn verify(secret: String, stored: String) -> Result<bool, AppError> {
+    let _timer = METRICS.password_verify_seconds.start_timer();
     tokio::task::spawn_blocking(move || {
         let parsed = PasswordHash::new(&stored).map_err(|e| {
             tracing::error!("stored hash will not parse: {e}");
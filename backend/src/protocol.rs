@@ -1,6 +1,16 @@
-use crate::models::{BoardView, Gif};
+use crate::models::{BoardPatch, BoardView, Gif, Phase, TimerAction};
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
+/// The owner a client picks for an action. A team member is named by the team, and the server
+/// reads the name itself.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AssigneeChoice {
+    Participant { participant_id: String, name: String },
+    TeamMember { team_id: String, member_id: String },
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(tag = "type", content = "payload")]
 pub enum ClientMessage {
@@ -8,6 +18,10 @@ pub enum ClientMessage {
         participant_name: String,
         facilitator_token: Option<String>,
         participant_id: Option<String>,
+        /// The credential `Authenticated` gave out with the id. An id without its own is not
+        /// resumed, and the socket joins under a new one.
+        #[serde(default)]
+        participant_token: Option<String>,
         /// The key a reader got for the password of a locked board. A board with no password
         /// takes no notice of it.
         #[serde(default)]
@@ -54,10 +68,22 @@ pub enum ClientMessage {
     },
     ToggleBlur,
     ToggleHideVotes,
+    /// Stops new cards outside the two action columns, or lets them in again.
+    ToggleCardsLocked,
+    /// Moves the board to one of the phases it runs through, forward or back.
+    SetPhase {
+        phase: Phase,
+    },
+    /// Closes the board once the retro is over. A closed board can be read but takes no change.
+    CloseBoard,
+    /// Opens a closed board to changes again.
+    ReopenBoard,
     MergeTickets {
         source_ticket_id: String,
         target_ticket_id: String,
     },
+    /// Walks back the last merge of this participant. The history took over its work; this is
+    /// `Undo` that only acts when the step it would undo is a merge.
     UndoMerge,
     SplitTicket {
         ticket_id: String,
@@ -68,6 +94,9 @@ pub enum ClientMessage {
     },
     StartTimer {
         duration_secs: u32,
+        /// What the server does to the board when the time is up. None leaves the board as it is.
+        #[serde(default)]
+        on_expiry: Option<TimerAction>,
     },
     StopTimer,
     /// Closes an action, or opens it again. Only a card in one of the two action columns.
@@ -75,6 +104,19 @@ pub enum ClientMessage {
         ticket_id: String,
         done: bool,
     },
+    /// Names who takes an action on. Absent takes the owner off the card. Only a card in one of
+    /// the two action columns.
+    SetTicketAssignee {
+        ticket_id: String,
+        #[serde(default)]
+        assignee: Option<AssigneeChoice>,
+    },
+    /// Gives an action the day it is due, as `YYYY-MM-DD`. Absent takes the day off the card.
+    SetTicketDueDate {
+        ticket_id: String,
+        #[serde(default)]
+        due_on: Option<NaiveDate>,
+    },
     /// Marks a rock on track or off track. Absent takes the mark off the card.
     SetRockStatus {
         ticket_id: String,
@@ -112,15 +154,107 @@ pub enum ClientMessage {
     RemoveEditor {
         participant_id: String,
     },
+    /// Adds a column just before Actions. The facilitator and the editors only.
+    AddColumn {
+        name: String,
+    },
+    /// Renames a column. A column with a role — Previous Actions, Actions, the Rocks — keeps the
+    /// name it has.
+    RenameColumn {
+        column_id: String,
+        name: String,
+    },
+    /// Puts the columns in a new order. It names every column of the board once, Actions last.
+    ReorderColumns {
+        column_ids: Vec<String>,
+    },
+    /// Removes a column with no role. The cards in it go to `target_column_id`, which a column
+    /// that still holds cards cannot do without.
+    RemoveColumn {
+        column_id: String,
+        #[serde(default)]
+        target_column_id: Option<String>,
+    },
+    /// Walks back the newest step in the history of this participant on this board: a card
+    /// removed, edited, moved, merged or split, a comment, a done mark or a rock status.
+    Undo,
+    /// Makes again the step this participant undid last. A new step in between drops the redo.
+    Redo,
+    /// Asks for the whole board again. A client sends it when the revision of a patch skips one,
+    /// which means a patch went missing and the board it holds is no longer the real one.
+    Resync,
+}
+
+impl ClientMessage {
+    /// The type of the message, as the client writes it in `type`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ClientMessage::Join { .. } => "Join",
+            ClientMessage::AddTicket { .. } => "AddTicket",
+            ClientMessage::RemoveTicket { .. } => "RemoveTicket",
+            ClientMessage::EditTicket { .. } => "EditTicket",
+            ClientMessage::MoveTicket { .. } => "MoveTicket",
+            ClientMessage::AddComment { .. } => "AddComment",
+            ClientMessage::EditComment { .. } => "EditComment",
+            ClientMessage::RemoveComment { .. } => "RemoveComment",
+            ClientMessage::ToggleVote { .. } => "ToggleVote",
+            ClientMessage::ToggleBlur => "ToggleBlur",
+            ClientMessage::ToggleHideVotes => "ToggleHideVotes",
+            ClientMessage::ToggleCardsLocked => "ToggleCardsLocked",
+            ClientMessage::SetPhase { .. } => "SetPhase",
+            ClientMessage::CloseBoard => "CloseBoard",
+            ClientMessage::ReopenBoard => "ReopenBoard",
+            ClientMessage::MergeTickets { .. } => "MergeTickets",
+            ClientMessage::UndoMerge => "UndoMerge",
+            ClientMessage::SplitTicket { .. } => "SplitTicket",
+            ClientMessage::SetVoteLimit { .. } => "SetVoteLimit",
+            ClientMessage::StartTimer { .. } => "StartTimer",
+            ClientMessage::StopTimer => "StopTimer",
+            ClientMessage::SetTicketDone { .. } => "SetTicketDone",
+            ClientMessage::SetTicketAssignee { .. } => "SetTicketAssignee",
+            ClientMessage::SetTicketDueDate { .. } => "SetTicketDueDate",
+            ClientMessage::SetRockStatus { .. } => "SetRockStatus",
+            ClientMessage::RateMeeting { .. } => "RateMeeting",
+            ClientMessage::AddScorecardMetric { .. } => "AddScorecardMetric",
+            ClientMessage::UpdateScorecardMetric { .. } => "UpdateScorecardMetric",
+            ClientMessage::RemoveScorecardMetric { .. } => "RemoveScorecardMetric",
+            ClientMessage::RequestEditor { .. } => "RequestEditor",
+            ClientMessage::ApproveEditor { .. } => "ApproveEditor",
+            ClientMessage::DeclineEditor { .. } => "DeclineEditor",
+            ClientMessage::RemoveEditor { .. } => "RemoveEditor",
+            ClientMessage::AddColumn { .. } => "AddColumn",
+            ClientMessage::RenameColumn { .. } => "RenameColumn",
+            ClientMessage::ReorderColumns { .. } => "ReorderColumns",
+            ClientMessage::RemoveColumn { .. } => "RemoveColumn",
+            ClientMessage::Undo => "Undo",
+            ClientMessage::Redo => "Redo",
+            ClientMessage::Resync => "Resync",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(tag = "type", content = "payload")]
 pub enum ServerMessage {
-    BoardState { board: BoardView },
-    Authenticated { is_facilitator: bool, participant_id: String },
+    BoardState { board: Box<BoardView> },
+    /// One change, to lay on top of the board at `revision - 1`. A client that holds any other
+    /// revision sends `Resync` rather than apply it.
+    BoardPatch { revision: i64, patch: Box<BoardPatch> },
+    /// Who the socket joined as. The client keeps the credential, and sends it with the id to join
+    /// as the same participant again and to act as them over the REST API. `author_id` is the id
+    /// the board knows them by, on their cards and votes: on an anonymous board a pseudonym of
+    /// `participant_id`, on any other the same.
+    Authenticated {
+        is_facilitator: bool,
+        participant_id: String,
+        participant_token: String,
+        author_id: String,
+    },
     /// The board asks for a password that this reader did not bring. The socket closes after it,
     /// and the client puts the gate back up in place of the board.
     PasswordRequired,
+    /// The timer of the board ran out, and the server did `action` to it. What the action changed
+    /// follows as a state or a patch of its own.
+    TimerExpired { action: Option<TimerAction> },
     Error { message: String },
 }
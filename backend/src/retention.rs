@@ -0,0 +1,165 @@
+//! Removes old boards under the retention policy.
+//!
+//! A board goes once nothing has changed on it for the number of days the policy sets: it is
+//! deleted, or it keeps its words and loses every name on it. Boards with one of the exempt labels
+//! stay. The policy comes from the environment unless an admin has set one over the API, and an
+//! admin can always see what the next run would take before it runs. Each replica runs the purge;
+//! a board is locked while it goes, so two replicas never take the same one twice.
+
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::db;
+use crate::models::{
+    normalize_labels, RetentionAction, RetentionCandidate, RetentionPolicy, RetentionRecord,
+    RetentionSettings, RetentionSource,
+};
+use crate::state::AppState;
+
+/// How often a replica applies the policy. A board goes at most this long after its time is up.
+const INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// The longest a policy can keep a board: a hundred years is as good as forever.
+pub const MAX_RETENTION_DAYS: i32 = 36_500;
+
+/// Reads a policy, from the environment or from an admin, or says what is wrong with it.
+pub fn read_policy(
+    days: Option<i32>,
+    action: RetentionAction,
+    exempt_labels: &[String],
+) -> Result<RetentionPolicy, String> {
+    if let Some(days) = days {
+        if !(1..=MAX_RETENTION_DAYS).contains(&days) {
+            return Err(format!(
+                "The retention is between 1 and {MAX_RETENTION_DAYS} days"
+            ));
+        }
+    }
+    Ok(RetentionPolicy {
+        days,
+        action,
+        exempt_labels: normalize_labels(exempt_labels),
+    })
+}
+
+/// The policy of `RETENTION_DAYS`, `RETENTION_ACTION` and `RETENTION_EXEMPT_LABELS`. With no days
+/// set every board is kept, as before.
+pub fn from_env() -> Result<RetentionPolicy, String> {
+    let var = |name: &str| {
+        std::env::var(name)
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    let days = var("RETENTION_DAYS")
+        .map(|raw| {
+            raw.parse::<i32>()
+                .map_err(|_| format!("RETENTION_DAYS is a number of days, not {raw:?}"))
+        })
+        .transpose()?;
+    let action = match var("RETENTION_ACTION") {
+        None => RetentionAction::default(),
+        Some(raw) => RetentionAction::parse(&raw)
+            .ok_or_else(|| format!("RETENTION_ACTION is delete or anonymize, not {raw:?}"))?,
+    };
+    let labels: Vec<String> = var("RETENTION_EXEMPT_LABELS")
+        .map(|raw| raw.split(',').map(str::to_string).collect())
+        .unwrap_or_default();
+    read_policy(days, action, &labels)
+}
+
+/// The policy in force: the one an admin set, or else the one of the environment.
+pub async fn settings(state: &AppState) -> Result<RetentionSettings, sqlx::Error> {
+    Ok(match db::get_retention_policy(&state.db).await? {
+        Some(policy) => RetentionSettings {
+            policy,
+            source: RetentionSource::Admin,
+        },
+        None => RetentionSettings {
+            policy: (*state.retention).clone(),
+            source: RetentionSource::Environment,
+        },
+    })
+}
+
+/// What the next run would take, as it stands now.
+pub async fn preview(state: &AppState) -> Result<Vec<RetentionCandidate>, sqlx::Error> {
+    let policy = settings(state).await?.policy;
+    let Some(days) = policy.days else {
+        return Ok(Vec::new());
+    };
+    db::list_retention_candidates(&state.db, days, &policy).await
+}
+
+/// Applies the policy once, and gives back what it took.
+pub async fn apply(state: &AppState) -> Result<Vec<RetentionRecord>, sqlx::Error> {
+    let policy = settings(state).await?.policy;
+    let Some(days) = policy.days else {
+        return Ok(Vec::new());
+    };
+    let mut purged = Vec::new();
+    for candidate in db::list_retention_candidates(&state.db, days, &policy).await? {
+        if let Some(record) = db::purge_board(&state.db, &candidate, days, &policy.exempt_labels).await? {
+            info!(
+                board_id = record.board_id,
+                action = record.action,
+                "retention purged a board"
+            );
+            purged.push(record);
+        }
+    }
+    Ok(purged)
+}
+
+/// Applies the policy every hour, for the life of the process.
+pub async fn run(state: AppState) {
+    let mut interval = tokio::time::interval(INTERVAL);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        if let Err(e) = apply(&state).await {
+            warn!("Failed to apply the retention policy: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn days_stay_in_range() {
+        assert!(read_policy(Some(0), RetentionAction::Delete, &[]).is_err());
+        assert!(read_policy(Some(MAX_RETENTION_DAYS + 1), RetentionAction::Delete, &[]).is_err());
+        assert_eq!(
+            read_policy(Some(90), RetentionAction::Anonymize, &[])
+                .unwrap()
+                .days,
+            Some(90)
+        );
+        // No days at all is the policy that keeps everything.
+        assert_eq!(
+            read_policy(None, RetentionAction::Delete, &[]).unwrap(),
+            RetentionPolicy::default()
+        );
+    }
+
+    #[test]
+    fn exempt_labels_read_as_board_labels() {
+        let policy = read_policy(
+            Some(30),
+            RetentionAction::Delete,
+            &[" Legal ".to_string(), "legal".to_string(), "".to_string()],
+        )
+        .unwrap();
+        assert_eq!(policy.exempt_labels, vec!["legal".to_string()]);
+    }
+
+    #[test]
+    fn actions_round_trip() {
+        for action in [RetentionAction::Delete, RetentionAction::Anonymize] {
+            assert_eq!(RetentionAction::parse(action.as_str()), Some(action));
+        }
+        assert_eq!(RetentionAction::parse("shred"), None);
+    }
+}
@@ -1,18 +1,39 @@
-use axum::extract::{Path, State};
+use axum::extract::{Path, Query, State};
 use axum::http::request::Parts;
+use axum::http::Method;
 use axum::Json;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use tracing::info;
 
+use crate::audit::{self, Actor};
+use crate::auth::Identity;
 use crate::db;
 use crate::error::AppError;
-use crate::models::RESERVED_COLUMN_NAMES;
+use crate::models::{
+    normalize_phases, read_audit_action_filter, ApiCaller, AuditAction, AuditEntry, CreatedWebhook,
+    Phase, RetentionAction, RetentionCandidate, RetentionRecord, RetentionSettings,
+    RetentionSource, Template, TokenScope, WebhookDelivery, WebhookView, RESERVED_COLUMN_NAMES,
+};
 use crate::password;
 use crate::state::AppState;
 
 // --- Auth extractor ---
 
-pub struct AdminAuth;
+/// Lets an admin through, and says in the log who it was.
+///
+/// On a deployment with a sign-in, a member of one of the admin groups of the provider is an admin
+/// on their own account and needs nothing else. The shared token of `ADMIN_TOKEN_HASH` still opens
+/// the door — it is the way in when the provider is down, or before anyone is in the group — but
+/// it names nobody, so a request made with it is logged as the token's, with the account beside it
+/// when there is one.
+///
+/// A personal API token with the `admin:read` scope reads the admin routes and changes nothing.
+///
+/// The admin it let through is carried on, for the audit log of the routes that change something.
+pub struct AdminAuth {
+    pub actor: Actor,
+}
 
 impl axum::extract::FromRequestParts<AppState> for AdminAuth {
     type Rejection = AppError;
@@ -22,10 +43,60 @@ impl axum::extract::FromRequestParts<AppState> for AdminAuth {
         state: &AppState,
     ) -> impl std::future::Future<Output = Result<Self, Self::Rejection>> + Send {
         async move {
-            let hash = state
-                .admin_token_hash
-                .as_ref()
-                .ok_or_else(|| AppError::NotFound("Admin interface not enabled".to_string()))?;
+            let oidc = state.oidc.as_ref().filter(|oidc| oidc.has_admin_groups());
+            if state.admin_token_hash.is_none() && oidc.is_none() {
+                return Err(AppError::NotFound("Admin interface not enabled".to_string()));
+            }
+
+            // The gate checked a personal token and left its caller behind.
+            if let Some(caller) = parts.extensions.get::<ApiCaller>() {
+                if !caller.scopes.contains(&TokenScope::AdminRead) {
+                    return Err(AppError::Forbidden(
+                        "This API token does not have the admin:read scope".to_string(),
+                    ));
+                }
+                if parts.method != Method::GET {
+                    return Err(AppError::Forbidden(
+                        "An API token only reads the admin routes".to_string(),
+                    ));
+                }
+                info!(
+                    actor = caller.owner_sub.as_deref().or(caller.facilitator_id.as_deref()),
+                    token = caller.token_id,
+                    token_name = caller.name,
+                    method = %parts.method,
+                    path = parts.uri.path(),
+                    "admin request"
+                );
+                return Ok(AdminAuth {
+                    actor: Actor::token(caller),
+                });
+            }
+
+            // The gate left the identity behind, on a deployment that asks for one.
+            let identity = parts.extensions.get::<Identity>();
+            let signed_in_as = identity.map(|i| i.email.clone().unwrap_or_else(|| i.sub.clone()));
+
+            if let (Some(oidc), Some(identity)) = (oidc, identity) {
+                if oidc.is_admin(identity) {
+                    let account = signed_in_as.unwrap_or_default();
+                    info!(
+                        actor = account,
+                        method = %parts.method,
+                        path = parts.uri.path(),
+                        "admin request"
+                    );
+                    return Ok(AdminAuth {
+                        actor: Actor::admin(&account),
+                    });
+                }
+            }
+
+            let Some(hash) = state.admin_token_hash.as_ref() else {
+                return Err(AppError::Forbidden(
+                    "Your account is not in an admin group".to_string(),
+                ));
+            };
 
             let auth_header = parts
                 .headers
@@ -43,7 +114,16 @@ impl axum::extract::FromRequestParts<AppState> for AdminAuth {
                 return Err(AppError::Unauthorized("Invalid admin token".to_string()));
             }
 
-            Ok(AdminAuth)
+            info!(
+                actor = "admin token",
+                signed_in_as = signed_in_as.as_deref(),
+                method = %parts.method,
+                path = parts.uri.path(),
+                "admin request"
+            );
+            Ok(AdminAuth {
+                actor: Actor::admin_token(signed_in_as.as_deref()),
+            })
         }
     }
 }
@@ -64,6 +144,8 @@ pub struct AdminBoardSummary {
     pub title: String,
     pub is_blurred: bool,
     pub created_at: DateTime<Utc>,
+    /// When the facilitator closed the board. None while it is open.
+    pub archived_at: Option<DateTime<Utc>>,
     pub column_count: i64,
     pub ticket_count: i64,
     pub vote_count: i64,
@@ -100,8 +182,8 @@ pub async fn global_stats(
 ) -> Result<Json<GlobalStats>, AppError> {
     let stats = db::admin_global_stats(&state.db).await?;
 
-    let participants = state.participants.read().await;
-    let online: usize = participants.values().map(|v| v.len()).sum();
+    // Every replica writes its sockets to the database, so the sum covers them all.
+    let online = db::presence_counts(&state.db).await?.values().sum::<i64>() as usize;
 
     Ok(Json(GlobalStats {
         board_count: stats.board_count,
@@ -116,16 +198,17 @@ pub async fn list_boards(
     State(state): State<AppState>,
 ) -> Result<Json<Vec<AdminBoardSummary>>, AppError> {
     let rows = db::admin_list_boards(&state.db).await?;
-    let participants = state.participants.read().await;
+    let online = db::presence_counts(&state.db).await?;
 
     let boards = rows
         .into_iter()
         .map(|r| AdminBoardSummary {
-            online_participants: participants.get(&r.id).map(|v| v.len()).unwrap_or(0),
+            online_participants: online.get(&r.id).copied().unwrap_or(0) as usize,
             id: r.id,
             title: r.title,
             is_blurred: r.is_blurred,
             created_at: r.created_at,
+            archived_at: r.archived_at,
             column_count: r.column_count,
             ticket_count: r.ticket_count,
             vote_count: r.vote_count,
@@ -188,6 +271,9 @@ pub struct CreateTemplateRequest {
     /// How a board from this template opens. Absent means blurred, as a retro is.
     #[serde(default = "default_blurred")]
     pub default_blurred: bool,
+    /// The phases a board from it runs through. Absent gives boards with no phases.
+    #[serde(default)]
+    pub phases: Vec<Phase>,
 }
 
 /// A board hides its cards until the team has written them. Only a template that says otherwise
@@ -197,7 +283,7 @@ fn default_blurred() -> bool {
 }
 
 pub async fn create_template(
-    _auth: AdminAuth,
+    auth: AdminAuth,
     State(state): State<AppState>,
     Json(req): Json<CreateTemplateRequest>,
 ) -> Result<Json<serde_json::Value>, AppError> {
@@ -209,16 +295,19 @@ pub async fn create_template(
             "At least one column is required".to_string(),
         ));
     }
-    db::create_template(
-        &state.db,
-        &req.id,
-        &req.name,
-        &req.description,
-        &req.columns,
-        req.position,
-        req.default_blurred,
-    )
-    .await?;
+    let template = Template {
+        id: req.id,
+        name: req.name,
+        description: req.description,
+        columns: req.columns,
+        default_blurred: req.default_blurred,
+        position: req.position,
+        phases: normalize_phases(&req.phases),
+    };
+    db::create_template(&state.db, &template).await?;
+    let target = format!("template:{}", template.id);
+    audit::record(&state, None, &auth.actor, AuditAction::AdminTemplateCreate, Some(&target), None)
+        .await;
     Ok(Json(serde_json::json!({ "ok": true })))
 }
 
@@ -231,10 +320,12 @@ pub struct UpdateTemplateRequest {
     pub position: i32,
     #[serde(default = "default_blurred")]
     pub default_blurred: bool,
+    #[serde(default)]
+    pub phases: Vec<Phase>,
 }
 
 pub async fn update_template(
-    _auth: AdminAuth,
+    auth: AdminAuth,
     State(state): State<AppState>,
     Path(template_id): Path<String>,
     Json(req): Json<UpdateTemplateRequest>,
@@ -247,19 +338,22 @@ pub async fn update_template(
             "At least one column is required".to_string(),
         ));
     }
-    let updated = db::update_template(
-        &state.db,
-        &template_id,
-        &req.name,
-        &req.description,
-        &req.columns,
-        req.position,
-        req.default_blurred,
-    )
-    .await?;
+    let template = Template {
+        id: template_id,
+        name: req.name,
+        description: req.description,
+        columns: req.columns,
+        default_blurred: req.default_blurred,
+        position: req.position,
+        phases: normalize_phases(&req.phases),
+    };
+    let updated = db::update_template(&state.db, &template).await?;
     if !updated {
         return Err(AppError::NotFound("Template not found".to_string()));
     }
+    let target = format!("template:{}", template.id);
+    audit::record(&state, None, &auth.actor, AuditAction::AdminTemplateUpdate, Some(&target), None)
+        .await;
     Ok(Json(serde_json::json!({ "ok": true })))
 }
 
@@ -270,7 +364,7 @@ pub async fn update_template(
 /// position and adds what the board has not got. It deletes nothing, so a board keeps every
 /// card it holds and every column the template no longer names.
 pub async fn apply_template(
-    _auth: AdminAuth,
+    auth: AdminAuth,
     State(state): State<AppState>,
     Path(template_id): Path<String>,
 ) -> Result<Json<crate::models::ApplyTemplateResult>, AppError> {
@@ -289,6 +383,23 @@ pub async fn apply_template(
 
     let result = db::apply_template_to_boards(&state.db, &template_id, &names).await?;
 
+    // Each board it changed keeps an entry of its own, so that the log of a board says why its
+    // columns moved.
+    let target = format!("template:{template_id}");
+    audit::record(&state, None, &auth.actor, AuditAction::AdminTemplateApply, Some(&target), None)
+        .await;
+    for board_id in &result.changed_board_ids {
+        audit::record(
+            &state,
+            Some(board_id),
+            &auth.actor,
+            AuditAction::AdminTemplateApply,
+            Some(&target),
+            None,
+        )
+        .await;
+    }
+
     // A board open in someone's browser holds the old column names until it is told otherwise.
     for board_id in &result.changed_board_ids {
         crate::routes::ws::broadcast_board_state(&state, board_id).await;
@@ -298,7 +409,7 @@ pub async fn apply_template(
 }
 
 pub async fn delete_template(
-    _auth: AdminAuth,
+    auth: AdminAuth,
     State(state): State<AppState>,
     Path(template_id): Path<String>,
 ) -> Result<Json<serde_json::Value>, AppError> {
@@ -306,6 +417,9 @@ pub async fn delete_template(
     if !deleted {
         return Err(AppError::NotFound("Template not found".to_string()));
     }
+    let target = format!("template:{template_id}");
+    audit::record(&state, None, &auth.actor, AuditAction::AdminTemplateDelete, Some(&target), None)
+        .await;
     Ok(Json(serde_json::json!({ "ok": true })))
 }
 
@@ -314,12 +428,17 @@ pub async fn delete_template(
 /// The pod has `restartPolicy: Always`, so the container comes back as soon as the process ends.
 /// The exit code is 0, because this stop is asked for and is not a fault. Nothing on a board is
 /// lost: the boards live in PostgreSQL, and a browser opens its socket again after two seconds.
-/// The participant list and a pending merge undo are in memory, so both go.
+/// The sockets of this replica go, and with them their places on the boards; a pending merge undo
+/// is in memory, so it goes too.
 ///
-/// The deployment runs one replica. With more than one, this stops the pod that answers the
-/// request and no other.
-pub async fn restart_service(_auth: AdminAuth) -> Json<serde_json::Value> {
+/// With more than one replica, this stops the pod that answers the request and no other. The
+/// others keep their sockets and keep serving every board.
+pub async fn restart_service(
+    auth: AdminAuth,
+    State(state): State<AppState>,
+) -> Json<serde_json::Value> {
     tracing::warn!("Admin asked for a restart. The server stops now.");
+    audit::record(&state, None, &auth.actor, AuditAction::AdminRestart, None, None).await;
 
     // The answer goes out first. A process that exits inside the handler writes no response, and
     // the admin page would show a network error for a restart that worked.
@@ -348,7 +467,7 @@ pub struct CreateTeamRequest {
 }
 
 pub async fn create_team(
-    _auth: AdminAuth,
+    auth: AdminAuth,
     State(state): State<AppState>,
     Json(req): Json<CreateTeamRequest>,
 ) -> Result<Json<serde_json::Value>, AppError> {
@@ -367,6 +486,9 @@ pub async fn create_team(
         .map(|name| (nanoid::nanoid!(8), name.clone()))
         .collect();
     db::create_team(&state.db, &id, req.name.trim(), &members).await?;
+    let target = format!("team:{id}");
+    audit::record(&state, None, &auth.actor, AuditAction::AdminTeamCreate, Some(&target), None)
+        .await;
     Ok(Json(serde_json::json!({ "ok": true })))
 }
 
@@ -377,7 +499,7 @@ pub struct UpdateTeamRequest {
 }
 
 pub async fn update_team(
-    _auth: AdminAuth,
+    auth: AdminAuth,
     State(state): State<AppState>,
     Path(team_id): Path<String>,
     Json(req): Json<UpdateTeamRequest>,
@@ -399,11 +521,14 @@ pub async fn update_team(
     if !updated {
         return Err(AppError::NotFound("Team not found".to_string()));
     }
+    let target = format!("team:{team_id}");
+    audit::record(&state, None, &auth.actor, AuditAction::AdminTeamUpdate, Some(&target), None)
+        .await;
     Ok(Json(serde_json::json!({ "ok": true })))
 }
 
 pub async fn delete_team(
-    _auth: AdminAuth,
+    auth: AdminAuth,
     State(state): State<AppState>,
     Path(team_id): Path<String>,
 ) -> Result<Json<serde_json::Value>, AppError> {
@@ -411,11 +536,14 @@ pub async fn delete_team(
     if !deleted {
         return Err(AppError::NotFound("Team not found".to_string()));
     }
+    let target = format!("team:{team_id}");
+    audit::record(&state, None, &auth.actor, AuditAction::AdminTeamDelete, Some(&target), None)
+        .await;
     Ok(Json(serde_json::json!({ "ok": true })))
 }
 
 pub async fn delete_board(
-    _auth: AdminAuth,
+    auth: AdminAuth,
     State(state): State<AppState>,
     Path(board_id): Path<String>,
 ) -> Result<Json<serde_json::Value>, AppError> {
@@ -423,16 +551,242 @@ pub async fn delete_board(
     if !deleted {
         return Err(AppError::NotFound("Board not found".to_string()));
     }
+    audit::record(&state, Some(&board_id), &auth.actor, AuditAction::AdminBoardDelete, None, None)
+        .await;
 
     // Clean up in-memory state
     {
         let mut participants = state.participants.write().await;
         participants.remove(&board_id);
     }
-    {
-        let mut channels = state.channels.write().await;
-        channels.remove(&board_id);
-    }
+    state.drop_channel(&board_id).await;
+
+    Ok(Json(serde_json::json!({ "ok": true })))
+}
+
+// --- Webhooks ---
+
+/// The global webhooks, which hear every board. The webhooks of one board belong to its
+/// facilitator and show up here only through their deliveries.
+pub async fn list_webhooks(
+    _auth: AdminAuth,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<WebhookView>>, AppError> {
+    Ok(Json(db::list_webhooks(&state.db, None).await?))
+}
+
+#[derive(Deserialize)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    pub events: Vec<String>,
+}
+
+pub async fn create_webhook(
+    auth: AdminAuth,
+    State(state): State<AppState>,
+    Json(req): Json<CreateWebhookRequest>,
+) -> Result<Json<CreatedWebhook>, AppError> {
+    let created = crate::webhooks::create(&state, None, &req.url, &req.events).await?;
+    let target = format!("webhook:{}", created.webhook.id);
+    audit::record(
+        &state,
+        None,
+        &auth.actor,
+        AuditAction::AdminWebhookCreate,
+        Some(&target),
+        Some(&created.webhook.url),
+    )
+    .await;
+    Ok(Json(created))
+}
 
+pub async fn delete_webhook(
+    auth: AdminAuth,
+    State(state): State<AppState>,
+    Path(webhook_id): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    if !db::delete_webhook(&state.db, &webhook_id, None).await? {
+        return Err(AppError::NotFound("Webhook not found".to_string()));
+    }
+    let target = format!("webhook:{webhook_id}");
+    audit::record(&state, None, &auth.actor, AuditAction::AdminWebhookDelete, Some(&target), None)
+        .await;
     Ok(Json(serde_json::json!({ "ok": true })))
 }
+
+#[derive(Deserialize)]
+pub struct DeliveryQuery {
+    #[serde(default)]
+    pub webhook_id: Option<String>,
+    #[serde(default)]
+    pub board_id: Option<String>,
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub limit: Option<i64>,
+}
+
+/// The delivery log of every webhook, the newest first.
+pub async fn list_webhook_deliveries(
+    _auth: AdminAuth,
+    State(state): State<AppState>,
+    Query(query): Query<DeliveryQuery>,
+) -> Result<Json<Vec<WebhookDelivery>>, AppError> {
+    if let Some(status) = query.status.as_deref() {
+        if !matches!(status, "pending" | "delivered" | "failed") {
+            return Err(AppError::BadRequest(
+                "The status is pending, delivered or failed".to_string(),
+            ));
+        }
+    }
+    let deliveries = db::list_webhook_deliveries(
+        &state.db,
+        query.webhook_id.as_deref(),
+        query.board_id.as_deref(),
+        query.status.as_deref(),
+        query.limit.unwrap_or(100).clamp(1, 500),
+    )
+    .await?;
+    Ok(Json(deliveries))
+}
+
+// --- Retention ---
+
+/// The retention policy in force, and whether it is the environment's or an admin's.
+pub async fn get_retention(
+    _auth: AdminAuth,
+    State(state): State<AppState>,
+) -> Result<Json<RetentionSettings>, AppError> {
+    Ok(Json(crate::retention::settings(&state).await?))
+}
+
+#[derive(Deserialize)]
+pub struct SetRetentionRequest {
+    /// Absent, or null, keeps every board.
+    #[serde(default)]
+    pub days: Option<i32>,
+    #[serde(default)]
+    pub action: RetentionAction,
+    #[serde(default)]
+    pub exempt_labels: Vec<String>,
+}
+
+/// Sets the policy over the one of the environment. The next run applies it.
+pub async fn set_retention(
+    auth: AdminAuth,
+    State(state): State<AppState>,
+    Json(req): Json<SetRetentionRequest>,
+) -> Result<Json<RetentionSettings>, AppError> {
+    let policy = crate::retention::read_policy(req.days, req.action, &req.exempt_labels)
+        .map_err(AppError::BadRequest)?;
+    db::set_retention_policy(&state.db, &policy).await?;
+    let detail = match policy.days {
+        Some(days) => format!("{} after {days} days", policy.action.as_str()),
+        None => "keep every board".to_string(),
+    };
+    audit::record(&state, None, &auth.actor, AuditAction::AdminRetentionSet, None, Some(&detail))
+        .await;
+    Ok(Json(RetentionSettings {
+        policy,
+        source: RetentionSource::Admin,
+    }))
+}
+
+/// Drops the policy an admin set, and the environment decides again.
+pub async fn clear_retention(
+    auth: AdminAuth,
+    State(state): State<AppState>,
+) -> Result<Json<RetentionSettings>, AppError> {
+    db::clear_retention_policy(&state.db).await?;
+    audit::record(&state, None, &auth.actor, AuditAction::AdminRetentionClear, None, None).await;
+    Ok(Json(crate::retention::settings(&state).await?))
+}
+
+/// The boards the next run would take, and nothing taken.
+pub async fn preview_retention(
+    _auth: AdminAuth,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<RetentionCandidate>>, AppError> {
+    Ok(Json(crate::retention::preview(&state).await?))
+}
+
+#[derive(Deserialize)]
+pub struct RetentionLogQuery {
+    #[serde(default)]
+    pub limit: Option<i64>,
+}
+
+/// What the purge has removed, the newest first.
+pub async fn list_retention_log(
+    _auth: AdminAuth,
+    State(state): State<AppState>,
+    Query(query): Query<RetentionLogQuery>,
+) -> Result<Json<Vec<RetentionRecord>>, AppError> {
+    let records =
+        db::list_retention_log(&state.db, query.limit.unwrap_or(100).clamp(1, 500)).await?;
+    Ok(Json(records))
+}
+
+// --- Audit log ---
+
+#[derive(Deserialize)]
+pub struct AuditQuery {
+    #[serde(default)]
+    pub board_id: Option<String>,
+    /// One action, such as `ticket.remove`, or every action under a prefix, such as `admin.`.
+    #[serde(default)]
+    pub action: Option<String>,
+    /// One actor, exactly as the log writes it: `participant:<id>`, `admin token`, and so on.
+    #[serde(default)]
+    pub actor: Option<String>,
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub until: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub limit: Option<i64>,
+}
+
+/// The audit log of every board and of the admins, the newest first.
+pub async fn list_audit_log(
+    _auth: AdminAuth,
+    State(state): State<AppState>,
+    Query(query): Query<AuditQuery>,
+) -> Result<Json<Vec<AuditEntry>>, AppError> {
+    audit_entries(&state, query.board_id.as_deref(), &query).await
+}
+
+/// The audit log of one board, the newest first. It is there after the board is gone.
+pub async fn list_board_audit_log(
+    _auth: AdminAuth,
+    State(state): State<AppState>,
+    Path(board_id): Path<String>,
+    Query(query): Query<AuditQuery>,
+) -> Result<Json<Vec<AuditEntry>>, AppError> {
+    audit_entries(&state, Some(&board_id), &query).await
+}
+
+async fn audit_entries(
+    state: &AppState,
+    board_id: Option<&str>,
+    query: &AuditQuery,
+) -> Result<Json<Vec<AuditEntry>>, AppError> {
+    let action = query
+        .action
+        .as_deref()
+        .filter(|a| !a.trim().is_empty())
+        .map(read_audit_action_filter)
+        .transpose()
+        .map_err(AppError::BadRequest)?;
+    let entries = db::list_audit_entries(
+        &state.db,
+        board_id,
+        action.as_deref(),
+        query.actor.as_deref().filter(|a| !a.is_empty()),
+        query.since,
+        query.until,
+        query.limit.unwrap_or(100).clamp(1, 500),
+    )
+    .await?;
+    Ok(Json(entries))
+}
@@ -1,22 +1,34 @@
 use axum::extract::{Path, Query, State};
-use axum::http::HeaderMap;
+use axum::http::{header, HeaderMap};
+use axum::response::IntoResponse;
 use axum::Json;
 use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
 use serde::Deserialize;
 use time::Duration;
 
+use crate::audit::{self, Actor};
 use crate::db;
 use crate::db::CopyOutcome;
+use crate::auth::{ClientAddr, Owner};
+use crate::credentials;
 use crate::error::AppError;
+use crate::export::{self, ExportFormat};
 use crate::models::{
-    normalize_labels, plan_new_board_columns, read_password, read_title, ActionSourceBoard,
-    BoardAccessView, CreateBoardRequest, CreateBoardResponse, ImportResult, LabelCount,
-    MyBoardSummary, PasswordResponse, Template, UnlockResponse, TEMPLATE_LEVEL10,
+    normalize_labels, normalize_phases, plan_imported_columns, plan_new_board_columns, read_assignee_name, read_column_name,
+    read_password, read_title, sanitize_gif, trace_actions, valid_rock_status, ActionSourceBoard,
+    ActionStatus, Assignee, AuditAction, Board, BoardAccessView, BoardArchive, Column, Comment,
+    CreateBoardRequest, CreateBoardResponse, CreatedWebhook, ImportResult, LabelCount, MeetingRatingView,
+    MyBoardSummary, PasswordResponse, ScorecardMetric, Template, Ticket, TokenScope, TrackedAction,
+    UnlockAttempt, UnlockResponse, WebhookView, BOARD_ARCHIVE_VERSION, DONE_COLUMN_ROLES, ROLE_ROCKS, TEMPLATE_LEVEL10,
 };
+use crate::routes::ws::{clean_comment, clean_scorecard_field, clean_ticket};
 use crate::password;
 use crate::state::AppState;
-use chrono::Utc;
+use crate::webhooks::{self, WebhookEvent};
+use chrono::{DateTime, Utc};
 use nanoid::nanoid;
+use std::collections::HashMap;
+use tracing::warn;
 use uuid::Uuid;
 
 /// The largest number of source boards that one list request returns.
@@ -38,6 +50,7 @@ fn access_token_from(headers: &HeaderMap) -> Option<&str> {
 pub async fn create_board(
     State(state): State<AppState>,
     jar: CookieJar,
+    owner: Owner,
     Json(req): Json<CreateBoardRequest>,
 ) -> Result<(CookieJar, Json<CreateBoardResponse>), AppError> {
     let title = read_title(&req.title).map_err(AppError::BadRequest)?;
@@ -47,10 +60,8 @@ pub async fn create_board(
         ));
     }
 
-    let facilitator_id = jar
-        .get("facilitator_id")
-        .map(|c| c.value().to_string())
-        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    owner.require(TokenScope::BoardWrite)?;
+    let facilitator_id = facilitator_id_or_new(&owner);
 
     let board_id = nanoid!(10);
     let facilitator_token = nanoid!(32);
@@ -78,6 +89,12 @@ pub async fn create_board(
     // The template says how the board opens. A custom board opens blurred, as a retro does.
     let is_blurred = template.as_ref().map(|t| t.default_blurred).unwrap_or(true);
 
+    // The caller can name the phases; otherwise the template does, and a custom board has none.
+    let phases = match &req.phases {
+        Some(phases) => normalize_phases(phases),
+        None => template.as_ref().map(|t| t.phases.clone()).unwrap_or_default(),
+    };
+
     // Every board has Previous Actions and Actions. The caller places Previous Actions by
     // naming it; Actions goes last, always, and its names stay reserved. The plan itself is
     // pure and lives in `models::plan_new_board_columns`.
@@ -93,6 +110,7 @@ pub async fn create_board(
         &title,
         &facilitator_token,
         &facilitator_id,
+        owner.sub.as_deref(),
         &columns,
         created_at,
         req.is_anonymous,
@@ -101,18 +119,243 @@ pub async fn create_board(
         password_hash.as_deref(),
         &access_token,
         is_blurred,
+        &phases,
     )
     .await?;
 
-    let view = board.to_view_with_participants(0, Vec::new(), Vec::new());
+    // A new board has no webhooks of its own yet, so this one is for the global ones.
+    webhooks::emit(&state, &board_id, WebhookEvent::RetroStarted, None).await;
+
+    // The one who asked is the facilitator of the board.
+    let mut view = board.to_view_with_participants(0, Vec::new(), Vec::new());
+    view.redact_hidden_for("", true);
+    let jar = with_facilitator_cookie(jar, facilitator_id);
+
+    Ok((
+        jar,
+        Json(CreateBoardResponse {
+            board: view,
+            facilitator_token,
+            access_token,
+        }),
+    ))
+}
+
+/// The facilitator id this browser already carries, or a new one for a browser that made no board
+/// before. A script's token carries the one of the browser it was made in.
+pub fn facilitator_id_or_new(owner: &Owner) -> String {
+    owner
+        .facilitator_id
+        .clone()
+        .unwrap_or_else(|| Uuid::new_v4().to_string())
+}
 
+/// Sets the cookie that makes this browser the facilitator of the boards it creates.
+pub fn with_facilitator_cookie(jar: CookieJar, facilitator_id: String) -> CookieJar {
     let cookie = Cookie::build(("facilitator_id", facilitator_id))
         .path("/")
         .http_only(true)
         .same_site(SameSite::None)
         .secure(true)
         .max_age(Duration::days(365));
-    let jar = jar.add(cookie);
+    jar.add(cookie)
+}
+
+/// Makes a new board out of an archive that `export_board` wrote, here or on another instance.
+///
+/// Everything gets a fresh id: the board, its columns, cards and comments, and the participants,
+/// who are the same person on every card, vote and rating of the board and nobody outside it.
+/// The columns follow the same rules a new board does, and what the archive cannot hold in the
+/// place it ends up is let go: a done mark outside the two action columns, a rock status outside
+/// the Rocks, a GIF that is not from GIPHY, a comment over the limit. The caller becomes the
+/// facilitator, as with `create_board`. The board comes in without a password; the facilitator
+/// sets one afterwards if it needs one.
+pub async fn import_board(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    owner: Owner,
+    Json(archive): Json<BoardArchive>,
+) -> Result<(CookieJar, Json<CreateBoardResponse>), AppError> {
+    owner.require(TokenScope::BoardWrite)?;
+    if archive.version != BOARD_ARCHIVE_VERSION {
+        return Err(AppError::BadRequest(format!(
+            "This server reads version {BOARD_ARCHIVE_VERSION} of the archive"
+        )));
+    }
+    let title = read_title(&archive.title).map_err(AppError::BadRequest)?;
+
+    let template = match archive.template_id.as_deref() {
+        Some(id) => db::get_template(&state.db, id).await?,
+        None => None,
+    };
+    // The archive keeps no phase. The board starts at the first phase of its template, if any.
+    let phases = template.as_ref().map(|t| t.phases.clone()).unwrap_or_default();
+    let template_id = template.map(|t| t.id);
+    let is_level10 = template_id.as_deref() == Some(TEMPLATE_LEVEL10);
+
+    // The names come from a file, but they keep to the rules of a column named on the board. The
+    // two action columns are found by role and take their own names, so theirs are let be.
+    for column in archive
+        .columns
+        .iter()
+        .filter(|c| !c.is_actions() && !c.is_previous_actions())
+    {
+        read_column_name(&column.name).map_err(AppError::BadRequest)?;
+    }
+    let (plan, targets) = plan_imported_columns(&archive.columns, is_level10);
+    let mut columns: Vec<Column> = plan
+        .into_iter()
+        .map(|(name, role)| Column {
+            id: nanoid!(8),
+            name,
+            role: role.map(String::from),
+            tickets: Vec::new(),
+        })
+        .collect();
+
+    let mut participants: HashMap<String, String> = HashMap::new();
+    let mut participant = |name: &str| {
+        participants
+            .entry(name.to_string())
+            .or_insert_with(|| nanoid!(8))
+            .clone()
+    };
+
+    for (archived, &target) in archive.columns.iter().zip(&targets) {
+        let role = columns[target].role.clone();
+        let can_be_done = role.as_deref().is_some_and(|r| DONE_COLUMN_ROLES.contains(&r));
+        let is_rocks = role.as_deref() == Some(ROLE_ROCKS);
+
+        for card in &archived.cards {
+            let gif = card.gif.clone().and_then(sanitize_gif);
+            let Some(content) = clean_ticket(&card.content, gif.is_some()) else {
+                continue;
+            };
+            let comments = card
+                .comments
+                .iter()
+                .filter_map(|c| {
+                    let gif = c.gif.clone().and_then(sanitize_gif);
+                    let content = clean_comment(&c.content, gif.is_some())?;
+                    Some(Comment {
+                        id: nanoid!(8),
+                        content,
+                        author_id: participant(&c.author),
+                        author_name: c.author_name.clone(),
+                        created_at: c.created_at,
+                        gif,
+                    })
+                })
+                .collect();
+            columns[target].tickets.push(Ticket {
+                id: nanoid!(8),
+                content,
+                author_id: participant(&card.author),
+                author_name: card.author_name.clone(),
+                votes: card.votes.iter().map(|v| participant(v)).collect(),
+                vote_count: None,
+                voted: false,
+                created_at: card.created_at,
+                carried_from_board_id: None,
+                carried_from_board_title: card.carried_from_board_title.clone(),
+                comments,
+                gif,
+                rock_status: card
+                    .rock_status
+                    .clone()
+                    .filter(|s| is_rocks && valid_rock_status(s)),
+                done_at: card.done_at.filter(|_| can_be_done),
+                // The owner arrives as someone on the board, since the teams of the other
+                // instance are not here.
+                assignee: match (&card.assignee, &card.assignee_name) {
+                    (Some(assignee), Some(name)) if can_be_done => read_assignee_name(name)
+                        .ok()
+                        .map(|name| Assignee::Participant {
+                            participant_id: participant(assignee),
+                            name,
+                        }),
+                    _ => None,
+                },
+                due_on: card.due_on.filter(|_| can_be_done),
+                is_overdue: false,
+            });
+        }
+    }
+
+    // The scorecard and the ratings belong to a Level 10 board alone, as on a new board.
+    let (scorecard, meeting_ratings) = if is_level10 {
+        let scorecard = archive
+            .scorecard
+            .iter()
+            .filter_map(|m| {
+                let name = clean_scorecard_field(&m.name).filter(|n| !n.is_empty())?;
+                Some(ScorecardMetric {
+                    id: nanoid!(8),
+                    name,
+                    goal: clean_scorecard_field(&m.goal)?,
+                    actual: clean_scorecard_field(&m.actual)?,
+                    on_track: m.on_track,
+                })
+            })
+            .collect();
+        let ratings = archive
+            .meeting_ratings
+            .iter()
+            .filter(|r| (1..=10).contains(&r.rating))
+            .map(|r| MeetingRatingView {
+                participant_id: participant(&r.participant),
+                rating: r.rating,
+            })
+            .collect();
+        (scorecard, ratings)
+    } else {
+        (Vec::new(), Vec::new())
+    };
+
+    let facilitator_id = facilitator_id_or_new(&owner);
+    let facilitator_token = nanoid!(32);
+    let access_token = nanoid!(32);
+    let board = Board {
+        id: nanoid!(10),
+        title,
+        columns,
+        is_blurred: archive.is_blurred,
+        is_anonymous: archive.is_anonymous,
+        hide_votes: archive.hide_votes,
+        created_at: archive.created_at,
+        facilitator_token: facilitator_token.clone(),
+        facilitator_id: Some(facilitator_id.clone()),
+        participants: Vec::new(),
+        vote_limit_per_column: archive.vote_limit_per_column.filter(|l| *l > 0),
+        timer_end: None,
+        timer_action: None,
+        cards_locked: false,
+        phase: phases.first().copied(),
+        phases,
+        archived_at: None,
+        labels: normalize_labels(&archive.labels),
+        template_id,
+        scorecard,
+        meeting_ratings,
+        has_password: false,
+        password_lockout: None,
+        revision: 0,
+    };
+    db::import_board(&state.db, &board, &access_token, owner.sub.as_deref()).await?;
+    audit::record(
+        &state,
+        Some(&board.id),
+        &Actor::owner(&owner, None),
+        AuditAction::BoardImport,
+        None,
+        None,
+    )
+    .await;
+
+    // The one who asked is the facilitator of the board.
+    let mut view = board.to_view_with_participants(0, Vec::new(), Vec::new());
+    view.redact_hidden_for("", true);
+    let jar = with_facilitator_cookie(jar, facilitator_id);
 
     Ok((
         jar,
@@ -126,13 +369,20 @@ pub async fn create_board(
 
 pub async fn get_board(
     State(state): State<AppState>,
-    jar: CookieJar,
+    owner: Owner,
     headers: HeaderMap,
     Path(board_id): Path<String>,
 ) -> Result<Json<crate::models::BoardView>, AppError> {
     // The gate comes before the board. This route would otherwise hand the whole of a locked
     // board to anyone who asks for it by id.
-    authorize_read(&state, &jar, &board_id, access_token_from(&headers)).await?;
+    authorize_read(
+        &state,
+        &owner,
+        &board_id,
+        access_token_from(&headers),
+        TokenScope::BoardRead,
+    )
+    .await?;
 
     let board = db::get_board(&state.db, &board_id)
         .await?
@@ -145,14 +395,111 @@ pub async fn get_board(
 
     // This route names no participant, so it can hold no cards of its own. Only the facilitator
     // reads a blurred board here; for everyone else the words stay on the server.
-    let facilitator_id = jar.get("facilitator_id").map(|c| c.value());
-    let is_facilitator =
-        db::is_board_privileged(&state.db, &board_id, None, facilitator_id, None).await?;
+    let is_facilitator = db::is_board_privileged(
+        &state.db,
+        &board_id,
+        None,
+        owner.facilitator_id.as_deref(),
+        owner.sub.as_deref(),
+        None,
+    )
+    .await?;
     view.redact_hidden_for("", is_facilitator);
 
     Ok(Json(view))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    /// `markdown`, `csv`, `json` or `archive`. Absent gives Markdown, which is what goes into a
+    /// wiki page.
+    #[serde(default)]
+    pub format: Option<String>,
+    /// Who the caller is on the board, for a board whose cards or votes are still hidden.
+    #[serde(flatten)]
+    pub auth: BoardAuth,
+}
+
+/// Hands the board over as a file: its columns, cards, votes, comments, done marks, rock status,
+/// scorecard and meeting rating.
+///
+/// The same gate as `get_board` comes first. A blurred board is refused to everyone but the
+/// facilitator and the editors, known the way `authorize` knows them: filler words are of no use
+/// in a file, and the real ones are not for this caller yet. Carried actions would be readable,
+/// but half a retro passed off as the whole would mislead more than it helps.
+pub async fn export_board(
+    State(state): State<AppState>,
+    owner: Owner,
+    headers: HeaderMap,
+    Path(board_id): Path<String>,
+    Query(query): Query<ExportQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let format = match query.format.as_deref() {
+        None => ExportFormat::Markdown,
+        Some(raw) => ExportFormat::parse(raw).ok_or_else(|| {
+            AppError::BadRequest("The format is markdown, csv, json or archive".to_string())
+        })?,
+    };
+
+    authorize_read(
+        &state,
+        &owner,
+        &board_id,
+        access_token_from(&headers),
+        TokenScope::BoardRead,
+    )
+    .await?;
+
+    let board = db::get_board(&state.db, &board_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Board not found".to_string()))?;
+
+    let participant = query.auth.participant(&state, &board_id).await?;
+    let privileged = db::is_board_privileged(
+        &state.db,
+        &board_id,
+        query.auth.facilitator_token.as_deref(),
+        owner.facilitator_id.as_deref(),
+        owner.sub.as_deref(),
+        participant.as_deref(),
+    )
+    .await?;
+    if board.is_blurred && !privileged {
+        return Err(AppError::Forbidden(
+            "The board is still blurred. It can be exported once the cards are revealed"
+                .to_string(),
+        ));
+    }
+    // So is a board whose votes are hidden. The file would give the counts away.
+    if board.hide_votes && !privileged {
+        return Err(AppError::Forbidden(
+            "The votes are still hidden. The board can be exported once they are shown"
+                .to_string(),
+        ));
+    }
+    // The archive keeps who voted for what, under names of its own, so that an import can count
+    // the votes again. That is still more than a reader of the board learns, so it is for the
+    // facilitator and the editors to take.
+    if format == ExportFormat::Archive && !privileged {
+        return Err(AppError::Forbidden(
+            "Only the facilitator and the editors can export the archive of a board".to_string(),
+        ));
+    }
+
+    let view = board.to_view_with_participants(0, Vec::new(), Vec::new());
+    let disposition = format!(
+        "attachment; filename=\"{}\"",
+        export::file_name(&view, format)
+    );
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        export::render(&view, format),
+    ))
+}
+
 pub async fn list_templates(
     State(state): State<AppState>,
 ) -> Result<Json<Vec<Template>>, AppError> {
@@ -167,16 +514,22 @@ pub async fn list_teams(
     Ok(Json(teams))
 }
 
+/// The boards the caller made. A signed-in caller gets those of their account, from any device;
+/// anyone else gets those of the facilitator cookie of this browser.
 pub async fn my_boards(
     State(state): State<AppState>,
-    jar: CookieJar,
+    owner: Owner,
 ) -> Result<Json<Vec<MyBoardSummary>>, AppError> {
-    let facilitator_id = match jar.get("facilitator_id") {
-        Some(c) => c.value().to_string(),
-        None => return Ok(Json(Vec::new())),
-    };
+    if owner.facilitator_id.is_none() && owner.sub.is_none() {
+        return Ok(Json(Vec::new()));
+    }
 
-    let boards = db::get_boards_by_facilitator_id(&state.db, &facilitator_id).await?;
+    let boards = db::get_boards_by_owner(
+        &state.db,
+        owner.facilitator_id.as_deref(),
+        owner.sub.as_deref(),
+    )
+    .await?;
     Ok(Json(boards))
 }
 
@@ -190,7 +543,7 @@ pub async fn my_boards(
 /// Nothing that is on the board comes with it.
 pub async fn board_access(
     State(state): State<AppState>,
-    jar: CookieJar,
+    owner: Owner,
     headers: HeaderMap,
     Path(board_id): Path<String>,
 ) -> Result<Json<BoardAccessView>, AppError> {
@@ -203,7 +556,7 @@ pub async fn board_access(
         Some(_) => {
             !holds_key(
                 &state,
-                &jar,
+                &owner,
                 &board_id,
                 access_token_from(&headers),
                 &access.access_token,
@@ -227,10 +580,14 @@ pub struct UnlockRequest {
 
 /// Takes the password of a board and gives back the key to it.
 ///
-/// The Argon2 check is the only guard on how fast this route can be tried, and it is a good one:
-/// each attempt costs the server the same tens of milliseconds it costs the caller.
+/// Each try is counted against the address it came from and against the board, and past a few
+/// wrong ones the caller waits, longer after each, before the next one is checked. A try that has
+/// to wait is turned away with 429 and a `Retry-After` before Argon2 runs, so a guesser burns no
+/// more of the server than the waits allow. Each wrong try goes out to the board, for the
+/// facilitator to see.
 pub async fn unlock_board(
     State(state): State<AppState>,
+    ClientAddr(address): ClientAddr,
     Path(board_id): Path<String>,
     Json(req): Json<UnlockRequest>,
 ) -> Result<Json<UnlockResponse>, AppError> {
@@ -244,11 +601,37 @@ pub async fn unlock_board(
         ));
     };
 
+    let wait_until = match db::begin_unlock_attempt(&state.db, &board_id, &address).await? {
+        UnlockAttempt::Counted { wait_until } => wait_until,
+        UnlockAttempt::Refused { until } => {
+            let seconds = seconds_until(until);
+            return Err(AppError::TooManyRequests(
+                format!("Too many wrong passwords. Try again in {seconds} seconds"),
+                seconds,
+            ));
+        }
+    };
+
     // Trimmed the same way it was trimmed when it was set, so that a paste with a space at the
     // end opens the board it was meant to open.
     let password = req.password.trim().to_string();
     if password.is_empty() || !password::verify(password, hash).await? {
-        return Err(AppError::Unauthorized("The password is wrong".to_string()));
+        warn!(board_id, address, "wrong board password");
+        crate::routes::ws::broadcast_settings(&state, &board_id).await;
+        return Err(match wait_until {
+            Some(until) => {
+                let seconds = seconds_until(until);
+                AppError::TooManyRequests(
+                    format!("The password is wrong. Try again in {seconds} seconds"),
+                    seconds,
+                )
+            }
+            None => AppError::Unauthorized("The password is wrong".to_string()),
+        });
+    }
+
+    if let Err(e) = db::take_back_unlock_attempt(&state.db, &board_id, &address).await {
+        warn!("Failed to take a right password off the tally: {e}");
     }
 
     Ok(Json(UnlockResponse {
@@ -256,6 +639,13 @@ pub async fn unlock_board(
     }))
 }
 
+/// The whole seconds until `until`, rounded up, so that a caller who waits them is not turned away
+/// again.
+fn seconds_until(until: DateTime<Utc>) -> u64 {
+    let millis = (until - Utc::now()).num_milliseconds().max(0) as u64;
+    millis.div_ceil(1000).max(1)
+}
+
 #[derive(Debug, Deserialize)]
 pub struct SetPasswordRequest {
     /// The new word. Absent, or empty, takes the lock off the board.
@@ -273,11 +663,12 @@ pub struct SetPasswordRequest {
 /// reader out and not to throw the room out.
 pub async fn set_password(
     State(state): State<AppState>,
-    jar: CookieJar,
+    owner: Owner,
     Path(board_id): Path<String>,
     Json(req): Json<SetPasswordRequest>,
 ) -> Result<Json<PasswordResponse>, AppError> {
-    authorize_facilitator(&state, &jar, &board_id, &req.auth).await?;
+    let participant = authorize_facilitator(&state, &owner, &board_id, &req.auth).await?;
+    refuse_if_archived(&state, &board_id).await?;
 
     let password = read_password(req.password.as_deref()).map_err(AppError::BadRequest)?;
     let hash = match password {
@@ -290,8 +681,20 @@ pub async fn set_password(
         return Err(AppError::NotFound("Board not found".to_string()));
     }
 
+    // The log says that the password changed, and never what it is.
+    let detail = if hash.is_some() { "set" } else { "removed" };
+    audit::record(
+        &state,
+        Some(&board_id),
+        &Actor::owner(&owner, participant.as_deref()),
+        AuditAction::BoardPassword,
+        None,
+        Some(detail),
+    )
+    .await;
+
     // The board now says whether it is locked, so the open clients have to hear it.
-    crate::routes::ws::broadcast_board_state(&state, &board_id).await;
+    crate::routes::ws::broadcast_settings(&state, &board_id).await;
 
     Ok(Json(PasswordResponse {
         has_password: hash.is_some(),
@@ -312,19 +715,30 @@ pub struct SetTitleRequest {
 /// the same rule the labels follow, not the facilitator-only rule of the password.
 pub async fn set_title(
     State(state): State<AppState>,
-    jar: CookieJar,
+    owner: Owner,
     Path(board_id): Path<String>,
     Json(req): Json<SetTitleRequest>,
 ) -> Result<Json<String>, AppError> {
-    authorize(&state, &jar, &board_id, &req.auth).await?;
+    let participant =
+        authorize(&state, &owner, &board_id, &req.auth, TokenScope::BoardWrite).await?;
+    refuse_if_archived(&state, &board_id).await?;
 
     let title = read_title(&req.title).map_err(AppError::BadRequest)?;
     if !db::set_board_title(&state.db, &board_id, &title).await? {
         return Err(AppError::NotFound("Board not found".to_string()));
     }
+    audit::record(
+        &state,
+        Some(&board_id),
+        &Actor::owner(&owner, participant.as_deref()),
+        AuditAction::BoardTitle,
+        None,
+        Some(&title),
+    )
+    .await;
 
     // The board carries a new name, so the open clients have to hear it.
-    crate::routes::ws::broadcast_board_state(&state, &board_id).await;
+    crate::routes::ws::broadcast_settings(&state, &board_id).await;
 
     Ok(Json(title))
 }
@@ -364,13 +778,44 @@ pub async fn list_action_sources(
     Ok(Json(boards))
 }
 
-/// The caller states who they are. The WebSocket handler trusts the same values.
+/// The caller states who they are. A participant id counts only with the credential the socket
+/// gave out with it, as it does when the socket is joined again.
 #[derive(Debug, Deserialize)]
 pub struct BoardAuth {
     #[serde(default)]
     pub facilitator_token: Option<String>,
     #[serde(default)]
     pub participant_id: Option<String>,
+    #[serde(default)]
+    pub participant_token: Option<String>,
+}
+
+impl BoardAuth {
+    /// The id the caller goes by on the board, if their credential says who they are. On an
+    /// anonymous board that is the pseudonym of their id; see `credentials::author_id`.
+    async fn participant(
+        &self,
+        state: &AppState,
+        board_id: &str,
+    ) -> Result<Option<String>, AppError> {
+        let Some(participant_id) = credentials::verified(
+            state,
+            board_id,
+            self.participant_id.as_deref(),
+            self.participant_token.as_deref(),
+        ) else {
+            return Ok(None);
+        };
+        let anonymous = db::get_board_anonymous(&state.db, board_id)
+            .await?
+            .unwrap_or(false);
+        Ok(Some(credentials::author_id(
+            &state.participant_key,
+            board_id,
+            participant_id,
+            anonymous,
+        )))
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -393,7 +838,7 @@ pub struct ImportActionsRequest {
 /// Copies cards from a column of another board into a column of this one.
 pub async fn import_actions(
     State(state): State<AppState>,
-    jar: CookieJar,
+    owner: Owner,
     Path(board_id): Path<String>,
     Json(req): Json<ImportActionsRequest>,
 ) -> Result<Json<ImportResult>, AppError> {
@@ -403,16 +848,19 @@ pub async fn import_actions(
         ));
     }
 
-    authorize(&state, &jar, &board_id, &req.auth).await?;
+    let participant =
+        authorize(&state, &owner, &board_id, &req.auth, TokenScope::ActionImport).await?;
+    refuse_if_archived(&state, &board_id).await?;
 
     // The gate of the source board stands in the way of the copy as well. The actions of a locked
     // board would otherwise reach a board that anyone can open, which takes the lock off them.
     // Being the facilitator of the target board says nothing about the source.
     authorize_read(
         &state,
-        &jar,
+        &owner,
         &req.source_board_id,
         req.source_access_token.as_deref(),
+        TokenScope::ActionImport,
     )
     .await
     .map_err(|e| match e {
@@ -444,12 +892,101 @@ pub async fn import_actions(
             ))
         }
     };
+    let target = format!("board:{}", req.source_board_id);
+    let detail = format!("{} copied, {} skipped", result.imported, result.skipped);
+    audit::record(
+        &state,
+        Some(&board_id),
+        &Actor::owner(&owner, participant.as_deref()),
+        AuditAction::ActionsImport,
+        Some(&target),
+        Some(&detail),
+    )
+    .await;
 
     crate::routes::ws::broadcast_board_state(&state, &board_id).await;
 
     Ok(Json(result))
 }
 
+// --- Action tracker ---
+
+#[derive(Debug, Deserialize)]
+pub struct ActionTrackerQuery {
+    /// Labels that the board of an action must carry, separated by commas. An empty value
+    /// matches every board.
+    #[serde(default)]
+    pub labels: String,
+    /// `open` or `done`. Absent means both.
+    #[serde(default)]
+    pub status: Option<String>,
+    /// A team, by id: only the actions one of its members owns.
+    #[serde(default)]
+    pub team: Option<String>,
+    /// How many actions at most; the newest are the ones kept. 100 when absent, 500 at most.
+    #[serde(default)]
+    pub limit: Option<i64>,
+}
+
+/// Lists the actions of every board in one place, each action once however many retros it was
+/// carried through. An action stands where the team last looked at it: its labels, its owner and
+/// its done mark are those of the newest board that holds it.
+///
+/// The boards are read as `authorize_read` would let anyone read them: a board with a password
+/// stays out, whoever asks. A token needs the `board:read` scope.
+pub async fn list_actions(
+    State(state): State<AppState>,
+    owner: Owner,
+    Query(query): Query<ActionTrackerQuery>,
+) -> Result<Json<Vec<TrackedAction>>, AppError> {
+    owner.require(TokenScope::BoardRead)?;
+    let labels: Vec<String> = query
+        .labels
+        .split(',')
+        .map(|l| l.trim().to_lowercase())
+        .filter(|l| !l.is_empty())
+        .collect();
+    let status = match query.status.as_deref().map(str::trim) {
+        None | Some("") => None,
+        Some(raw) => Some(ActionStatus::parse(raw).ok_or_else(|| {
+            AppError::BadRequest("The status is open or done".to_string())
+        })?),
+    };
+    let team = query.team.as_deref().map(str::trim).filter(|t| !t.is_empty());
+
+    let limit = query.limit.unwrap_or(100).clamp(1, 500);
+
+    // The cards of one line make one action, and a line can fail once its newest card judges it,
+    // so the newest `limit` cards can make fewer than `limit` actions. More are read until there
+    // are enough, or no card is left.
+    let done = status.map(|s| s == ActionStatus::Done);
+    let mut fetch = limit;
+    let mut actions = loop {
+        let (cards, more) = db::list_action_cards(&state.db, &labels, done, team, fetch).await?;
+        let actions: Vec<TrackedAction> = trace_actions(cards, Utc::now().date_naive())
+            .into_iter()
+            .filter(|a| labels.is_empty() || a.labels.iter().any(|l| labels.contains(l)))
+            .filter(|a| status.is_none_or(|s| s.matches(a)))
+            .filter(|a| {
+                team.is_none_or(|team| {
+                    matches!(
+                        &a.assignee,
+                        Some(Assignee::TeamMember { team_id, .. }) if team_id == team
+                    )
+                })
+            })
+            .collect();
+        if actions.len() >= limit as usize || !more {
+            break actions;
+        }
+        fetch *= 2;
+    };
+    // The actions come oldest first; the newest are the ones kept.
+    let oldest = actions.len().saturating_sub(limit as usize);
+    actions.drain(..oldest);
+    Ok(Json(actions))
+}
+
 // --- Labels ---
 
 pub async fn list_labels(
@@ -469,27 +1006,94 @@ pub struct SetLabelsRequest {
 /// Replaces the labels of a board.
 pub async fn set_labels(
     State(state): State<AppState>,
-    jar: CookieJar,
+    owner: Owner,
     Path(board_id): Path<String>,
     Json(req): Json<SetLabelsRequest>,
 ) -> Result<Json<Vec<String>>, AppError> {
-    authorize(&state, &jar, &board_id, &req.auth).await?;
+    let participant =
+        authorize(&state, &owner, &board_id, &req.auth, TokenScope::BoardWrite).await?;
+    refuse_if_archived(&state, &board_id).await?;
 
     let labels = normalize_labels(&req.labels);
     db::set_board_labels(&state.db, &board_id, &labels).await?;
+    audit::record(
+        &state,
+        Some(&board_id),
+        &Actor::owner(&owner, participant.as_deref()),
+        AuditAction::BoardLabels,
+        None,
+        Some(&labels.join(", ")),
+    )
+    .await;
 
-    crate::routes::ws::broadcast_board_state(&state, &board_id).await;
+    crate::routes::ws::broadcast_settings(&state, &board_id).await;
 
     Ok(Json(labels))
 }
 
-/// Lets through the facilitator and the editors of the board.
+#[derive(Debug, Deserialize)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    pub events: Vec<String>,
+    #[serde(flatten)]
+    pub auth: BoardAuth,
+}
+
+/// The webhooks of a board. The secrets stay out: each one is shown once, when it is made.
+pub async fn list_webhooks(
+    State(state): State<AppState>,
+    owner: Owner,
+    Path(board_id): Path<String>,
+    Query(auth): Query<BoardAuth>,
+) -> Result<Json<Vec<WebhookView>>, AppError> {
+    authorize_facilitator(&state, &owner, &board_id, &auth).await?;
+    Ok(Json(db::list_webhooks(&state.db, Some(&board_id)).await?))
+}
+
+pub async fn create_webhook(
+    State(state): State<AppState>,
+    owner: Owner,
+    Path(board_id): Path<String>,
+    Json(req): Json<CreateWebhookRequest>,
+) -> Result<Json<CreatedWebhook>, AppError> {
+    authorize_facilitator(&state, &owner, &board_id, &req.auth).await?;
+    refuse_if_archived(&state, &board_id).await?;
+    let created = webhooks::create(&state, Some(&board_id), &req.url, &req.events).await?;
+    Ok(Json(created))
+}
+
+pub async fn delete_webhook(
+    State(state): State<AppState>,
+    owner: Owner,
+    Path((board_id, webhook_id)): Path<(String, String)>,
+    Query(auth): Query<BoardAuth>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    authorize_facilitator(&state, &owner, &board_id, &auth).await?;
+    refuse_if_archived(&state, &board_id).await?;
+    if !db::delete_webhook(&state.db, &webhook_id, Some(&board_id)).await? {
+        return Err(AppError::NotFound("Webhook not found".to_string()));
+    }
+    Ok(Json(serde_json::json!({"ok": true})))
+}
+
+/// Turns away a change to a closed board. The facilitator reopens it over the socket first.
+async fn refuse_if_archived(state: &AppState, board_id: &str) -> Result<(), AppError> {
+    if db::is_archived(&state.db, board_id).await? {
+        return Err(AppError::Forbidden("This board is closed".to_string()));
+    }
+    Ok(())
+}
+
+/// Lets through the facilitator and the editors of the board, and a token of theirs that has the
+/// scope. Gives the id the caller goes by on the board, when they sent one, for the audit log.
 async fn authorize(
     state: &AppState,
-    jar: &CookieJar,
+    owner: &Owner,
     board_id: &str,
     auth: &BoardAuth,
-) -> Result<(), AppError> {
+    scope: TokenScope,
+) -> Result<Option<String>, AppError> {
+    owner.require(scope)?;
     if db::get_board_facilitator_token(&state.db, board_id)
         .await?
         .is_none()
@@ -497,18 +1101,19 @@ async fn authorize(
         return Err(AppError::NotFound("Board not found".to_string()));
     }
 
-    let facilitator_id_cookie = jar.get("facilitator_id").map(|c| c.value().to_string());
+    let participant = auth.participant(state, board_id).await?;
     let privileged = db::is_board_privileged(
         &state.db,
         board_id,
         auth.facilitator_token.as_deref(),
-        facilitator_id_cookie.as_deref(),
-        auth.participant_id.as_deref(),
+        owner.facilitator_id.as_deref(),
+        owner.sub.as_deref(),
+        participant.as_deref(),
     )
     .await?;
 
     if privileged {
-        Ok(())
+        Ok(participant)
     } else {
         Err(AppError::Forbidden(
             "Only the facilitator and the editors can do this".to_string(),
@@ -518,13 +1123,14 @@ async fn authorize(
 
 /// Lets through the facilitator alone — the token they were given, or the cookie of the person who
 /// made the board. An editor may write on a board; the lock on it belongs to whoever called the
-/// meeting.
+/// meeting. Gives the id the caller goes by on the board, as `authorize` does.
 async fn authorize_facilitator(
     state: &AppState,
-    jar: &CookieJar,
+    owner: &Owner,
     board_id: &str,
     auth: &BoardAuth,
-) -> Result<(), AppError> {
+) -> Result<Option<String>, AppError> {
+    owner.require(TokenScope::BoardWrite)?;
     if db::get_board_facilitator_token(&state.db, board_id)
         .await?
         .is_none()
@@ -532,18 +1138,18 @@ async fn authorize_facilitator(
         return Err(AppError::NotFound("Board not found".to_string()));
     }
 
-    let facilitator_id_cookie = jar.get("facilitator_id").map(|c| c.value().to_string());
     let is_facilitator = db::is_board_privileged(
         &state.db,
         board_id,
         auth.facilitator_token.as_deref(),
-        facilitator_id_cookie.as_deref(),
+        owner.facilitator_id.as_deref(),
+        owner.sub.as_deref(),
         None,
     )
     .await?;
 
     if is_facilitator {
-        Ok(())
+        auth.participant(state, board_id).await
     } else {
         Err(AppError::Forbidden(
             "Only the facilitator can do this".to_string(),
@@ -553,13 +1159,15 @@ async fn authorize_facilitator(
 
 /// Lets a reader see the whole of a board. An open board is open to anyone who holds the link; a
 /// locked one opens to the key it gave out, and to the facilitator and the editors, who were let
-/// in already.
+/// in already. A token needs the scope even for an open board.
 async fn authorize_read(
     state: &AppState,
-    jar: &CookieJar,
+    owner: &Owner,
     board_id: &str,
     access_token: Option<&str>,
+    scope: TokenScope,
 ) -> Result<(), AppError> {
+    owner.require(scope)?;
     let Some(access) = db::get_board_access(&state.db, board_id).await? else {
         return Err(AppError::NotFound("Board not found".to_string()));
     };
@@ -568,7 +1176,7 @@ async fn authorize_read(
         return Ok(());
     }
 
-    if holds_key(state, jar, board_id, access_token, &access.access_token).await? {
+    if holds_key(state, owner, board_id, access_token, &access.access_token).await? {
         Ok(())
     } else {
         Err(AppError::Unauthorized(
@@ -580,7 +1188,7 @@ async fn authorize_read(
 /// Whether the caller holds the key of a locked board, or stands above the gate.
 async fn holds_key(
     state: &AppState,
-    jar: &CookieJar,
+    owner: &Owner,
     board_id: &str,
     presented: Option<&str>,
     board_token: &str,
@@ -589,6 +1197,139 @@ async fn holds_key(
         return Ok(true);
     }
 
-    let facilitator_id_cookie = jar.get("facilitator_id").map(|c| c.value());
-    Ok(db::is_board_privileged(&state.db, board_id, None, facilitator_id_cookie, None).await?)
+    Ok(db::is_board_privileged(
+        &state.db,
+        board_id,
+        None,
+        owner.facilitator_id.as_deref(),
+        owner.sub.as_deref(),
+        None,
+    )
+    .await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::tests::{test_board, test_pool};
+    use crate::models::{ROLE_ACTIONS, ROLE_PREVIOUS_ACTIONS, UNLOCK_FREE_TRIES_PER_ADDRESS};
+
+    async fn unlock(state: &AppState, board_id: &str, password: &str) -> Result<String, AppError> {
+        unlock_board(
+            State(state.clone()),
+            ClientAddr("203.0.113.7".to_string()),
+            Path(board_id.to_string()),
+            Json(UnlockRequest {
+                password: password.to_string(),
+            }),
+        )
+        .await
+        .map(|Json(answer)| answer.access_token)
+    }
+
+    #[tokio::test]
+    async fn the_right_password_waits_out_the_lockout_of_its_address() {
+        let Some(pool) = test_pool().await else {
+            return;
+        };
+        let state = AppState::for_tests(pool.clone());
+        let board_id = test_board(&pool, &nanoid!(), None).await;
+        let Ok(hash) = password::hash("open sesame".to_string()).await else {
+            panic!("could not hash the password");
+        };
+        db::set_board_password(&pool, &board_id, Some(&hash), "key").await.unwrap();
+
+        for _ in 1..UNLOCK_FREE_TRIES_PER_ADDRESS {
+            assert!(matches!(
+                unlock(&state, &board_id, "wrong").await,
+                Err(AppError::Unauthorized(_))
+            ));
+        }
+        let Err(AppError::TooManyRequests(_, retry_after)) =
+            unlock(&state, &board_id, "wrong").await
+        else {
+            panic!("the last free try should start the wait");
+        };
+
+        // The right password is not even checked while the wait holds.
+        let Err(AppError::TooManyRequests(_, still)) =
+            unlock(&state, &board_id, "open sesame").await
+        else {
+            panic!("the right password should wait as well");
+        };
+        assert!(still <= retry_after && still > 0);
+
+        // Once the time Retry-After named has passed, it opens the board.
+        sqlx::query(
+            "UPDATE unlock_failures SET locked_until = now() - interval '1 second' \
+             WHERE board_id = $1 AND locked_until IS NOT NULL",
+        )
+        .bind(&board_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        assert_eq!(unlock(&state, &board_id, "open sesame").await.ok().as_deref(), Some("key"));
+    }
+
+    /// An action column on `board_id`, and cards in it with the given words, oldest first. A card
+    /// with a board beside its words was carried from that board.
+    async fn action_cards(
+        pool: &sqlx::PgPool,
+        board_id: &str,
+        role: &str,
+        cards: &[(&str, Option<&str>)],
+    ) {
+        let column_id = nanoid!(8);
+        sqlx::query(
+            "INSERT INTO columns (id, board_id, name, position, role) \
+             VALUES ($1, $2, 'Actions', 1, $3)",
+        )
+        .bind(&column_id)
+        .bind(board_id)
+        .bind(role)
+        .execute(pool)
+        .await
+        .unwrap();
+        for (words, carried_from) in cards {
+            sqlx::query(
+                "INSERT INTO tickets (id, column_id, content, author_id, author_name, created_at, \
+                 carried_from_board_id) VALUES ($1, $2, $3, 'p1', 'P', clock_timestamp(), $4)",
+            )
+            .bind(nanoid!(8))
+            .bind(&column_id)
+            .bind(words)
+            .bind(carried_from)
+            .execute(pool)
+            .await
+            .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn the_limit_counts_actions_not_the_cards_they_were_carried_through() {
+        let Some(pool) = test_pool().await else {
+            return;
+        };
+        let state = AppState::for_tests(pool.clone());
+        let label = format!("test-{}", nanoid!(8).to_lowercase());
+        let first = test_board(&pool, &nanoid!(), None).await;
+        let second = test_board(&pool, &nanoid!(), None).await;
+        for board in [&first, &second] {
+            db::set_board_labels(&pool, board, std::slice::from_ref(&label)).await.unwrap();
+        }
+        action_cards(&pool, &first, ROLE_ACTIONS, &[("Write the docs", None), ("Ship it", None)])
+            .await;
+        // The two newest cards are one action, carried from the first retro into the second.
+        action_cards(&pool, &second, ROLE_PREVIOUS_ACTIONS, &[("Ship it", Some(&first))]).await;
+
+        let owner = Owner { facilitator_id: None, sub: None, scopes: None, token_id: None };
+        let query = ActionTrackerQuery { labels: label, status: None, team: None, limit: Some(2) };
+        let Ok(Json(actions)) = list_actions(State(state), owner, Query(query)).await else {
+            panic!("the actions should list");
+        };
+        let words: Vec<&str> = actions.iter().map(|a| a.content.as_str()).collect();
+        assert_eq!(words, vec!["Write the docs", "Ship it"]);
+        assert_eq!(actions[1].board_id, second);
+        assert_eq!(actions[1].carry_count, 1);
+    }
 }
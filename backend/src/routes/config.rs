@@ -25,7 +25,7 @@ pub async fn get_config(
         // Read at compile time from `backend/Cargo.toml`, which the release bumps.
         version: env!("CARGO_PKG_VERSION").to_string(),
         auth: AuthConfig {
-            enabled: state.entra.is_some(),
+            enabled: state.oidc.is_some(),
             user: identity.map(|Extension(identity)| identity),
         },
     })
@@ -41,11 +41,11 @@ pub struct Health {
 
 /// Says the server is up, to anyone, signed in or not.
 ///
-/// The Kubernetes probes read this rather than a real route: behind an Entra deployment every other
+/// The Kubernetes probes read this rather than a real route: behind a sign-in every other
 /// route answers 401, and a probe that reads a 401 as "unhealthy" would restart the pod for ever.
 pub async fn health(State(state): State<AppState>) -> Json<Health> {
     Json(Health {
         status: "ok",
-        auth_required: state.entra.is_some(),
+        auth_required: state.oidc.is_some(),
     })
 }
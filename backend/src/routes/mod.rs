@@ -1,4 +1,5 @@
 pub mod admin;
 pub mod boards;
 pub mod config;
+pub mod tokens;
 pub mod ws;
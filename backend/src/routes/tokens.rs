@@ -0,0 +1,87 @@
+use axum::extract::{Path, State};
+use axum::Json;
+use axum_extra::extract::CookieJar;
+
+use crate::auth::Owner;
+use crate::db;
+use crate::error::AppError;
+use crate::models::{ApiTokenView, CreateApiTokenRequest, CreatedApiToken, TokenScope};
+use crate::routes::admin::AdminAuth;
+use crate::routes::boards::{facilitator_id_or_new, with_facilitator_cookie};
+use crate::state::AppState;
+use crate::tokens;
+
+/// Turns away a request that comes with a token: a token makes no tokens and revokes none, so one
+/// that leaks cannot be turned into another that outlives it.
+fn refuse_token(owner: &Owner) -> Result<(), AppError> {
+    if owner.scopes.is_some() {
+        return Err(AppError::Forbidden(
+            "API tokens are managed from the browser".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// The caller's tokens. The secrets stay out: each one is shown once, when it is made.
+pub async fn list_tokens(
+    State(state): State<AppState>,
+    owner: Owner,
+) -> Result<Json<Vec<ApiTokenView>>, AppError> {
+    refuse_token(&owner)?;
+    if owner.facilitator_id.is_none() && owner.sub.is_none() {
+        return Ok(Json(Vec::new()));
+    }
+    Ok(Json(
+        db::list_api_tokens(
+            &state.db,
+            owner.facilitator_id.as_deref(),
+            owner.sub.as_deref(),
+        )
+        .await?,
+    ))
+}
+
+/// Makes a token that acts for the caller.
+///
+/// A browser that made no board yet is given its facilitator cookie here, as `create_board` would
+/// give it, so that the token has someone to act for. `admin:read` is for an admin to give: a
+/// member of an admin group, or the holder of the admin token.
+pub async fn create_token(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    mut owner: Owner,
+    admin: Result<AdminAuth, AppError>,
+    Json(req): Json<CreateApiTokenRequest>,
+) -> Result<(CookieJar, Json<CreatedApiToken>), AppError> {
+    refuse_token(&owner)?;
+    if req.scopes.contains(&TokenScope::AdminRead) && admin.is_err() {
+        return Err(AppError::Forbidden(
+            "Only an admin can give a token the admin:read scope".to_string(),
+        ));
+    }
+
+    let facilitator_id = facilitator_id_or_new(&owner);
+    owner.facilitator_id = Some(facilitator_id.clone());
+    let created = tokens::mint(&state, &owner, &req.name, &req.scopes).await?;
+    Ok((with_facilitator_cookie(jar, facilitator_id), Json(created)))
+}
+
+/// Revokes one of the caller's tokens. A script that used it is refused from its next request.
+pub async fn delete_token(
+    State(state): State<AppState>,
+    owner: Owner,
+    Path(token_id): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    refuse_token(&owner)?;
+    if !db::delete_api_token(
+        &state.db,
+        &token_id,
+        owner.facilitator_id.as_deref(),
+        owner.sub.as_deref(),
+    )
+    .await?
+    {
+        return Err(AppError::NotFound("Token not found".to_string()));
+    }
+    Ok(Json(serde_json::json!({"ok": true})))
+}
@@ -1,57 +1,293 @@
 use axum::extract::ws::{Message, WebSocket};
 use axum::extract::{Path, State, WebSocketUpgrade};
 use axum::response::Response;
-use axum_extra::extract::CookieJar;
 use futures_util::{SinkExt, StreamExt};
 use nanoid::nanoid;
+use std::time::Instant;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::mpsc;
 use tracing::{info, warn};
 
+use crate::audit::{self, Actor, Touch};
+use crate::auth::Owner;
+use crate::credentials;
 use crate::db;
+use crate::error::AppError;
+use crate::fanout::Update;
 use crate::models::{
-    sanitize_gif, valid_rock_status, Participant, MAX_COMMENT_LENGTH,
-    MAX_SCORECARD_FIELD_LENGTH, TEMPLATE_LEVEL10,
+    read_assignee_name, read_column_name, sanitize_gif, valid_rock_status, Assignee, AuditAction,
+    BoardPatch,
+    BoardView, Changes, OperationKind, Redaction, Participant, Phase, Snapshot, Ticket, TimerAction,
+    MAX_COMMENT_LENGTH, MAX_SCORECARD_FIELD_LENGTH, MAX_TICKET_LENGTH, TEMPLATE_LEVEL10,
 };
-use crate::protocol::{ClientMessage, ServerMessage};
-use crate::state::AppState;
+use crate::protocol::{AssigneeChoice, ClientMessage, ServerMessage};
+use crate::metrics::METRICS;
+use crate::state::{send_local, AppState};
+use crate::throttle::{RateClass, Throttle, Verdict, MAX_MESSAGE_BYTES};
+use crate::webhooks::{self, WebhookEvent};
 use chrono::Utc;
 
 pub async fn ws_handler(
-    jar: CookieJar,
+    owner: Owner,
     ws: WebSocketUpgrade,
     Path(board_id): Path<String>,
     State(state): State<AppState>,
-) -> Response {
-    let facilitator_id_from_cookie = jar
-        .get("facilitator_id")
-        .map(|c| c.value().to_string());
-    ws.on_upgrade(move |socket| {
-        handle_socket(socket, board_id, state, facilitator_id_from_cookie)
-    })
+) -> Result<Response, AppError> {
+    refuse_token(&owner)?;
+    // A message past the limit closes the socket. No client of ours sends one.
+    Ok(ws
+        .max_message_size(MAX_MESSAGE_BYTES)
+        .max_frame_size(MAX_MESSAGE_BYTES)
+        .on_upgrade(move |socket| handle_socket(socket, board_id, state, owner)))
 }
 
-/// Sends the full board state to every client of the board. The REST handlers that change a board
-/// call this too, so that all views stay in step.
-pub async fn broadcast_board_state(state: &AppState, board_id: &str) {
-    let board = match db::get_board(&state.db, board_id).await {
-        Ok(Some(b)) => b,
-        _ => return,
-    };
+/// Turns away a socket opened with an API token. The socket knows the facilitator by the cookie
+/// or the account of the caller, and a token stands in for both, whatever its scopes: its holder
+/// would run the board with a token made to read it. A token reads and writes a board over the
+/// REST API instead, where each route checks the scope it needs.
+fn refuse_token(owner: &Owner) -> Result<(), AppError> {
+    if owner.scopes.is_some() {
+        return Err(AppError::Forbidden(
+            "An API token cannot join a board over the socket".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// What a message changed, and so what the other clients of the board have to hear.
+pub enum Change {
+    /// Enough of the board changed that only the whole of it will do.
+    Board,
+    /// One part changed, and the patch says which.
+    Patch(Box<BoardPatch>),
+}
+
+/// Reads the board as a client sees it, before anything is taken out for one reader.
+async fn board_view(state: &AppState, board_id: &str) -> Option<BoardView> {
+    let board = db::get_board(&state.db, board_id).await.ok()??;
     let count = state.participant_count(board_id).await;
     let editors = db::get_board_editors(&state.db, board_id).await.unwrap_or_default();
     let editor_requests = db::get_editor_requests(&state.db, board_id).await.unwrap_or_default();
-    let view = board.to_view_with_participants(count, editors, editor_requests);
-    let tx = state.get_or_create_channel(board_id).await;
-    let _ = tx.send(ServerMessage::BoardState { board: view });
+    Some(board.to_view_with_participants(count, editors, editor_requests))
+}
+
+/// The board as it now stands, for a socket that fell `skipped` messages behind its channel. The
+/// receiver skips the rest of what is waiting and reads on from here. None when the board is
+/// gone.
+async fn catch_up(
+    state: &AppState,
+    board_id: &str,
+    participant_id: &str,
+    rx: &mut broadcast::Receiver<ServerMessage>,
+    skipped: u64,
+) -> Option<ServerMessage> {
+    METRICS.broadcast_lagged.inc_by(skipped);
+    warn!(
+        participant_id,
+        board_id,
+        skipped,
+        "socket fell behind the board; sending the whole board again"
+    );
+    *rx = rx.resubscribe();
+    let view = board_view(state, board_id).await?;
+    Some(ServerMessage::BoardState { board: Box::new(view) })
+}
+
+/// One reader at the end of a socket, and what of the board they may not read yet.
+///
+/// The channel carries the whole board, so each socket takes out what its own reader may not
+/// read before a message goes down the wire. A patch is redacted the way the last state was;
+/// until the reader has had a state at all, a patch has nothing to lay itself on.
+struct Reader {
+    participant_id: String,
+    is_facilitator: bool,
+    redaction: Option<Redaction>,
+}
+
+impl Reader {
+    fn new(participant_id: String, is_facilitator: bool) -> Self {
+        Self { participant_id, is_facilitator, redaction: None }
+    }
+
+    /// The message as this reader may read it. None for a patch that came before any state.
+    fn outgoing(&mut self, msg: ServerMessage) -> Option<ServerMessage> {
+        Some(match msg {
+            ServerMessage::BoardState { mut board } => {
+                self.redaction = Some(board.redaction_for(&self.participant_id, self.is_facilitator));
+                board.redact_hidden_for(&self.participant_id, self.is_facilitator);
+                ServerMessage::BoardState { board }
+            }
+            ServerMessage::BoardPatch { revision, mut patch } => {
+                patch.redact_for(self.redaction.as_ref()?, &self.participant_id);
+                ServerMessage::BoardPatch { revision, patch }
+            }
+            other => other,
+        })
+    }
+}
+
+/// Sends the full board state to every client of the board, on this replica and on the others.
+/// The REST handlers that change a board call this too, so that all views stay in step.
+///
+/// The state takes a revision of its own, so that a patch read before it can never be laid on
+/// top of it.
+pub async fn broadcast_board_state(state: &AppState, board_id: &str) {
+    let _timer = METRICS
+        .broadcast_seconds
+        .with_label_values(&["state"])
+        .start_timer();
+    if let Err(e) = db::next_revision(&state.db, board_id, true).await {
+        warn!("Failed to take the next revision: {e}");
+    }
+    send_board_state_here(state, board_id).await;
+    state.fanout.publish(&state.db, board_id, Update::State).await;
+}
+
+/// Tells every client of the board that its timer ran out.
+pub async fn broadcast_timer_expired(
+    state: &AppState,
+    board_id: &str,
+    action: Option<TimerAction>,
+) {
+    if let Some(tx) = state.local_channel(board_id).await {
+        send_local(&tx, ServerMessage::TimerExpired { action });
+    }
+    state
+        .fanout
+        .publish(&state.db, board_id, Update::TimerExpired { action })
+        .await;
+}
+
+/// Sends the full board state to the clients of the board on this replica only.
+pub async fn send_board_state_here(state: &AppState, board_id: &str) {
+    let Some(tx) = state.local_channel(board_id).await else {
+        return;
+    };
+    let Some(view) = board_view(state, board_id).await else {
+        return;
+    };
+    send_local(&tx, ServerMessage::BoardState { board: Box::new(view) });
+}
+
+/// Sends one change to every client of the board, under the next revision of the board.
+pub async fn broadcast_patch(state: &AppState, board_id: &str, patch: BoardPatch) {
+    let _timer = METRICS
+        .broadcast_seconds
+        .with_label_values(&["patch"])
+        .start_timer();
+    let touches = !matches!(patch, BoardPatch::ParticipantCount { .. });
+    let revision = match db::next_revision(&state.db, board_id, touches).await {
+        Ok(Some(revision)) => revision,
+        Ok(None) => return,
+        Err(e) => {
+            warn!("Failed to take the next revision: {e}");
+            return;
+        }
+    };
+    if let Some(tx) = state.local_channel(board_id).await {
+        send_local(
+            &tx,
+            ServerMessage::BoardPatch {
+                revision,
+                patch: Box::new(patch.clone()),
+            },
+        );
+    }
+    state
+        .fanout
+        .publish(
+            &state.db,
+            board_id,
+            Update::Patch {
+                revision,
+                patch: Box::new(patch),
+            },
+        )
+        .await;
+}
+
+/// Sends the settings of a board as they now stand. The REST routes that rename a board, label it
+/// or lock it call this.
+pub async fn broadcast_settings(state: &AppState, board_id: &str) {
+    let change = settings_change(state, board_id).await;
+    broadcast_change(state, board_id, change).await;
+}
+
+async fn broadcast_change(state: &AppState, board_id: &str, change: Change) {
+    match change {
+        Change::Board => broadcast_board_state(state, board_id).await,
+        Change::Patch(patch) => broadcast_patch(state, board_id, *patch).await,
+    }
+}
+
+fn comment_changed(column_id: String, ticket: Ticket) -> BoardPatch {
+    BoardPatch::CommentChanged { column_id, ticket }
+}
+
+/// The patch for a card that changed, read back as it now stands. A card that cannot be read
+/// falls back to the whole board, which is never wrong, only bigger.
+async fn ticket_change(
+    state: &AppState,
+    ticket_id: &str,
+    patch: fn(String, Ticket) -> BoardPatch,
+) -> Change {
+    match db::get_ticket(&state.db, ticket_id).await {
+        Ok(Some((column_id, ticket))) => Change::Patch(Box::new(patch(column_id, ticket))),
+        Ok(None) => Change::Board,
+        Err(e) => {
+            warn!("Failed to read back the card: {e}");
+            Change::Board
+        }
+    }
+}
+
+/// Tells the webhooks about the card a change carries.
+async fn emit_card_event(state: &AppState, board_id: &str, event: WebhookEvent, change: &Change) {
+    let Change::Patch(patch) = change else {
+        return;
+    };
+    if let BoardPatch::TicketAdded { ticket, .. } | BoardPatch::TicketEdited { ticket, .. } =
+        patch.as_ref()
+    {
+        let card = (ticket.id.as_str(), ticket.content.as_str());
+        webhooks::emit(state, board_id, event, Some(card)).await;
+    }
+}
+
+async fn settings_change(state: &AppState, board_id: &str) -> Change {
+    match db::get_board_settings(&state.db, board_id).await {
+        Ok(Some(settings)) => {
+            Change::Patch(Box::new(BoardPatch::SettingsChanged { settings }))
+        }
+        Ok(None) => Change::Board,
+        Err(e) => {
+            warn!("Failed to read the board settings: {e}");
+            Change::Board
+        }
+    }
+}
+
+async fn participant_change(state: &AppState, board_id: &str) {
+    let participant_count = state.participant_count(board_id).await;
+    broadcast_patch(
+        state,
+        board_id,
+        BoardPatch::ParticipantCount { participant_count },
+    )
+    .await;
 }
 
 async fn handle_socket(
     socket: WebSocket,
     board_id: String,
     state: AppState,
-    facilitator_id_from_cookie: Option<String>,
+    owner: Owner,
 ) {
     let (mut sender, mut receiver) = socket.split();
 
+    // The presence of this socket, apart from any other socket of the same participant
+    let socket_id = nanoid!(12);
+
     // Wait for Join message first
     let (participant_id, participant_name, is_facilitator) = loop {
         match receiver.next().await {
@@ -61,11 +297,24 @@ async fn handle_socket(
                         participant_name,
                         facilitator_token,
                         participant_id,
+                        participant_token,
                         access_token,
                     }) => {
-                        let participant_id = participant_id
-                            .filter(|id| !id.is_empty())
-                            .unwrap_or_else(|| nanoid!(8));
+                        METRICS.messages.with_label_values(&["Join"]).inc();
+                        // An id is resumed only with the credential it was given out with. Anyone
+                        // can read the id of an editor off the board; without this they could join
+                        // as them.
+                        let resumed = credentials::verified(
+                            &state,
+                            &board_id,
+                            participant_id.as_deref(),
+                            participant_token.as_deref(),
+                        )
+                        .map(str::to_string);
+                        if resumed.is_none() && participant_id.is_some_and(|id| !id.is_empty()) {
+                            info!(board_id, "a join claimed an id without its credential");
+                        }
+                        let participant_id = resumed.unwrap_or_else(|| nanoid!(8));
 
                         // Verify board exists and check facilitator auth
                         let token = match db::get_board_facilitator_token(&state.db, &board_id)
@@ -105,7 +354,7 @@ async fn handle_socket(
                             .map(|t| t == &token)
                             .unwrap_or(false);
 
-                        let cookie_match = if let Some(ref fid) = facilitator_id_from_cookie {
+                        let cookie_match = if let Some(ref fid) = owner.facilitator_id {
                             db::get_board_facilitator_id(&state.db, &board_id)
                                 .await
                                 .ok()
@@ -116,7 +365,15 @@ async fn handle_socket(
                             false
                         };
 
-                        let is_facilitator = token_match || cookie_match;
+                        // A signed-in owner is the facilitator on any device.
+                        let sub_match = match owner.sub {
+                            Some(ref sub) => db::is_board_owner(&state.db, &board_id, sub)
+                                .await
+                                .unwrap_or(false),
+                            None => false,
+                        };
+
+                        let is_facilitator = token_match || cookie_match || sub_match;
 
                         // The gate of a locked board. A reader gets in with the key that the
                         // password gave them; the facilitator needs no key. The check sits before
@@ -148,33 +405,73 @@ async fn handle_socket(
                             }
                         }
 
-                        // For anonymous boards, discard the participant name
-                        let board_anonymous = db::get_board_anonymous(&state.db, &board_id)
-                            .await
-                            .unwrap_or(Some(false))
-                            .unwrap_or(false);
+                        // For anonymous boards, discard the participant name. A board whose mode
+                        // cannot be read lets nobody in, rather than put an id on it in the open.
+                        let board_anonymous =
+                            match db::get_board_anonymous(&state.db, &board_id).await {
+                                Ok(anonymous) => anonymous.unwrap_or(false),
+                                Err(e) => {
+                                    warn!("DB error reading whether the board is anonymous: {e}");
+                                    let _ = sender
+                                        .send(Message::Text(
+                                            serde_json::to_string(&ServerMessage::Error {
+                                                message: "Internal error".to_string(),
+                                            })
+                                            .unwrap()
+                                            .into(),
+                                        ))
+                                        .await;
+                                    return;
+                                }
+                            };
                         let effective_name = if board_anonymous {
                             String::new()
                         } else {
                             participant_name
                         };
-
-                        // Add participant to in-memory map
+                        // and the id, for the pseudonym the board knows this reader by. From here
+                        // on the socket is that pseudonym; only the browser holds the id.
+                        let author_id = credentials::author_id(
+                            &state.participant_key,
+                            &board_id,
+                            &participant_id,
+                            board_anonymous,
+                        );
+
+                        // Add participant to in-memory map, and to the presence every replica
+                        // counts from
                         {
                             let mut participants = state.participants.write().await;
                             participants
                                 .entry(board_id.clone())
                                 .or_default()
                                 .push(Participant {
-                                    id: participant_id.clone(),
+                                    id: author_id.clone(),
                                     name: effective_name.clone(),
                                 });
                         }
+                        if let Err(e) = db::add_presence(
+                            &state.db,
+                            &socket_id,
+                            &state.fanout.replica_id,
+                            &board_id,
+                            &author_id,
+                        )
+                        .await
+                        {
+                            warn!("Failed to record the participant: {e}");
+                        }
 
                         // Send Authenticated
                         let auth_msg = ServerMessage::Authenticated {
                             is_facilitator,
                             participant_id: participant_id.clone(),
+                            participant_token: credentials::issue(
+                                &state.participant_key,
+                                &board_id,
+                                &participant_id,
+                            ),
+                            author_id: author_id.clone(),
                         };
                         let _ = sender
                             .send(Message::Text(
@@ -182,10 +479,10 @@ async fn handle_socket(
                             ))
                             .await;
 
-                        // Broadcast updated state (new participant count)
-                        broadcast_board_state(&state, &board_id).await;
+                        // Tell the board it has one more reader
+                        participant_change(&state, &board_id).await;
 
-                        break (participant_id, effective_name, is_facilitator);
+                        break (author_id, effective_name, is_facilitator);
                     }
                     Ok(_) => {
                         let _ = sender
@@ -222,33 +519,49 @@ async fn handle_socket(
     let tx = state.get_or_create_channel(&board_id).await;
     let mut rx = tx.subscribe();
 
-    // Send current board state
-    {
-        if let Ok(Some(board)) = db::get_board(&state.db, &board_id).await {
-            let count = state.participant_count(&board_id).await;
-            let editors = db::get_board_editors(&state.db, &board_id).await.unwrap_or_default();
-            let editor_requests = db::get_editor_requests(&state.db, &board_id).await.unwrap_or_default();
-            let mut view = board.to_view_with_participants(count, editors, editor_requests);
-            view.redact_hidden_for(&participant_id, is_facilitator);
-            let msg = ServerMessage::BoardState { board: view };
+    // Send current board state. The reader keeps the redaction made from it, so that the
+    // patches that follow are redacted for them the same way.
+    let mut reader = Reader::new(participant_id.clone(), is_facilitator);
+    if let Some(view) = board_view(&state, &board_id).await {
+        if let Some(msg) = reader.outgoing(ServerMessage::BoardState { board: Box::new(view) }) {
             let _ = sender
                 .send(Message::Text(serde_json::to_string(&msg).unwrap().into()))
                 .await;
         }
     }
 
-    // Spawn a task to forward broadcast messages to this client.
-    // The channel carries the whole board, so each client takes out what its own reader may not
-    // read yet before the state goes down the wire.
-    let redact_for = participant_id.clone();
+    // Messages for this client alone, such as the board it asked for again.
+    let (direct_tx, mut direct_rx) = mpsc::unbounded_channel::<ServerMessage>();
+
+    // Spawn a task to forward broadcast messages to this client, each redacted for its reader.
+    //
+    // A client too slow for the channel — a busy board, a slow link — falls behind it, and the
+    // messages it missed are gone. It skips the rest of what is waiting and gets the board as it
+    // now stands, which holds everything it missed; a patch still waiting from before is older
+    // than that board, and the client lays it aside by its revision.
+    let lag_participant_id = participant_id.clone();
+    let lag_state = state.clone();
+    let lag_board_id = board_id.clone();
     let mut send_task = tokio::spawn(async move {
-        while let Ok(msg) = rx.recv().await {
-            let msg = match msg {
-                ServerMessage::BoardState { mut board } => {
-                    board.redact_hidden_for(&redact_for, is_facilitator);
-                    ServerMessage::BoardState { board }
-                }
-                other => other,
+        loop {
+            let msg = tokio::select! {
+                msg = rx.recv() => match msg {
+                    Ok(msg) => msg,
+                    Err(RecvError::Lagged(skipped)) => {
+                        let caught_up =
+                            catch_up(&lag_state, &lag_board_id, &lag_participant_id, &mut rx, skipped)
+                                .await;
+                        match caught_up {
+                            Some(msg) => msg,
+                            None => continue,
+                        }
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+                Some(msg) = direct_rx.recv() => msg,
+            };
+            let Some(msg) = reader.outgoing(msg) else {
+                continue;
             };
             let text = serde_json::to_string(&msg).unwrap();
             if sender.send(Message::Text(text.into())).await.is_err() {
@@ -264,6 +577,7 @@ async fn handle_socket(
     let participant_name_clone = participant_name.clone();
 
     let mut recv_task = tokio::spawn(async move {
+        let mut throttle = Throttle::default();
         while let Some(Ok(msg)) = receiver.next().await {
             let Message::Text(text) = msg else {
                 continue;
@@ -277,19 +591,33 @@ async fn handle_socket(
                 }
             };
 
-            let should_broadcast = handle_message(
+            // Past its burst, a class of message is dropped here, before it reaches the database.
+            let class = RateClass::of(&client_msg);
+            if let Verdict::Dropped(message) = throttle.check(class, Instant::now()) {
+                METRICS.throttled.with_label_values(&[class.as_str()]).inc();
+                if let Some(message) = message {
+                    warn!(
+                        participant_id = participant_id_clone,
+                        board_id = board_id_clone,
+                        ?class,
+                        "socket throttled"
+                    );
+                    let _ = direct_tx.send(ServerMessage::Error { message });
+                }
+                continue;
+            }
+            METRICS.messages.with_label_values(&[client_msg.kind()]).inc();
+
+            take_message(
                 &state_clone,
                 &board_id_clone,
                 &participant_id_clone,
                 &participant_name_clone,
                 is_facilitator,
                 client_msg,
+                &direct_tx,
             )
             .await;
-
-            if should_broadcast {
-                broadcast_board_state(&state_clone, &board_id_clone).await;
-            }
         }
     });
 
@@ -299,25 +627,46 @@ async fn handle_socket(
         _ = &mut recv_task => send_task.abort(),
     }
 
-    // Remove participant on disconnect
+    // Remove participant on disconnect. One entry goes, not every entry of the id: the same
+    // participant in a second tab is still here. The channel goes with the last socket, under the
+    // same lock, so that a socket joining at this moment finds either both or neither.
     {
         let mut participants = state.participants.write().await;
         if let Some(list) = participants.get_mut(&board_id) {
-            list.retain(|p| p.id != participant_id);
+            if let Some(at) = list.iter().position(|p| p.id == participant_id) {
+                list.remove(at);
+            }
             if list.is_empty() {
                 participants.remove(&board_id);
+                state.drop_channel(&board_id).await;
             }
         }
     }
+    if let Err(e) = db::remove_presence(&state.db, &socket_id).await {
+        warn!("Failed to remove the participant: {e}");
+    }
 
-    broadcast_board_state(&state, &board_id).await;
+    participant_change(&state, &board_id).await;
 
     info!(participant_id, board_id, "participant left");
 }
 
+/// Removes the space at the two ends of a card. Gives None if the card is longer than the limit,
+/// or if it is empty and carries no GIF.
+pub fn clean_ticket(content: &str, has_gif: bool) -> Option<String> {
+    let trimmed = content.trim();
+    if trimmed.chars().count() > MAX_TICKET_LENGTH {
+        return None;
+    }
+    if trimmed.is_empty() && !has_gif {
+        return None;
+    }
+    Some(trimmed.to_string())
+}
+
 /// Removes the space at the two ends of a comment. Gives None if the comment is longer than
 /// the limit, or if it is empty and carries no GIF: a GIF on its own is a whole remark.
-fn clean_comment(content: &str, has_gif: bool) -> Option<String> {
+pub fn clean_comment(content: &str, has_gif: bool) -> Option<String> {
     let trimmed = content.trim();
     if trimmed.chars().count() > MAX_COMMENT_LENGTH {
         return None;
@@ -329,7 +678,7 @@ fn clean_comment(content: &str, has_gif: bool) -> Option<String> {
 }
 
 /// Removes the space at the two ends of one scorecard field. Gives None if it is too long.
-fn clean_scorecard_field(value: &str) -> Option<String> {
+pub fn clean_scorecard_field(value: &str) -> Option<String> {
     let trimmed = value.trim();
     if trimmed.chars().count() > MAX_SCORECARD_FIELD_LENGTH {
         return None;
@@ -348,6 +697,247 @@ async fn is_level10_board(state: &AppState, board_id: &str) -> bool {
         == Some(TEMPLATE_LEVEL10)
 }
 
+/// Whether a new card may go up in `column_id` now. A locked board, and a phase past the writing,
+/// still take actions: they come out of the discussion after the writing is over.
+async fn takes_card_in(
+    state: &AppState,
+    board_id: &str,
+    phase: Option<Phase>,
+    column_id: &str,
+) -> bool {
+    let writing_over = phase.is_some_and(|p| !p.takes_cards());
+    match db::get_cards_locked(&state.db, board_id).await {
+        Ok(Some(false)) if !writing_over => true,
+        Ok(Some(_)) => matches!(db::is_action_column(&state.db, column_id).await, Ok(true)),
+        _ => false,
+    }
+}
+
+// --- History ---
+
+/// An action under way, with the cards it touches as they were before it.
+struct Step {
+    kind: OperationKind,
+    ticket_ids: Vec<String>,
+    before: Snapshot,
+}
+
+/// Reads the cards an action is about to touch. None when the read fails: the action still
+/// goes ahead, it only stays out of the history.
+async fn begin_step(state: &AppState, kind: OperationKind, ticket_ids: &[&str]) -> Option<Step> {
+    let ticket_ids: Vec<String> = ticket_ids.iter().map(|id| id.to_string()).collect();
+    match db::snapshot(&state.db, &ticket_ids).await {
+        Ok(before) => Some(Step {
+            kind,
+            ticket_ids,
+            before,
+        }),
+        Err(e) => {
+            warn!("Failed to read the cards before an action: {e}");
+            None
+        }
+    }
+}
+
+/// Writes a finished action into the history of the one who made it. `author_id` is whose work
+/// the action was on; walking it back later asks for the same standing the action itself did,
+/// and None lets anyone on the board.
+async fn finish_step(
+    state: &AppState,
+    step: Option<Step>,
+    board_id: &str,
+    participant_id: &str,
+    author_id: Option<&str>,
+) {
+    let Some(step) = step else { return };
+    let after = match db::snapshot(&state.db, &step.ticket_ids).await {
+        Ok(after) => after,
+        Err(e) => {
+            warn!("Failed to read the cards after an action: {e}");
+            return;
+        }
+    };
+    let changes = Changes::between(step.before, after);
+    if changes.is_empty() {
+        return;
+    }
+    if let Err(e) =
+        db::record_operation(&state.db, board_id, participant_id, step.kind, author_id, &changes)
+            .await
+    {
+        warn!("Failed to record an action: {e}");
+    }
+}
+
+/// Undoes the newest step in the history of a participant, or redoes the one they undid last.
+/// `only` leaves the history alone unless that step is of the given kind.
+///
+/// A step on a card that someone changed since is not made: it would throw their work away. It
+/// leaves the history instead, so that the next undo reaches the step before it. So does a step
+/// that fails, such as one on a card whose column has been removed since, or it would stand in
+/// the way of every undo after it.
+///
+/// A step that would put a card up waits while the board takes no new cards there, as adding
+/// one would.
+///
+/// A step taken goes into the audit log by the rule of `audit::is_audited`, with its kind and the
+/// cards it touched. Walking back a merge is always kept, as the merge was.
+async fn walk_history(
+    state: &AppState,
+    board_id: &str,
+    participant_id: &str,
+    actor: &Actor,
+    is_privileged: bool,
+    undo: bool,
+    only: Option<OperationKind>,
+) -> Option<Change> {
+    let operation = if undo {
+        db::operation_to_undo(&state.db, board_id, participant_id).await
+    } else {
+        db::operation_to_redo(&state.db, board_id, participant_id).await
+    };
+    let operation = match operation {
+        Ok(Some(operation)) => operation,
+        Ok(None) => return None,
+        Err(e) => {
+            warn!("Failed to read the history: {e}");
+            return None;
+        }
+    };
+    if only.is_some_and(|kind| kind != operation.kind) {
+        return None;
+    }
+
+    // The same standing the action asked for. An editor who lost the role since can no longer
+    // walk back what they did to the cards of others.
+    match operation.author_id.as_deref() {
+        Some(author_id) if author_id != participant_id && !is_privileged => return None,
+        _ => {}
+    }
+    // A merge shows the text of two cards in one, so it waits for the reveal both ways.
+    if operation.kind == OperationKind::MergeTickets {
+        match db::get_blur_state(&state.db, board_id).await {
+            Ok(Some(false)) => {}
+            _ => return None,
+        }
+    }
+
+    let changes = if undo {
+        operation.changes.reversed()
+    } else {
+        operation.changes
+    };
+    if changes.added_cards().next().is_some() {
+        let phase = match db::get_phase(&state.db, board_id).await {
+            Ok(phase) => phase,
+            Err(e) => {
+                warn!("Failed to read the phase: {e}");
+                return None;
+            }
+        };
+        for card in changes.added_cards() {
+            if !takes_card_in(state, board_id, phase, &card.column_id).await {
+                return None;
+            }
+        }
+    }
+    match db::apply_changes(&state.db, &changes).await {
+        Ok(true) => {
+            if let Err(e) = db::set_operation_undone(&state.db, operation.id, undo).await {
+                warn!("Failed to mark a step in the history: {e}");
+            }
+            let touch = if changes.removes_any() { Touch::Remove } else { Touch::Edit };
+            let author_id = operation.author_id.as_deref();
+            let action = match (undo, operation.kind) {
+                (true, OperationKind::MergeTickets) => Some(AuditAction::TicketUnmerge),
+                _ if !audit::is_audited(touch, author_id, participant_id) => None,
+                (true, _) => Some(AuditAction::HistoryUndo),
+                (false, _) => Some(AuditAction::HistoryRedo),
+            };
+            if let Some(action) = action {
+                let target = changes
+                    .ticket_ids()
+                    .iter()
+                    .map(|id| format!("ticket:{id}"))
+                    .collect::<Vec<_>>()
+                    .join(",");
+                let detail = match author_id {
+                    Some(author_id) => {
+                        format!("{} author participant:{author_id}", operation.kind.as_str())
+                    }
+                    None => operation.kind.as_str().to_string(),
+                };
+                audit::record(state, Some(board_id), actor, action, Some(&target), Some(&detail))
+                    .await;
+            }
+            Some(Change::Board)
+        }
+        Ok(false) => {
+            if let Err(e) = db::drop_operation(&state.db, operation.id).await {
+                warn!("Failed to drop a step from the history: {e}");
+            }
+            None
+        }
+        Err(e) => {
+            warn!("Failed to walk the history, dropping the step: {e}");
+            if let Err(e) = db::drop_operation(&state.db, operation.id).await {
+                warn!("Failed to drop a step from the history: {e}");
+            }
+            None
+        }
+    }
+}
+
+/// Whether a closed board takes the message. The facilitator can open it again, and nothing else
+/// goes through.
+fn is_taken_when_archived(msg: &ClientMessage) -> bool {
+    matches!(msg, ClientMessage::ReopenBoard)
+}
+
+/// What a closed board answers the rest.
+const CLOSED_BOARD: &str = "The board is closed. The facilitator can open it again";
+
+/// Does what one message that got past the throttle asks, and tells the board what changed. What
+/// is for this client alone — the board it asked for again, why a closed board took nothing — goes
+/// down `direct_tx`.
+async fn take_message(
+    state: &AppState,
+    board_id: &str,
+    participant_id: &str,
+    participant_name: &str,
+    is_facilitator: bool,
+    msg: ClientMessage,
+    direct_tx: &mpsc::UnboundedSender<ServerMessage>,
+) {
+    // A client that missed a revision asks for the whole board, and only it gets it.
+    if matches!(msg, ClientMessage::Resync) {
+        if let Some(view) = board_view(state, board_id).await {
+            let _ = direct_tx.send(ServerMessage::BoardState { board: Box::new(view) });
+        }
+        return;
+    }
+
+    // A closed board stays as it was left.
+    let archived = match db::is_archived(&state.db, board_id).await {
+        Ok(archived) => archived,
+        Err(e) => {
+            warn!("Failed to read whether the board is closed: {e}");
+            return;
+        }
+    };
+    if archived && !is_taken_when_archived(&msg) {
+        let _ = direct_tx.send(ServerMessage::Error { message: CLOSED_BOARD.to_string() });
+        return;
+    }
+
+    let change =
+        handle_message(state, board_id, participant_id, participant_name, is_facilitator, msg)
+            .await;
+    if let Some(change) = change {
+        broadcast_change(state, board_id, change).await;
+    }
+}
+
 async fn handle_message(
     state: &AppState,
     board_id: &str,
@@ -355,15 +945,36 @@ async fn handle_message(
     participant_name: &str,
     is_facilitator: bool,
     msg: ClientMessage,
-) -> bool {
+) -> Option<Change> {
     // Check editor status for privileged actions
     let is_editor = db::is_editor(&state.db, board_id, participant_id)
         .await
         .unwrap_or(false);
     let is_privileged = is_facilitator || is_editor;
+    // The audit log keeps what this participant does to the board and to the cards of others.
+    let actor = Actor::participant(participant_id, participant_name);
+
+    // A board that runs through phases lets each one do only its own work, and a closed board
+    // takes nothing but a move to another phase.
+    let phase = match db::get_phase(&state.db, board_id).await {
+        Ok(phase) => phase,
+        Err(e) => {
+            warn!("Failed to read the phase: {e}");
+            return None;
+        }
+    };
+    if phase == Some(Phase::Closed)
+        && !matches!(
+            msg,
+            ClientMessage::SetPhase { .. } | ClientMessage::CloseBoard | ClientMessage::ReopenBoard
+        )
+    {
+        return None;
+    }
 
     match msg {
-        ClientMessage::Join { .. } => false,
+        // Both are answered in the socket loop and change nothing on the board.
+        ClientMessage::Join { .. } | ClientMessage::Resync => None,
 
         ClientMessage::AddTicket {
             column_id,
@@ -373,23 +984,25 @@ async fn handle_message(
             // Verify column belongs to this board
             match db::column_belongs_to_board(&state.db, &column_id, board_id).await {
                 Ok(true) => {}
-                _ => return false,
+                _ => return None,
+            }
+
+            if !takes_card_in(state, board_id, phase, &column_id).await {
+                return None;
             }
 
             // The client chooses the picture, so the server checks it before it keeps it.
             let gif = gif.and_then(sanitize_gif);
 
             // A card is either words or a picture. Empty on both counts is nothing at all.
-            if content.trim().is_empty() && gif.is_none() {
-                return false;
-            }
+            let content = clean_ticket(&content, gif.is_some())?;
 
             let ticket_id = nanoid!(8);
             match db::add_ticket(
                 &state.db,
                 &ticket_id,
                 &column_id,
-                content.trim(),
+                &content,
                 participant_id,
                 participant_name,
                 Utc::now(),
@@ -397,10 +1010,19 @@ async fn handle_message(
             )
             .await
             {
-                Ok(()) => true,
+                Ok(()) => {
+                    let change = ticket_change(state, &ticket_id, |column_id, ticket| {
+                        BoardPatch::TicketAdded { column_id, ticket }
+                    })
+                    .await;
+                    if let Ok(true) = db::ticket_in_action_column(&state.db, &ticket_id, board_id).await {
+                        emit_card_event(state, board_id, WebhookEvent::ActionCreated, &change).await;
+                    }
+                    Some(change)
+                }
                 Err(e) => {
                     warn!("Failed to add ticket: {e}");
-                    false
+                    None
                 }
             }
         }
@@ -410,19 +1032,34 @@ async fn handle_message(
             // says nothing about any other, so the id is scoped before the privilege counts.
             match db::ticket_belongs_to_board(&state.db, &ticket_id, board_id).await {
                 Ok(true) => {}
-                _ => return false,
+                _ => return None,
             }
             // Check authorization: author, facilitator, or editor
-            match db::get_ticket_author(&state.db, &ticket_id).await {
-                Ok(Some(author_id)) if author_id == participant_id || is_privileged => {}
-                _ => return false,
-            }
+            let author_id = match db::get_ticket_author(&state.db, &ticket_id).await {
+                Ok(Some(author_id)) if author_id == participant_id || is_privileged => author_id,
+                _ => return None,
+            };
 
+            let step = begin_step(state, OperationKind::RemoveTicket, &[&ticket_id]).await;
             match db::remove_ticket(&state.db, &ticket_id).await {
-                Ok(()) => true,
+                Ok(()) => {
+                    finish_step(state, step, board_id, participant_id, Some(&author_id)).await;
+                    if audit::is_audited(Touch::Remove, Some(&author_id), participant_id) {
+                        audit::record(
+                            state,
+                            Some(board_id),
+                            &actor,
+                            AuditAction::TicketRemove,
+                            Some(&format!("ticket:{ticket_id}")),
+                            Some(&format!("author participant:{author_id}")),
+                        )
+                        .await;
+                    }
+                    Some(Change::Patch(Box::new(BoardPatch::TicketRemoved { ticket_id })))
+                }
                 Err(e) => {
                     warn!("Failed to remove ticket: {e}");
-                    false
+                    None
                 }
             }
         }
@@ -435,24 +1072,40 @@ async fn handle_message(
             // The card must sit on this board, for the same reason RemoveTicket asks.
             match db::ticket_belongs_to_board(&state.db, &ticket_id, board_id).await {
                 Ok(true) => {}
-                _ => return false,
+                _ => return None,
             }
             // Author, facilitator, or editor
-            match db::get_ticket_author(&state.db, &ticket_id).await {
-                Ok(Some(author_id)) if author_id == participant_id || is_privileged => {}
-                _ => return false,
-            }
+            let author_id = match db::get_ticket_author(&state.db, &ticket_id).await {
+                Ok(Some(author_id)) if author_id == participant_id || is_privileged => author_id,
+                _ => return None,
+            };
 
             let gif = gif.and_then(sanitize_gif);
-            if content.trim().is_empty() && gif.is_none() {
-                return false;
-            }
-
-            match db::edit_ticket(&state.db, &ticket_id, content.trim(), gif.as_ref()).await {
-                Ok(()) => true,
+            let content = clean_ticket(&content, gif.is_some())?;
+
+            let step = begin_step(state, OperationKind::EditTicket, &[&ticket_id]).await;
+            match db::edit_ticket(&state.db, &ticket_id, &content, gif.as_ref()).await {
+                Ok(()) => {
+                    finish_step(state, step, board_id, participant_id, Some(&author_id)).await;
+                    if audit::is_audited(Touch::Edit, Some(&author_id), participant_id) {
+                        audit::record(
+                            state,
+                            Some(board_id),
+                            &actor,
+                            AuditAction::TicketEdit,
+                            Some(&format!("ticket:{ticket_id}")),
+                            Some(&format!("author participant:{author_id}")),
+                        )
+                        .await;
+                    }
+                    Some(ticket_change(state, &ticket_id, |column_id, ticket| {
+                        BoardPatch::TicketEdited { column_id, ticket }
+                    })
+                    .await)
+                }
                 Err(e) => {
                     warn!("Failed to edit ticket: {e}");
-                    false
+                    None
                 }
             }
         }
@@ -464,29 +1117,36 @@ async fn handle_message(
             // The card and the target column must both belong to this board
             match db::column_belongs_to_board(&state.db, &column_id, board_id).await {
                 Ok(true) => {}
-                _ => return false,
+                _ => return None,
             }
             match db::get_ticket_column_id(&state.db, &ticket_id).await {
                 Ok(Some(current)) => {
                     match db::column_belongs_to_board(&state.db, &current, board_id).await {
                         Ok(true) => {}
-                        _ => return false,
+                        _ => return None,
                     }
                 }
-                _ => return false,
+                _ => return None,
             }
 
             // Authorization: author, facilitator, or editor
-            match db::get_ticket_author(&state.db, &ticket_id).await {
-                Ok(Some(author_id)) if author_id == participant_id || is_privileged => {}
-                _ => return false,
-            }
+            let author_id = match db::get_ticket_author(&state.db, &ticket_id).await {
+                Ok(Some(author_id)) if author_id == participant_id || is_privileged => author_id,
+                _ => return None,
+            };
 
+            let step = begin_step(state, OperationKind::MoveTicket, &[&ticket_id]).await;
             match db::move_ticket(&state.db, &ticket_id, &column_id).await {
-                Ok(()) => true,
+                Ok(()) => {
+                    finish_step(state, step, board_id, participant_id, Some(&author_id)).await;
+                    Some(ticket_change(state, &ticket_id, |column_id, ticket| {
+                        BoardPatch::TicketMoved { column_id, ticket }
+                    })
+                    .await)
+                }
                 Err(e) => {
                     warn!("Failed to move ticket: {e}");
-                    false
+                    None
                 }
             }
         }
@@ -499,15 +1159,14 @@ async fn handle_message(
             // Anyone on the board can comment, but only on a card of this board
             match db::ticket_belongs_to_board(&state.db, &ticket_id, board_id).await {
                 Ok(true) => {}
-                _ => return false,
+                _ => return None,
             }
 
             let gif = gif.and_then(sanitize_gif);
-            let Some(content) = clean_comment(&content, gif.is_some()) else {
-                return false;
-            };
+            let content = clean_comment(&content, gif.is_some())?;
 
             let comment_id = nanoid!(8);
+            let step = begin_step(state, OperationKind::AddComment, &[&ticket_id]).await;
             match db::add_comment(
                 &state.db,
                 &comment_id,
@@ -520,10 +1179,14 @@ async fn handle_message(
             )
             .await
             {
-                Ok(()) => true,
+                Ok(()) => {
+                    // Anyone may comment, so anyone who still may is let to take it back.
+                    finish_step(state, step, board_id, participant_id, None).await;
+                    Some(ticket_change(state, &ticket_id, comment_changed).await)
+                }
                 Err(e) => {
                     warn!("Failed to add comment: {e}");
-                    false
+                    None
                 }
             }
         }
@@ -534,48 +1197,97 @@ async fn handle_message(
             gif,
         } => {
             // Author, facilitator, or editor, and only on this board
-            match db::get_comment_author_on_board(&state.db, &comment_id, board_id).await {
-                Ok(Some(author_id)) if author_id == participant_id || is_privileged => {}
-                _ => return false,
-            }
+            let author_id =
+                match db::get_comment_author_on_board(&state.db, &comment_id, board_id).await {
+                    Ok(Some(author_id)) if author_id == participant_id || is_privileged => author_id,
+                    _ => return None,
+                };
+
+            let ticket_id = db::get_comment_ticket_id(&state.db, &comment_id)
+                .await
+                .ok()
+                .flatten()?;
 
             let gif = gif.and_then(sanitize_gif);
-            let Some(content) = clean_comment(&content, gif.is_some()) else {
-                return false;
-            };
+            let content = clean_comment(&content, gif.is_some())?;
 
+            let step = begin_step(state, OperationKind::EditComment, &[&ticket_id]).await;
             match db::edit_comment(&state.db, &comment_id, &content, gif.as_ref()).await {
-                Ok(()) => true,
+                Ok(()) => {
+                    finish_step(state, step, board_id, participant_id, Some(&author_id)).await;
+                    if audit::is_audited(Touch::Edit, Some(&author_id), participant_id) {
+                        audit::record(
+                            state,
+                            Some(board_id),
+                            &actor,
+                            AuditAction::CommentEdit,
+                            Some(&format!("comment:{comment_id}")),
+                            Some(&format!("author participant:{author_id}")),
+                        )
+                        .await;
+                    }
+                    Some(ticket_change(state, &ticket_id, comment_changed).await)
+                }
                 Err(e) => {
                     warn!("Failed to edit comment: {e}");
-                    false
+                    None
                 }
             }
         }
 
         ClientMessage::RemoveComment { comment_id } => {
             // Author, facilitator, or editor
-            match db::get_comment_author_on_board(&state.db, &comment_id, board_id).await {
-                Ok(Some(author_id)) if author_id == participant_id || is_privileged => {}
-                _ => return false,
-            }
-
+            let author_id =
+                match db::get_comment_author_on_board(&state.db, &comment_id, board_id).await {
+                    Ok(Some(author_id)) if author_id == participant_id || is_privileged => author_id,
+                    _ => return None,
+                };
+            // Read before the comment goes, since afterwards nothing leads back to its card.
+            let ticket_id = db::get_comment_ticket_id(&state.db, &comment_id)
+                .await
+                .ok()
+                .flatten()?;
+
+            let step = begin_step(state, OperationKind::RemoveComment, &[&ticket_id]).await;
             match db::remove_comment(&state.db, &comment_id).await {
-                Ok(()) => true,
+                Ok(()) => {
+                    finish_step(state, step, board_id, participant_id, Some(&author_id)).await;
+                    if audit::is_audited(Touch::Remove, Some(&author_id), participant_id) {
+                        audit::record(
+                            state,
+                            Some(board_id),
+                            &actor,
+                            AuditAction::CommentRemove,
+                            Some(&format!("comment:{comment_id}")),
+                            Some(&format!("author participant:{author_id}")),
+                        )
+                        .await;
+                    }
+                    Some(ticket_change(state, &ticket_id, comment_changed).await)
+                }
                 Err(e) => {
                     warn!("Failed to remove comment: {e}");
-                    false
+                    None
                 }
             }
         }
 
         ClientMessage::ToggleVote { ticket_id } => {
+            if phase.is_some_and(|p| !p.takes_votes()) {
+                return None;
+            }
+            // The card must sit on this board, or its voters would go out to the wrong room.
+            match db::ticket_belongs_to_board(&state.db, &ticket_id, board_id).await {
+                Ok(true) => {}
+                _ => return None,
+            }
+
             // Check vote limit before adding a vote
             let already_voted = match db::has_vote(&state.db, &ticket_id, participant_id).await {
                 Ok(v) => v,
                 Err(e) => {
                     warn!("Failed to check vote: {e}");
-                    return false;
+                    return None;
                 }
             };
 
@@ -584,60 +1296,175 @@ async fn handle_message(
                 if let Ok(Some(limit)) = db::get_vote_limit(&state.db, board_id).await {
                     let column_id = match db::get_ticket_column_id(&state.db, &ticket_id).await {
                         Ok(Some(cid)) => cid,
-                        _ => return false,
+                        _ => return None,
                     };
                     let count = match db::count_votes_in_column(&state.db, &column_id, participant_id).await {
                         Ok(c) => c,
                         Err(e) => {
                             warn!("Failed to count votes: {e}");
-                            return false;
+                            return None;
                         }
                     };
                     if count >= limit as i64 {
-                        return false; // At limit, reject
+                        return None; // At limit, reject
                     }
                 }
             }
 
             match db::toggle_vote(&state.db, &ticket_id, participant_id).await {
-                Ok(()) => true,
+                Ok(()) => Some(match db::get_ticket_votes(&state.db, &ticket_id).await {
+                    Ok(votes) => Change::Patch(Box::new(BoardPatch::VoteToggled {
+                        ticket_id,
+                        votes,
+                        vote_count: None,
+                        voted: false,
+                    })),
+                    Err(_) => Change::Board,
+                }),
                 Err(e) => {
                     warn!("Failed to toggle vote: {e}");
-                    false
+                    None
                 }
             }
         }
 
         ClientMessage::ToggleBlur => {
             if !is_privileged {
-                return false;
+                return None;
             }
             let current = match db::get_blur_state(&state.db, board_id).await {
                 Ok(Some(v)) => v,
-                _ => return false,
+                _ => return None,
             };
             match db::set_blur(&state.db, board_id, !current).await {
-                Ok(()) => true,
+                Ok(()) => {
+                    let action = if current {
+                        AuditAction::BoardReveal
+                    } else {
+                        AuditAction::BoardBlur
+                    };
+                    audit::record(state, Some(board_id), &actor, action, None, None).await;
+                    if current {
+                        webhooks::emit(state, board_id, WebhookEvent::BoardRevealed, None).await;
+                    }
+                    Some(Change::Board)
+                }
                 Err(e) => {
                     warn!("Failed to toggle blur: {e}");
-                    false
+                    None
                 }
             }
         }
 
         ClientMessage::ToggleHideVotes => {
             if !is_privileged {
-                return false;
+                return None;
             }
             let current = match db::get_hide_votes(&state.db, board_id).await {
                 Ok(Some(v)) => v,
-                _ => return false,
+                _ => return None,
             };
             match db::set_hide_votes(&state.db, board_id, !current).await {
-                Ok(()) => true,
+                Ok(()) => {
+                    let action = if current {
+                        AuditAction::VotesShow
+                    } else {
+                        AuditAction::VotesHide
+                    };
+                    audit::record(state, Some(board_id), &actor, action, None, None).await;
+                    // The counts come off every card or go back on, so each reader gets the board
+                    // again, as after a reveal.
+                    Some(Change::Board)
+                }
                 Err(e) => {
                     warn!("Failed to toggle hide votes: {e}");
-                    false
+                    None
+                }
+            }
+        }
+
+        ClientMessage::SetPhase { phase } => {
+            if !is_privileged {
+                return None;
+            }
+            match db::set_phase(&state.db, board_id, phase).await {
+                Ok(true) => {
+                    audit::record(
+                        state,
+                        Some(board_id),
+                        &actor,
+                        AuditAction::BoardPhase,
+                        None,
+                        Some(phase.as_str()),
+                    )
+                    .await;
+                    Some(settings_change(state, board_id).await)
+                }
+                Ok(false) => None,
+                Err(e) => {
+                    warn!("Failed to set the phase: {e}");
+                    None
+                }
+            }
+        }
+
+        ClientMessage::CloseBoard => {
+            if !is_facilitator {
+                return None;
+            }
+            match db::close_board(&state.db, board_id).await {
+                Ok(true) => {
+                    audit::record(state, Some(board_id), &actor, AuditAction::BoardClose, None, None)
+                        .await;
+                    Some(settings_change(state, board_id).await)
+                }
+                Ok(false) => None,
+                Err(e) => {
+                    warn!("Failed to close the board: {e}");
+                    None
+                }
+            }
+        }
+
+        ClientMessage::ReopenBoard => {
+            if !is_facilitator {
+                return None;
+            }
+            match db::reopen_board(&state.db, board_id).await {
+                Ok(true) => {
+                    audit::record(state, Some(board_id), &actor, AuditAction::BoardReopen, None, None)
+                        .await;
+                    Some(settings_change(state, board_id).await)
+                }
+                Ok(false) => None,
+                Err(e) => {
+                    warn!("Failed to reopen the board: {e}");
+                    None
+                }
+            }
+        }
+
+        ClientMessage::ToggleCardsLocked => {
+            if !is_privileged {
+                return None;
+            }
+            let current = match db::get_cards_locked(&state.db, board_id).await {
+                Ok(Some(v)) => v,
+                _ => return None,
+            };
+            match db::set_cards_locked(&state.db, board_id, !current).await {
+                Ok(()) => {
+                    let action = if current {
+                        AuditAction::CardsUnlock
+                    } else {
+                        AuditAction::CardsLock
+                    };
+                    audit::record(state, Some(board_id), &actor, action, None, None).await;
+                    Some(settings_change(state, board_id).await)
+                }
+                Err(e) => {
+                    warn!("Failed to toggle the card lock: {e}");
+                    None
                 }
             }
         }
@@ -647,53 +1474,193 @@ async fn handle_message(
             target_ticket_id,
         } => {
             // Block merges while the board is blurred to prevent leaking card contents
-            match db::get_blur_state(&state.db, board_id).await {
-                Ok(Some(true)) => return false,
-                _ => {}
+            if let Ok(Some(true)) = db::get_blur_state(&state.db, board_id).await {
+                return None;
+            }
+            // Both cards must sit on this board, or the step would go into the wrong history.
+            for ticket_id in [&source_ticket_id, &target_ticket_id] {
+                match db::ticket_belongs_to_board(&state.db, ticket_id, board_id).await {
+                    Ok(true) => {}
+                    _ => return None,
+                }
             }
 
+            let step = begin_step(
+                state,
+                OperationKind::MergeTickets,
+                &[&source_ticket_id, &target_ticket_id],
+            )
+            .await;
             match db::merge_tickets(&state.db, &source_ticket_id, &target_ticket_id).await {
-                Ok(Some(snapshot)) => {
-                    let mut merges = state.last_merge.write().await;
-                    merges.insert(board_id.to_string(), snapshot);
-                    true
+                Ok(true) => {
+                    finish_step(state, step, board_id, participant_id, None).await;
+                    audit::record(
+                        state,
+                        Some(board_id),
+                        &actor,
+                        AuditAction::TicketMerge,
+                        Some(&format!("ticket:{source_ticket_id}")),
+                        Some(&format!("into ticket:{target_ticket_id}")),
+                    )
+                    .await;
+                    Some(Change::Board)
                 }
-                Ok(None) => false,
+                Ok(false) => None,
                 Err(e) => {
                     warn!("Failed to merge tickets: {e}");
-                    false
+                    None
                 }
             }
         }
 
-        ClientMessage::UndoMerge => {
-            let snapshot = {
-                let mut merges = state.last_merge.write().await;
-                merges.remove(board_id)
-            };
-            match snapshot {
-                Some(snap) => match db::undo_merge(&state.db, &snap).await {
-                    Ok(()) => true,
-                    Err(e) => {
-                        warn!("Failed to undo merge: {e}");
-                        false
-                    }
-                },
-                None => false,
+        ClientMessage::AddColumn { name } => {
+            if !is_privileged {
+                return None;
+            }
+            let name = read_column_name(&name).ok()?;
+            let is_level10 = is_level10_board(state, board_id).await;
+            let column_id = nanoid!(8);
+            match db::add_column(&state.db, board_id, &column_id, &name, is_level10).await {
+                Ok(true) => {
+                    audit::record(
+                        state,
+                        Some(board_id),
+                        &actor,
+                        AuditAction::ColumnAdd,
+                        Some(&format!("column:{column_id}")),
+                        Some(&name),
+                    )
+                    .await;
+                    Some(Change::Board)
+                }
+                Ok(false) => None,
+                Err(e) => {
+                    warn!("Failed to add column: {e}");
+                    None
+                }
             }
         }
 
+        ClientMessage::RenameColumn { column_id, name } => {
+            if !is_privileged {
+                return None;
+            }
+            let name = read_column_name(&name).ok()?;
+            match db::rename_column(&state.db, board_id, &column_id, &name).await {
+                Ok(true) => {
+                    audit::record(
+                        state,
+                        Some(board_id),
+                        &actor,
+                        AuditAction::ColumnRename,
+                        Some(&format!("column:{column_id}")),
+                        Some(&name),
+                    )
+                    .await;
+                    Some(Change::Board)
+                }
+                Ok(false) => None,
+                Err(e) => {
+                    warn!("Failed to rename column: {e}");
+                    None
+                }
+            }
+        }
+
+        ClientMessage::ReorderColumns { column_ids } => {
+            if !is_privileged {
+                return None;
+            }
+            match db::reorder_columns(&state.db, board_id, &column_ids).await {
+                Ok(true) => {
+                    audit::record(
+                        state,
+                        Some(board_id),
+                        &actor,
+                        AuditAction::ColumnReorder,
+                        None,
+                        Some(&column_ids.join(", ")),
+                    )
+                    .await;
+                    Some(Change::Board)
+                }
+                Ok(false) => None,
+                Err(e) => {
+                    warn!("Failed to reorder columns: {e}");
+                    None
+                }
+            }
+        }
+
+        ClientMessage::RemoveColumn {
+            column_id,
+            target_column_id,
+        } => {
+            if !is_privileged {
+                return None;
+            }
+            match db::remove_column(&state.db, board_id, &column_id, target_column_id.as_deref())
+                .await
+            {
+                Ok(true) => {
+                    let detail = target_column_id.map(|target| format!("cards to column:{target}"));
+                    audit::record(
+                        state,
+                        Some(board_id),
+                        &actor,
+                        AuditAction::ColumnRemove,
+                        Some(&format!("column:{column_id}")),
+                        detail.as_deref(),
+                    )
+                    .await;
+                    Some(Change::Board)
+                }
+                Ok(false) => None,
+                Err(e) => {
+                    warn!("Failed to remove column: {e}");
+                    None
+                }
+            }
+        }
+
+        ClientMessage::Undo => {
+            walk_history(state, board_id, participant_id, &actor, is_privileged, true, None).await
+        }
+
+        ClientMessage::Redo => {
+            walk_history(state, board_id, participant_id, &actor, is_privileged, false, None).await
+        }
+
+        ClientMessage::UndoMerge => {
+            let merge = Some(OperationKind::MergeTickets);
+            walk_history(state, board_id, participant_id, &actor, is_privileged, true, merge).await
+        }
+
         ClientMessage::SplitTicket {
             ticket_id,
             segment_index,
         } => {
+            match db::ticket_belongs_to_board(&state.db, &ticket_id, board_id).await {
+                Ok(true) => {}
+                _ => return None,
+            }
             // Auth: author, facilitator, or editor
-            match db::get_ticket_author(&state.db, &ticket_id).await {
-                Ok(Some(author_id)) if author_id == participant_id || is_privileged => {}
-                _ => return false,
+            let author_id = match db::get_ticket_author(&state.db, &ticket_id).await {
+                Ok(Some(author_id)) if author_id == participant_id || is_privileged => author_id,
+                _ => return None,
+            };
+            // The second half is a new card in the same column, so it waits as a new card would.
+            let column_id = match db::get_ticket_column_id(&state.db, &ticket_id).await {
+                Ok(Some(column_id)) => column_id,
+                _ => return None,
+            };
+            if !takes_card_in(state, board_id, phase, &column_id).await {
+                return None;
             }
 
             let new_ticket_id = nanoid!(8);
+            let step =
+                begin_step(state, OperationKind::SplitTicket, &[&ticket_id, &new_ticket_id]).await;
             match db::split_ticket(
                 &state.db,
                 &ticket_id,
@@ -704,60 +1671,79 @@ async fn handle_message(
             )
             .await
             {
-                Ok(true) => true,
-                Ok(false) => false,
+                Ok(true) => {
+                    finish_step(state, step, board_id, participant_id, Some(&author_id)).await;
+                    Some(Change::Board)
+                }
+                Ok(false) => None,
                 Err(e) => {
                     warn!("Failed to split ticket: {e}");
-                    false
+                    None
                 }
             }
         }
 
         ClientMessage::SetVoteLimit { limit } => {
             if !is_privileged {
-                return false;
+                return None;
             }
             // Validate: must be >= 1 or None
             if let Some(l) = limit {
                 if l < 1 {
-                    return false;
+                    return None;
                 }
             }
             match db::set_vote_limit(&state.db, board_id, limit).await {
-                Ok(()) => true,
+                Ok(()) => {
+                    let detail = limit.map_or_else(|| "none".to_string(), |l| l.to_string());
+                    audit::record(
+                        state,
+                        Some(board_id),
+                        &actor,
+                        AuditAction::BoardVoteLimit,
+                        None,
+                        Some(&detail),
+                    )
+                    .await;
+                    Some(settings_change(state, board_id).await)
+                }
                 Err(e) => {
                     warn!("Failed to set vote limit: {e}");
-                    false
+                    None
                 }
             }
         }
 
-        ClientMessage::StartTimer { duration_secs } => {
+        ClientMessage::StartTimer {
+            duration_secs,
+            on_expiry,
+        } => {
             if !is_privileged {
-                return false;
+                return None;
             }
             if !(1..=3600).contains(&duration_secs) {
-                return false;
+                return None;
             }
+            // `crate::timers` ends it, on whichever replica looks first.
             let end = Utc::now() + chrono::Duration::seconds(duration_secs as i64);
-            match db::set_timer_end(&state.db, board_id, Some(end)).await {
-                Ok(()) => true,
+            match db::set_timer_end(&state.db, board_id, Some(end), on_expiry).await {
+                Ok(()) => Some(settings_change(state, board_id).await),
                 Err(e) => {
                     warn!("Failed to start timer: {e}");
-                    false
+                    None
                 }
             }
         }
 
         ClientMessage::StopTimer => {
             if !is_privileged {
-                return false;
+                return None;
             }
-            match db::set_timer_end(&state.db, board_id, None).await {
-                Ok(()) => true,
+            match db::set_timer_end(&state.db, board_id, None, None).await {
+                Ok(()) => Some(settings_change(state, board_id).await),
                 Err(e) => {
                     warn!("Failed to stop timer: {e}");
-                    false
+                    None
                 }
             }
         }
@@ -767,21 +1753,114 @@ async fn handle_message(
             // also keeps one board out of the cards of another.
             match db::ticket_in_action_column(&state.db, &ticket_id, board_id).await {
                 Ok(true) => {}
-                _ => return false,
+                _ => return None,
             }
 
             // Author, facilitator, or editor, as with a move and with the rock status.
-            match db::get_ticket_author(&state.db, &ticket_id).await {
-                Ok(Some(author_id)) if author_id == participant_id || is_privileged => {}
-                _ => return false,
-            }
+            let author_id = match db::get_ticket_author(&state.db, &ticket_id).await {
+                Ok(Some(author_id)) if author_id == participant_id || is_privileged => author_id,
+                _ => return None,
+            };
 
             let done_at = if done { Some(Utc::now()) } else { None };
+            let step = begin_step(state, OperationKind::SetTicketDone, &[&ticket_id]).await;
             match db::set_ticket_done(&state.db, &ticket_id, done_at).await {
-                Ok(()) => true,
+                Ok(()) => {
+                    finish_step(state, step, board_id, participant_id, Some(&author_id)).await;
+                    let change = ticket_change(state, &ticket_id, |column_id, ticket| {
+                        BoardPatch::TicketEdited { column_id, ticket }
+                    })
+                    .await;
+                    if done {
+                        emit_card_event(state, board_id, WebhookEvent::ActionDone, &change).await;
+                    }
+                    Some(change)
+                }
                 Err(e) => {
                     warn!("Failed to set the done mark: {e}");
-                    false
+                    None
+                }
+            }
+        }
+
+        ClientMessage::SetTicketAssignee { ticket_id, assignee } => {
+            // An owner belongs to an action, as the done mark does.
+            match db::ticket_in_action_column(&state.db, &ticket_id, board_id).await {
+                Ok(true) => {}
+                _ => return None,
+            }
+
+            // Author, facilitator, or editor, as with the done mark.
+            let author_id = match db::get_ticket_author(&state.db, &ticket_id).await {
+                Ok(Some(author_id)) if author_id == participant_id || is_privileged => author_id,
+                _ => return None,
+            };
+
+            // A team member takes the name the team gives them, not one the client makes up.
+            let assignee = match assignee {
+                None => None,
+                Some(AssigneeChoice::Participant {
+                    participant_id,
+                    name,
+                }) => Some(Assignee::Participant {
+                    participant_id,
+                    name: read_assignee_name(&name).ok()?,
+                }),
+                Some(AssigneeChoice::TeamMember { team_id, member_id }) => {
+                    match db::get_team_member(&state.db, &team_id, &member_id).await {
+                        Ok(Some(member)) => Some(Assignee::TeamMember {
+                            team_id,
+                            member_id: member.id,
+                            name: member.name,
+                        }),
+                        Ok(None) => return None,
+                        Err(e) => {
+                            warn!("Failed to read the team member: {e}");
+                            return None;
+                        }
+                    }
+                }
+            };
+
+            let step = begin_step(state, OperationKind::SetTicketAssignee, &[&ticket_id]).await;
+            match db::set_ticket_assignee(&state.db, &ticket_id, assignee.as_ref()).await {
+                Ok(()) => {
+                    finish_step(state, step, board_id, participant_id, Some(&author_id)).await;
+                    Some(ticket_change(state, &ticket_id, |column_id, ticket| {
+                        BoardPatch::TicketEdited { column_id, ticket }
+                    })
+                    .await)
+                }
+                Err(e) => {
+                    warn!("Failed to set the owner: {e}");
+                    None
+                }
+            }
+        }
+
+        ClientMessage::SetTicketDueDate { ticket_id, due_on } => {
+            match db::ticket_in_action_column(&state.db, &ticket_id, board_id).await {
+                Ok(true) => {}
+                _ => return None,
+            }
+
+            let author_id = match db::get_ticket_author(&state.db, &ticket_id).await {
+                Ok(Some(author_id)) if author_id == participant_id || is_privileged => author_id,
+                _ => return None,
+            };
+
+            let step = begin_step(state, OperationKind::SetTicketDueDate, &[&ticket_id]).await;
+            match db::set_ticket_due_on(&state.db, &ticket_id, due_on).await {
+                Ok(()) => {
+                    finish_step(state, step, board_id, participant_id, Some(&author_id)).await;
+                    Some(ticket_change(state, &ticket_id, |column_id, ticket| {
+                        BoardPatch::TicketEdited { column_id, ticket }
+                    })
+                    .await)
+                }
+                Err(e) => {
+                    warn!("Failed to set the due day: {e}");
+                    None
                 }
             }
         }
@@ -789,7 +1868,7 @@ async fn handle_message(
         ClientMessage::SetRockStatus { ticket_id, status } => {
             if let Some(ref status) = status {
                 if !valid_rock_status(status) {
-                    return false;
+                    return None;
                 }
             }
 
@@ -797,20 +1876,27 @@ async fn handle_message(
             // column — every board that is not a Level 10 board — refuses the message here.
             match db::ticket_in_rocks_column(&state.db, &ticket_id, board_id).await {
                 Ok(true) => {}
-                _ => return false,
+                _ => return None,
             }
 
             // Author, facilitator, or editor, as with a move
-            match db::get_ticket_author(&state.db, &ticket_id).await {
-                Ok(Some(author_id)) if author_id == participant_id || is_privileged => {}
-                _ => return false,
-            }
+            let author_id = match db::get_ticket_author(&state.db, &ticket_id).await {
+                Ok(Some(author_id)) if author_id == participant_id || is_privileged => author_id,
+                _ => return None,
+            };
 
+            let step = begin_step(state, OperationKind::SetRockStatus, &[&ticket_id]).await;
             match db::set_rock_status(&state.db, &ticket_id, status.as_deref()).await {
-                Ok(()) => true,
+                Ok(()) => {
+                    finish_step(state, step, board_id, participant_id, Some(&author_id)).await;
+                    Some(ticket_change(state, &ticket_id, |column_id, ticket| {
+                        BoardPatch::TicketEdited { column_id, ticket }
+                    })
+                    .await)
+                }
                 Err(e) => {
                     warn!("Failed to set rock status: {e}");
-                    false
+                    None
                 }
             }
         }
@@ -818,40 +1904,40 @@ async fn handle_message(
         ClientMessage::RateMeeting { rating } => {
             // Anyone in the meeting rates it, as anyone votes.
             if !(1..=10).contains(&rating) {
-                return false;
+                return None;
             }
             if !is_level10_board(state, board_id).await {
-                return false;
+                return None;
             }
             match db::upsert_meeting_rating(&state.db, board_id, participant_id, rating).await {
-                Ok(()) => true,
+                Ok(()) => Some(Change::Board),
                 Err(e) => {
                     warn!("Failed to rate meeting: {e}");
-                    false
+                    None
                 }
             }
         }
 
         ClientMessage::AddScorecardMetric { name, goal } => {
             if !is_privileged || !is_level10_board(state, board_id).await {
-                return false;
+                return None;
             }
             let (Some(name), Some(goal)) =
                 (clean_scorecard_field(&name), clean_scorecard_field(&goal))
             else {
-                return false;
+                return None;
             };
             // A line with no name says nothing. The goal may wait.
             if name.is_empty() {
-                return false;
+                return None;
             }
 
             let metric_id = nanoid!(8);
             match db::add_scorecard_metric(&state.db, &metric_id, board_id, &name, &goal).await {
-                Ok(()) => true,
+                Ok(()) => Some(Change::Board),
                 Err(e) => {
                     warn!("Failed to add scorecard metric: {e}");
-                    false
+                    None
                 }
             }
         }
@@ -864,17 +1950,17 @@ async fn handle_message(
             on_track,
         } => {
             if !is_privileged || !is_level10_board(state, board_id).await {
-                return false;
+                return None;
             }
             let (Some(name), Some(goal), Some(actual)) = (
                 clean_scorecard_field(&name),
                 clean_scorecard_field(&goal),
                 clean_scorecard_field(&actual),
             ) else {
-                return false;
+                return None;
             };
             if name.is_empty() {
-                return false;
+                return None;
             }
 
             // The board is part of the WHERE, so a line of another board does not answer.
@@ -889,25 +1975,25 @@ async fn handle_message(
             )
             .await
             {
-                Ok(true) => true,
-                Ok(false) => false,
+                Ok(true) => Some(Change::Board),
+                Ok(false) => None,
                 Err(e) => {
                     warn!("Failed to update scorecard metric: {e}");
-                    false
+                    None
                 }
             }
         }
 
         ClientMessage::RemoveScorecardMetric { metric_id } => {
             if !is_privileged || !is_level10_board(state, board_id).await {
-                return false;
+                return None;
             }
             match db::remove_scorecard_metric(&state.db, &metric_id, board_id).await {
-                Ok(true) => true,
-                Ok(false) => false,
+                Ok(true) => Some(Change::Board),
+                Ok(false) => None,
                 Err(e) => {
                     warn!("Failed to remove scorecard metric: {e}");
-                    false
+                    None
                 }
             }
         }
@@ -915,26 +2001,26 @@ async fn handle_message(
         ClientMessage::RequestEditor { name } => {
             // Can't request if already facilitator or editor
             if is_facilitator || is_editor {
-                return false;
+                return None;
             }
             // For anonymous boards, a name must be provided
             let request_name = if let Some(n) = name {
                 if n.trim().is_empty() {
-                    return false;
+                    return None;
                 }
                 n.trim().to_string()
             } else if participant_name.is_empty() {
                 // Anonymous board with no name provided
-                return false;
+                return None;
             } else {
                 participant_name.to_string()
             };
 
             match db::create_editor_request(&state.db, board_id, participant_id, &request_name).await {
-                Ok(()) => true,
+                Ok(()) => Some(Change::Board),
                 Err(e) => {
                     warn!("Failed to create editor request: {e}");
-                    false
+                    None
                 }
             }
         }
@@ -942,14 +2028,25 @@ async fn handle_message(
         ClientMessage::ApproveEditor { participant_id: target_id } => {
             // Only facilitator can approve
             if !is_facilitator {
-                return false;
+                return None;
             }
             match db::approve_editor(&state.db, board_id, &target_id).await {
-                Ok(true) => true,
-                Ok(false) => false,
+                Ok(true) => {
+                    audit::record(
+                        state,
+                        Some(board_id),
+                        &actor,
+                        AuditAction::EditorApprove,
+                        Some(&format!("participant:{target_id}")),
+                        None,
+                    )
+                    .await;
+                    Some(Change::Board)
+                }
+                Ok(false) => None,
                 Err(e) => {
                     warn!("Failed to approve editor: {e}");
-                    false
+                    None
                 }
             }
         }
@@ -957,14 +2054,25 @@ async fn handle_message(
         ClientMessage::DeclineEditor { participant_id: target_id } => {
             // Only facilitator can decline
             if !is_facilitator {
-                return false;
+                return None;
             }
             match db::decline_editor(&state.db, board_id, &target_id).await {
-                Ok(true) => true,
-                Ok(false) => false,
+                Ok(true) => {
+                    audit::record(
+                        state,
+                        Some(board_id),
+                        &actor,
+                        AuditAction::EditorDecline,
+                        Some(&format!("participant:{target_id}")),
+                        None,
+                    )
+                    .await;
+                    Some(Change::Board)
+                }
+                Ok(false) => None,
                 Err(e) => {
                     warn!("Failed to decline editor: {e}");
-                    false
+                    None
                 }
             }
         }
@@ -972,16 +2080,264 @@ async fn handle_message(
         ClientMessage::RemoveEditor { participant_id: target_id } => {
             // Only facilitator can remove editors
             if !is_facilitator {
-                return false;
+                return None;
             }
             match db::remove_editor(&state.db, board_id, &target_id).await {
-                Ok(true) => true,
-                Ok(false) => false,
+                Ok(true) => {
+                    audit::record(
+                        state,
+                        Some(board_id),
+                        &actor,
+                        AuditAction::EditorRemove,
+                        Some(&format!("participant:{target_id}")),
+                        None,
+                    )
+                    .await;
+                    Some(Change::Board)
+                }
+                Ok(false) => None,
                 Err(e) => {
                     warn!("Failed to remove editor: {e}");
-                    false
+                    None
                 }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TokenScope;
+
+    /// One of each message a client can send. The match in `name` has no catch-all, so a message
+    /// added to the protocol does not build here until it has a name, and a sample below.
+    fn every_message() -> Vec<ClientMessage> {
+        let id = || "t1".to_string();
+        vec![
+            ClientMessage::Join {
+                participant_name: "Ann".to_string(),
+                facilitator_token: None,
+                participant_id: None,
+                participant_token: None,
+                access_token: None,
+            },
+            ClientMessage::AddTicket { column_id: id(), content: "c".to_string(), gif: None },
+            ClientMessage::RemoveTicket { ticket_id: id() },
+            ClientMessage::EditTicket { ticket_id: id(), content: "c".to_string(), gif: None },
+            ClientMessage::MoveTicket { ticket_id: id(), column_id: id() },
+            ClientMessage::AddComment { ticket_id: id(), content: "c".to_string(), gif: None },
+            ClientMessage::EditComment { comment_id: id(), content: "c".to_string(), gif: None },
+            ClientMessage::RemoveComment { comment_id: id() },
+            ClientMessage::ToggleVote { ticket_id: id() },
+            ClientMessage::ToggleBlur,
+            ClientMessage::ToggleHideVotes,
+            ClientMessage::ToggleCardsLocked,
+            ClientMessage::SetPhase { phase: Phase::Write },
+            ClientMessage::CloseBoard,
+            ClientMessage::ReopenBoard,
+            ClientMessage::MergeTickets { source_ticket_id: id(), target_ticket_id: id() },
+            ClientMessage::UndoMerge,
+            ClientMessage::SplitTicket { ticket_id: id(), segment_index: 1 },
+            ClientMessage::SetVoteLimit { limit: Some(3) },
+            ClientMessage::StartTimer { duration_secs: 60, on_expiry: None },
+            ClientMessage::StopTimer,
+            ClientMessage::SetTicketDone { ticket_id: id(), done: true },
+            ClientMessage::SetTicketAssignee { ticket_id: id(), assignee: None },
+            ClientMessage::SetTicketDueDate { ticket_id: id(), due_on: None },
+            ClientMessage::SetRockStatus { ticket_id: id(), status: None },
+            ClientMessage::RateMeeting { rating: 7 },
+            ClientMessage::AddScorecardMetric { name: "n".to_string(), goal: "g".to_string() },
+            ClientMessage::UpdateScorecardMetric {
+                metric_id: id(),
+                name: "n".to_string(),
+                goal: "g".to_string(),
+                actual: "a".to_string(),
+                on_track: None,
+            },
+            ClientMessage::RemoveScorecardMetric { metric_id: id() },
+            ClientMessage::RequestEditor { name: None },
+            ClientMessage::ApproveEditor { participant_id: id() },
+            ClientMessage::DeclineEditor { participant_id: id() },
+            ClientMessage::RemoveEditor { participant_id: id() },
+            ClientMessage::AddColumn { name: "n".to_string() },
+            ClientMessage::RenameColumn { column_id: id(), name: "n".to_string() },
+            ClientMessage::ReorderColumns { column_ids: vec![id()] },
+            ClientMessage::RemoveColumn { column_id: id(), target_column_id: None },
+            ClientMessage::Undo,
+            ClientMessage::Redo,
+            ClientMessage::Resync,
+        ]
+    }
+
+    fn name(msg: &ClientMessage) -> &'static str {
+        match msg {
+            ClientMessage::Join { .. } => "Join",
+            ClientMessage::AddTicket { .. } => "AddTicket",
+            ClientMessage::RemoveTicket { .. } => "RemoveTicket",
+            ClientMessage::EditTicket { .. } => "EditTicket",
+            ClientMessage::MoveTicket { .. } => "MoveTicket",
+            ClientMessage::AddComment { .. } => "AddComment",
+            ClientMessage::EditComment { .. } => "EditComment",
+            ClientMessage::RemoveComment { .. } => "RemoveComment",
+            ClientMessage::ToggleVote { .. } => "ToggleVote",
+            ClientMessage::ToggleBlur => "ToggleBlur",
+            ClientMessage::ToggleHideVotes => "ToggleHideVotes",
+            ClientMessage::ToggleCardsLocked => "ToggleCardsLocked",
+            ClientMessage::SetPhase { .. } => "SetPhase",
+            ClientMessage::CloseBoard => "CloseBoard",
+            ClientMessage::ReopenBoard => "ReopenBoard",
+            ClientMessage::MergeTickets { .. } => "MergeTickets",
+            ClientMessage::UndoMerge => "UndoMerge",
+            ClientMessage::SplitTicket { .. } => "SplitTicket",
+            ClientMessage::SetVoteLimit { .. } => "SetVoteLimit",
+            ClientMessage::StartTimer { .. } => "StartTimer",
+            ClientMessage::StopTimer => "StopTimer",
+            ClientMessage::SetTicketDone { .. } => "SetTicketDone",
+            ClientMessage::SetTicketAssignee { .. } => "SetTicketAssignee",
+            ClientMessage::SetTicketDueDate { .. } => "SetTicketDueDate",
+            ClientMessage::SetRockStatus { .. } => "SetRockStatus",
+            ClientMessage::RateMeeting { .. } => "RateMeeting",
+            ClientMessage::AddScorecardMetric { .. } => "AddScorecardMetric",
+            ClientMessage::UpdateScorecardMetric { .. } => "UpdateScorecardMetric",
+            ClientMessage::RemoveScorecardMetric { .. } => "RemoveScorecardMetric",
+            ClientMessage::RequestEditor { .. } => "RequestEditor",
+            ClientMessage::ApproveEditor { .. } => "ApproveEditor",
+            ClientMessage::DeclineEditor { .. } => "DeclineEditor",
+            ClientMessage::RemoveEditor { .. } => "RemoveEditor",
+            ClientMessage::AddColumn { .. } => "AddColumn",
+            ClientMessage::RenameColumn { .. } => "RenameColumn",
+            ClientMessage::ReorderColumns { .. } => "ReorderColumns",
+            ClientMessage::RemoveColumn { .. } => "RemoveColumn",
+            ClientMessage::Undo => "Undo",
+            ClientMessage::Redo => "Redo",
+            ClientMessage::Resync => "Resync",
+        }
+    }
+
+    #[test]
+    fn a_socket_is_not_opened_with_an_api_token() {
+        let browser = Owner {
+            facilitator_id: Some("f1".to_string()),
+            sub: None,
+            scopes: None,
+            token_id: None,
+        };
+        assert!(refuse_token(&browser).is_ok());
+
+        // Not even with every scope: the socket has none of its own to check.
+        for scopes in [vec![], vec![TokenScope::BoardRead], vec![TokenScope::BoardWrite]] {
+            let token = Owner {
+                facilitator_id: Some("f1".to_string()),
+                sub: None,
+                scopes: Some(scopes),
+                token_id: Some("t1".to_string()),
+            };
+            assert!(matches!(refuse_token(&token), Err(AppError::Forbidden(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn a_socket_that_fell_behind_gets_the_board_again_redacted_for_its_reader() {
+        let Some(pool) = db::tests::test_pool().await else {
+            return;
+        };
+        let board_id = db::tests::test_board(&pool, &nanoid!(), None).await;
+        sqlx::query("UPDATE boards SET is_blurred = true WHERE id = $1")
+            .bind(&board_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let column_id = db::get_board(&pool, &board_id).await.unwrap().unwrap().columns[0].id.clone();
+        for (author, content) in [("ann", "Ann wrote this"), ("bob", "Bob wrote this")] {
+            db::add_ticket(&pool, &nanoid!(), &column_id, content, author, author, Utc::now(), None)
+                .await
+                .unwrap();
+        }
+        let state = AppState::for_tests(pool);
+
+        let (tx, mut rx) = broadcast::channel(2);
+        for _ in 0..4 {
+            tx.send(ServerMessage::Error { message: "missed".to_string() }).unwrap();
+        }
+        let Err(RecvError::Lagged(skipped)) = rx.recv().await else {
+            panic!("the receiver should have fallen behind");
+        };
+        let caught_up = catch_up(&state, &board_id, "ann", &mut rx, skipped).await;
+
+        let mut reader = Reader::new("ann".to_string(), false);
+        let Some(ServerMessage::BoardState { board }) = caught_up.and_then(|m| reader.outgoing(m))
+        else {
+            panic!("a socket that fell behind should get the whole board");
+        };
+        let mut contents: Vec<&str> =
+            board.columns[0].tickets.iter().map(|t| t.content.as_str()).collect();
+        contents.sort();
+        assert_eq!(contents.len(), 2);
+        assert!(contents.contains(&"Ann wrote this"));
+        assert!(!contents.contains(&"Bob wrote this"), "another reader's card stays hidden");
+
+        // What was waiting is skipped, and what comes next arrives.
+        assert!(rx.try_recv().is_err());
+        tx.send(ServerMessage::Error { message: "next".to_string() }).unwrap();
+        assert!(matches!(rx.recv().await, Ok(ServerMessage::Error { message }) if message == "next"));
+    }
+
+    #[tokio::test]
+    async fn a_closed_board_turns_away_every_message_but_being_opened_again() {
+        let Some(pool) = db::tests::test_pool().await else {
+            return;
+        };
+        let board_id = db::tests::test_board(&pool, &nanoid!(), None).await;
+        let column_id = db::get_board(&pool, &board_id).await.unwrap().unwrap().columns[0].id.clone();
+        let ticket_id = nanoid!();
+        let content = "As it was left";
+        db::add_ticket(&pool, &ticket_id, &column_id, content, "ann", "ann", Utc::now(), None)
+            .await
+            .unwrap();
+        assert!(db::close_board(&pool, &board_id).await.unwrap());
+        let state = AppState::for_tests(pool.clone());
+        let (direct_tx, mut direct_rx) = mpsc::unbounded_channel();
+
+        let edit = ClientMessage::EditTicket {
+            ticket_id: ticket_id.clone(),
+            content: "Changed after the close".to_string(),
+            gif: None,
+        };
+        take_message(&state, &board_id, "ann", "Ann", true, edit, &direct_tx).await;
+        assert!(matches!(
+            direct_rx.try_recv(),
+            Ok(ServerMessage::Error { message }) if message == CLOSED_BOARD
+        ));
+        let board = db::get_board(&pool, &board_id).await.unwrap().unwrap();
+        assert_eq!(board.columns[0].tickets[0].content, "As it was left");
+
+        // Asking for the board again is a read, and opening it again is what a closed board is for.
+        let messages = every_message();
+        let names: std::collections::HashSet<&str> = messages.iter().map(name).collect();
+        assert_eq!(names.len(), messages.len(), "each message once");
+        for msg in messages {
+            let kind = name(&msg);
+            if matches!(msg, ClientMessage::Resync | ClientMessage::ReopenBoard) {
+                continue;
+            }
+            take_message(&state, &board_id, "ann", "Ann", true, msg, &direct_tx).await;
+            assert!(
+                matches!(
+                    direct_rx.try_recv(),
+                    Ok(ServerMessage::Error { message }) if message == CLOSED_BOARD
+                ),
+                "a closed board should turn away {kind}"
+            );
+            assert!(db::is_archived(&pool, &board_id).await.unwrap(), "{kind} left it closed");
+        }
+        let board = db::get_board(&pool, &board_id).await.unwrap().unwrap();
+        assert_eq!(board.columns[0].tickets.len(), 1);
+        assert_eq!(board.columns[0].tickets[0].content, "As it was left");
+
+        take_message(&state, &board_id, "ann", "Ann", true, ClientMessage::ReopenBoard, &direct_tx)
+            .await;
+        assert!(direct_rx.try_recv().is_err(), "opening the board again is no error");
+        assert!(!db::is_archived(&pool, &board_id).await.unwrap());
+    }
+}
@@ -1,6 +1,7 @@
-use crate::models::{Gif, Participant};
+use crate::db;
+use crate::fanout::Fanout;
+use crate::models::Participant;
 use crate::protocol::ServerMessage;
-use chrono::{DateTime, Utc};
 use sqlx::PgPool;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -8,64 +9,93 @@ use tokio::sync::{broadcast, RwLock};
 
 pub type BoardChannel = broadcast::Sender<ServerMessage>;
 
-#[derive(Debug, Clone)]
-pub struct MergeSnapshot {
-    pub source_id: String,
-    pub source_column_id: String,
-    pub source_content: String,
-    pub source_author_id: String,
-    pub source_author_name: String,
-    pub source_created_at: DateTime<Utc>,
-    pub source_votes: Vec<String>,
-    pub source_carried_from_board_id: Option<String>,
-    pub source_carried_from_board_title: Option<String>,
-    /// The comments that moved to the target card with the merge. An undo sends them back.
-    pub source_comment_ids: Vec<String>,
-    /// The GIF of the source card. A merge onto a card with no GIF hands this over,
-    /// so the undo has to know where it came from.
-    pub source_gif: Option<Gif>,
-    /// The rock status of the source card. A merge keeps the mark of the target,
-    /// so this one only comes back with the undo.
-    pub source_rock_status: Option<String>,
-    /// When the source action was closed, for the same reason as the rock status above.
-    pub source_done_at: Option<DateTime<Utc>>,
-    pub target_id: String,
-    pub target_original_content: String,
-    pub target_original_gif: Option<Gif>,
+/// Sends a message to the sockets of a board on this replica. One that finds no socket left is
+/// counted and goes nowhere.
+pub fn send_local(tx: &BoardChannel, msg: ServerMessage) {
+    if tx.send(msg).is_err() {
+        crate::metrics::METRICS.broadcast_dropped.inc();
+    }
 }
 
 #[derive(Clone)]
 pub struct AppState {
     pub db: PgPool,
+    /// The participants whose sockets this replica holds. The count of a board spans every
+    /// replica and comes from the database; this only tells when the last local socket is gone.
     pub participants: Arc<RwLock<HashMap<String, Vec<Participant>>>>,
+    /// The boards this replica has sockets for. Each one is followed on the fan-out.
     pub channels: Arc<RwLock<HashMap<String, BoardChannel>>>,
     pub admin_token_hash: Option<String>,
-    pub last_merge: Arc<RwLock<HashMap<String, MergeSnapshot>>>,
     /// The GIPHY key from the Kubernetes secret. None leaves the GIF controls out of the frontend.
     pub giphy_api_key: Option<String>,
-    /// The Entra app registration this deployment signs people in with. None leaves the server as
-    /// open as it was — no door, and nothing in the frontend that mentions one.
-    pub entra: Option<Arc<crate::auth::EntraAuth>>,
+    /// The identity provider this deployment signs people in with. None leaves the server as open
+    /// as it was — no door, and nothing in the frontend that mentions one.
+    pub oidc: Option<Arc<crate::auth::OidcAuth>>,
+    /// How this replica reaches the sockets of the others.
+    pub fanout: Arc<Fanout>,
+    /// How this replica sends the webhooks of the boards.
+    pub webhooks: Arc<crate::webhooks::Webhooks>,
+    /// The retention policy of the environment. One an admin sets over the API takes its place.
+    pub retention: Arc<crate::models::RetentionPolicy>,
+    /// How many proxies stand between this server and the client. See `auth::ClientAddr`.
+    pub trusted_proxy_hops: usize,
+    /// The bearer token a scrape of `/metrics` has to send. None leaves the metrics open.
+    pub metrics_token: Option<String>,
+    /// How many messages the channel of a board holds for a socket that has not sent them on yet.
+    /// A socket further behind than this skips to the whole board.
+    pub channel_capacity: usize,
+    /// The key the credentials of the participants are signed with. See `credentials`.
+    pub participant_key: String,
 }
 
+/// The capacity of a board channel when the environment names none.
+pub const DEFAULT_CHANNEL_CAPACITY: usize = 64;
+
 impl AppState {
     pub fn new(
         db: PgPool,
         admin_token_hash: Option<String>,
         giphy_api_key: Option<String>,
-        entra: Option<Arc<crate::auth::EntraAuth>>,
+        oidc: Option<Arc<crate::auth::OidcAuth>>,
+        fanout: Fanout,
+        webhooks: crate::webhooks::Webhooks,
+        retention: crate::models::RetentionPolicy,
     ) -> Self {
         Self {
             db,
             participants: Arc::new(RwLock::new(HashMap::new())),
             channels: Arc::new(RwLock::new(HashMap::new())),
             admin_token_hash,
-            last_merge: Arc::new(RwLock::new(HashMap::new())),
             giphy_api_key,
-            entra,
+            oidc,
+            fanout: Arc::new(fanout),
+            webhooks: Arc::new(webhooks),
+            retention: Arc::new(retention),
+            trusted_proxy_hops: 0,
+            metrics_token: None,
+            channel_capacity: DEFAULT_CHANNEL_CAPACITY,
+            // A key of this process alone. The server reads the one every replica shares from the
+            // database in its place.
+            participant_key: nanoid::nanoid!(43),
         }
     }
 
+    /// A state on `db` and nothing else, for the tests that call a handler or a socket task. Its
+    /// fan-out listens to nothing.
+    #[cfg(test)]
+    pub fn for_tests(db: PgPool) -> Self {
+        let (fanout, _) = Fanout::new();
+        Self::new(
+            db,
+            None,
+            None,
+            None,
+            fanout,
+            crate::webhooks::Webhooks::from_env(),
+            Default::default(),
+        )
+    }
+
     pub async fn get_or_create_channel(&self, board_id: &str) -> BoardChannel {
         let channels = self.channels.read().await;
         if let Some(tx) = channels.get(board_id) {
@@ -78,13 +108,33 @@ impl AppState {
         if let Some(tx) = channels.get(board_id) {
             return tx.clone();
         }
-        let (tx, _) = broadcast::channel(64);
+        let (tx, _) = broadcast::channel(self.channel_capacity);
         channels.insert(board_id.to_string(), tx.clone());
+        self.fanout.follow(board_id);
         tx
     }
 
+    /// The channel of a board this replica has sockets for. None when it has none, and then a
+    /// change of the board has nobody here to reach.
+    pub async fn local_channel(&self, board_id: &str) -> Option<BoardChannel> {
+        self.channels.read().await.get(board_id).cloned()
+    }
+
+    /// Drops the channel of a board this replica no longer has sockets for.
+    pub async fn drop_channel(&self, board_id: &str) {
+        if self.channels.write().await.remove(board_id).is_some() {
+            self.fanout.unfollow(board_id);
+        }
+    }
+
+    /// The open sockets on a board, across every replica.
     pub async fn participant_count(&self, board_id: &str) -> usize {
-        let participants = self.participants.read().await;
-        participants.get(board_id).map(|v| v.len()).unwrap_or(0)
+        match db::count_presence(&self.db, board_id).await {
+            Ok(count) => count as usize,
+            Err(e) => {
+                tracing::warn!("Failed to count the participants: {e}");
+                0
+            }
+        }
     }
 }
@@ -0,0 +1,230 @@
+//! How fast one socket may change a board.
+//!
+//! Each message that changes a board costs a write to the database and a patch, or the whole
+//! board, sent to everyone on it. A client that misbehaves, or a key held down, would otherwise
+//! send as many as the connection carries. Each socket keeps a token bucket for each class of
+//! message: a burst goes through at once, and past it the messages of that class are dropped until
+//! the bucket fills again. The classes are apart so that a flood of votes does not stop the
+//! facilitator's timer.
+
+use std::collections::HashMap;
+use std::time::Instant;
+
+use crate::protocol::ClientMessage;
+
+/// The largest message a socket takes, frames and all. A card at its longest, with a GIF and the
+/// JSON around it, is well under a tenth of it.
+pub const MAX_MESSAGE_BYTES: usize = 32 * 1024;
+
+/// The messages of a socket, sorted by what each one costs the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RateClass {
+    /// New words on the board: a card, a comment, a split, a scorecard line, a column.
+    Write,
+    /// A change to what is there: an edit, a move, a merge, a mark on a card.
+    Change,
+    /// A vote, or a rating of the meeting.
+    Vote,
+    /// The controls of the facilitator and the editors, and the requests for them.
+    Control,
+    /// An undo or a redo.
+    History,
+    /// The whole board, sent again to this socket.
+    Resync,
+}
+
+impl RateClass {
+    pub fn of(msg: &ClientMessage) -> Self {
+        match msg {
+            ClientMessage::AddTicket { .. }
+            | ClientMessage::AddComment { .. }
+            | ClientMessage::SplitTicket { .. }
+            | ClientMessage::AddScorecardMetric { .. }
+            | ClientMessage::AddColumn { .. } => RateClass::Write,
+            ClientMessage::EditTicket { .. }
+            | ClientMessage::RemoveTicket { .. }
+            | ClientMessage::MoveTicket { .. }
+            | ClientMessage::EditComment { .. }
+            | ClientMessage::RemoveComment { .. }
+            | ClientMessage::MergeTickets { .. }
+            | ClientMessage::UndoMerge
+            | ClientMessage::SetTicketDone { .. }
+            | ClientMessage::SetTicketAssignee { .. }
+            | ClientMessage::SetTicketDueDate { .. }
+            | ClientMessage::SetRockStatus { .. }
+            | ClientMessage::UpdateScorecardMetric { .. }
+            | ClientMessage::RemoveScorecardMetric { .. } => RateClass::Change,
+            ClientMessage::ToggleVote { .. } | ClientMessage::RateMeeting { .. } => {
+                RateClass::Vote
+            }
+            ClientMessage::Undo | ClientMessage::Redo => RateClass::History,
+            ClientMessage::Resync => RateClass::Resync,
+            ClientMessage::Join { .. }
+            | ClientMessage::ToggleBlur
+            | ClientMessage::ToggleHideVotes
+            | ClientMessage::ToggleCardsLocked
+            | ClientMessage::SetPhase { .. }
+            | ClientMessage::CloseBoard
+            | ClientMessage::ReopenBoard
+            | ClientMessage::SetVoteLimit { .. }
+            | ClientMessage::StartTimer { .. }
+            | ClientMessage::StopTimer
+            | ClientMessage::RequestEditor { .. }
+            | ClientMessage::ApproveEditor { .. }
+            | ClientMessage::DeclineEditor { .. }
+            | ClientMessage::RemoveEditor { .. }
+            | ClientMessage::RenameColumn { .. }
+            | ClientMessage::ReorderColumns { .. }
+            | ClientMessage::RemoveColumn { .. } => RateClass::Control,
+        }
+    }
+
+    /// How many go through at once, and how many a second after that.
+    fn limit(self) -> (f64, f64) {
+        match self {
+            RateClass::Write => (10.0, 1.0),
+            RateClass::Change => (30.0, 5.0),
+            RateClass::Vote => (30.0, 5.0),
+            RateClass::Control => (20.0, 2.0),
+            RateClass::History => (20.0, 5.0),
+            RateClass::Resync => (3.0, 0.2),
+        }
+    }
+
+    /// The class as the metrics name it.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RateClass::Write => "write",
+            RateClass::Change => "change",
+            RateClass::Vote => "vote",
+            RateClass::Control => "control",
+            RateClass::History => "history",
+            RateClass::Resync => "resync",
+        }
+    }
+
+    /// The messages of the class, as the person who sent them would name them.
+    fn name(self) -> &'static str {
+        match self {
+            RateClass::Write => "new cards and comments",
+            RateClass::Change => "changes to cards",
+            RateClass::Vote => "votes",
+            RateClass::Control => "changes to the board",
+            RateClass::History => "undos",
+            RateClass::Resync => "reloads of the board",
+        }
+    }
+}
+
+/// What the throttle says about one message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Allowed,
+    /// The message is dropped. The first one dropped in a row carries the words to tell the
+    /// client; the ones after it are dropped without a word, so that a flood is not answered
+    /// with another.
+    Dropped(Option<String>),
+}
+
+struct Bucket {
+    tokens: f64,
+    filled_at: Instant,
+    dropping: bool,
+}
+
+/// The buckets of one socket.
+#[derive(Default)]
+pub struct Throttle {
+    buckets: HashMap<RateClass, Bucket>,
+}
+
+impl Throttle {
+    /// Takes a token for a message of `class`, sent at `now`.
+    pub fn check(&mut self, class: RateClass, now: Instant) -> Verdict {
+        let (burst, per_second) = class.limit();
+        let bucket = self.buckets.entry(class).or_insert(Bucket {
+            tokens: burst,
+            filled_at: now,
+            dropping: false,
+        });
+
+        let elapsed = now.saturating_duration_since(bucket.filled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * per_second).min(burst);
+        bucket.filled_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            bucket.dropping = false;
+            return Verdict::Allowed;
+        }
+
+        let first = !bucket.dropping;
+        bucket.dropping = true;
+        Verdict::Dropped(first.then(|| {
+            format!(
+                "Too many {} at once. That one was not kept; wait a moment and try again.",
+                class.name()
+            )
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn a_burst_goes_through_and_the_rest_waits_for_the_bucket() {
+        let mut throttle = Throttle::default();
+        let now = Instant::now();
+        for _ in 0..10 {
+            assert_eq!(throttle.check(RateClass::Write, now), Verdict::Allowed);
+        }
+        assert!(matches!(
+            throttle.check(RateClass::Write, now),
+            Verdict::Dropped(Some(_))
+        ));
+        // A second later, one more card fits.
+        let later = now + Duration::from_secs(1);
+        assert_eq!(throttle.check(RateClass::Write, later), Verdict::Allowed);
+        assert!(matches!(
+            throttle.check(RateClass::Write, later),
+            Verdict::Dropped(Some(_))
+        ));
+    }
+
+    #[test]
+    fn only_the_first_message_dropped_in_a_row_is_answered() {
+        let mut throttle = Throttle::default();
+        let now = Instant::now();
+        for _ in 0..3 {
+            throttle.check(RateClass::Resync, now);
+        }
+        assert!(matches!(
+            throttle.check(RateClass::Resync, now),
+            Verdict::Dropped(Some(_))
+        ));
+        assert_eq!(throttle.check(RateClass::Resync, now), Verdict::Dropped(None));
+    }
+
+    #[test]
+    fn each_class_has_a_bucket_of_its_own() {
+        let mut throttle = Throttle::default();
+        let now = Instant::now();
+        while throttle.check(RateClass::Vote, now) == Verdict::Allowed {}
+        assert_eq!(throttle.check(RateClass::Control, now), Verdict::Allowed);
+    }
+
+    #[test]
+    fn a_bucket_fills_no_further_than_its_burst() {
+        let mut throttle = Throttle::default();
+        let now = Instant::now();
+        throttle.check(RateClass::Write, now);
+        let much_later = now + Duration::from_secs(3600);
+        for _ in 0..10 {
+            assert_eq!(throttle.check(RateClass::Write, much_later), Verdict::Allowed);
+        }
+        assert_ne!(throttle.check(RateClass::Write, much_later), Verdict::Allowed);
+    }
+}
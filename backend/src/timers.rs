@@ -0,0 +1,145 @@
+//! Ends the timers of the boards on the server.
+//!
+//! A client counts down on its own from the end it was sent; the server is where the end
+//! actually happens. Each replica looks for timers that ran out, and the one that takes a timer
+//! tells every socket of the board, does what the facilitator asked for when the timer started,
+//! and lets the webhooks know. The end lives in the board row, so a timer that runs out while no
+//! replica is up is ended by the first one to start again.
+
+use std::time::Duration;
+use tracing::warn;
+
+use crate::db::{self, ExpiredTimer};
+use crate::models::{Phase, TimerAction};
+use crate::routes::ws::{broadcast_board_state, broadcast_settings, broadcast_timer_expired};
+use crate::state::AppState;
+use crate::webhooks::{self, WebhookEvent};
+
+/// How often a replica looks for timers that ran out. A timer ends at most this late.
+const TICK: Duration = Duration::from_secs(1);
+
+/// Ends the timers that run out, for the life of the process.
+pub async fn run(state: AppState) {
+    let mut interval = tokio::time::interval(TICK);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        match db::take_expired_timers(&state.db).await {
+            Ok(timers) => {
+                for timer in timers {
+                    expire(&state, timer).await;
+                }
+            }
+            Err(e) => warn!("Failed to read the timers: {e}"),
+        }
+    }
+}
+
+async fn expire(state: &AppState, timer: ExpiredTimer) {
+    let ExpiredTimer { board_id, action } = timer;
+    broadcast_timer_expired(state, &board_id, action).await;
+    if let Some(action) = action {
+        run_action(state, &board_id, action).await;
+    }
+    webhooks::emit(state, &board_id, WebhookEvent::TimerEnded, None).await;
+}
+
+async fn run_action(state: &AppState, board_id: &str, action: TimerAction) {
+    // A board that runs through phases moves on to the vote as well, if it has one and has not
+    // gone past it while the timer ran.
+    if action == TimerAction::StartVoting {
+        if let Err(e) = db::advance_phase(&state.db, board_id, Phase::Vote).await {
+            warn!("Failed to start the vote when the timer ran out: {e}");
+        }
+    }
+    if action.locks_cards() {
+        if let Err(e) = db::set_cards_locked(&state.db, board_id, true).await {
+            warn!("Failed to lock the cards when the timer ran out: {e}");
+            return;
+        }
+    }
+
+    if !action.reveals() {
+        broadcast_settings(state, board_id).await;
+        return;
+    }
+    let was_blurred = match db::get_blur_state(&state.db, board_id).await {
+        Ok(Some(is_blurred)) => is_blurred,
+        Ok(None) => return,
+        Err(e) => {
+            warn!("Failed to read the blur when the timer ran out: {e}");
+            return;
+        }
+    };
+    if was_blurred {
+        if let Err(e) = db::set_blur(&state.db, board_id, false).await {
+            warn!("Failed to reveal the board when the timer ran out: {e}");
+            return;
+        }
+        webhooks::emit(state, board_id, WebhookEvent::BoardRevealed, None).await;
+    }
+    // A reveal changes the words every reader gets, so the whole board goes out, the lock with it.
+    broadcast_board_state(state, board_id).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    async fn board_in(pool: &sqlx::PgPool, phase: Phase) -> String {
+        let board_id = db::tests::test_board(pool, &nanoid::nanoid!(), None).await;
+        sqlx::query(
+            "UPDATE boards SET phases = ARRAY['write', 'vote', 'discuss', 'closed'], phase = $1 \
+             WHERE id = $2",
+        )
+        .bind(phase.as_str())
+        .bind(&board_id)
+        .execute(pool)
+        .await
+        .unwrap();
+        board_id
+    }
+
+    async fn phase_of(pool: &sqlx::PgPool, board_id: &str) -> Option<Phase> {
+        db::get_board(pool, board_id).await.unwrap().unwrap().phase
+    }
+
+    #[tokio::test]
+    async fn a_vote_timer_does_not_open_a_board_that_was_closed_while_it_ran() {
+        let Some(pool) = db::tests::test_pool().await else {
+            return;
+        };
+        let board_id = board_in(&pool, Phase::Write).await;
+        let end = Utc::now() + chrono::Duration::minutes(5);
+        db::set_timer_end(&pool, &board_id, Some(end), Some(TimerAction::StartVoting))
+            .await
+            .unwrap();
+
+        assert!(db::set_phase(&pool, &board_id, Phase::Closed).await.unwrap());
+        let board = db::get_board(&pool, &board_id).await.unwrap().unwrap();
+        assert_eq!(board.timer_end, None);
+        assert_eq!(board.timer_action, None);
+
+        // Had the timer been taken before the board closed, its end still leaves it closed.
+        let state = AppState::for_tests(pool.clone());
+        run_action(&state, &board_id, TimerAction::StartVoting).await;
+        assert_eq!(phase_of(&pool, &board_id).await, Some(Phase::Closed));
+    }
+
+    #[tokio::test]
+    async fn a_vote_timer_moves_the_board_on_only_from_before_the_vote() {
+        let Some(pool) = db::tests::test_pool().await else {
+            return;
+        };
+        let state = AppState::for_tests(pool.clone());
+
+        let writing = board_in(&pool, Phase::Write).await;
+        run_action(&state, &writing, TimerAction::StartVoting).await;
+        assert_eq!(phase_of(&pool, &writing).await, Some(Phase::Vote));
+
+        let discussing = board_in(&pool, Phase::Discuss).await;
+        run_action(&state, &discussing, TimerAction::StartVoting).await;
+        assert_eq!(phase_of(&pool, &discussing).await, Some(Phase::Discuss));
+    }
+}
@@ -0,0 +1,122 @@
+//! Personal API tokens, for the scripts that call the REST API.
+//!
+//! A script has no browser: no facilitator cookie, and no session to get past a sign-in with. A
+//! token stands in for both. It acts for the person who made it — the boards they would be the
+//! facilitator of in their own browser are the boards it reaches — and only within the scopes it
+//! was given, so a script that carries actions over cannot also change a board's password.
+//!
+//! A token reads `rwd_<id>.<secret>`. The id is what a request is looked up by; the secret is kept
+//! only as an Argon2 hash, the same way as the admin token, so the database cannot hand one back.
+
+use axum::http::{header, HeaderMap};
+use tracing::warn;
+
+use crate::auth::Owner;
+use crate::db;
+use crate::error::AppError;
+use crate::models::{read_token_request, ApiCaller, CreatedApiToken, TokenScope};
+use crate::password;
+use crate::state::AppState;
+
+/// What every token starts with, so that it is told apart from the admin token, and so that a
+/// secret scanner can tell one in a leaked file.
+pub const TOKEN_PREFIX: &str = "rwd_";
+
+/// The bearer credential of a request, if it carries one.
+pub fn bearer(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim)
+}
+
+/// Splits a token into its id and its secret. Anything not in the shape of one is None, and is
+/// then not a personal token at all — the admin token, most likely.
+pub fn split(token: &str) -> Option<(&str, &str)> {
+    let (id, secret) = token.strip_prefix(TOKEN_PREFIX)?.split_once('.')?;
+    (!id.is_empty() && !secret.is_empty()).then_some((id, secret))
+}
+
+/// Makes a token for the caller. The secret is in the answer and nowhere else.
+pub async fn mint(
+    state: &AppState,
+    owner: &Owner,
+    name: &str,
+    scopes: &[TokenScope],
+) -> Result<CreatedApiToken, AppError> {
+    let (name, scopes) = read_token_request(name, scopes).map_err(AppError::BadRequest)?;
+
+    let id = nanoid::nanoid!(12);
+    let secret = nanoid::nanoid!(32);
+    let secret_hash = password::hash(secret.clone()).await?;
+    let token = db::create_api_token(
+        &state.db,
+        &id,
+        &name,
+        &secret_hash,
+        &scopes,
+        owner.sub.as_deref(),
+        owner.facilitator_id.as_deref(),
+    )
+    .await?;
+
+    Ok(CreatedApiToken {
+        token,
+        secret: format!("{TOKEN_PREFIX}{id}.{secret}"),
+    })
+}
+
+/// Reads the personal token of a request. `Ok(None)` when it carries none; an error when it carries
+/// one that is unknown, revoked or wrong, because a script should hear that its token stopped
+/// working rather than be treated as nobody.
+pub async fn authenticate(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> Result<Option<ApiCaller>, AppError> {
+    let Some((id, secret)) = bearer(headers).and_then(split) else {
+        return Ok(None);
+    };
+    let refused = || AppError::Unauthorized("This API token is not valid".to_string());
+
+    let Some((secret_hash, caller)) = db::get_api_token(&state.db, id).await? else {
+        return Err(refused());
+    };
+    if !password::verify(secret.to_string(), secret_hash).await? {
+        return Err(refused());
+    }
+
+    if let Err(e) = db::touch_api_token(&state.db, id).await {
+        warn!("Failed to note the use of an API token: {e}");
+    }
+    Ok(Some(caller))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_token_splits_into_its_id_and_its_secret() {
+        assert_eq!(
+            split("rwd_Ab3-x_9QzL1k.s3cr3t-_value"),
+            Some(("Ab3-x_9QzL1k", "s3cr3t-_value"))
+        );
+    }
+
+    #[test]
+    fn anything_else_is_not_a_personal_token() {
+        assert_eq!(split("an-admin-token"), None);
+        assert_eq!(split("rwd_no-secret"), None);
+        assert_eq!(split("rwd_.secret"), None);
+        assert_eq!(split("rwd_id."), None);
+    }
+
+    #[test]
+    fn the_bearer_is_read_from_the_authorization_header() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(bearer(&headers), None);
+        headers.insert(header::AUTHORIZATION, "Bearer rwd_a.b".parse().unwrap());
+        assert_eq!(bearer(&headers), Some("rwd_a.b"));
+    }
+}
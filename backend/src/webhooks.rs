@@ -0,0 +1,620 @@
+//! Tells other systems what happens on a board.
+//!
+//! A webhook is a URL that hears some of the events of a board. The facilitator sets the ones of
+//! their board; an admin sets global ones, which hear every board. An event is written to Postgres
+//! as one delivery for each webhook that wants it, and a worker on each replica sends what is due.
+//! A delivery that does not land is tried again, with more time between each try, until it lands
+//! or runs out of tries. The row stays behind as the log of how it went.
+//!
+//! Each POST carries the event as JSON, with a `text` line that a Slack or Teams incoming webhook
+//! posts as it is. It is signed: `X-Rewind-Signature` is `sha256=` and the hex HMAC-SHA256 of
+//! `{timestamp}.{body}` under the secret of the webhook, and `X-Rewind-Timestamp` is that
+//! timestamp, so a receiver can tell an old request sent again from a new one.
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use openidconnect::reqwest;
+use serde::Serialize;
+use sha2::Sha256;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::db;
+use crate::error::AppError;
+use crate::models::CreatedWebhook;
+use crate::state::AppState;
+
+/// How often a replica looks for deliveries that are due.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// The most deliveries one look takes.
+const BATCH_SIZE: i64 = 20;
+
+/// How long a delivery stays with the replica that took it before another may take it.
+const LEASE_SECS: i64 = 60;
+
+/// How long a receiver has to answer.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The tries a delivery gets, the first included, before it is marked failed.
+pub const MAX_ATTEMPTS: i32 = 6;
+
+/// The most webhooks one board can have.
+pub const MAX_WEBHOOKS_PER_BOARD: usize = 5;
+
+/// The longest URL a webhook takes.
+const MAX_URL_LENGTH: usize = 2_000;
+
+/// The most of an error that the log keeps.
+const MAX_ERROR_LENGTH: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookEvent {
+    RetroStarted,
+    BoardRevealed,
+    TimerEnded,
+    ActionCreated,
+    ActionDone,
+}
+
+impl WebhookEvent {
+    const ALL: [WebhookEvent; 5] = [
+        WebhookEvent::RetroStarted,
+        WebhookEvent::BoardRevealed,
+        WebhookEvent::TimerEnded,
+        WebhookEvent::ActionCreated,
+        WebhookEvent::ActionDone,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            WebhookEvent::RetroStarted => "retro.started",
+            WebhookEvent::BoardRevealed => "board.revealed",
+            WebhookEvent::TimerEnded => "timer.ended",
+            WebhookEvent::ActionCreated => "action.created",
+            WebhookEvent::ActionDone => "action.done",
+        }
+    }
+
+    pub fn parse(raw: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|event| event.as_str() == raw)
+    }
+}
+
+/// Reads the events a new webhook asks for, or says which one does not exist.
+pub fn read_events(raw: &[String]) -> Result<Vec<String>, String> {
+    let mut events: Vec<String> = Vec::new();
+    for name in raw {
+        let name = name.trim();
+        let event =
+            WebhookEvent::parse(name).ok_or_else(|| format!("No event is called {name}"))?;
+        if !events.iter().any(|e| e == event.as_str()) {
+            events.push(event.as_str().to_string());
+        }
+    }
+    Ok(events)
+}
+
+/// Reads the URL of a new webhook, or says what is wrong with it.
+pub fn read_url(raw: &str) -> Result<String, String> {
+    let raw = raw.trim();
+    if raw.len() > MAX_URL_LENGTH {
+        return Err(format!(
+            "A webhook URL is at most {MAX_URL_LENGTH} characters"
+        ));
+    }
+    let url = url::Url::parse(raw).map_err(|_| "That is not a URL".to_string())?;
+    if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+        return Err("A webhook URL starts with http:// or https:// and names a host".to_string());
+    }
+    Ok(url.to_string())
+}
+
+/// The signature of one request: `sha256=` and the hex HMAC of `{timestamp}.{body}`.
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes a key of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// How long to wait after a failed try: half a minute after the first, doubling each time, and
+/// never more than an hour.
+fn retry_delay(attempts: i32) -> chrono::Duration {
+    let doublings = attempts.clamp(1, 8) - 1;
+    chrono::Duration::seconds((30i64 << doublings).min(3_600))
+}
+
+/// True for an address inside a network rather than on the internet: loopback, private, link
+/// local, and the like.
+fn is_private_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                // 100.64.0.0/10, the shared space a carrier or a cluster hands out.
+                || (ip.octets()[0] == 100 && (ip.octets()[1] & 0xc0) == 64)
+        }
+        IpAddr::V6(ip) => {
+            let first = ip.segments()[0];
+            ip.is_loopback()
+                || ip.is_unspecified()
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80
+                || ip
+                    .to_ipv4_mapped()
+                    .is_some_and(|v4| is_private_ip(IpAddr::V4(v4)))
+        }
+    }
+}
+
+/// Whether the host of a URL resolves to an address inside a network. A host that does not
+/// resolve is left to fail when it is sent to.
+async fn reaches_private(raw: &str) -> bool {
+    let Ok(url) = url::Url::parse(raw) else {
+        return false;
+    };
+    let (Some(host), Some(port)) = (url.host_str(), url.port_or_known_default()) else {
+        return false;
+    };
+    let host = host
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_string();
+    match tokio::net::lookup_host((host, port)).await {
+        Ok(mut addrs) => addrs.any(|addr| is_private_ip(addr.ip())),
+        Err(_) => false,
+    }
+}
+
+/// Looks a host up as the system would, and keeps only the addresses on the internet.
+///
+/// `reaches_private` looks before a request goes, but a name can answer differently the moment
+/// after. A client with this resolver connects only to an address it has vetted itself, in the same
+/// lookup. An address written into the URL is not looked up, and `reaches_private` turns it away.
+struct PublicResolver;
+
+impl reqwest::dns::Resolve for PublicResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| !is_private_ip(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{} reaches only private addresses", name.as_str()).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+/// The HTTP side of the webhooks of this process.
+pub struct Webhooks {
+    http: reqwest::Client,
+    /// The client for the webhooks of a board while they may not reach a private address. It
+    /// resolves through `PublicResolver`.
+    public_http: reqwest::Client,
+    /// Whether a webhook set by a facilitator may reach an address inside the network. A
+    /// facilitator is anyone who made a board, so this is off unless the operator turns it on.
+    /// The global webhooks of an admin can always reach one.
+    allow_private: bool,
+}
+
+impl Webhooks {
+    pub fn from_env() -> Self {
+        let allow_private = matches!(
+            std::env::var("WEBHOOK_ALLOW_PRIVATE_TARGETS")
+                .as_deref()
+                .map(str::trim),
+            Ok("true") | Ok("1")
+        );
+        if allow_private {
+            info!("webhooks of a board may reach private addresses");
+        }
+        Self::new(allow_private)
+    }
+
+    fn new(allow_private: bool) -> Self {
+        // A redirect would take the request, signature and all, to a place nobody named.
+        let builder = || {
+            reqwest::ClientBuilder::new()
+                .redirect(reqwest::redirect::Policy::none())
+                .timeout(REQUEST_TIMEOUT)
+        };
+        let http = builder()
+            .build()
+            .expect("could not build the HTTP client for webhooks");
+        let public_http = builder()
+            .dns_resolver(Arc::new(PublicResolver))
+            .build()
+            .expect("could not build the HTTP client for webhooks");
+        Self {
+            http,
+            public_http,
+            allow_private,
+        }
+    }
+
+    /// Sends one delivery. The status code comes back on success; on failure, the status code if
+    /// there was an answer, and what went wrong. The webhook of a board goes only to a public
+    /// address unless the operator allows otherwise.
+    async fn send(
+        &self,
+        url: &str,
+        secret: &str,
+        event: &str,
+        delivery_id: i64,
+        body: &str,
+        is_board_webhook: bool,
+    ) -> Result<i32, (Option<i32>, String)> {
+        let http = if is_board_webhook && !self.allow_private {
+            &self.public_http
+        } else {
+            &self.http
+        };
+        let timestamp = Utc::now().timestamp();
+        let response = http
+            .post(url)
+            .header("content-type", "application/json")
+            .header("user-agent", "rewind-webhooks")
+            .header("x-rewind-event", event)
+            .header("x-rewind-delivery", delivery_id.to_string())
+            .header("x-rewind-timestamp", timestamp.to_string())
+            .header("x-rewind-signature", sign(secret, timestamp, body))
+            .body(body.to_string())
+            .send()
+            .await
+            .map_err(|e| (None, e.to_string()))?;
+
+        let code = i32::from(response.status().as_u16());
+        if response.status().is_success() {
+            Ok(code)
+        } else {
+            Err((Some(code), format!("The receiver answered {code}")))
+        }
+    }
+}
+
+// --- Events ---
+
+#[derive(Serialize)]
+struct Payload<'a> {
+    event: &'a str,
+    occurred_at: DateTime<Utc>,
+    board: PayloadBoard<'a>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    card: Option<PayloadCard<'a>>,
+    /// One line for a chat channel.
+    text: String,
+}
+
+#[derive(Serialize)]
+struct PayloadBoard<'a> {
+    id: &'a str,
+    title: &'a str,
+}
+
+#[derive(Serialize)]
+struct PayloadCard<'a> {
+    id: &'a str,
+    /// Absent while the board is blurred or asks for a password: a channel is no place for words
+    /// the board itself does not show yet.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content: Option<&'a str>,
+}
+
+/// Slack reads these three as markup, so they go in escaped.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn text_of(event: WebhookEvent, title: &str, content: Option<&str>) -> String {
+    let title = escape(title);
+    let with_content = |line: String| match content {
+        Some(content) => format!("{line}: {}", escape(content)),
+        None => line,
+    };
+    match event {
+        WebhookEvent::RetroStarted => format!("Retro \"{title}\" has started"),
+        WebhookEvent::BoardRevealed => format!("The cards on \"{title}\" are revealed"),
+        WebhookEvent::TimerEnded => format!("The timer on \"{title}\" has run out"),
+        WebhookEvent::ActionCreated => with_content(format!("New action on \"{title}\"")),
+        WebhookEvent::ActionDone => with_content(format!("Action done on \"{title}\"")),
+    }
+}
+
+/// Queues an event of a board for the webhooks that want it. `card` is the id and the words of
+/// the card the event is about.
+pub async fn emit(
+    state: &AppState,
+    board_id: &str,
+    event: WebhookEvent,
+    card: Option<(&str, &str)>,
+) {
+    let access = match db::get_board_access(&state.db, board_id).await {
+        Ok(Some(access)) => access,
+        Ok(None) => return,
+        Err(e) => {
+            warn!("Failed to read the board for a webhook: {e}");
+            return;
+        }
+    };
+    let is_blurred = match db::get_blur_state(&state.db, board_id).await {
+        Ok(Some(is_blurred)) => is_blurred,
+        _ => return,
+    };
+    let shows_words = access.password_hash.is_none() && !is_blurred;
+    let content = card.map(|(_, content)| content).filter(|_| shows_words);
+
+    let payload = Payload {
+        event: event.as_str(),
+        occurred_at: Utc::now(),
+        board: PayloadBoard {
+            id: board_id,
+            title: &access.title,
+        },
+        card: card.map(|(id, _)| PayloadCard { id, content }),
+        text: text_of(event, &access.title, content),
+    };
+    let payload = serde_json::to_string(&payload).unwrap();
+
+    if let Err(e) =
+        db::enqueue_webhook_deliveries(&state.db, board_id, event.as_str(), &payload).await
+    {
+        warn!("Failed to queue a webhook event: {e}");
+    }
+}
+
+/// Makes a webhook, for a board or, with None, for every board.
+pub async fn create(
+    state: &AppState,
+    board_id: Option<&str>,
+    url: &str,
+    events: &[String],
+) -> Result<CreatedWebhook, AppError> {
+    let url = read_url(url).map_err(AppError::BadRequest)?;
+    let events = read_events(events).map_err(AppError::BadRequest)?;
+
+    if let Some(board_id) = board_id {
+        if db::list_webhooks(&state.db, Some(board_id)).await?.len() >= MAX_WEBHOOKS_PER_BOARD {
+            return Err(AppError::BadRequest(format!(
+                "A board has at most {MAX_WEBHOOKS_PER_BOARD} webhooks"
+            )));
+        }
+        if !state.webhooks.allow_private && reaches_private(&url).await {
+            return Err(AppError::BadRequest(
+                "A webhook of a board cannot reach a private address".to_string(),
+            ));
+        }
+    }
+
+    let secret = nanoid::nanoid!(32);
+    let webhook = db::create_webhook(
+        &state.db,
+        &nanoid::nanoid!(10),
+        board_id,
+        &url,
+        &secret,
+        &events,
+    )
+    .await?;
+    Ok(CreatedWebhook { webhook, secret })
+}
+
+// --- Delivery ---
+
+/// Sends the deliveries that are due, for the life of the process.
+pub async fn run(state: AppState) {
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        let due = match db::claim_webhook_deliveries(&state.db, BATCH_SIZE, LEASE_SECS).await {
+            Ok(due) => due,
+            Err(e) => {
+                warn!("Failed to read the webhook queue: {e}");
+                continue;
+            }
+        };
+        futures_util::future::join_all(due.into_iter().map(|d| deliver(&state, d))).await;
+    }
+}
+
+async fn deliver(state: &AppState, delivery: db::DueDelivery) {
+    // The address is looked at again on each try: a name can point somewhere else by now.
+    let result = if delivery.is_board_webhook
+        && !state.webhooks.allow_private
+        && reaches_private(&delivery.url).await
+    {
+        Err((None, "The URL reaches a private address".to_string()))
+    } else {
+        state
+            .webhooks
+            .send(
+                &delivery.url,
+                &delivery.secret,
+                &delivery.event,
+                delivery.id,
+                &delivery.payload,
+                delivery.is_board_webhook,
+            )
+            .await
+    };
+
+    let outcome = match result {
+        Ok(code) => db::mark_webhook_delivered(&state.db, delivery.id, code).await,
+        Err((code, error)) => {
+            let error: String = error.chars().take(MAX_ERROR_LENGTH).collect();
+            let retry_at = (delivery.attempts < MAX_ATTEMPTS)
+                .then(|| Utc::now() + retry_delay(delivery.attempts));
+            if retry_at.is_none() {
+                warn!(
+                    "Gave up on webhook delivery {} after {} tries: {error}",
+                    delivery.id, delivery.attempts
+                );
+            }
+            db::mark_webhook_failed(&state.db, delivery.id, code, &error, retry_at).await
+        }
+    };
+    if let Err(e) = outcome {
+        warn!("Failed to record a webhook delivery: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[test]
+    fn the_signature_is_the_hmac_of_the_timestamp_and_the_body() {
+        // The same value as `printf '1700000000.{"a":1}' | openssl dgst -sha256 -hmac s3cret`.
+        assert_eq!(
+            sign("s3cret", 1_700_000_000, r#"{"a":1}"#),
+            "sha256=1698a50bc74d1ff1db85c4e0a5297c2ad9fdba245d5737cdb789e4cc6e098940"
+        );
+    }
+
+    #[test]
+    fn a_retry_waits_longer_each_time_up_to_an_hour() {
+        assert_eq!(retry_delay(1).num_seconds(), 30);
+        assert_eq!(retry_delay(2).num_seconds(), 60);
+        assert_eq!(retry_delay(5).num_seconds(), 480);
+        assert_eq!(retry_delay(20).num_seconds(), 3_600);
+    }
+
+    #[test]
+    fn only_known_events_are_taken_and_each_once() {
+        let events = read_events(&["action.done".into(), " action.done".into()]).unwrap();
+        assert_eq!(events, vec!["action.done"]);
+        assert!(read_events(&["board.deleted".into()]).is_err());
+    }
+
+    #[test]
+    fn a_url_needs_http_and_a_host() {
+        assert!(read_url("https://hooks.slack.com/services/x").is_ok());
+        assert!(read_url("ftp://example.com").is_err());
+        assert!(read_url("not a url").is_err());
+    }
+
+    #[test]
+    fn addresses_inside_a_network_are_private() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "192.168.0.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "::1",
+            "fd00::1",
+            "::ffff:10.0.0.1",
+        ] {
+            assert!(is_private_ip(ip.parse().unwrap()), "{ip}");
+        }
+        for ip in ["1.1.1.1", "100.128.0.1", "2606:4700::1111"] {
+            assert!(!is_private_ip(ip.parse().unwrap()), "{ip}");
+        }
+    }
+
+    /// Answers one request with `status` and hands back what it was sent.
+    async fn stub(status: u16) -> (String, tokio::task::JoinHandle<String>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            loop {
+                let n = socket.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request);
+                if let Some(end) = text.find("\r\n\r\n") {
+                    let length = text[..end]
+                        .lines()
+                        .find_map(|l| {
+                            l.to_lowercase()
+                                .strip_prefix("content-length:")
+                                .map(|v| v.trim().parse::<usize>().unwrap())
+                        })
+                        .unwrap_or(0);
+                    if request.len() >= end + 4 + length {
+                        break;
+                    }
+                }
+                if n == 0 {
+                    break;
+                }
+            }
+            let answer =
+                format!("HTTP/1.1 {status} X\r\ncontent-length: 0\r\nconnection: close\r\n\r\n");
+            socket.write_all(answer.as_bytes()).await.unwrap();
+            String::from_utf8(request).unwrap()
+        });
+        (url, handle)
+    }
+
+    #[tokio::test]
+    async fn a_delivery_reaches_a_local_stub_signed() {
+        let (url, request) = stub(204).await;
+        let body = r#"{"event":"action.done"}"#;
+        let result = Webhooks::new(true)
+            .send(&url, "s3cret", "action.done", 7, body, false)
+            .await;
+        assert_eq!(result, Ok(204));
+
+        let request = request.await.unwrap();
+        let header = |name: &str| {
+            request
+                .lines()
+                .find_map(|l| {
+                    let (key, value) = l.split_once(':')?;
+                    key.eq_ignore_ascii_case(name)
+                        .then(|| value.trim().to_string())
+                })
+                .unwrap()
+        };
+        let timestamp: i64 = header("x-rewind-timestamp").parse().unwrap();
+        assert_eq!(
+            header("x-rewind-signature"),
+            sign("s3cret", timestamp, body)
+        );
+        assert_eq!(header("x-rewind-event"), "action.done");
+        assert_eq!(header("x-rewind-delivery"), "7");
+        assert!(request.ends_with(body));
+    }
+
+    #[tokio::test]
+    async fn a_refusal_comes_back_with_its_status() {
+        let (url, _request) = stub(500).await;
+        let result = Webhooks::new(true)
+            .send(&url, "s", "timer.ended", 1, "{}", false)
+            .await;
+        assert_eq!(result.unwrap_err().0, Some(500));
+    }
+
+    #[tokio::test]
+    async fn a_board_webhook_is_not_sent_to_a_name_that_points_inside_the_network() {
+        // The check before the request would have passed a name that pointed elsewhere then. The
+        // connection itself goes only where the lookup it makes allows.
+        let (url, _request) = stub(204).await;
+        let url = url.replace("127.0.0.1", "localhost");
+        let webhooks = Webhooks::new(false);
+        let result = webhooks.send(&url, "s", "timer.ended", 1, "{}", true).await;
+        assert_eq!(result.unwrap_err().0, None);
+
+        // A global webhook, and a board one where the operator allows it, still get there.
+        assert_eq!(
+            webhooks.send(&url, "s", "timer.ended", 1, "{}", false).await,
+            Ok(204)
+        );
+    }
+}
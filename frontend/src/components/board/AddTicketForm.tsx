@@ -1,6 +1,7 @@
 import { useRef, useState } from "react";
 import { useGifComposer } from "../../hooks/useGifComposer";
 import { GifAttachment } from "./GifAttachment";
+import { MAX_TICKET_LENGTH } from "../../lib/types";
 import type { ClientMessage, Gif } from "../../lib/types";
 
 interface AddTicketFormProps {
@@ -50,6 +51,7 @@ export function AddTicketForm({ columnId, send }: AddTicketFormProps) {
           }
         }}
         placeholder="Add a card..."
+        maxLength={MAX_TICKET_LENGTH}
         rows={2}
         className="w-full rounded-lg border border-border px-3 py-2 text-sm resize-none focus:outline-none focus:ring-2 focus:ring-accent/40 bg-surface"
       />
@@ -8,7 +8,7 @@ import { TimerDisplay } from "./TimerDisplay";
 import { SortControls } from "./SortControls";
 import { EditorRequestButton } from "./EditorRequestButton";
 import { MeetingRating } from "./MeetingRating";
-import { isLevel10 } from "../../lib/types";
+import { isLevel10, PHASES } from "../../lib/types";
 import type { ClientMessage } from "../../lib/types";
 
 interface BoardHeaderProps {
@@ -16,7 +16,7 @@ interface BoardHeaderProps {
 }
 
 export function BoardHeader({ send }: BoardHeaderProps) {
-  const { board, isFacilitator, isConnected, participantId } = useBoardStore();
+  const { board, isFacilitator, isConnected, participantId, serverNotice } = useBoardStore();
   const [copied, setCopied] = useState(false);
   const isEditor = !!(board && participantId && board.editors.some((e) => e.participant_id === participantId));
 
@@ -72,12 +72,30 @@ export function BoardHeader({ send }: BoardHeaderProps) {
               ))}
             </div>
           )}
+          {board.archived_at && (
+            <span
+              className="text-[11px] px-1.5 py-0.5 rounded-md border border-border text-muted shrink-0"
+              title={`Closed ${new Date(board.archived_at).toLocaleDateString()}`}
+            >
+              Closed
+            </span>
+          )}
+          {board.phase && (
+            <span className="text-[11px] px-1.5 py-0.5 rounded-md bg-accent/10 text-accent shrink-0">
+              {PHASES.find((p) => p.phase === board.phase)?.label}
+            </span>
+          )}
           <span className="text-xs text-muted shrink-0">
             {board.participant_count} {board.participant_count === 1 ? "person" : "people"}
           </span>
           {!isConnected && (
             <span className="text-xs text-red-500 shrink-0">Reconnecting...</span>
           )}
+          {serverNotice && (
+            <span role="alert" className="text-xs text-red-500 truncate">
+              {serverNotice}
+            </span>
+          )}
         </div>
 
         <div className="flex items-center gap-3 shrink-0">
@@ -2,6 +2,7 @@ import { useState } from "react";
 import { useBoardStore } from "../../store/boardStore";
 import { setBoardPassword } from "../../lib/api";
 import { MIN_BOARD_PASSWORD_LENGTH } from "../../lib/types";
+import type { PasswordLockout } from "../../lib/types";
 
 interface BoardPasswordControlProps {
   boardId: string;
@@ -15,6 +16,7 @@ interface BoardPasswordControlProps {
  */
 export function BoardPasswordControl({ boardId }: BoardPasswordControlProps) {
   const hasPassword = useBoardStore((s) => s.board?.has_password ?? false);
+  const lockout = useBoardStore((s) => s.board?.password_lockout ?? null);
   const [editing, setEditing] = useState(false);
   const [password, setPassword] = useState("");
   const [busy, setBusy] = useState(false);
@@ -95,6 +97,8 @@ export function BoardPasswordControl({ boardId }: BoardPasswordControlProps) {
           : "Anyone with the link can open this board."}
       </p>
 
+      {hasPassword && lockout && <LockoutNotice lockout={lockout} />}
+
       {editing && (
         <div className="mt-2.5 animate-card-enter">
           <input
@@ -143,3 +147,40 @@ export function BoardPasswordControl({ boardId }: BoardPasswordControlProps) {
     </div>
   );
 }
+
+/**
+ * Wrong passwords tried at the gate. Past a few from one address the server makes that address
+ * wait, and past many it stops taking tries from anyone for a while — newcomers included, which
+ * is why the facilitator hears of it. A new password starts the count again.
+ */
+function LockoutNotice({ lockout }: { lockout: PasswordLockout }) {
+  const lockedUntil = lockout.locked_until ? new Date(lockout.locked_until) : null;
+  const boardLocked = lockedUntil !== null && lockedUntil.getTime() > Date.now();
+
+  return (
+    <div
+      role="status"
+      className="mt-2 rounded-lg border border-amber-500/30 bg-amber-500/10 px-3 py-2 text-xs space-y-0.5"
+    >
+      <p>
+        {lockout.failures === 1
+          ? "1 wrong password was tried today."
+          : `${lockout.failures} wrong passwords were tried today.`}
+      </p>
+      {lockout.locked_addresses > 0 && (
+        <p className="text-muted">
+          {lockout.locked_addresses === 1
+            ? "1 address has to wait before its next try."
+            : `${lockout.locked_addresses} addresses have to wait before their next try.`}
+        </p>
+      )}
+      {boardLocked && (
+        <p className="font-medium">
+          Nobody can open the board with the password until{" "}
+          {lockedUntil.toLocaleTimeString(undefined, { hour: "2-digit", minute: "2-digit" })}.
+          Change it to let people in now.
+        </p>
+      )}
+    </div>
+  );
+}
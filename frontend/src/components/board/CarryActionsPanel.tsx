@@ -268,6 +268,9 @@ export function CarryActionsPanel({ boardId }: CarryActionsPanelProps) {
                         <path d="M8 11V7a4 4 0 0 1 8 0v4" />
                       </svg>
                     )}
+                    {board.archived_at && (
+                      <span className="text-[11px] text-muted shrink-0">closed</span>
+                    )}
                   </span>
                   <span className="text-[11px] text-muted whitespace-nowrap shrink-0">
                     {formatRelativeDate(board.created_at)}
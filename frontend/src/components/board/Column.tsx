@@ -3,6 +3,7 @@ import { useBoardStore } from "../../store/boardStore";
 import { sortTickets } from "../../utils/sort";
 import { DraggableTicket } from "./DraggableTicket";
 import { AddTicketForm } from "./AddTicketForm";
+import { isActionColumn } from "../../lib/types";
 import type { Column as ColumnType, ClientMessage } from "../../lib/types";
 
 interface ColumnProps {
@@ -13,22 +14,20 @@ interface ColumnProps {
 
 export function Column({ column, color, send }: ColumnProps) {
   const sortMode = useBoardStore((s) => s.sortMode);
-  const participantId = useBoardStore((s) => s.participantId);
   const voteLimit = useBoardStore((s) => s.board?.vote_limit_per_column ?? null);
   const hideVotes = useBoardStore((s) => s.board?.hide_votes ?? false);
   const isBlurred = useBoardStore((s) => s.board?.is_blurred ?? false);
+  const cardsLocked = useBoardStore((s) => s.board?.cards_locked ?? false);
+  const phase = useBoardStore((s) => s.board?.phase ?? null);
+  const isArchived = useBoardStore((s) => !!s.board?.archived_at);
+  const writingOver = phase !== null && phase !== "write" && phase !== "group";
   // Carried actions come from the last retro. They hold no votes and they never hide.
   const isArchive = column.role === "previous_actions";
   const effectiveSortMode = hideVotes ? "newest" : sortMode;
   const sorted = sortTickets(column.tickets, isArchive ? "newest" : effectiveSortMode);
 
   // Count how many votes the current participant has in this column
-  const myVotesInColumn = participantId
-    ? column.tickets.reduce(
-        (count, t) => count + (t.votes.includes(participantId) ? 1 : 0),
-        0,
-      )
-    : 0;
+  const myVotesInColumn = column.tickets.reduce((count, t) => count + (t.voted ? 1 : 0), 0);
 
   const voteLimitReached = voteLimit !== null && myVotesInColumn >= voteLimit;
 
@@ -64,7 +63,14 @@ export function Column({ column, color, send }: ColumnProps) {
         )}
       </div>
 
-      <AddTicketForm columnId={column.id} send={send} />
+      {/* A locked board still takes actions, which come out of the discussion. A closed one takes nothing. */}
+      {isArchived || phase === "closed" ? (
+        <p className="text-xs text-muted mb-3">The retro is closed.</p>
+      ) : (cardsLocked || writingOver) && !isActionColumn(column.role) ? (
+        <p className="text-xs text-muted mb-3">The writing is over for this column.</p>
+      ) : (
+        <AddTicketForm columnId={column.id} send={send} />
+      )}
 
       <div
         className="space-y-2.5 overflow-y-auto min-h-0 flex-1 rounded-lg transition-colors"
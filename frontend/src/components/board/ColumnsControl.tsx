@@ -0,0 +1,193 @@
+import { useEffect, useState } from "react";
+import { useBoardStore } from "../../store/boardStore";
+import { MAX_COLUMN_NAME_LENGTH } from "../../lib/types";
+import type { ClientMessage, Column } from "../../lib/types";
+
+interface ColumnsControlProps {
+  send: (msg: ClientMessage) => void;
+}
+
+/**
+ * Adds, renames, reorders and removes the columns of a live board. The columns with a role —
+ * Previous Actions, Actions, the Rocks — keep their names and stay on the board, and Actions
+ * stays last. A column that still holds cards names the column they go to before it goes.
+ */
+export function ColumnsControl({ send }: ColumnsControlProps) {
+  const columns = useBoardStore((s) => s.board?.columns ?? []);
+  const [newName, setNewName] = useState("");
+
+  function move(index: number, by: -1 | 1) {
+    const order = columns.map((c) => c.id);
+    [order[index], order[index + by]] = [order[index + by], order[index]];
+    send({ type: "ReorderColumns", payload: { column_ids: order } });
+  }
+
+  function add() {
+    const name = newName.trim();
+    if (!name) return;
+    send({ type: "AddColumn", payload: { name } });
+    setNewName("");
+  }
+
+  return (
+    <div>
+      <span className="text-sm font-medium">Columns</span>
+      <div className="mt-2 space-y-2">
+        {columns.map((column, i) => (
+          <ColumnRow
+            key={column.id}
+            column={column}
+            others={columns.filter((c) => c.id !== column.id)}
+            // Actions is last and stays there, so nothing moves below the column before it.
+            canMoveUp={i > 0 && column.role !== "actions"}
+            canMoveDown={i < columns.length - 2 && column.role !== "actions"}
+            onMove={(by) => move(i, by)}
+            send={send}
+          />
+        ))}
+      </div>
+      <form
+        className="flex gap-2 mt-3"
+        onSubmit={(e) => {
+          e.preventDefault();
+          add();
+        }}
+      >
+        <input
+          type="text"
+          value={newName}
+          onChange={(e) => setNewName(e.target.value)}
+          maxLength={MAX_COLUMN_NAME_LENGTH}
+          placeholder="New column, e.g. Kudos"
+          className="flex-1 min-w-0 rounded-lg border border-border px-3 py-1.5 text-sm focus:outline-none focus:ring-2 focus:ring-accent/40 bg-canvas"
+        />
+        <button
+          type="submit"
+          disabled={!newName.trim()}
+          className="px-3 py-1.5 text-sm rounded-lg border border-border hover:bg-canvas disabled:opacity-30 disabled:cursor-not-allowed transition-colors"
+        >
+          Add
+        </button>
+      </form>
+      <p className="text-xs text-muted mt-1">New columns go just before Actions.</p>
+    </div>
+  );
+}
+
+interface ColumnRowProps {
+  column: Column;
+  others: Column[];
+  canMoveUp: boolean;
+  canMoveDown: boolean;
+  onMove: (by: -1 | 1) => void;
+  send: (msg: ClientMessage) => void;
+}
+
+function ColumnRow({ column, others, canMoveUp, canMoveDown, onMove, send }: ColumnRowProps) {
+  const [name, setName] = useState(column.name);
+  const [removing, setRemoving] = useState(false);
+  const [target, setTarget] = useState(others[0]?.id ?? "");
+  const hasCards = column.tickets.length > 0;
+  const hasRole = column.role !== null;
+
+  // Follow the board, so that a rename by another editor shows here too.
+  useEffect(() => {
+    setName(column.name);
+  }, [column.name]);
+
+  function rename() {
+    const next = name.trim();
+    if (!next || next === column.name) {
+      setName(column.name);
+      return;
+    }
+    send({ type: "RenameColumn", payload: { column_id: column.id, name: next } });
+  }
+
+  function remove() {
+    send({
+      type: "RemoveColumn",
+      payload: { column_id: column.id, target_column_id: hasCards ? target : null },
+    });
+    setRemoving(false);
+  }
+
+  const arrow =
+    "w-6 h-6 rounded-md border border-border flex items-center justify-center text-xs hover:bg-canvas disabled:opacity-30 disabled:cursor-not-allowed transition-colors";
+
+  return (
+    <div className="p-2 rounded-lg bg-canvas border border-border">
+      <div className="flex items-center gap-1.5">
+        {hasRole ? (
+          <span className="flex-1 min-w-0 text-sm truncate" title="This column keeps its name">
+            {column.name}
+          </span>
+        ) : (
+          <input
+            type="text"
+            value={name}
+            onChange={(e) => setName(e.target.value)}
+            onBlur={rename}
+            onKeyDown={(e) => {
+              if (e.key === "Enter") e.currentTarget.blur();
+              if (e.key === "Escape") setName(column.name);
+            }}
+            maxLength={MAX_COLUMN_NAME_LENGTH}
+            aria-label={`Name of ${column.name}`}
+            className="flex-1 min-w-0 rounded-md border border-transparent hover:border-border focus:border-border px-1.5 py-0.5 text-sm focus:outline-none bg-transparent"
+          />
+        )}
+        <button
+          onClick={() => onMove(-1)}
+          disabled={!canMoveUp}
+          className={arrow}
+          aria-label="Move left"
+        >
+          ←
+        </button>
+        <button
+          onClick={() => onMove(1)}
+          disabled={!canMoveDown}
+          className={arrow}
+          aria-label="Move right"
+        >
+          →
+        </button>
+        {!hasRole && (
+          <button
+            onClick={() => (hasCards ? setRemoving((v) => !v) : remove())}
+            className="px-2 py-1 text-xs rounded-md text-muted hover:text-red-600 hover:bg-red-500/10 border border-transparent hover:border-red-500/30 transition-colors shrink-0"
+          >
+            Remove
+          </button>
+        )}
+      </div>
+      {removing && (
+        <div className="mt-2 flex items-center gap-2">
+          <label className="text-xs text-muted shrink-0" htmlFor={`target-${column.id}`}>
+            Move its {column.tickets.length} cards to
+          </label>
+          <select
+            id={`target-${column.id}`}
+            value={target}
+            onChange={(e) => setTarget(e.target.value)}
+            className="flex-1 min-w-0 rounded-md border border-border px-1.5 py-1 text-xs bg-surface"
+          >
+            {others.map((c) => (
+              <option key={c.id} value={c.id}>
+                {c.name}
+              </option>
+            ))}
+          </select>
+          <button
+            onClick={remove}
+            disabled={!target}
+            className="px-2 py-1 text-xs rounded-md bg-red-500/10 text-red-600 border border-red-500/30 hover:bg-red-500/20 transition-colors shrink-0"
+          >
+            Remove
+          </button>
+        </div>
+      )}
+    </div>
+  );
+}
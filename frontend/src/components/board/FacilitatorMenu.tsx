@@ -3,6 +3,8 @@ import { createPortal } from "react-dom";
 import { useBoardStore } from "../../store/boardStore";
 import { VoteLimitControl } from "./VoteLimitControl";
 import { TimerControl } from "./TimerControl";
+import { PhaseControl } from "./PhaseControl";
+import { ColumnsControl } from "./ColumnsControl";
 import { BoardLabelsControl } from "./BoardLabelsControl";
 import { CarryActionsPanel } from "./CarryActionsPanel";
 import { BoardPasswordControl } from "./BoardPasswordControl";
@@ -16,6 +18,8 @@ export function FacilitatorMenu({ send }: FacilitatorMenuProps) {
   const [open, setOpen] = useState(false);
   const isBlurred = useBoardStore((s) => s.board?.is_blurred ?? true);
   const hideVotes = useBoardStore((s) => s.board?.hide_votes ?? false);
+  const cardsLocked = useBoardStore((s) => s.board?.cards_locked ?? false);
+  const isArchived = useBoardStore((s) => !!s.board?.archived_at);
   const facilitatorPeek = useBoardStore((s) => s.facilitatorPeek);
   const toggleFacilitatorPeek = useBoardStore((s) => s.toggleFacilitatorPeek);
   const isFacilitator = useBoardStore((s) => s.isFacilitator);
@@ -146,6 +150,33 @@ export function FacilitatorMenu({ send }: FacilitatorMenuProps) {
 
               <hr className="border-border" />
 
+              {/* Card Lock */}
+              <div>
+                <div className="flex items-center justify-between">
+                  <span className="text-sm font-medium">New Cards</span>
+                  <button
+                    onClick={() => send({ type: "ToggleCardsLocked" })}
+                    className={`px-3 py-1.5 text-sm rounded-lg border transition-colors ${
+                      cardsLocked
+                        ? "border-accent bg-accent/10 text-accent hover:bg-accent/20"
+                        : "border-border hover:bg-canvas"
+                    }`}
+                  >
+                    {cardsLocked ? "Unlock Cards" : "Lock Cards"}
+                  </button>
+                </div>
+                <p className="text-xs text-muted mt-1">
+                  {cardsLocked
+                    ? "Only actions can be added."
+                    : "Everyone can add cards to every column."}
+                </p>
+              </div>
+
+              <hr className="border-border" />
+
+              {/* Phase */}
+              <PhaseControl send={send} />
+
               {/* Vote Limit */}
               <VoteLimitControl send={send} />
 
@@ -154,6 +185,14 @@ export function FacilitatorMenu({ send }: FacilitatorMenuProps) {
               {/* Timer */}
               <TimerControl send={send} />
 
+              {/* Columns — a closed board keeps the ones it has */}
+              {!isArchived && (
+                <>
+                  <hr className="border-border" />
+                  <ColumnsControl send={send} />
+                </>
+              )}
+
               {boardId && (
                 <>
                   <hr className="border-border" />
@@ -176,6 +215,33 @@ export function FacilitatorMenu({ send }: FacilitatorMenuProps) {
                 </>
               )}
 
+              {/* Closing the retro — facilitator only */}
+              {isFacilitator && (
+                <>
+                  <hr className="border-border" />
+                  <div>
+                    <div className="flex items-center justify-between">
+                      <span className="text-sm font-medium">Board</span>
+                      <button
+                        onClick={() => send({ type: isArchived ? "ReopenBoard" : "CloseBoard" })}
+                        className={`px-3 py-1.5 text-sm rounded-lg border transition-colors ${
+                          isArchived
+                            ? "border-accent bg-accent/10 text-accent hover:bg-accent/20"
+                            : "border-border hover:bg-canvas"
+                        }`}
+                      >
+                        {isArchived ? "Reopen Board" : "Close Board"}
+                      </button>
+                    </div>
+                    <p className="text-xs text-muted mt-1">
+                      {isArchived
+                        ? "The board is closed. Everyone can read it, nobody can change it."
+                        : "Closing keeps the board as it is, for reading only."}
+                    </p>
+                  </div>
+                </>
+              )}
+
               {/* Editors & Requests — facilitator only */}
               {isFacilitator && (editors.length > 0 || editorRequests.length > 0) && (
                 <>
@@ -0,0 +1,44 @@
+import { useBoardStore } from "../../store/boardStore";
+import { PHASES } from "../../lib/types";
+import type { ClientMessage } from "../../lib/types";
+
+interface PhaseControlProps {
+  send: (msg: ClientMessage) => void;
+}
+
+/** Moves the board between the phases it was given. A board with none shows nothing. */
+export function PhaseControl({ send }: PhaseControlProps) {
+  const phase = useBoardStore((s) => s.board?.phase ?? null);
+  const phases = useBoardStore((s) => s.board?.phases ?? []);
+
+  if (phases.length === 0) return null;
+
+  return (
+    <>
+      <div>
+        <span className="text-sm font-medium">Phase</span>
+        <div className="flex flex-wrap gap-1.5 mt-2">
+          {PHASES.filter((p) => phases.includes(p.phase)).map(({ phase: p, label }) => (
+            <button
+              key={p}
+              onClick={() => send({ type: "SetPhase", payload: { phase: p } })}
+              className={`px-3 py-1.5 text-sm rounded-lg border transition-colors ${
+                phase === p
+                  ? "border-accent bg-accent/10 text-accent"
+                  : "border-border hover:bg-canvas"
+              }`}
+            >
+              {label}
+            </button>
+          ))}
+        </div>
+        <p className="text-xs text-muted mt-1">
+          Cards go up while writing and grouping, votes only during the vote, and a closed board
+          takes nothing until it is moved back.
+        </p>
+      </div>
+
+      <hr className="border-border" />
+    </>
+  );
+}
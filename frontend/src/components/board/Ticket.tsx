@@ -208,6 +208,18 @@ export function TicketCard({
         </div>
       )}
 
+      {/* Who took the action on and by when. An action past its day says so. */}
+      {isAction && !isBlurred && (ticket.assignee || ticket.due_on) && (
+        <div className="flex items-center gap-2 mt-2 text-xs text-muted">
+          {ticket.assignee && <span className="truncate max-w-[140px]">{ticket.assignee.name}</span>}
+          {ticket.due_on && (
+            <span className={ticket.is_overdue ? "font-medium text-[#c0504d]" : undefined}>
+              {ticket.is_overdue ? `Overdue since ${ticket.due_on}` : `Due ${ticket.due_on}`}
+            </span>
+          )}
+        </div>
+      )}
+
       {/* Footer: author, votes, actions */}
       <div className="flex items-center justify-between mt-2 pt-1.5 border-t border-border/40">
         {!board?.is_anonymous && (
@@ -242,7 +254,7 @@ export function TicketCard({
           {!isCarried && (
             <VoteButton
               ticketId={ticket.id}
-              voteCount={ticket.votes.length}
+              voteCount={ticket.vote_count ?? 0}
               hasVoted={hasVoted}
               voteLimitReached={voteLimitReached}
               hideVotes={board?.hide_votes}
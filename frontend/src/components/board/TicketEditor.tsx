@@ -1,6 +1,7 @@
 import { useRef, useState } from "react";
 import { useGifComposer } from "../../hooks/useGifComposer";
 import { GifAttachment } from "./GifAttachment";
+import { MAX_TICKET_LENGTH } from "../../lib/types";
 import type { Gif } from "../../lib/types";
 
 interface TicketEditorProps {
@@ -68,6 +69,7 @@ export function TicketEditor({
             onCancel();
           }
         }}
+        maxLength={MAX_TICKET_LENGTH}
         rows={rows}
         className={`w-full rounded border border-border px-2 py-1 resize-none focus:outline-none focus:ring-2 focus:ring-accent/40 bg-surface ${textClassName}`}
         autoFocus
@@ -252,7 +252,7 @@ export function TicketModal({
               <span className="ml-auto">
                 <VoteButton
                   ticketId={ticket.id}
-                  voteCount={ticket.votes.length}
+                  voteCount={ticket.vote_count ?? 0}
                   hasVoted={hasVoted}
                   voteLimitReached={voteLimitReached}
                   hideVotes={board?.hide_votes}
@@ -1,5 +1,6 @@
+import { useState } from "react";
 import { useBoardStore } from "../../store/boardStore";
-import type { ClientMessage } from "../../lib/types";
+import type { ClientMessage, TimerAction } from "../../lib/types";
 
 interface TimerControlProps {
   send: (msg: ClientMessage) => void;
@@ -13,12 +14,21 @@ const PRESETS = [
   { label: "10 min", secs: 600 },
 ];
 
+const EXPIRY_ACTIONS: { label: string; action: TimerAction | null }[] = [
+  { label: "Nothing", action: null },
+  { label: "Reveal the cards", action: "reveal" },
+  { label: "Lock new cards", action: "lock_cards" },
+  { label: "Start voting", action: "start_voting" },
+];
+
 export function TimerControl({ send }: TimerControlProps) {
   const timerEnd = useBoardStore((s) => s.board?.timer_end ?? null);
+  const timerAction = useBoardStore((s) => s.board?.timer_action ?? null);
   const isActive = timerEnd !== null && new Date(timerEnd) > new Date();
+  const [onExpiry, setOnExpiry] = useState<TimerAction | null>(null);
 
   function handleStart(secs: number) {
-    send({ type: "StartTimer", payload: { duration_secs: secs } });
+    send({ type: "StartTimer", payload: { duration_secs: secs, on_expiry: onExpiry } });
   }
 
   function handleStop() {
@@ -29,14 +39,35 @@ export function TimerControl({ send }: TimerControlProps) {
     <div>
       <span className="text-sm font-medium block mb-2">Discussion Timer</span>
       {isActive ? (
-        <button
-          onClick={handleStop}
-          className="px-3 py-1.5 text-sm rounded-lg bg-red-500/10 text-red-600 border border-red-200 hover:bg-red-500/20 transition-colors"
-        >
-          Stop Timer
-        </button>
+        <div className="flex items-center gap-2">
+          <button
+            onClick={handleStop}
+            className="px-3 py-1.5 text-sm rounded-lg bg-red-500/10 text-red-600 border border-red-200 hover:bg-red-500/20 transition-colors"
+          >
+            Stop Timer
+          </button>
+          {timerAction && (
+            <span className="text-xs text-muted">
+              Then: {EXPIRY_ACTIONS.find((a) => a.action === timerAction)?.label}
+            </span>
+          )}
+        </div>
       ) : (
         <div className="flex flex-wrap gap-1.5">
+          <label className="w-full flex items-center gap-2 text-xs text-muted mb-1">
+            When time is up
+            <select
+              value={onExpiry ?? ""}
+              onChange={(e) => setOnExpiry((e.target.value || null) as TimerAction | null)}
+              className="px-2 py-1 text-xs rounded-md border border-border bg-canvas text-ink"
+            >
+              {EXPIRY_ACTIONS.map((a) => (
+                <option key={a.label} value={a.action ?? ""}>
+                  {a.label}
+                </option>
+              ))}
+            </select>
+          </label>
           {PRESETS.map((p) => (
             <button
               key={p.secs}
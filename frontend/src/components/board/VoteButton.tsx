@@ -1,4 +1,5 @@
 import { useState } from "react";
+import { useBoardStore } from "../../store/boardStore";
 import type { ClientMessage } from "../../lib/types";
 
 interface VoteButtonProps {
@@ -12,7 +13,11 @@ interface VoteButtonProps {
 
 export function VoteButton({ ticketId, voteCount, hasVoted, voteLimitReached, hideVotes, send }: VoteButtonProps) {
   const [bouncing, setBouncing] = useState(false);
-  const disabled = !!(voteLimitReached && !hasVoted);
+  // A board with phases takes votes only while it is in the vote.
+  const phase = useBoardStore((s) => s.board?.phase ?? null);
+  const isArchived = useBoardStore((s) => !!s.board?.archived_at);
+  const disabled =
+    !!(voteLimitReached && !hasVoted) || (phase !== null && phase !== "vote") || isArchived;
 
   function handleClick() {
     if (disabled) return;
@@ -29,7 +29,7 @@ export function useAuth(): AuthConfig | undefined {
 }
 
 /**
- * The name Entra holds for this reader, or null.
+ * The name the identity provider holds for this reader, or null.
  *
  * It fills a name field rather than replacing it: a person may still write what the board should
  * call them, and an anonymous board shows no name at all.
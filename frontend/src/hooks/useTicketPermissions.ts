@@ -73,7 +73,7 @@ export function useTicketPermissions(
     isBlurred,
     canEdit: isAuthor || isPrivileged,
     canSetDone: isAction && (isAuthor || isPrivileged),
-    hasVoted: participantId ? ticket.votes.includes(participantId) : false,
+    hasVoted: ticket.voted,
     actionsColumn: board?.columns.find((c) => c.role === "actions"),
   };
 }
@@ -32,9 +32,11 @@ export function useWebSocket(
   // holds the socket must not re-render — and re-render every card — each time the connection
   // flag or the board changes. Zustand setters are stable references, so these never change.
   const setBoard = useBoardStore((s) => s.setBoard);
+  const applyPatch = useBoardStore((s) => s.applyPatch);
   const setAuth = useBoardStore((s) => s.setAuth);
   const setConnected = useBoardStore((s) => s.setConnected);
   const setPasswordRequired = useBoardStore((s) => s.setPasswordRequired);
+  const setServerNotice = useBoardStore((s) => s.setServerNotice);
 
   const send = useCallback((msg: ClientMessage) => {
     if (wsRef.current?.readyState === WebSocket.OPEN) {
@@ -46,6 +48,7 @@ export function useWebSocket(
     if (!boardId || !participantName) return;
 
     let reconnectTimer: ReturnType<typeof setTimeout>;
+    let noticeTimer: ReturnType<typeof setTimeout>;
     let alive = true;
 
     function connect() {
@@ -61,12 +64,14 @@ export function useWebSocket(
         everOpened = true;
         setConnected(true);
         const storedId = sessionStorage.getItem(`participant_id_${boardId}`);
+        const storedToken = sessionStorage.getItem(`participant_token_${boardId}`);
         const facToken = sessionStorage.getItem(`facilitator_token_${boardId}`);
         const joinMsg: ClientMessage = {
           type: "Join",
           payload: {
             participant_name: participantName,
             ...(storedId ? { participant_id: storedId } : {}),
+            ...(storedToken ? { participant_token: storedToken } : {}),
             ...(facToken ? { facilitator_token: facToken } : {}),
             ...(accessToken ? { access_token: accessToken } : {}),
           },
@@ -80,9 +85,17 @@ export function useWebSocket(
           case "BoardState":
             setBoard(msg.payload.board);
             break;
+          case "BoardPatch":
+            // A skipped revision means a change went by unseen, so the whole board comes again.
+            if (!applyPatch(msg.payload.revision, msg.payload.patch)) {
+              const resyncMsg: ClientMessage = { type: "Resync" };
+              ws.send(JSON.stringify(resyncMsg));
+            }
+            break;
           case "Authenticated":
             sessionStorage.setItem(`participant_id_${boardId}`, msg.payload.participant_id);
-            setAuth(msg.payload.participant_id, msg.payload.is_facilitator);
+            sessionStorage.setItem(`participant_token_${boardId}`, msg.payload.participant_token);
+            setAuth(msg.payload.author_id, msg.payload.is_facilitator);
             setPasswordRequired(false);
             break;
           case "PasswordRequired":
@@ -91,8 +104,17 @@ export function useWebSocket(
             setPasswordRequired(true);
             alive = false;
             break;
+          case "TimerExpired":
+            // The display already reads "Time's up!" from the end it holds, and whatever the
+            // action changed follows in its own message.
+            break;
           case "Error":
+            // Shown for a few seconds: most often a message the server dropped for coming too
+            // fast, which the person can simply send again.
             console.error("Server error:", msg.payload.message);
+            setServerNotice(msg.payload.message);
+            clearTimeout(noticeTimer);
+            noticeTimer = setTimeout(() => setServerNotice(null), 5000);
             break;
         }
       };
@@ -126,6 +148,7 @@ export function useWebSocket(
     return () => {
       alive = false;
       clearTimeout(reconnectTimer);
+      clearTimeout(noticeTimer);
       wsRef.current?.close();
       wsRef.current = null;
     };
@@ -134,9 +157,11 @@ export function useWebSocket(
     participantName,
     accessToken,
     setBoard,
+    applyPatch,
     setAuth,
     setConnected,
     setPasswordRequired,
+    setServerNotice,
   ]);
 
   return { send };
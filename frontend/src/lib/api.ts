@@ -1,6 +1,7 @@
 import type {
   ActionSourceBoard,
   ApplyTemplateResult,
+  AuditEntry,
   Board,
   BoardAccess,
   ClientConfig,
@@ -11,12 +12,25 @@ import type {
   LabelCount,
   MyBoardSummary,
   PasswordResponse,
+  Phase,
   Template,
   Team,
+  TrackedAction,
   UnlockResponse,
+  Webhook,
+  WebhookDelivery,
+  RetentionCandidate,
+  RetentionPolicy,
+  RetentionRecord,
+  RetentionSettings,
+  WebhookEvent,
+  CreatedWebhook,
   GlobalStats,
   AdminBoardSummary,
   AdminBoardDetail,
+  ApiTokenView,
+  CreatedApiToken,
+  TokenScope,
 } from "./types";
 import { getServerUrl } from "./serverUrl";
 import { accessHeader, getAccessToken, setAccessToken } from "./boardAccess";
@@ -127,11 +141,13 @@ export async function fetchMyBoards(): Promise<MyBoardSummary[]> {
 
 // --- Actions carry-over and labels ---
 
-/** Tells the server who asks. The board settings hold both values. */
+/** Tells the server who asks. The participant id counts only with the credential the socket gave
+ * out with it. */
 function boardAuth(boardId: string) {
   return {
     facilitator_token: sessionStorage.getItem(`facilitator_token_${boardId}`) ?? undefined,
     participant_id: sessionStorage.getItem(`participant_id_${boardId}`) ?? undefined,
+    participant_token: sessionStorage.getItem(`participant_token_${boardId}`) ?? undefined,
   };
 }
 
@@ -151,6 +167,22 @@ export async function fetchActionSources(
   return res.json();
 }
 
+/** The actions of every board, each one once, however many retros it was carried through. */
+export async function fetchActions(
+  opts: { labels?: string[]; status?: "open" | "done"; team?: string } = {},
+): Promise<TrackedAction[]> {
+  const params = new URLSearchParams();
+  if (opts.labels?.length) params.set("labels", opts.labels.join(","));
+  if (opts.status) params.set("status", opts.status);
+  if (opts.team) params.set("team", opts.team);
+  const query = params.toString();
+  const res = await fetch(`${getServerUrl()}/api/actions${query ? `?${query}` : ""}`, {
+    credentials: "include",
+  });
+  if (!res.ok) throw new Error(await res.text());
+  return res.json();
+}
+
 /**
  * Copies cards from a column of another board into a column of this one. Naming no column keeps
  * the carry-over this route was written for: the actions of the source into Previous Actions.
@@ -193,6 +225,48 @@ export async function updateBoardTitle(boardId: string, title: string): Promise<
   return res.json();
 }
 
+/** The query string that says who is asking, for a GET or a DELETE that has no body. */
+function boardAuthQuery(boardId: string): string {
+  const params = new URLSearchParams();
+  for (const [key, value] of Object.entries(boardAuth(boardId))) {
+    if (value) params.set(key, value);
+  }
+  return params.toString();
+}
+
+export async function fetchBoardWebhooks(boardId: string): Promise<Webhook[]> {
+  const res = await fetch(
+    `${getServerUrl()}/api/boards/${boardId}/webhooks?${boardAuthQuery(boardId)}`,
+    { credentials: "include" },
+  );
+  if (!res.ok) throw new Error(await res.text());
+  return res.json();
+}
+
+/** Makes a webhook for the board. The secret in the answer is never shown again. */
+export async function createBoardWebhook(
+  boardId: string,
+  url: string,
+  events: WebhookEvent[],
+): Promise<CreatedWebhook> {
+  const res = await fetch(`${getServerUrl()}/api/boards/${boardId}/webhooks`, {
+    method: "POST",
+    headers: { "Content-Type": "application/json" },
+    credentials: "include",
+    body: JSON.stringify({ url, events, ...boardAuth(boardId) }),
+  });
+  if (!res.ok) throw new Error(await res.text());
+  return res.json();
+}
+
+export async function deleteBoardWebhook(boardId: string, webhookId: string): Promise<void> {
+  const res = await fetch(
+    `${getServerUrl()}/api/boards/${boardId}/webhooks/${webhookId}?${boardAuthQuery(boardId)}`,
+    { method: "DELETE", credentials: "include" },
+  );
+  if (!res.ok) throw new Error(await res.text());
+}
+
 export async function fetchLabels(): Promise<LabelCount[]> {
   const res = await fetch(`${getServerUrl()}/api/labels`);
   if (!res.ok) throw new Error(await res.text());
@@ -213,10 +287,49 @@ export async function updateBoardLabels(
   return res.json();
 }
 
+// --- Personal API tokens ---
+
+export async function fetchApiTokens(): Promise<ApiTokenView[]> {
+  const res = await fetch(`${getServerUrl()}/api/tokens`, { credentials: "include" });
+  if (!res.ok) throw new Error(await res.text());
+  return res.json();
+}
+
+/**
+ * Makes a token for the person asking. The secret in the answer is never shown again. A token with
+ * the admin:read scope needs the admin token too, or a session in an admin group.
+ */
+export async function createApiToken(
+  name: string,
+  scopes: TokenScope[],
+  adminToken = "",
+): Promise<CreatedApiToken> {
+  const res = await fetch(`${getServerUrl()}/api/tokens`, {
+    method: "POST",
+    headers: { ...adminHeaders(adminToken), "Content-Type": "application/json" },
+    credentials: "include",
+    body: JSON.stringify({ name, scopes }),
+  });
+  if (!res.ok) throw new Error(await res.text());
+  return res.json();
+}
+
+export async function revokeApiToken(id: string): Promise<void> {
+  const res = await fetch(`${getServerUrl()}/api/tokens/${id}`, {
+    method: "DELETE",
+    credentials: "include",
+  });
+  if (!res.ok) throw new Error(await res.text());
+}
+
 // --- Admin API ---
 
+/**
+ * The shared admin token, when there is one. A member of an admin group on a signed-in deployment
+ * has none: their session cookie is what the server reads.
+ */
 function adminHeaders(token: string): HeadersInit {
-  return { Authorization: `Bearer ${token}` };
+  return token ? { Authorization: `Bearer ${token}` } : {};
 }
 
 export async function verifyAdminToken(token: string): Promise<void> {
@@ -293,6 +406,7 @@ export async function createAdminTemplate(
     columns: string[];
     position: number;
     default_blurred: boolean;
+    phases: Phase[];
   },
 ): Promise<void> {
   const res = await fetch(`${getServerUrl()}/api/admin/templates`, {
@@ -312,6 +426,7 @@ export async function updateAdminTemplate(
     columns: string[];
     position: number;
     default_blurred: boolean;
+    phases: Phase[];
   },
 ): Promise<void> {
   const res = await fetch(`${getServerUrl()}/api/admin/templates/${id}`, {
@@ -396,3 +511,133 @@ export async function deleteAdminTeam(token: string, id: string): Promise<void>
   });
   if (!res.ok) throw new Error(await res.text());
 }
+
+export async function fetchAdminWebhooks(token: string): Promise<Webhook[]> {
+  const res = await fetch(`${getServerUrl()}/api/admin/webhooks`, {
+    headers: adminHeaders(token),
+  });
+  if (!res.ok) throw new Error(await res.text());
+  return res.json();
+}
+
+export async function createAdminWebhook(
+  token: string,
+  url: string,
+  events: WebhookEvent[],
+): Promise<CreatedWebhook> {
+  const res = await fetch(`${getServerUrl()}/api/admin/webhooks`, {
+    method: "POST",
+    headers: { ...adminHeaders(token), "Content-Type": "application/json" },
+    body: JSON.stringify({ url, events }),
+  });
+  if (!res.ok) throw new Error(await res.text());
+  return res.json();
+}
+
+export async function deleteAdminWebhook(token: string, id: string): Promise<void> {
+  const res = await fetch(`${getServerUrl()}/api/admin/webhooks/${id}`, {
+    method: "DELETE",
+    headers: adminHeaders(token),
+  });
+  if (!res.ok) throw new Error(await res.text());
+}
+
+export async function fetchWebhookDeliveries(
+  token: string,
+  opts: {
+    webhookId?: string;
+    boardId?: string;
+    status?: WebhookDelivery["status"];
+    limit?: number;
+  } = {},
+): Promise<WebhookDelivery[]> {
+  const params = new URLSearchParams();
+  if (opts.webhookId) params.set("webhook_id", opts.webhookId);
+  if (opts.boardId) params.set("board_id", opts.boardId);
+  if (opts.status) params.set("status", opts.status);
+  if (opts.limit) params.set("limit", String(opts.limit));
+  const query = params.toString();
+  const res = await fetch(
+    `${getServerUrl()}/api/admin/webhooks/deliveries${query ? `?${query}` : ""}`,
+    { headers: adminHeaders(token) },
+  );
+  if (!res.ok) throw new Error(await res.text());
+  return res.json();
+}
+
+export async function fetchRetention(token: string): Promise<RetentionSettings> {
+  const res = await fetch(`${getServerUrl()}/api/admin/retention`, {
+    headers: adminHeaders(token),
+  });
+  if (!res.ok) throw new Error(await res.text());
+  return res.json();
+}
+
+export async function setRetention(
+  token: string,
+  policy: RetentionPolicy,
+): Promise<RetentionSettings> {
+  const res = await fetch(`${getServerUrl()}/api/admin/retention`, {
+    method: "PUT",
+    headers: { ...adminHeaders(token), "Content-Type": "application/json" },
+    body: JSON.stringify(policy),
+  });
+  if (!res.ok) throw new Error(await res.text());
+  return res.json();
+}
+
+/** Drops the policy an admin set, so the one of the environment applies again. */
+export async function clearRetention(token: string): Promise<RetentionSettings> {
+  const res = await fetch(`${getServerUrl()}/api/admin/retention`, {
+    method: "DELETE",
+    headers: adminHeaders(token),
+  });
+  if (!res.ok) throw new Error(await res.text());
+  return res.json();
+}
+
+/** The boards the next purge would take. Nothing is removed. */
+export async function fetchRetentionPreview(token: string): Promise<RetentionCandidate[]> {
+  const res = await fetch(`${getServerUrl()}/api/admin/retention/preview`, {
+    headers: adminHeaders(token),
+  });
+  if (!res.ok) throw new Error(await res.text());
+  return res.json();
+}
+
+export async function fetchRetentionLog(token: string): Promise<RetentionRecord[]> {
+  const res = await fetch(`${getServerUrl()}/api/admin/retention/log`, {
+    headers: adminHeaders(token),
+  });
+  if (!res.ok) throw new Error(await res.text());
+  return res.json();
+}
+
+/** The audit log, newest first. `action` may end in a dot to take every action under it. */
+export async function fetchAuditLog(
+  token: string,
+  opts: {
+    boardId?: string;
+    action?: string;
+    actor?: string;
+    since?: string;
+    until?: string;
+    limit?: number;
+  } = {},
+): Promise<AuditEntry[]> {
+  const params = new URLSearchParams();
+  if (opts.action) params.set("action", opts.action);
+  if (opts.actor) params.set("actor", opts.actor);
+  if (opts.since) params.set("since", opts.since);
+  if (opts.until) params.set("until", opts.until);
+  if (opts.limit) params.set("limit", String(opts.limit));
+  const query = params.toString();
+  const path = opts.boardId
+    ? `/api/admin/boards/${opts.boardId}/audit`
+    : "/api/admin/audit";
+  const res = await fetch(`${getServerUrl()}${path}${query ? `?${query}` : ""}`, {
+    headers: adminHeaders(token),
+  });
+  if (!res.ok) throw new Error(await res.text());
+  return res.json();
+}
@@ -19,7 +19,7 @@ export function signInUrl(
   return `${getServerUrl()}${LOGIN_PATH}?redirect=${encodeURIComponent(redirectTo)}`;
 }
 
-/** Where to send the browser to sign out — here first, and then Entra. */
+/** Where to send the browser to sign out — here first, and then the identity provider. */
 export function signOutUrl(): string {
   return `${getServerUrl()}/api/auth/logout`;
 }
@@ -10,7 +10,7 @@ import type { ClientConfig } from "./types";
  * later one. A server that does not answer gives null, and each reader falls back on its own.
  *
  * A refusal is the one failure worth acting on. In the built app the page itself comes from the
- * server, so a browser with no session is sent to Entra before any of this code runs — but under
+ * server, so a browser with no session is sent to the provider before any of this code runs — but under
  * `pnpm dev` the page comes from Vite, which asks nobody for anything, and a session can run out
  * while a tab sits open. Either way this is the first request of the session, so it is the right
  * place to find out and go to the door.
@@ -19,6 +19,16 @@ export interface Board {
   hide_votes: boolean;
   vote_limit_per_column: number | null;
   timer_end: string | null;
+  /** What the server does to the board when the timer runs out. */
+  timer_action: TimerAction | null;
+  /** Whether new cards are stopped outside the two action columns. */
+  cards_locked: boolean;
+  /** Where the board stands among its phases. Null on a board with no phases. */
+  phase: Phase | null;
+  /** The phases the board runs through, in order. Empty lets everything through at any time. */
+  phases: Phase[];
+  /** When the facilitator closed the board. A closed board takes no change until reopened. */
+  archived_at: string | null;
   editors: EditorInfo[];
   editor_requests: EditorRequest[];
   labels: string[];
@@ -30,8 +40,78 @@ export interface Board {
   meeting_ratings: MeetingRating[];
   /** Whether the board asks a newcomer for a password. Never the password itself. */
   has_password: boolean;
+  /** Someone trying passwords at the gate. Null when nobody got one wrong lately. */
+  password_lockout: PasswordLockout | null;
+  /** How many changes the board has been through. Each patch carries the next one. */
+  revision: number;
 }
 
+/** The settings of a board that a patch can change without changing what anyone may read. */
+export interface BoardSettings {
+  title: string;
+  hide_votes: boolean;
+  vote_limit_per_column: number | null;
+  timer_end: string | null;
+  timer_action: TimerAction | null;
+  cards_locked: boolean;
+  phase: Phase | null;
+  phases: Phase[];
+  archived_at: string | null;
+  labels: string[];
+  has_password: boolean;
+  password_lockout: PasswordLockout | null;
+}
+
+/** The wrong passwords tried on a board lately, for its facilitator to see. */
+export interface PasswordLockout {
+  /** Wrong tries in the last day, from every address together. */
+  failures: number;
+  /** The addresses that wait before their next try counts. */
+  locked_addresses: number;
+  /** Until when the board takes no try from anyone. */
+  locked_until: string | null;
+}
+
+/**
+ * What the server does when a timer runs out: take the blur off, stop new cards outside the
+ * action columns, or both, which ends the writing and opens the vote.
+ */
+export type TimerAction = "reveal" | "lock_cards" | "start_voting";
+
+/**
+ * The stage of a retro. Writing and grouping take new cards, only voting takes votes, and a
+ * closed board takes nothing. Action columns take cards in every phase but the last.
+ */
+export type Phase = "write" | "group" | "vote" | "discuss" | "closed";
+
+export const PHASES: { phase: Phase; label: string }[] = [
+  { phase: "write", label: "Write" },
+  { phase: "group", label: "Group" },
+  { phase: "vote", label: "Vote" },
+  { phase: "discuss", label: "Discuss" },
+  { phase: "closed", label: "Closed" },
+];
+
+/**
+ * One change to a board, sent in place of the whole of it. A card that changed comes whole, so
+ * it replaces the card of the same id wherever it stood.
+ */
+export type BoardPatch =
+  | { kind: "TicketAdded"; column_id: string; ticket: Ticket }
+  | { kind: "TicketEdited"; column_id: string; ticket: Ticket }
+  | { kind: "TicketMoved"; column_id: string; ticket: Ticket }
+  | { kind: "TicketRemoved"; ticket_id: string }
+  | {
+      kind: "VoteToggled";
+      ticket_id: string;
+      votes: string[];
+      vote_count: number | null;
+      voted: boolean;
+    }
+  | { kind: "CommentChanged"; column_id: string; ticket: Ticket }
+  | { kind: "SettingsChanged"; settings: BoardSettings }
+  | { kind: "ParticipantCount"; participant_count: number };
+
 /**
  * Every board has a Previous Actions column and an Actions column. Only a Level 10 board has a
  * Rocks column. Every other column has no role.
@@ -50,7 +130,13 @@ export interface Ticket {
   content: string;
   author_id: string;
   author_name: string;
+  /** Who voted for the card. Empty unless the reader is the facilitator or an editor of a board
+   * that is not anonymous, and the votes are shown. */
   votes: string[];
+  /** How many voted for it. Null while the votes are hidden. */
+  vote_count: number | null;
+  /** Whether the reader voted for it. */
+  voted: boolean;
   created_at: string;
   carried_from_board_id: string | null;
   carried_from_board_title: string | null;
@@ -63,8 +149,24 @@ export interface Ticket {
    * action columns. A finished action stays on the board: the mark is what closes it.
    */
   done_at: string | null;
+  /** Who took the action on. Null on every card outside the two action columns. */
+  assignee: Assignee | null;
+  /** The day the action is due, as `YYYY-MM-DD`. */
+  due_on: string | null;
+  /** An open action whose day has passed. */
+  is_overdue: boolean;
 }
 
+/** The owner of an action: someone on the board, or a member of a team. */
+export type Assignee =
+  | { kind: "participant"; participant_id: string; name: string }
+  | { kind: "team_member"; team_id: string; member_id: string; name: string };
+
+/** The owner a client picks. A team member's name comes from the team. */
+export type AssigneeChoice =
+  | { kind: "participant"; participant_id: string; name: string }
+  | { kind: "team_member"; team_id: string; member_id: string };
+
 /** Where a rock stands. Null until someone marks it. */
 export type RockStatus = "on_track" | "off_track";
 
@@ -111,12 +213,18 @@ export interface Gif {
   title: string;
 }
 
+/** The most characters that one card can hold. The backend applies the same limit. */
+export const MAX_TICKET_LENGTH = 2000;
+
 /** The most characters that one comment can hold. The backend applies the same limit. */
 export const MAX_COMMENT_LENGTH = 500;
 
 /** The most characters that one field of a scorecard line can hold. Same limit on the backend. */
 export const MAX_SCORECARD_FIELD_LENGTH = 200;
 
+/** The most characters that the name of a column can hold. The backend applies the same limit. */
+export const MAX_COLUMN_NAME_LENGTH = 60;
+
 /** The template that turns the Level 10 parts on: the scorecard, the rock status, the rating. */
 export const LEVEL10_TEMPLATE_ID = "level10";
 
@@ -136,19 +244,21 @@ export interface ClientConfig {
 }
 
 export interface AuthConfig {
-  /** False on a deployment that named no Entra app registration. Nothing about signing in shows. */
+  /** False on a deployment that named no identity provider. Nothing about signing in shows. */
   enabled: boolean;
   /** Who the reader is. Always set when `enabled`, because the request came through the gate. */
   user: AuthUser | null;
 }
 
 export interface AuthUser {
-  /** The account, not the person. Nothing keys off it yet. */
+  /** The account, not the person. The boards made while signed in belong to it. */
   sub: string;
-  /** The display name from Entra, which is what fills the join field. */
+  /** The display name from the identity provider, which is what fills the join field. */
   name: string;
-  /** The user principal name, or null on the rare account that has none. */
+  /** The email, or null on the rare account that has none. */
   email: string | null;
+  /** The admin groups of the provider the account is in. Empty for everyone else. */
+  groups: string[];
 }
 
 /** What the one open route says: the server is up, and whether it asks for a work account. */
@@ -166,6 +276,8 @@ export interface CreateBoardRequest {
   template_id?: string;
   /** The word the board will ask for. Absent leaves the board open to anyone with the link. */
   password?: string;
+  /** The phases the board runs through. Absent takes those of the template. */
+  phases?: Phase[];
 }
 
 /** The shortest password a board takes. The backend applies the same floor. */
@@ -202,6 +314,135 @@ export interface ActionSourceBoard {
   labels: string[];
   /** True when the board asks for a password before it hands its cards over. */
   is_locked: boolean;
+  /** When the board was closed. Its cards still carry over. */
+  archived_at: string | null;
+}
+
+/** One action across the retros it went through, read where the team last looked at it. */
+export interface TrackedAction {
+  ticket_id: string;
+  content: string;
+  board_id: string;
+  board_title: string;
+  labels: string[];
+  assignee: Assignee | null;
+  due_on: string | null;
+  is_overdue: boolean;
+  done_at: string | null;
+  first_board_id: string;
+  first_board_title: string;
+  first_raised_at: string;
+  /** How many times the action was carried into the next retro. */
+  carry_count: number;
+}
+
+export type WebhookEvent =
+  | "retro.started"
+  | "board.revealed"
+  | "timer.ended"
+  | "action.created"
+  | "action.done";
+
+/** A URL that hears the events of a board, or of every board when `board_id` is null. */
+export interface Webhook {
+  id: string;
+  board_id: string | null;
+  url: string;
+  events: WebhookEvent[];
+  created_at: string;
+}
+
+/** A new webhook and the secret its requests are signed with, shown this once. */
+export interface CreatedWebhook {
+  webhook: Webhook;
+  secret: string;
+}
+
+/** What a personal API token may do. */
+export type TokenScope = "board:read" | "board:write" | "actions:import" | "admin:read";
+
+/** A personal API token, without its secret. */
+export interface ApiTokenView {
+  id: string;
+  name: string;
+  scopes: TokenScope[];
+  created_at: string;
+  last_used_at: string | null;
+}
+
+/** A new API token and the secret a script sends, shown this once. */
+export interface CreatedApiToken {
+  token: ApiTokenView;
+  secret: string;
+}
+
+/** One event on its way to one webhook, and how its tries went. */
+export interface WebhookDelivery {
+  id: number;
+  webhook_id: string;
+  url: string;
+  board_id: string;
+  event: WebhookEvent;
+  status: "pending" | "delivered" | "failed";
+  attempts: number;
+  next_attempt_at: string;
+  last_status_code: number | null;
+  last_error: string | null;
+  created_at: string;
+  delivered_at: string | null;
+}
+
+/** What the purge does to a board whose time is up. */
+export type RetentionAction = "delete" | "anonymize";
+
+/** How long boards are kept. A board goes after `days` without a change, unless it carries an exempt label. */
+export interface RetentionPolicy {
+  /** Null keeps every board forever. */
+  days: number | null;
+  action: RetentionAction;
+  exempt_labels: string[];
+}
+
+export interface RetentionSettings {
+  policy: RetentionPolicy;
+  /** Whether the policy comes from the environment or was set by an admin over it. */
+  source: "environment" | "admin";
+}
+
+/** A board the next purge would take. */
+export interface RetentionCandidate {
+  board_id: string;
+  title: string;
+  labels: string[];
+  card_count: number;
+  last_activity_at: string;
+  action: RetentionAction;
+}
+
+/** One board the purge removed or anonymized. */
+export interface RetentionRecord {
+  id: number;
+  board_id: string;
+  title: string;
+  action: RetentionAction;
+  labels: string[];
+  card_count: number;
+  last_activity_at: string;
+  purged_at: string;
+}
+
+/** One entry of the audit log. */
+export interface AuditEntry {
+  id: number;
+  at: string;
+  board_id: string | null;
+  /** `participant:<id>`, `account:<sub>`, `token:<id>`, `admin:<account>`, `admin token` or `facilitator`. */
+  actor: string;
+  actor_name: string | null;
+  /** Such as `ticket.remove`, `board.reveal` or `admin.template.apply`. */
+  action: string;
+  target: string | null;
+  detail: string | null;
 }
 
 /** What an apply run did to the boards already made from a template. */
@@ -239,6 +480,8 @@ export type ClientMessage =
         participant_name: string;
         facilitator_token?: string;
         participant_id?: string;
+        /** The credential the server gave out with the id. Without it the id is not resumed. */
+        participant_token?: string;
         /** The key to a locked board. A board with no password takes no notice of it. */
         access_token?: string;
       };
@@ -253,13 +496,22 @@ export type ClientMessage =
   | { type: "ToggleVote"; payload: { ticket_id: string } }
   | { type: "ToggleBlur" }
   | { type: "ToggleHideVotes" }
+  /** Stops new cards outside the two action columns, or lets them in again. */
+  | { type: "ToggleCardsLocked" }
+  /** Moves the board to one of its phases, forward or back. */
+  | { type: "SetPhase"; payload: { phase: Phase } }
+  /** Closes the board to every change, or opens it again. The facilitator alone. */
+  | { type: "CloseBoard" }
+  | { type: "ReopenBoard" }
   | { type: "MergeTickets"; payload: { source_ticket_id: string; target_ticket_id: string } }
   | { type: "UndoMerge" }
   | { type: "SplitTicket"; payload: { ticket_id: string; segment_index: number } }
   | { type: "SetVoteLimit"; payload: { limit: number | null } }
-  | { type: "StartTimer"; payload: { duration_secs: number } }
+  | { type: "StartTimer"; payload: { duration_secs: number; on_expiry?: TimerAction | null } }
   | { type: "StopTimer" }
   | { type: "SetTicketDone"; payload: { ticket_id: string; done: boolean } }
+  | { type: "SetTicketAssignee"; payload: { ticket_id: string; assignee?: AssigneeChoice | null } }
+  | { type: "SetTicketDueDate"; payload: { ticket_id: string; due_on?: string | null } }
   | { type: "SetRockStatus"; payload: { ticket_id: string; status?: RockStatus | null } }
   | { type: "RateMeeting"; payload: { rating: number } }
   | { type: "AddScorecardMetric"; payload: { name: string; goal: string } }
@@ -277,13 +529,40 @@ export type ClientMessage =
   | { type: "RequestEditor"; payload: { name?: string } }
   | { type: "ApproveEditor"; payload: { participant_id: string } }
   | { type: "DeclineEditor"; payload: { participant_id: string } }
-  | { type: "RemoveEditor"; payload: { participant_id: string } };
+  | { type: "RemoveEditor"; payload: { participant_id: string } }
+  | { type: "AddColumn"; payload: { name: string } }
+  | { type: "RenameColumn"; payload: { column_id: string; name: string } }
+  /** Every column of the board, once, with Actions last. */
+  | { type: "ReorderColumns"; payload: { column_ids: string[] } }
+  /** A column that still holds cards needs somewhere to send them. */
+  | { type: "RemoveColumn"; payload: { column_id: string; target_column_id?: string | null } }
+  /** Walks back the newest step this participant took on the board. */
+  | { type: "Undo" }
+  /** Makes again the step this participant undid last. */
+  | { type: "Redo" }
+  /** Asks for the whole board again, after a patch arrived with a revision skipped. */
+  | { type: "Resync" };
 
 export type ServerMessage =
   | { type: "BoardState"; payload: { board: Board } }
-  | { type: "Authenticated"; payload: { is_facilitator: boolean; participant_id: string } }
+  /** A change to lay on top of the board at `revision - 1`. */
+  | { type: "BoardPatch"; payload: { revision: number; patch: BoardPatch } }
+  | {
+      type: "Authenticated";
+      /** `participant_id` and its credential join this board again. `author_id` is what the board
+       * knows this reader by: a pseudonym of the id on an anonymous board, the id itself on any
+       * other. */
+      payload: {
+        is_facilitator: boolean;
+        participant_id: string;
+        participant_token: string;
+        author_id: string;
+      };
+    }
   /** The board asks for a password this reader did not bring. The socket closes after it. */
   | { type: "PasswordRequired" }
+  /** The timer ran out. What the action changed comes after it as a state or a patch. */
+  | { type: "TimerExpired"; payload: { action: TimerAction | null } }
   | { type: "Error"; payload: { message: string } };
 
 export interface MyBoardSummary {
@@ -294,6 +573,7 @@ export interface MyBoardSummary {
   ticket_count: number;
   is_anonymous: boolean;
   labels: string[];
+  archived_at: string | null;
 }
 
 export interface Template {
@@ -309,6 +589,8 @@ export interface Template {
   default_blurred: boolean;
   /** Where the template stands in the list on the home page. */
   position: number;
+  /** The phases a board from this template runs through. Empty gives a board with none. */
+  phases: Phase[];
 }
 
 export type SortMode = "newest" | "most-votes";
@@ -340,6 +622,7 @@ export interface AdminBoardSummary {
   title: string;
   is_blurred: boolean;
   created_at: string;
+  archived_at: string | null;
   column_count: number;
   ticket_count: number;
   vote_count: number;
@@ -5,11 +5,18 @@ import type {
   AdminBoardSummary,
   ApplyTemplateResult,
   GlobalStats,
+  RetentionAction,
+  RetentionCandidate,
+  RetentionRecord,
+  RetentionSettings,
   Team,
+  Phase,
   Template,
 } from "../lib/types";
+import { PHASES } from "../lib/types";
 import {
   applyAdminTemplate,
+  clearRetention,
   createAdminTemplate,
   createAdminTeam,
   deleteAdminBoard,
@@ -20,7 +27,11 @@ import {
   fetchAdminStats,
   fetchAdminTeams,
   fetchAdminTemplates,
+  fetchRetention,
+  fetchRetentionLog,
+  fetchRetentionPreview,
   restartService,
+  setRetention,
   updateAdminTeam,
   updateAdminTemplate,
   verifyAdminToken,
@@ -249,6 +260,7 @@ interface TemplateFormData {
   columns: string[];
   position: number;
   default_blurred: boolean;
+  phases: Phase[];
 }
 
 function TemplateForm({
@@ -268,6 +280,7 @@ function TemplateForm({
   // which moved the template to the head of the list on the home page.
   const [position, setPosition] = useState(initial?.position ?? 0);
   const [defaultBlurred, setDefaultBlurred] = useState(initial?.default_blurred ?? true);
+  const [phases, setPhases] = useState<Phase[]>(initial?.phases ?? []);
   const [saving, setSaving] = useState(false);
   const [error, setError] = useState("");
 
@@ -288,6 +301,7 @@ function TemplateForm({
         columns: trimmedCols,
         position,
         default_blurred: defaultBlurred,
+        phases,
       });
       onCancel();
     } catch (err) {
@@ -424,6 +438,34 @@ function TemplateForm({
         </p>
       </div>
 
+      <div>
+        <label className="block text-xs text-muted mb-1">Phases</label>
+        <div className="flex flex-wrap gap-3">
+          {PHASES.map(({ phase, label }) => (
+            <label key={phase} className="flex items-center gap-1.5 cursor-pointer select-none">
+              <input
+                type="checkbox"
+                checked={phases.includes(phase)}
+                onChange={(e) =>
+                  setPhases((current) =>
+                    e.target.checked
+                      ? PHASES.map((p) => p.phase).filter((p) => p === phase || current.includes(p))
+                      : current.filter((p) => p !== phase),
+                  )
+                }
+                className="accent-[var(--color-accent)]"
+              />
+              <span className="text-sm">{label}</span>
+            </label>
+          ))}
+        </div>
+        <p className="text-xs text-muted mt-1">
+          A board runs through the phases ticked here, in this order, and the server holds each
+          one to its own work: no votes while writing, no new cards once the vote starts, nothing
+          once closed. None ticked leaves the board free, as before.
+        </p>
+      </div>
+
       {error && <p className="text-sm text-red-600">{error}</p>}
 
       <div className="flex gap-2">
@@ -582,6 +624,7 @@ function TemplatesPanel({
       columns: data.columns,
       position: data.position,
       default_blurred: data.default_blurred,
+      phases: data.phases,
     });
     setEditing(null);
     onReload();
@@ -945,6 +988,174 @@ function TeamsPanel({
   );
 }
 
+function RetentionPanel({ getToken }: { getToken: () => string }) {
+  const [settings, setSettings] = useState<RetentionSettings | null>(null);
+  const [preview, setPreview] = useState<RetentionCandidate[]>([]);
+  const [log, setLog] = useState<RetentionRecord[]>([]);
+  const [days, setDays] = useState("");
+  const [action, setAction] = useState<RetentionAction>("delete");
+  const [labels, setLabels] = useState("");
+  const [saving, setSaving] = useState(false);
+  const [error, setError] = useState("");
+
+  const load = useCallback(async () => {
+    const [s, p, l] = await Promise.all([
+      fetchRetention(getToken()),
+      fetchRetentionPreview(getToken()),
+      fetchRetentionLog(getToken()),
+    ]);
+    setSettings(s);
+    setDays(s.policy.days === null ? "" : String(s.policy.days));
+    setAction(s.policy.action);
+    setLabels(s.policy.exempt_labels.join(", "));
+    setPreview(p);
+    setLog(l);
+  }, [getToken]);
+
+  useEffect(() => {
+    load().catch((err) => setError(err instanceof Error ? err.message : "Failed to load"));
+  }, [load]);
+
+  async function save(apply: () => Promise<RetentionSettings>) {
+    setError("");
+    setSaving(true);
+    try {
+      await apply();
+      await load();
+    } catch (err) {
+      setError(err instanceof Error ? err.message : "Failed to save");
+    } finally {
+      setSaving(false);
+    }
+  }
+
+  function handleSubmit(e: React.FormEvent) {
+    e.preventDefault();
+    const trimmed = days.trim();
+    save(() =>
+      setRetention(getToken(), {
+        days: trimmed ? Number(trimmed) : null,
+        action,
+        exempt_labels: labels.split(",").map((l) => l.trim()).filter(Boolean),
+      }),
+    );
+  }
+
+  return (
+    <div className="space-y-4">
+      <form onSubmit={handleSubmit} className="bg-surface border border-border rounded-lg p-5 space-y-4">
+        <div className="flex items-center justify-between">
+          <h2 className="font-display font-semibold text-sm">Retention policy</h2>
+          {settings && (
+            <span className="text-xs text-muted">
+              {settings.source === "admin" ? "Set here" : "From the environment"}
+            </span>
+          )}
+        </div>
+        <div className="grid grid-cols-1 sm:grid-cols-3 gap-4">
+          <div>
+            <label className="block text-xs text-muted mb-1">Days without a change</label>
+            <input
+              type="number"
+              min={1}
+              value={days}
+              onChange={(e) => setDays(e.target.value)}
+              placeholder="Keep forever"
+              className="w-full px-3 py-2 text-sm rounded-lg border border-border bg-canvas"
+            />
+          </div>
+          <div>
+            <label className="block text-xs text-muted mb-1">Then</label>
+            <select
+              value={action}
+              onChange={(e) => setAction(e.target.value as RetentionAction)}
+              className="w-full px-3 py-2 text-sm rounded-lg border border-border bg-canvas"
+            >
+              <option value="delete">Delete the board</option>
+              <option value="anonymize">Remove the names</option>
+            </select>
+          </div>
+          <div>
+            <label className="block text-xs text-muted mb-1">Exempt labels</label>
+            <input
+              value={labels}
+              onChange={(e) => setLabels(e.target.value)}
+              placeholder="legal, keep"
+              className="w-full px-3 py-2 text-sm rounded-lg border border-border bg-canvas"
+            />
+          </div>
+        </div>
+        <p className="text-xs text-muted">
+          The server applies the policy every hour. An empty number of days keeps every board,
+          even if the environment sets a policy.
+        </p>
+        {error && <p className="text-xs text-red-500">{error}</p>}
+        <div className="flex gap-2">
+          <button
+            type="submit"
+            disabled={saving}
+            className="px-4 py-2 text-sm rounded-lg bg-accent text-white hover:bg-accent-hover transition-colors disabled:opacity-50"
+          >
+            Save
+          </button>
+          {settings?.source === "admin" && (
+            <button
+              type="button"
+              disabled={saving}
+              onClick={() => save(() => clearRetention(getToken()))}
+              className="px-4 py-2 text-sm rounded-lg border border-border hover:bg-canvas transition-colors disabled:opacity-50"
+            >
+              Use the environment
+            </button>
+          )}
+        </div>
+      </form>
+
+      <div className="bg-surface border border-border rounded-lg overflow-hidden">
+        <div className="px-4 py-3 border-b border-border">
+          <h2 className="font-display font-semibold text-sm">Next run ({preview.length})</h2>
+        </div>
+        {preview.length === 0 ? (
+          <p className="px-4 py-6 text-sm text-muted text-center">The next run takes no board.</p>
+        ) : (
+          <ul className="divide-y divide-border">
+            {preview.map((c) => (
+              <li key={c.board_id} className="px-4 py-2.5 flex items-center justify-between gap-3 text-sm">
+                <span className="truncate">{c.title}</span>
+                <span className="text-xs text-muted shrink-0">
+                  {c.card_count} {c.card_count === 1 ? "card" : "cards"} · last change{" "}
+                  {new Date(c.last_activity_at).toLocaleDateString()} · {c.action}
+                </span>
+              </li>
+            ))}
+          </ul>
+        )}
+      </div>
+
+      <div className="bg-surface border border-border rounded-lg overflow-hidden">
+        <div className="px-4 py-3 border-b border-border">
+          <h2 className="font-display font-semibold text-sm">Removed</h2>
+        </div>
+        {log.length === 0 ? (
+          <p className="px-4 py-6 text-sm text-muted text-center">Nothing removed yet.</p>
+        ) : (
+          <ul className="divide-y divide-border">
+            {log.map((r) => (
+              <li key={r.id} className="px-4 py-2.5 flex items-center justify-between gap-3 text-sm">
+                <span className="truncate">{r.title}</span>
+                <span className="text-xs text-muted shrink-0">
+                  {r.action === "delete" ? "deleted" : "anonymized"}{" "}
+                  {new Date(r.purged_at).toLocaleDateString()}
+                </span>
+              </li>
+            ))}
+          </ul>
+        )}
+      </div>
+    </div>
+  );
+}
+
 // --- Main component ---
 
 export default function Admin() {
@@ -960,7 +1171,7 @@ export default function Admin() {
   const [detail, setDetail] = useState<AdminBoardDetail | null>(null);
   const [deleteTarget, setDeleteTarget] = useState<AdminBoardSummary | null>(null);
   const [loadingData, setLoadingData] = useState(false);
-  const [tab, setTab] = useState<"boards" | "templates" | "teams">("boards");
+  const [tab, setTab] = useState<"boards" | "templates" | "teams" | "retention">("boards");
   const [templates, setTemplates] = useState<Template[]>([]);
   const [teams, setTeams] = useState<Team[]>([]);
   const [restarting, setRestarting] = useState(false);
@@ -991,22 +1202,19 @@ export default function Admin() {
     }
   }, []);
 
-  // Auto-verify stored token on mount
+  // Auto-verify on mount: the stored token, or else the account itself — a member of an admin
+  // group on a signed-in deployment gets in with no token at all.
   useEffect(() => {
-    const stored = sessionStorage.getItem(STORAGE_KEY);
-    if (stored) {
-      verifyAdminToken(stored)
-        .then(() => {
-          setAuthenticated(true);
-          loadDashboard(stored);
-        })
-        .catch(() => {
-          sessionStorage.removeItem(STORAGE_KEY);
-        })
-        .finally(() => setInitializing(false));
-    } else {
-      setInitializing(false);
-    }
+    const stored = sessionStorage.getItem(STORAGE_KEY) ?? "";
+    verifyAdminToken(stored)
+      .then(() => {
+        setAuthenticated(true);
+        loadDashboard(stored);
+      })
+      .catch(() => {
+        sessionStorage.removeItem(STORAGE_KEY);
+      })
+      .finally(() => setInitializing(false));
   }, [loadDashboard]);
 
   // Load detail when selection changes
@@ -1170,7 +1378,7 @@ export default function Admin() {
 
         {/* Tab switcher */}
         <div className="flex gap-1 mb-4 border-b border-border">
-          {(["boards", "templates", "teams"] as const).map((t) => (
+          {(["boards", "templates", "teams", "retention"] as const).map((t) => (
             <button
               key={t}
               onClick={() => setTab(t)}
@@ -1237,6 +1445,14 @@ export default function Admin() {
                                   blur
                                 </span>
                               )}
+                              {b.archived_at && (
+                                <span
+                                  className="ml-1.5 text-xs text-muted"
+                                  title={`Closed ${new Date(b.archived_at).toLocaleDateString()}`}
+                                >
+                                  closed
+                                </span>
+                              )}
                             </td>
                             <td className="px-4 py-2.5 text-muted">{b.column_count}</td>
                             <td className="px-4 py-2.5 text-muted">{b.ticket_count}</td>
@@ -1299,6 +1515,8 @@ export default function Admin() {
             getToken={getToken}
           />
         )}
+
+        {tab === "retention" && <RetentionPanel getToken={getToken} />}
       </div>
 
       {/* Delete confirmation */}
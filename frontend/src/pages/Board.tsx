@@ -42,7 +42,7 @@ export default function Board() {
   });
   const [nameInput, setNameInput] = useState("");
 
-  // On a deployment that asks for a work account, Entra already knows the name. It fills the field
+  // On a deployment that asks for a work account, the identity provider already knows the name. It fills the field
   // rather than settling it: the prompt still stands, and a person may write what the board should
   // call them. Nothing is filled in once they have started typing.
   const signedInName = useSignedInName();
@@ -102,6 +102,26 @@ export default function Board() {
     };
   }, [reset]);
 
+  // Ctrl+Z walks back the last thing this reader did to the board, Ctrl+Shift+Z or Ctrl+Y does it
+  // again. Inside a text field the keys keep their usual meaning for the text.
+  useEffect(() => {
+    function handleHistoryKeys(e: KeyboardEvent) {
+      if (!(e.ctrlKey || e.metaKey) || e.altKey) return;
+      const target = e.target as HTMLElement | null;
+      if (target?.closest("input, textarea, [contenteditable='true']")) return;
+      const key = e.key.toLowerCase();
+      if (key === "z") {
+        e.preventDefault();
+        send({ type: e.shiftKey ? "Redo" : "Undo" });
+      } else if (key === "y") {
+        e.preventDefault();
+        send({ type: "Redo" });
+      }
+    }
+    document.addEventListener("keydown", handleHistoryKeys);
+    return () => document.removeEventListener("keydown", handleHistoryKeys);
+  }, [send]);
+
   // Drag-and-drop for ticket merging
   const [activeTicket, setActiveTicket] = useState<{ ticket: Ticket; color: string } | null>(null);
   const sensors = useSensors(
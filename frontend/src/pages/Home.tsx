@@ -70,8 +70,8 @@ export default function Home() {
   const [joinError, setJoinError] = useState("");
   const version = useServerVersion();
 
-  // On a deployment that asks for a work account, the name field starts filled with the one Entra
-  // holds. It is still a field: a person may write what the board should call them instead.
+  // On a deployment that asks for a work account, the name field starts filled with the one the
+  // identity provider holds. It is still a field: a person may write what the board should call them instead.
   const signedInName = useSignedInName();
   const [nameTouched, setNameTouched] = useState(false);
   useEffect(() => {
@@ -486,6 +486,7 @@ export default function Home() {
                         {" \u00B7 "}
                         {b.ticket_count} {b.ticket_count === 1 ? "card" : "cards"}
                         {b.is_anonymous && " \u00B7 anonymous"}
+                        {b.archived_at && " \u00B7 closed"}
                       </span>
                       {b.labels.length > 0 && (
                         <span className="flex flex-wrap gap-1.5 mt-1.5">
@@ -1,5 +1,5 @@
 import { create } from "zustand";
-import type { Board, SortMode } from "../lib/types";
+import type { Board, BoardPatch, Column, SortMode, Ticket } from "../lib/types";
 
 interface BoardState {
   board: Board | null;
@@ -11,11 +11,19 @@ interface BoardState {
   facilitatorPeek: boolean;
   /** True after the server turned this reader away at the gate of a locked board. */
   passwordRequired: boolean;
+  /** What the server last said went wrong, such as a message it dropped for coming too fast. */
+  serverNotice: string | null;
 
   setBoard: (board: Board) => void;
+  /**
+   * Lays one patch on the board. Gives false when a revision was skipped, and then the caller
+   * has to ask for the whole board again. A patch the board already holds is passed over.
+   */
+  applyPatch: (revision: number, patch: BoardPatch) => boolean;
   setAuth: (participantId: string, isFacilitator: boolean) => void;
   setConnected: (connected: boolean) => void;
   setPasswordRequired: (required: boolean) => void;
+  setServerNotice: (notice: string | null) => void;
   setSortMode: (mode: SortMode) => void;
   setPendingUndo: () => void;
   clearPendingUndo: () => void;
@@ -23,7 +31,55 @@ interface BoardState {
   reset: () => void;
 }
 
-export const useBoardStore = create<BoardState>((set) => ({
+/** Puts a card in its column, in place of the card of the same id wherever that stood. */
+function placeTicket(columns: Column[], columnId: string, ticket: Ticket): Column[] {
+  return columns.map((column) => {
+    const others = column.tickets.filter((t) => t.id !== ticket.id);
+    if (column.id !== columnId) {
+      return others.length === column.tickets.length ? column : { ...column, tickets: others };
+    }
+    const at = column.tickets.findIndex((t) => t.id === ticket.id);
+    const tickets = [...others];
+    tickets.splice(at === -1 ? tickets.length : at, 0, ticket);
+    return { ...column, tickets };
+  });
+}
+
+function patchBoard(board: Board, patch: BoardPatch): Board {
+  switch (patch.kind) {
+    case "TicketAdded":
+    case "TicketEdited":
+    case "TicketMoved":
+    case "CommentChanged":
+      return { ...board, columns: placeTicket(board.columns, patch.column_id, patch.ticket) };
+    case "TicketRemoved":
+      return {
+        ...board,
+        columns: board.columns.map((column) => ({
+          ...column,
+          tickets: column.tickets.filter((t) => t.id !== patch.ticket_id),
+        })),
+      };
+    case "VoteToggled":
+      return {
+        ...board,
+        columns: board.columns.map((column) => ({
+          ...column,
+          tickets: column.tickets.map((t) =>
+            t.id === patch.ticket_id
+              ? { ...t, votes: patch.votes, vote_count: patch.vote_count, voted: patch.voted }
+              : t,
+          ),
+        })),
+      };
+    case "SettingsChanged":
+      return { ...board, ...patch.settings };
+    case "ParticipantCount":
+      return { ...board, participant_count: patch.participant_count };
+  }
+}
+
+export const useBoardStore = create<BoardState>((set, get) => ({
   board: null,
   participantId: null,
   isFacilitator: false,
@@ -32,17 +88,27 @@ export const useBoardStore = create<BoardState>((set) => ({
   pendingUndo: false,
   facilitatorPeek: false,
   passwordRequired: false,
+  serverNotice: null,
 
   setBoard: (board) => set((state) => ({
     board,
     // Turn off peek when cards are unblurred
     facilitatorPeek: board.is_blurred ? state.facilitatorPeek : false,
   })),
+  applyPatch: (revision, patch) => {
+    const board = get().board;
+    if (!board) return false;
+    if (revision <= board.revision) return true;
+    if (revision !== board.revision + 1) return false;
+    set({ board: { ...patchBoard(board, patch), revision } });
+    return true;
+  },
   setAuth: (participantId, isFacilitator) => set({ participantId, isFacilitator }),
   setConnected: (connected) => set({ isConnected: connected }),
   // The board goes with it: what the gate shuts, the reader must not keep on screen.
   setPasswordRequired: (required) =>
     set(required ? { passwordRequired: true, board: null } : { passwordRequired: false }),
+  setServerNotice: (notice) => set({ serverNotice: notice }),
   setSortMode: (mode) => set({ sortMode: mode }),
   setPendingUndo: () => set({ pendingUndo: true }),
   clearPendingUndo: () => set({ pendingUndo: false }),
@@ -57,5 +123,6 @@ export const useBoardStore = create<BoardState>((set) => ({
       pendingUndo: false,
       facilitatorPeek: false,
       passwordRequired: false,
+      serverNotice: null,
     }),
 }));